@@ -4,7 +4,353 @@ Type stubs for pyripgrep module.
 This module provides a Python binding for ripgrep, a fast recursive search tool.
 """
 
-from typing import Dict, List, Literal, Optional, Union, overload
+from datetime import datetime, timedelta
+from typing import Any, BinaryIO, Callable, Dict, Iterator, List, Literal, Optional, Tuple, Union, overload
+
+class PatternError(ValueError):
+    """
+    A pattern failed to compile (invalid syntax, size limits, unsupported features).
+
+    Also raised by compile() and search_many(); the message names the pattern there.
+    """
+
+    pattern: str
+    """The pattern as given"""
+    path: Optional[str]
+    """Always None"""
+    offset: Optional[int]
+    """Byte offset of the offending part of the pattern, when the parser reports one"""
+    cause: str
+    """The regex engine's error message"""
+
+class WalkError(ValueError):
+    """Directory traversal failed: a missing root, or a walk error with errors="raise"."""
+
+    path: Optional[str]
+    """The path the walk failed on, when known"""
+    offset: Optional[int]
+    """Always None"""
+    cause: str
+    """The underlying error message"""
+
+class IOErrorInfo(ValueError):
+    """A file could not be read or searched with errors="raise" (I/O errors, undecodable contents)."""
+
+    path: Optional[str]
+    """The file that failed, spelled as in results"""
+    offset: Optional[int]
+    """Always None"""
+    cause: str
+    """The underlying error message"""
+
+class SearchTimeout(TimeoutError):
+    """A search ran past timeout= or walk_timeout=."""
+
+    path: Optional[str]
+    """Always None"""
+    offset: Optional[int]
+    """Always None"""
+    cause: Literal["timeout", "walk_timeout"]
+    """Which limit expired"""
+
+class SkipCounts:
+    """
+    Entries left out of a search, by cause (SearchStats.skipped).
+
+    A directory pruned by gitignore, hidden or glob rules counts once, not per file inside it.
+    """
+
+    gitignore: int
+    """Excluded by .gitignore, .ignore, custom ignore files, git excludes or the global gitignore"""
+    hidden: int
+    """Hidden files and directories (names starting with '.')"""
+    type_filter: int
+    """Files not selected by the type filter"""
+    glob: int
+    """Files and directories excluded by the glob pattern"""
+    binary: int
+    """Files whose contents are not valid UTF-8"""
+    size: int
+    """Files over max_filesize or under min_filesize"""
+    errors: int
+    """Files that could not be read (permissions, I/O errors, preprocessor failures, ...)"""
+    duplicate: int
+    """
+    Files reached again under another path, e.g. through a symlink (canonical_paths=True),
+    or repeating the contents of a file already searched (dedupe="content")
+    """
+    modified: int
+    """Files last modified outside modified_after / modified_before"""
+    content_filter: int
+    """Files missing an all_of pattern or containing a none_of pattern"""
+    generated: int
+    """Files matched by generated_globs or detected as minified (skip_minified)"""
+    special: int
+    """Symlinks, fifos, sockets and device files (special_files="report" or "follow")"""
+    changed: int
+    """Files that changed while they were searched (changed_files="skip")"""
+
+class TypeStats:
+    """One file type's share of a search (SearchStats.by_type)."""
+
+    files_searched: int
+    """Files of this type whose contents were searched"""
+    files_matched: int
+    """Files of this type with at least one match"""
+    matches: int
+    """Matching lines found in files of this type"""
+    bytes_searched: int
+    """Total size of the files of this type searched"""
+
+class SearchStats:
+    """Statistics collected during a search (returned when stats=True)."""
+
+    files_searched: int
+    """Files whose contents were searched"""
+    files_matched: int
+    """Files with at least one match"""
+    matches: int
+    """Matching lines found (files_with_matches stops at the first match per file)"""
+    bytes_searched: int
+    """Total size of the files searched"""
+    files_skipped: int
+    """Files skipped because they could not be read or decoded (e.g. binary)"""
+    skipped: SkipCounts
+    """Breakdown of skipped entries by cause, including those excluded by filters"""
+    elapsed: float
+    """Wall-clock duration of the search in seconds"""
+    warnings: List[str]
+    """Options downgraded because this build lacks a feature (strict_options=False)"""
+    truncated: bool
+    """Whether head_limit or a max_* limit left results out, in any output mode"""
+    suppressed_files: int
+    """Files with matches left out by the limits (a lower bound: the search stops there)"""
+    suppressed_matches: int
+    """Matches left out by the limits (a lower bound: the search stops there)"""
+    by_type: Dict[str, TypeStats]
+    """
+    Per-type breakdown keyed by ripgrep type (e.g. "py"), or by extension (e.g. ".cfg")
+    for files no type claims; "" for files with neither
+    """
+    duplicates: Dict[str, List[str]]
+    """Files left out by dedupe="content", listed under the first file found with the same contents"""
+    profile: Optional["Profile"]
+    """Where the time went, with profile=True"""
+    changed: List[str]
+    """Files whose size or modification time changed while they were searched (changed_files=)"""
+
+class Profile:
+    """
+    Where a search spent its time, in seconds (SearchStats.profile, profile=True).
+
+    read and matching add up the time of every worker thread, so with threads > 1
+    they can exceed total; walk runs alongside the workers on its own thread.
+    """
+
+    setup: float
+    """Compiling the pattern and building the walker and filters"""
+    walk: float
+    """Producing the files to search: listing directories and applying ignore, glob and hidden rules"""
+    read: float
+    """Opening, reading and decoding files"""
+    matching: float
+    """Matching file contents, i.e. the time spent on each file other than reading it"""
+    format: float
+    """Building the results after the files were searched"""
+    total: float
+    """Wall-clock duration of the whole search, as SearchStats.elapsed"""
+
+class Pattern:
+    """
+    Compiled search pattern that can be passed to Grep.search() repeatedly.
+
+    The regex and type filter are compiled once. When a Pattern is passed to
+    search(), its i/multiline/x/engine/case_smart/unicode options are used instead of the search arguments,
+    and its type filter applies unless search() is given an explicit type.
+    """
+
+    pattern: str
+    case_insensitive: bool
+    multiline: bool
+    line_regexp: bool
+    case_smart: bool
+    unicode: bool
+    engine: str
+
+    def __init__(
+        self,
+        pattern: str,
+        i: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        type_ignore_case: Optional[bool] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+    ) -> None:
+        """Compile a pattern; raises ValueError if the pattern or type is invalid."""
+        ...
+
+class SearchSession:
+    """
+    Incremental search created by Grep.start(); call step() until done.
+
+    Lets single-threaded programs (e.g. tkinter or Qt event loops) search large trees
+    in bounded slices of time. Must be used from the thread that created it.
+    """
+
+    done: bool
+    """True once every file has been searched"""
+    stats: SearchStats
+    """Statistics for the files searched so far (elapsed counts only time spent in step() or iterating)"""
+    errors: List[Tuple[str, str]]
+    """(path, message) pairs collected so far with errors="collect""""
+
+    def step(self, max_millis: int = 50) -> Union[List[str], Dict[str, int], List[Dict[str, Any]], bool]:
+        """
+        Search until max_millis have passed and return the results of the files finished in this step.
+
+        Results have the same shape as search() for the session's output mode. At least one
+        file is searched per step, so a step can overrun its budget by one file. Once done
+        is True, further steps return empty results. In "quiet" mode a step returns True
+        once a file matches, and the session is done.
+        """
+        ...
+
+    def __iter__(self) -> Iterator[str]:
+        """
+        Yield paths as they are found, in walk order (files_with_matches, files_without_match
+        and files mode).
+
+        Each path is returned as soon as its file matches, so interactive tools can show
+        the first hits before the walk ends. Iteration and step() share one walk.
+
+        Raises:
+            ValueError: If the session uses another output mode
+        """
+        ...
+
+    def __next__(self) -> str: ...
+
+class Workspace:
+    """
+    A directory tree walked once, with its ignore rules applied, for repeated searches.
+
+    For IDE-like usage where many searches hit the same tree: .gitignore/.ignore files
+    are parsed and the tree is enumerated only on creation and on refresh(), not on
+    every search. Files added or removed in between are not seen until refresh().
+    """
+
+    path: str
+    """Root directory of the workspace"""
+    files: List[str]
+    """Cached files, relative to path"""
+
+    def __init__(self, path: str = ".") -> None:
+        """Walk path and cache its files; raises ValueError if path is not a directory."""
+        ...
+
+    def refresh(self) -> int:
+        """Walk the tree again to pick up changes and return the number of cached files."""
+        ...
+
+    def search(self, pattern: Optional[Union[str, "Pattern"]] = None, **kwargs: Any) -> Any:
+        """
+        Grep.search() over the cached files.
+
+        Accepts every search() keyword except path and files; glob and type filters
+        still apply per call. Results have the same shape as search() returns.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+
+class FileSet:
+    """
+    Files with matches from Grep.find_files() or FileSet.refine().
+
+    Searches over the set read only its files, without walking the tree again, and
+    use the defaults of the Grep that found them.
+    """
+
+    files: List[str]
+    """The files in the set, as search() reported them"""
+
+    def search(self, pattern: Optional[Union[str, "Pattern"]] = None, **kwargs: Any) -> Any:
+        """
+        Grep.search() over the files in the set.
+
+        Accepts every search() keyword except path, files and scope; glob and type
+        filters still apply per call. Results have the same shape as search() returns.
+        """
+        ...
+
+    def refine(self, pattern: Optional[Union[str, "Pattern"]] = None, **kwargs: Any) -> "FileSet":
+        """The files in the set that also match pattern, as a new FileSet; kwargs as for Grep.find_files()."""
+        ...
+
+    def __len__(self) -> int: ...
+    def __contains__(self, path: str) -> bool: ...
+
+class SpilledResults:
+    """
+    Content results written to a temporary JSONL file, returned by search() past spill_over lines.
+
+    Iterating yields the lines in order, reading them back one at a time. The file is
+    deleted by close(), when a with block exits, or once the object is garbage collected.
+    """
+
+    path: str
+    """The JSONL file: one JSON string per result line"""
+
+    def close(self) -> None:
+        """Delete the file; iterating afterwards raises ValueError."""
+        ...
+
+    def __iter__(self) -> Iterator[str]: ...
+    def __len__(self) -> int: ...
+    def __enter__(self) -> "SpilledResults": ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool: ...
+
+class Watcher:
+    """
+    Live search started by Grep.watch(); stop() it or use it as a context manager.
+
+    The callback runs on a background thread. If it raises, watching stops and the
+    exception is re-raised by stop() (or when the with block exits).
+    """
+
+    running: bool
+    """False once stopped, or once the callback raised"""
+
+    def stop(self) -> None:
+        """Stop watching and wait for the background thread to finish."""
+        ...
+
+    def __enter__(self) -> "Watcher": ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool: ...
+
+class Explanation:
+    """Why a search would or would not reach a path, returned by Grep.explain(). Truthy when included."""
+
+    path: str
+    """The path as passed to explain()"""
+    included: bool
+    """Whether a search under the root would search this path"""
+    cause: Optional[str]
+    """The SkipCounts field the exclusion counts under ("gitignore", "hidden", "glob", "type_filter"), or None
+    when the walk leaves the path out for a reason no rule names (e.g. one_file_system=True)"""
+    reason: str
+    """Human-readable verdict, e.g. "skipped: ignored by ignore rules" """
+    rule: Optional[str]
+    """The rule that decided, e.g. "*.log", "!keep.log" or the glob= pattern"""
+    source: Optional[str]
+    """The ignore file the rule came from"""
+    matched_path: Optional[str]
+    """The path the rule matched: the path itself or the ancestor directory that pruned it"""
+
+    def __bool__(self) -> bool: ...
 
 class Grep:
     """
@@ -14,18 +360,109 @@ class Grep:
     to search through files and directories with various filtering options.
     """
 
-    def __init__(self) -> None:
-        """Initialize a new Grep instance."""
+    def __init__(self, **defaults: Any) -> None:
+        """
+        Initialize a new Grep instance.
+
+        Args:
+            **defaults: Any search() keyword argument except pattern, applied to every
+                search() call; arguments passed to search() take precedence.
+                validate_pattern(), preview(), search_rev(), compare(), watch() and
+                explain() apply the defaults for the options they accept.
+                case_insensitive and types are accepted as spellings of i and type.
+                Unknown names raise TypeError.
+
+        Example:
+            grep = Grep(case_insensitive=True, types=["py"], threads=8)
+            grep.search("todo")            # case-insensitive, Python files only
+            grep.search("TODO", i=False)   # overrides the default
+        """
+        ...
+
+    @property
+    def defaults(self) -> Dict[str, Any]:
+        """Copy of the constructor defaults, keyed by search() parameter name."""
+        ...
+
+    @staticmethod
+    def from_config(path: Optional[str] = None) -> "Grep":
+        """
+        Create a Grep whose defaults come from a ripgrep config file.
+
+        The file uses rg's format: one argument per line, blank lines and lines
+        starting with # ignored. Flags such as --smart-case, --hidden, --glob, --iglob,
+        --type, --type-add, --max-columns, --threads and -u map to the matching
+        search() options; display-only flags (--colors, --pretty, ...) are ignored.
+
+        Args:
+            path: Config file to read; defaults to $RIPGREP_CONFIG_PATH. With neither,
+                the Grep has no defaults.
+
+        Warns:
+            UserWarning: For flags without a pyripgrep equivalent, which are skipped.
+
+        Raises:
+            FileNotFoundError: If the config file does not exist
+            ValueError: If a flag is missing its value or the value is invalid
+        """
         ...
 
     # Overloaded signatures for different output modes
+    @overload
+    def search(self, pattern: Optional[Union[str, "Pattern"]] = None, *, stats: Literal[True], **kwargs: Any) -> Tuple[Any, ...]:
+        """Search and return (results, SearchStats[, errors]); see the overloads below for results."""
+        ...
+
+    @overload
+    def search(self, pattern: Optional[Union[str, "Pattern"]] = None, *, errors: Literal["collect"], **kwargs: Any) -> Tuple[Any, ...]:
+        """Search and return (results, [SearchStats,] errors) where errors is a list of (path, message)."""
+        ...
+
+    @overload
+    def search(self, pattern: Optional[Union[str, "Pattern"]] = None, *, forensic: Literal[True], **kwargs: Any) -> Tuple[Any, SearchStats, List[Tuple[str, str]]]:
+        """Search and return (results, SearchStats, skips) covering every candidate file; see forensic below."""
+        ...
+
+    @overload
+    def search(
+        self, pattern: Optional[Union[str, "Pattern"]] = None, *, output_mode: Literal["content"], group_by_file: Literal[True], **kwargs: Any
+    ) -> Dict[str, List[Dict[str, Any]]]:
+        """Search and return {path: [{"line_number", "end_line_number", "content", "before_context", "after_context"}, ...]}."""
+        ...
+
+    @overload
+    def search(
+        self, pattern: Optional[Union[str, "Pattern"]] = None, *, output_mode: Literal["content"], text_mode: Literal["bytes"], **kwargs: Any
+    ) -> List[bytes]:
+        """Search and return content lines as the bytes they were read as (grouped results carry bytes "content")."""
+        ...
+
+    @overload
+    def search(
+        self, pattern: Optional[Union[str, "Pattern"]] = None, *, output_mode: Literal["content"], spill_over: int, **kwargs: Any
+    ) -> Union[List[str], "SpilledResults"]:
+        """Search and return content lines, as a SpilledResults reader once there are more than spill_over of them."""
+        ...
+
+    @overload
+    def search(
+        self, pattern: Optional[Union[str, "Pattern"]] = None, *, output_mode: Literal["count"], aggregate: Literal["total"], **kwargs: Any
+    ) -> int:
+        """Search and return the number of matching lines across all files."""
+        ...
+
+    @overload
+    def search(self, pattern: Optional[Union[str, "Pattern"]] = None, *, metadata: Literal[True], **kwargs: Any) -> List[Dict[str, Any]]:
+        """Search and return [{"path", "first_line", "match_count", "size", "mtime"}, ...] for each file with matches."""
+        ...
+
     @overload
     def search(
         self,
-        pattern: Optional[str] = None,
+        pattern: Optional[Union[str, "Pattern"]] = None,
         *,
         path: Optional[str] = None,
-        glob: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
         output_mode: Literal["files_with_matches"] = "files_with_matches",
         B: Optional[int] = None,
         A: Optional[int] = None,
@@ -38,39 +475,418 @@ class Grep:
         compact_paths: Optional[bool] = None,
         multiline: Optional[bool] = None,
         timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
     ) -> List[str]:
         """
         Search for pattern and return list of files containing matches.
 
         Args:
-            pattern: Regular expression pattern to search for (required for this mode)
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
             path: Directory or file path to search (default: current directory)
-            glob: Glob pattern for file filtering (e.g., "*.py")
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
             output_mode: Output mode - "files_with_matches" returns file paths
             B: Number of lines before each match to include (requires content mode)
             A: Number of lines after each match to include (requires content mode)
             C: Number of lines before and after each match (overrides A and B)
             n: Show line numbers (requires content mode)
             i: Case insensitive search
-            type: File type filter (e.g., "rust", "python", ["python", "javascript"])
-            head_limit: Maximum number of results to return
-            truncation_warning: Add "[Content truncated]" message if results exceed head_limit
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Deprecated (emits DeprecationWarning); output lines in content mode, files or entries elsewhere. Use max_files / max_matches_total / max_matches_per_file
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
             compact_paths: Show filepath only once per content block (requires n=True and output_mode="content")
             multiline: Enable multiline mode
             timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Return one dict per matching file with path, first_line (first matching line number), match_count, size (bytes) and mtime (Unix timestamp or None), collected in the same pass. Each matching file is then read to the end
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
 
         Returns:
             List of file paths containing matches
         """
         ...
 
+
+    @overload
+    def search(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        *,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Literal["files_without_match"],
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        head_limit: Optional[int] = None,
+        truncation_warning: Optional[bool] = None,
+        compact_paths: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
+    ) -> List[str]:
+        """
+        Search for pattern and return the searched files that contain no match (rg --files-without-match).
+
+        Args:
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
+            path: Directory or file path to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
+            output_mode: Output mode - "files_without_match" returns paths of files without any match
+            B: Number of lines before each match to include (requires content mode)
+            A: Number of lines after each match to include (requires content mode)
+            C: Number of lines before and after each match (overrides A and B)
+            n: Show line numbers (requires content mode)
+            i: Case insensitive search
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Deprecated (emits DeprecationWarning); output lines in content mode, files or entries elsewhere. Use max_files / max_matches_total / max_matches_per_file
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
+            compact_paths: Show filepath only once per content block (requires n=True and output_mode="content")
+            multiline: Enable multiline mode
+            timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many listed files
+            max_matches_total: Ignored in this mode
+            max_matches_per_file: Ignored in this mode
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Not supported in this mode (raises ValueError)
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
+
+        Returns:
+            List of searched file paths without a single match; files that could not be read
+            are left out
+        """
+        ...
+
     @overload
     def search(
         self,
-        pattern: Optional[str] = None,
+        pattern: Optional[Union[str, "Pattern"]] = None,
         *,
         path: Optional[str] = None,
-        glob: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
         output_mode: Literal["content"],
         B: Optional[int] = None,
         A: Optional[int] = None,
@@ -83,26 +899,192 @@ class Grep:
         compact_paths: Optional[bool] = None,
         multiline: Optional[bool] = None,
         timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
     ) -> List[str]:
         """
         Search for pattern and return matching lines with context.
 
         Args:
-            pattern: Regular expression pattern to search for (required for this mode)
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
             path: Directory or file path to search (default: current directory)
-            glob: Glob pattern for file filtering (e.g., "*.py")
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
             output_mode: Output mode - "content" returns matching lines
             B: Number of lines before each match to include
             A: Number of lines after each match to include
             C: Number of lines before and after each match (overrides A and B)
             n: Show line numbers in format "path:line_num:content"
             i: Case insensitive search
-            type: File type filter (e.g., "rust", "python", ["python", "javascript"])
-            head_limit: Maximum number of results to return
-            truncation_warning: Add "[Content truncated]" message if results exceed head_limit
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Deprecated (emits DeprecationWarning); output lines in content mode, files or entries elsewhere. Use max_files / max_matches_total / max_matches_per_file
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
             compact_paths: Show filepath only once per content block (requires n=True). Format: first line "path:line_num:content", subsequent lines ":line_num:content" or "-line_num:content"
-            multiline: Enable multiline mode
+            multiline: Let matches span lines; each match is shown in full, one numbered line per line it covers
             timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Start each file with an "== path (N matches) ==" line instead of separating files with "--"
+            replace: Show matched lines with every match replaced by this text, like rg -r; $1, $name and ${name} expand capture groups. Files are not modified and context lines are shown unchanged
+            order_within_file: Order of lines within each file: "line" (default), "reverse" (last lines first, e.g. newest log entries) or "relevance" (context blocks with the most matches first). head_limit applies after ordering
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Return {path: [match, ...]} with one dict per match (line_number, end_line_number, content, before_context, after_context; a multiline match's content is its lines joined with "\\n") instead of a flat list with "--" separators; head_limit counts matches
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Line emitted between non-adjacent context blocks and between files, like rg --context-separator (default: "--")
+            heading: Print each file path once on its own line followed by bare "line:content" (match) / "line-content" (context) lines, with a blank line between files, like rg --heading
+            no_filename: Leave the path out of every line ("line:content", or just "content" without n), like rg -I; useful for single-file searches
+            passthru: Emit every line of each matching file, like rg --passthru; with n=True matches use "path:N:" and other lines "path-N:" (B/A/C are ignored)
+            color: Highlight matches on matched lines: "ansi" wraps them in bold red ANSI codes (like rg --color), an (open, close) tuple such as ("<<", ">>") or ("<mark>", "</mark>") uses custom markers, "never" disables it. Combined with replace, the replacement text is highlighted
+            max_columns: Replace content lines longer than this many bytes with "[Omitted long line with N matches]" (context: "[Omitted long context line]"), like rg -M
+            max_columns_preview: With max_columns, keep the first max_columns bytes of a long line followed by " [... N more matches]" (context: " [... omitted end of long line]")
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
 
         Returns:
             List of matching lines, optionally with line numbers and context.
@@ -114,10 +1096,10 @@ class Grep:
     @overload
     def search(
         self,
-        pattern: Optional[str] = None,
+        pattern: Optional[Union[str, "Pattern"]] = None,
         *,
         path: Optional[str] = None,
-        glob: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
         output_mode: Literal["count"],
         B: Optional[int] = None,
         A: Optional[int] = None,
@@ -130,25 +1112,191 @@ class Grep:
         compact_paths: Optional[bool] = None,
         multiline: Optional[bool] = None,
         timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
     ) -> Dict[str, int]:
         """
         Search for pattern and return match counts per file.
 
         Args:
-            pattern: Regular expression pattern to search for (required for this mode)
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
             path: Directory or file path to search (default: current directory)
-            glob: Glob pattern for file filtering (e.g., "*.py")
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
             output_mode: Output mode - "count" returns match counts
             B: Number of lines before each match (ignored in count mode)
             A: Number of lines after each match (ignored in count mode)
             C: Number of lines before and after each match (ignored in count mode)
             n: Show line numbers (ignored in count mode)
             i: Case insensitive search
-            type: File type filter (e.g., "rust", "python", ["python", "javascript"])
-            head_limit: Maximum number of results to return
-            truncation_warning: Add "[Content truncated]" message if results exceed head_limit
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Deprecated (emits DeprecationWarning); output lines in content mode, files or entries elsewhere. Use max_files / max_matches_total / max_matches_per_file
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
             multiline: Enable multiline mode
             timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: "per_file" (default) returns {path: count}; "per_dir" sums the files directly inside each directory into {directory: count}; "total" returns one int across all files
+            include_zero: Also list searched files with no matches as {path: 0}, like rg --count --include-zero. Unreadable files are still left out
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
 
         Returns:
             Dictionary mapping file paths to number of matches in each file
@@ -158,10 +1306,10 @@ class Grep:
     @overload
     def search(
         self,
-        pattern: Optional[str] = None,
+        pattern: Optional[Union[str, "Pattern"]] = None,
         *,
         path: Optional[str] = None,
-        glob: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
         output_mode: Literal["files"],
         B: Optional[int] = None,
         A: Optional[int] = None,
@@ -174,6 +1322,89 @@ class Grep:
         compact_paths: Optional[bool] = None,
         multiline: Optional[bool] = None,
         timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
     ) -> List[str]:
         """
         List all files that would be searched (like rg --files).
@@ -181,20 +1412,1786 @@ class Grep:
         Args:
             pattern: Pattern is ignored in files mode (optional)
             path: Directory or file path to search (default: current directory)
-            glob: Glob pattern for file filtering (e.g., "*.py")
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
             output_mode: Output mode - "files" returns all searchable file paths
             B: Number of lines before each match (ignored in files mode)
             A: Number of lines after each match (ignored in files mode)
             C: Number of lines before and after each match (ignored in files mode)
             n: Show line numbers (ignored in files mode)
             i: Case insensitive search (ignored in files mode)
-            type: File type filter (e.g., "rust", "python", ["python", "javascript"])
-            head_limit: Maximum number of results to return
-            truncation_warning: Add "[Content truncated]" message if results exceed head_limit
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Deprecated (emits DeprecationWarning); output lines in content mode, files or entries elsewhere. Use max_files / max_matches_total / max_matches_per_file
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
             multiline: Enable multiline mode (ignored in files mode)
             timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also lists FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Accepted for symmetry; file listing does not read contents
+            preprocessor: Ignored in files mode
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Ignored in files mode
+            x: Ignored in files mode
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
 
         Returns:
             List of file paths that match the glob and type filters
         """
         ...
+
+    @overload
+    def search(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        *,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Literal["vimgrep"],
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        head_limit: Optional[int] = None,
+        truncation_warning: Optional[bool] = None,
+        compact_paths: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
+    ) -> List[str]:
+        """
+        Search for pattern and return one entry per match, like rg --vimgrep.
+
+        Args:
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
+            path: Directory or file path to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
+            output_mode: Output mode - "vimgrep" returns "path:line:column:content" entries
+            B: Ignored in vimgrep mode
+            A: Ignored in vimgrep mode
+            C: Ignored in vimgrep mode
+            n: Ignored in vimgrep mode (line numbers are always included)
+            i: Case insensitive search
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Maximum number of entries to return
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
+            compact_paths: Ignored in vimgrep mode
+            multiline: Enable multiline mode
+            timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
+
+        Returns:
+            List of "path:line:column:content" strings, one per match (columns are 1-based
+            byte offsets). Lines with several matches appear several times.
+        """
+        ...
+
+    @overload
+    def search(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        *,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Literal["matches"],
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        head_limit: Optional[int] = None,
+        truncation_warning: Optional[bool] = None,
+        compact_paths: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
+    ) -> List[Dict[str, Any]]:
+        """
+        Search for pattern and return one dict per matched line with its absolute byte range.
+
+        Args:
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
+            path: Directory or file path to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
+            output_mode: Output mode - "matches" returns one dict per matched line
+            B: Ignored in matches mode
+            A: Ignored in matches mode
+            C: Ignored in matches mode
+            n: Ignored in matches mode (line numbers are always included)
+            i: Case insensitive search
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Maximum number of records to return
+            truncation_warning: Ignored in matches mode
+            compact_paths: Ignored in matches mode
+            multiline: Enable multiline mode
+            timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
+
+        Returns:
+            List of dicts with keys path, line_number, content, byte_offset and byte_length.
+            byte_offset is where the line starts in the file (in the decompressed stream when
+            search_zip applies) and byte_length excludes the line terminator, so
+            data[byte_offset:byte_offset + byte_length] is the line.
+        """
+        ...
+
+    @overload
+    def search(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        *,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Literal["first_match"],
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        head_limit: Optional[int] = None,
+        truncation_warning: Optional[bool] = None,
+        compact_paths: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
+    ) -> List[Dict[str, Any]]:
+        """
+        Search for pattern and return each matching file's first matching line with its byte offset.
+        Each file's search stops at that line, so this costs about as much as files_with_matches.
+
+        Args:
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
+            path: Directory or file path to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
+            output_mode: Output mode - "first_match" returns one dict per matching file
+            B: Ignored in first_match mode
+            A: Ignored in first_match mode
+            C: Ignored in first_match mode
+            n: Ignored in first_match mode (line numbers are always included)
+            i: Case insensitive search
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Maximum number of files to return
+            truncation_warning: Ignored in first_match mode
+            compact_paths: Ignored in first_match mode
+            multiline: Enable multiline mode
+            timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
+
+        Returns:
+            List of dicts with keys path, line_number, content, byte_offset and byte_length,
+            one per matching file, as in matches mode. byte_offset is where the line starts in the file (in the decompressed stream when
+            search_zip applies) and byte_length excludes the line terminator, so
+            data[byte_offset:byte_offset + byte_length] is the line.
+        """
+        ...
+
+    @overload
+    def search(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        *,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Literal["paths"],
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        head_limit: Optional[int] = None,
+        truncation_warning: Optional[bool] = None,
+        compact_paths: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
+    ) -> List[Dict[str, Any]]:
+        """
+        Match the pattern against file paths instead of contents, like `rg --files | rg pattern`.
+
+        Every ignore, hidden, glob, type, size and all_of/none_of filter applies; the
+        pattern is matched against each path as it would be returned.
+
+        Args:
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
+            path: Directory or file path to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
+            output_mode: Output mode - "paths" returns one dict per file whose path matches
+            B: Ignored in paths mode
+            A: Ignored in paths mode
+            C: Ignored in paths mode
+            n: Ignored in paths mode
+            i: Case insensitive search
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Maximum number of paths to return
+            truncation_warning: Ignored in paths mode
+            compact_paths: Ignored in paths mode
+            multiline: Enable multiline mode
+            timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
+
+        Returns:
+            List of dicts with keys path and spans: the (start, end) character ranges of
+            each match in path, so path[start:end] is the matched text (for highlighting).
+        """
+        ...
+
+    @overload
+    def search(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        *,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Literal["quiet"],
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        head_limit: Optional[int] = None,
+        truncation_warning: Optional[bool] = None,
+        compact_paths: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        stats: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+        type_ignore_case: Optional[bool] = None,
+        files: Optional[List[str]] = None,
+        walk_timeout: Optional[float] = None,
+        search_zip: Optional[bool] = None,
+        preprocessor: Optional[Union[str, Callable[[str], Union[str, bytes]]]] = None,
+        file_headers: Optional[bool] = None,
+        replace: Optional[str] = None,
+        order_within_file: Optional[Literal["line", "relevance", "reverse"]] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        strict_options: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        sort: Optional[Literal["none", "path", "modified", "created", "size"]] = None,
+        sort_order: Optional[Literal["asc", "desc"]] = None,
+        threads: Optional[int] = None,
+        queue_size: Optional[int] = None,
+        expand_vars: Optional[bool] = None,
+        max_files: Optional[int] = None,
+        max_matches_total: Optional[int] = None,
+        max_matches_per_file: Optional[int] = None,
+        group_by_file: Optional[bool] = None,
+        forensic: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        context_separator: Optional[str] = None,
+        heading: Optional[bool] = None,
+        no_filename: Optional[bool] = None,
+        passthru: Optional[bool] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        max_columns: Optional[int] = None,
+        max_columns_preview: Optional[bool] = None,
+        canonical_paths: Optional[bool] = None,
+        absolute_paths: Optional[bool] = None,
+        relative_to: Optional[str] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        one_file_system: Optional[bool] = None,
+        max_filesize: Optional[int] = None,
+        min_filesize: Optional[int] = None,
+        modified_after: Optional[Union[datetime, timedelta, float]] = None,
+        modified_before: Optional[Union[datetime, timedelta, float]] = None,
+        all_of: Optional[List[str]] = None,
+        none_of: Optional[List[str]] = None,
+        rank: Optional[bool] = None,
+        metadata: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        archives: Optional[bool] = None,
+        scope: Optional[Literal["tracked", "changed", "staged"]] = None,
+        batch_small_files: Optional[bool] = None,
+        skip_minified: Optional[Union[bool, int]] = None,
+        generated_globs: Optional[List[str]] = None,
+        hidden: Optional[bool] = None,
+        type_add: Optional[List[str]] = None,
+        aggregate: Optional[Literal["per_file", "per_dir", "total"]] = None,
+        include_zero: Optional[bool] = None,
+        special_files: Optional[Literal["skip", "report", "follow"]] = None,
+        backtrack_limit: Optional[int] = None,
+        spans: Optional[bool] = None,
+        max_bytes: Optional[int] = None,
+        dedupe: Optional[Literal["content"]] = None,
+        ignore_file_errors: Optional[Literal["warn", "ignore", "raise"]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        search_git_dir: Optional[bool] = None,
+        text_mode: Optional[Literal["str", "bytes"]] = None,
+        lossy: Optional[bool] = None,
+        column_unit: Optional[Literal["bytes", "chars", "display"]] = None,
+        tab_width: Optional[int] = None,
+        profile: Optional[bool] = None,
+        normalize_paths: Optional[bool] = None,
+        changed_files: Optional[Literal["ignore", "retry", "skip", "flag"]] = None,
+        root: Optional[str] = None,
+        ignore_parents: Optional[bool] = None,
+        spill_over: Optional[int] = None,
+        filter_logic: Optional[Literal["and", "or"]] = None,
+        context: Optional[Literal["block"]] = None,
+    ) -> bool:
+        """
+        Check whether anything matches, stopping at the first match (like rg -q).
+
+        Args:
+            pattern: Regular expression pattern or compiled Pattern to search for (required for this mode)
+            path: Directory or file path to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering; braces expand and a later glob wins (e.g., "*.py", ["*.{py,pyi}", "!tests/**"])
+            output_mode: Output mode - "quiet" returns a single bool; the walk stops at the first match
+            B: Number of lines before each match to include (requires content mode)
+            A: Number of lines after each match to include (requires content mode)
+            C: Number of lines before and after each match (overrides A and B)
+            n: Show line numbers (requires content mode)
+            i: Case insensitive search
+            type: File type filter: any ripgrep type or alias, case-insensitive (e.g., "rust", "python", ["python", "javascript"])
+            head_limit: Deprecated (emits DeprecationWarning); output lines in content mode, files or entries elsewhere. Use max_files / max_matches_total / max_matches_per_file
+            truncation_warning: Add "[Content truncated]" message if results were cut by head_limit or the max_* limits
+            compact_paths: Show filepath only once per content block (requires n=True and output_mode="content")
+            multiline: Enable multiline mode
+            timeout: Timeout in seconds for the search operation
+            stats: Return a (results, SearchStats) tuple instead of bare results
+            errors: Per-file error policy: "ignore" (default) skips unreadable/undecodable files, "collect" appends a list of (path, error) to the returned tuple, "raise" raises on the first failure
+            type_ignore_case: Match type globs case-insensitively, so type="python" also selects FILE.PY
+            files: Search exactly these files instead of walking path (relative entries resolve against path); ignore files are bypassed, glob and type still apply
+            walk_timeout: Maximum time in seconds spent enumerating directories (matching time is not counted); raises TimeoutError("directory traversal too slow ...") when exceeded
+            search_zip: Search inside .gz/.bz2/.xz/.zst files by decompressing them on the fly (rg -z)
+            preprocessor: Transform each candidate file before matching, like rg --pre: a command run as `command <path>` (file also on stdin) whose stdout is searched, or a callable taking the path and returning str/bytes. Only files passing glob/type filters are preprocessed; failures follow the errors policy. "ipynb" selects the built-in notebook mode: .ipynb files are searched cell by cell (code and markdown cells only), reported as "analysis.ipynb!/cells/3" with line numbers counted within the cell (cells count from 0)
+            file_headers: Ignored outside content mode
+            replace: Ignored outside content mode
+            order_within_file: Ignored outside content mode
+            engine: Regex engine: "default" (fast, linear time), "fancy" (fancy-regex backtracking engine supporting look-around and backreferences such as (?<!self\.)\bfoo\b) or "auto" (default, falling back to fancy when needed). Raises ValueError if the wheel lacks the fancy-regex feature. "pcre2" is recognized but never compiled in, so it raises ValueError as well
+            x: Only match when the pattern matches an entire line, like rg -x (works with alternations and multiline, unlike manual ^...$ anchoring)
+            strict_options: If False, options needing a feature this build lacks (engine="fancy" or "pcre2", search_zip, archives) fall back to the nearest supported behavior and a message is added to SearchStats.warnings instead of raising ValueError (default: True)
+            null_data: Treat NUL instead of newline as the line terminator, so NUL-separated records (find -print0 style) are matched one at a time (--null-data)
+            crlf: Treat \\r\\n as the line terminator so $ matches before it in every output mode (--crlf); ignored when null_data is set
+            encodings: Map of glob to encoding label (e.g. {"*.rc": "utf-16le", "*.csv": "latin-1"}); matching files are transcoded to UTF-8 before searching, the first matching glob wins and a BOM takes precedence
+            sort: Order files by path, modification time, creation time or size before searching, so every output mode (and head_limit truncation) is reproducible; "none" keeps walk order (default: "none")
+            sort_order: Direction for sort, like rg --sort vs --sortr (default: "asc")
+            threads: Worker threads that search file contents while the walk runs on its own thread; None or 0 uses one per CPU, 1 searches sequentially. Results keep walk order regardless (-j)
+            queue_size: Capacity of the bounded queues feeding files to the workers and results back, which caps memory held in flight (default: 64)
+            expand_vars: Expand a leading ~ and $NAME / ${NAME} environment variables in path, glob and files; undefined variables are left as written (default: False)
+            max_files: Stop after this many files with matches (in "files" mode, after this many listed files)
+            max_matches_total: Stop after this many matching lines across all files; context lines do not count
+            max_matches_per_file: Keep at most this many matching lines from each file, like rg -m; counts are capped too
+            group_by_file: Ignored outside content mode
+            forensic: Audit mode: no silent skipping. Returns (results, SearchStats, skips) where skips lists (path, reason) for every entry that was not searched (ignored, hidden, glob/type-filtered, binary, unreadable); a pruned directory is one entry. Requires errors="collect" (the default here) and no result limits
+            buffer_size: Read buffer capacity in bytes; larger buffers mean fewer, bigger reads (default: 8 KiB)
+            heap_limit: Most bytes a single line may occupy in memory; files with longer lines fail with a per-file error (see errors) instead of being buffered whole
+            regex_size_limit: Limit on the compiled regex size in bytes, like rg --regex-size-limit; larger patterns raise ValueError (engine="fancy": applies to the parts it delegates to the regex crate)
+            dfa_size_limit: Limit on the lazy DFA cache in bytes, like rg --dfa-size-limit
+            case_smart: Ignore case unless the pattern contains an uppercase literal, like rg -S (escapes such as \W or \p{Lu} do not count); i=True always ignores case
+            unicode: Unicode-aware \w, \b, \d and case folding (default True); False restricts them to ASCII, which is faster on ASCII-only corpora. Not supported by engine="fancy"
+            context_separator: Ignored outside content mode
+            heading: Ignored outside content mode
+            no_filename: Ignored outside content mode
+            passthru: Ignored outside content mode
+            color: Ignored outside content mode
+            max_columns: Ignored outside content mode
+            max_columns_preview: Ignored outside content mode
+            canonical_paths: Resolve symlinks and ./.. so every file is searched once and reported by its canonical path: relative to the working directory when inside it (e.g. "src/a.py" for path="./src", "/abs/src" or "src/../src"), absolute otherwise. Repeats count in stats.skipped.duplicate
+            absolute_paths: True writes result paths as absolute paths; False writes them relative to the working directory (with ".." where needed), however path= was spelled
+            relative_to: Write result paths relative to this directory (with ".." where needed); cannot be combined with absolute_paths=True
+            custom_ignore_filenames: Extra ignore file names honored in every directory like .gitignore (e.g. [".prettierignore"]); they take precedence over .ignore and .gitignore
+            ignore_file_paths: Extra gitignore-syntax files whose rules apply to the whole walk, like rg --ignore-file; they have the lowest precedence. Raises ValueError for a missing or invalid file
+            one_file_system: Don't descend into directories on a different filesystem than their search root (network, bind or proc mounts), like rg --one-file-system
+            max_filesize: Skip files larger than this many bytes (counted in stats.skipped.size)
+            min_filesize: Skip files smaller than this many bytes (counted in stats.skipped.size)
+            modified_after: Skip files last modified before this time: a datetime, a Unix timestamp, or a timedelta meaning that long ago (timedelta(days=1): touched in the last day). Counted in stats.skipped.modified
+            modified_before: Skip files last modified after this time; accepts the same values as modified_after
+            all_of: Only search files that contain every one of these patterns somewhere (AND); checked per file, stopping at the first missing pattern. Rejected files count in stats.skipped.content_filter
+            none_of: Skip files that contain any of these patterns (NOT). Uses the same case and engine settings as the main pattern
+            rank: Order matching files by relevance instead of walk order (files_with_matches, count and content modes; not with sort): a match in the file name scores highest, then one in a directory name, then the share of lines that match; shallower paths break near-ties. Content lines stay together per file
+            metadata: Not supported in this mode (raises ValueError); see files_with_matches
+            ignore_level: Shorthand like rg -u/-uu/-uuu: 1 stops honoring .gitignore, .ignore and custom ignore files (ignore_file_paths still apply), 2 also searches hidden files, 3 also searches files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (default: 0)
+            archives: Search the members of .zip, .jar, .whl, .tar and .tar.gz files instead of the archives themselves; results name them as "archive.zip!/inner/path.py". type= applies to member names, glob= to the archives on disk. Each archive is read once, start to end, and its members are held in memory until they are searched (default: False)
+            scope: Limit the search to files git lists under path, instead of walking it: "tracked" (in the index), "changed" (differs from HEAD in the worktree or index, plus untracked files that are not ignored) or "staged" (index differs from HEAD). Before the first commit every file in the index counts as changed and staged. Runs the git CLI (see features()["git"]); raises ValueError outside a repository or with files. Ignore and hidden rules do not apply to listed files; glob and type still do
+            batch_small_files: Hand files to worker threads in batches of 64 and read them into a buffer reused across files. Speeds up trees of many tiny files; results and order are unchanged
+            skip_minified: Skip minified files: True skips files whose first 64 KiB average over 300 bytes per line (which catches single-line bundles), an int sets that threshold. Skipped files count in stats.skipped.generated
+            generated_globs: Skip files matching any of these globs, e.g. ["*.lock", "package-lock.json", "*.min.js"]; as in .gitignore, globs without a "/" match the file name. Skipped files count in stats.skipped.generated
+            hidden: Search hidden files and directories (--hidden); overrides the hidden part of ignore_level when given
+            type_add: Extra file type definitions in --type-add form ("web:*.{html,css}", "src:include:py,rust") that type can then select
+            aggregate: How count mode combines counts: "per_file" (default) returns {path: count}, "per_dir" sums files directly inside each directory into {directory: count}, "total" returns one int. Other output modes raise ValueError unless it is "per_file"
+            include_zero: Count mode only; other output modes raise ValueError
+            special_files: Entries that are not regular files (symlinks, dangling symlinks, fifos, sockets, devices): "skip" (default) leaves them out silently, "report" counts them in stats.skipped.special and lists them with errors="collect", "follow" also searches symlinks to regular files. forensic=True defaults to "report"
+            backtrack_limit: Backtracking steps the fancy engine may take per match attempt (fancy-regex defaults to 1,000,000). A file that exceeds it fails with "regex backtrack limit exceeded" under the errors policy and the search continues. The default engine runs in linear time and ignores it
+            spans: Add "spans", the (start, end) character ranges of the matches within content, to each dict in matches and first_match mode and in content mode with group_by_file=True. Other modes, replace and color raise ValueError
+            max_bytes: Stop before the file that would take the data read past this many bytes; the results are marked truncated
+            dedupe: "content" searches each distinct file content once; stats.duplicates lists the skipped copies under the first file found
+            ignore_file_errors: Unparsable .gitignore/.ignore lines: "warn" (default) emits a UserWarning each, "ignore" drops them, "raise" raises WalkError; the rest of the file still applies
+            iglob: Like glob, but matched case-insensitively (rg --iglob); combines with glob
+            search_git_dir: True searches .git (config, hooks, packed-refs) even without hidden=True; False skips it even with hidden=True; None lets hidden decide
+            text_mode: "bytes" returns content lines as lossless bytes (undecodable files included rather than skipped as binary); content mode only
+            lossy: False fails undecodable files instead of replacing bytes with U+FFFD when ignore_level=3 searches binary files
+            column_unit: What vimgrep columns (default "bytes") and spans (default "chars") count; "display" expands tabs to tab_width stops
+            tab_width: Tab stop width for column_unit="display" (default 8)
+            profile: Time the phases of the search (setup, walk, read, matching, format) in SearchStats.profile; implies stats=True
+            normalize_paths: Resolve ./.. in result paths and drop Windows \\\\?\\ verbatim prefixes (long and UNC paths); absolute_paths and relative_to always do
+            changed_files: Files whose size or mtime changes while searched: "ignore" (default), "retry" once, "skip" them, or "flag" their results; listed in SearchStats.changed
+            root: Directory ignore rules are anchored at: ignore files from root down to path apply (none above it), and .gitignore applies without a .git directory; path must be inside root
+            ignore_parents: False skips ignore files in directories above path (rg --no-ignore-parent); default True
+            spill_over: Content mode: past this many result lines, return a SpilledResults reader over a temporary JSONL file instead of a list
+            filter_logic: "and" (default) searches files matching both glob and type; "or" searches files matching either, with "!" globs still excluding
+            context: "block" shows each match with its enclosing block, found from indentation, instead of A/B/C lines (content mode only)
+
+        Returns:
+            True if any file matches, False otherwise
+        """
+        ...
+
+    def search_page(
+        self,
+        pattern: Optional[Union[str, "Pattern"]] = None,
+        cursor: Optional[str] = None,
+        page_size: int = 100,
+        **kwargs: Any,
+    ) -> Tuple[Union[List[str], Dict[str, int], List[Dict[str, Any]]], Optional[str]]:
+        """
+        Return one page of search() results and a cursor for the next page.
+
+        Files are searched in path order, a chunk at a time, so a page only searches the
+        files it needs rather than the whole tree. Pass the returned cursor back to get the
+        next page; a page may end partway through a file's matches.
+
+        Args:
+            pattern: As for search(); may be None in "files" mode
+            cursor: Opaque cursor from the previous page, or None for the first page
+            page_size: Most entries per page (matching lines, files, or count entries)
+            **kwargs: search() keywords. output_mode must be "files_with_matches" (default),
+                "files_without_match", "files", "count", "matches" or "first_match"
+
+        Returns:
+            (results, cursor): results in the shape search() returns for output_mode, and
+            the cursor for the next page, or None after the last page
+
+        Raises:
+            TypeError: For options that change the shape or order of results (files, sort,
+                sort_order, rank, head_limit, max_files, max_matches_total, truncation_warning,
+                stats, forensic, profile, aggregate, metadata, relative_to, errors="collect")
+            ValueError: For other output modes, page_size=0 or an invalid cursor
+        """
+        ...
+
+    def find_files(self, pattern: Optional[Union[str, "Pattern"]] = None, **kwargs: Any) -> FileSet:
+        """
+        Search in files_with_matches mode and keep the files as a FileSet.
+
+        FileSet.refine() and FileSet.search() then read only those files, so follow-up
+        searches skip the walk and the files that already failed to match.
+
+        Args:
+            pattern: As for search()
+            **kwargs: search() keywords except output_mode
+
+        Raises:
+            TypeError: For options that change the shape of the results (output_mode, stats,
+                forensic, profile, metadata, aggregate, errors="collect")
+        """
+        ...
+
+    def search_stream(
+        self,
+        pattern: Union[str, "Pattern"],
+        fileobj: BinaryIO,
+        output_mode: Literal["content", "files_with_matches", "files_without_match", "count", "matches", "first_match", "vimgrep", "quiet"] = "content",
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        x: Optional[bool] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        replace: Optional[str] = None,
+        color: Optional[Union[Literal["ansi", "never"], Tuple[str, str]]] = None,
+        passthru: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        label: Optional[str] = None,
+    ) -> Union[List[str], Dict[str, int], List[Dict[str, Any]], bool]:
+        """
+        Search a binary file-like object instead of files on disk.
+
+        The stream is read in chunks through ``fileobj.read(n)``, so pipes such as
+        ``sys.stdin.buffer`` can be searched without a temporary file
+        (``cat log | python tool.py``).
+
+        Args:
+            pattern: Regular expression pattern or a compiled Pattern
+            fileobj: Object whose ``read(n)`` returns bytes (e.g. ``sys.stdin.buffer``, ``io.BytesIO``)
+            output_mode: Same modes as search(), except "files" and "paths"; defaults to "content"
+            B, A, C, n, i, multiline, x, engine, replace, color, passthru, null_data, crlf:
+                As for search()
+            buffer_size: Size of each read(n) chunk in bytes (default 8 KiB)
+            label: Name reported in place of a path (default "<stdin>"); content lines
+                carry no path, as with rg reading stdin
+
+        Returns:
+            The same shape as search() returns for output_mode
+
+        Raises:
+            Any exception raised by ``fileobj.read()`` propagates unchanged.
+            ValueError: If read() returns str, or the stream is not valid UTF-8
+        """
+        ...
+
+    def search_many(
+        self,
+        patterns: Dict[str, str],
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Optional[Literal["files_with_matches", "count", "content"]] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        timeout: Optional[float] = None,
+        threads: Optional[int] = None,
+    ) -> Dict[str, Any]:
+        """
+        Search several named patterns in a single walk.
+
+        The patterns are combined into one matcher, so each file is read once; every
+        matching line is then attributed to each pattern that matches it. Running N
+        search() calls over the same tree does N walks instead.
+
+        Args:
+            patterns: {name: regex} to search for; results keep this order
+            path: Directory or file to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering
+            output_mode: "files_with_matches" (default), "count" or "content"
+            n: Include line numbers in content results
+            i: Case insensitive matching for every pattern
+            type: File type filter
+            timeout: Maximum time in seconds for the whole search
+            threads: Worker threads searching file contents
+
+        Returns:
+            Dict keyed by pattern name (every name is present), each value shaped like
+            the matching search() result: a list of files, a {path: count} dict, or a
+            list of "path:content" / "path:line:content" lines
+
+        Raises:
+            ValueError: If a pattern is invalid (the message names it)
+        """
+        ...
+
+    def search_with_sink(
+        self,
+        pattern: str,
+        callback: Callable[[Dict[str, Any]], Any],
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+    ) -> None:
+        """
+        Search files and call back with every low-level searcher event.
+
+        For custom aggregations that no output mode covers. The callback runs on the
+        calling thread, one file at a time in walk order; matching runs without the GIL.
+
+        Args:
+            pattern: Regular expression pattern
+            callback: Called with an event dict; return False to stop searching the current
+                file, or "stop" to end the whole search. Any other return value continues.
+            path: Directory or file to search (default: current directory)
+            glob: Glob pattern or list of patterns for file filtering
+            B: Lines of context before each match
+            A: Lines of context after each match
+            C: Lines of context before and after each match
+            i: Case insensitive matching
+            type: File type filter
+            multiline: Enable multiline mode
+            timeout: Maximum time in seconds for the whole search
+
+        Every event has "type" and "path". By type:
+          - begin: A file is about to be searched
+          - match: line_number, absolute_offset and bytes (the raw matched line(s),
+            terminator included)
+          - context: the same keys, plus kind ("before", "after" or "other")
+          - context_break: Non-contiguous lines follow (where rg prints "--")
+          - end: bytes_searched; sent even when the callback stopped the file, but not
+            after "stop"
+
+        Raises:
+            Any exception raised by the callback propagates and ends the search.
+            TimeoutError: If the search exceeds timeout
+        """
+        ...
+
+    def search_rev(
+        self,
+        pattern: str,
+        rev: str = "HEAD",
+        repo: Optional[str] = None,
+        *,
+        glob: Optional[Union[str, List[str]]] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+    ) -> List[str]:
+        """
+        Search the files of a git revision without checking it out.
+
+        Blobs are read from the object database through the git CLI (`git ls-tree` and
+        `git cat-file --batch`), so the worktree is neither read nor touched. Ignore
+        files are not consulted: every file committed at `rev` is a candidate.
+        Options not given take this Grep's defaults.
+
+        Args:
+            pattern: Regular expression pattern
+            rev: Any revision git understands, e.g. "HEAD~5", "v1.2.0" or a commit id
+            repo: Path inside the repository (default: current directory)
+            glob: Glob pattern or list of patterns matched against paths within the tree
+            i: Case insensitive matching
+            type: File type filter matched against paths within the tree
+            multiline: Enable multiline mode
+            timeout: Maximum time in seconds
+
+        Returns:
+            List of "rev:path:line:content" strings in tree order, with paths relative to
+            the repository root. Files that are not valid UTF-8 are skipped
+
+        Raises:
+            ValueError: If git is missing, repo is not a repository or rev does not exist
+            TimeoutError: If the search exceeds timeout
+        """
+        ...
+
+    def compare(
+        self,
+        pattern: str,
+        before: str,
+        after: str,
+        *,
+        glob: Optional[Union[str, List[str]]] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        multiline: Optional[bool] = None,
+        timeout: Optional[float] = None,
+        threads: Optional[int] = None,
+    ) -> Dict[str, Any]:
+        """
+        Run the same pattern over two roots and report what changed between them.
+
+        Files are paired by their path relative to each root. Matching lines are
+        paired by content, so lines that only moved are not reported. Options not given
+        take this Grep's defaults.
+
+        Args:
+            pattern: Regular expression pattern
+            before: Root of the "before" tree (e.g. the old checkout)
+            after: Root of the "after" tree
+            glob: Glob pattern or list of patterns to filter files in both trees
+            i: Case insensitive matching
+            type: File type filter applied to both trees
+            multiline: Enable multiline mode
+            timeout: Maximum time in seconds for both searches combined
+            threads: Worker threads that search file contents; None or 0 uses one per CPU
+
+        Returns:
+            Dict with keys:
+              - deltas: {relative_path: (before_count, after_count, delta)} for files whose
+                match count changed
+              - added: List of (relative_path, line_number, content) matches only in `after`
+              - removed: List of (relative_path, line_number, content) matches only in `before`
+        """
+        ...
+
+    def watch(
+        self,
+        pattern: str,
+        path: str,
+        callback: Callable[[Dict[str, Any]], Any],
+        *,
+        glob: Optional[Union[str, List[str]]] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        multiline: Optional[bool] = None,
+        debounce_ms: Optional[int] = None,
+        encodings: Optional[Dict[str, str]] = None,
+        lossy: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        hidden: Optional[bool] = None,
+        search_git_dir: Optional[bool] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        ignore_parents: Optional[bool] = None,
+    ) -> Watcher:
+        """
+        Watch a tree and report how file changes affect the pattern's matches.
+
+        Returns immediately. The matches present when watching starts are the baseline;
+        after each burst of changes (quiet for debounce_ms), changed files are searched
+        again and callback is called once per file whose matches changed. Ignore rules,
+        hidden, glob, type and decoding options apply as in search(); new files are
+        picked up as they appear. Each file is searched whole, so with multiline=True a
+        match may span lines. Options not given take this Grep's defaults.
+        Requires the 'watch' feature (see features()).
+
+        Args:
+            pattern: Regular expression pattern
+            path: Directory to watch recursively
+            callback: Called with a dict per changed file (on a background thread)
+            glob: Glob pattern or list of patterns to filter files
+            i: Case insensitive matching
+            type: File type filter
+            multiline: Enable multiline mode; each line a match spans is reported
+            debounce_ms: Quiet period in milliseconds before changes are searched (default 100)
+            encodings: Encodings to transcode files from, by glob, as in search()
+            lossy: As in search(), with ignore_level=3
+            ignore_level: As in search(); level 3 also watches binary files
+            hidden: Include hidden files and directories, as in search()
+            search_git_dir: Walk into or skip .git whatever `hidden` says, as in search()
+            custom_ignore_filenames: Extra ignore file names, as in search()
+            ignore_file_paths: Extra ignore files, as in search()
+            ignore_parents: Read ignore files above path, as in search()
+
+        The callback receives a dict with keys:
+          - path: Changed file, joined to path as search() reports it
+          - added: [(line_number, content)] lines that now match
+          - removed: [(line_number, content)] lines that no longer match (all of them for
+            a deleted file)
+        Lines are paired by content, so matches that only moved are not reported.
+
+        Returns:
+            A running Watcher
+        """
+        ...
+
+    def start(
+        self,
+        pattern: Optional[Union[str, Pattern]] = None,
+        path: Optional[str] = None,
+        glob: Optional[Union[str, List[str]]] = None,
+        output_mode: Optional[Literal["files_with_matches", "files_without_match", "content", "count", "files", "vimgrep", "matches", "first_match", "quiet", "paths"]] = None,
+        B: Optional[int] = None,
+        A: Optional[int] = None,
+        C: Optional[int] = None,
+        n: Optional[bool] = None,
+        i: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        multiline: Optional[bool] = None,
+        errors: Optional[Literal["ignore", "collect", "raise"]] = None,
+    ) -> SearchSession:
+        """
+        Start an incremental search driven by SearchSession.step().
+
+        Nothing is searched until the first step. Arguments have the same meaning as in search().
+
+        Example:
+            >>> session = grep.start("TODO", path="src", output_mode="content", n=True)
+            >>> def poll():
+            ...     results_view.extend(session.step(max_millis=20))
+            ...     if not session.done:
+            ...         root.after(1, poll)
+        """
+        ...
+
+    def compile(
+        self,
+        pattern: str,
+        i: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        type_ignore_case: Optional[bool] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        x: Optional[bool] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+    ) -> Pattern:
+        """
+        Compile a pattern once for reuse across many searches.
+
+        Args:
+            pattern: Regular expression pattern
+            i: Case insensitive matching
+            multiline: Enable multiline mode
+            type: Default file type filter for searches using this pattern
+            type_ignore_case: Match type globs case-insensitively
+            engine: Regex engine ("default", "fancy" or "auto"), as in search()
+            x: Require the pattern to match entire lines
+            case_smart: Ignore case unless the pattern contains an uppercase literal, as in search()
+            unicode: Unicode-aware character classes (default True), as in search()
+
+        Returns:
+            A Pattern that can be passed as the pattern argument of search()
+        """
+        ...
+
+    def validate_pattern(
+        self,
+        pattern: str,
+        *,
+        i: Optional[bool] = None,
+        case_smart: Optional[bool] = None,
+        unicode: Optional[bool] = None,
+        multiline: Optional[bool] = None,
+        x: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        engine: Optional[Literal["default", "fancy", "auto", "pcre2"]] = None,
+        regex_size_limit: Optional[int] = None,
+        dfa_size_limit: Optional[int] = None,
+        backtrack_limit: Optional[int] = None,
+        strict_options: Optional[bool] = None,
+    ) -> Dict[str, Any]:
+        """
+        Compile a pattern with the same settings as search() without searching.
+
+        Takes search()'s regex flags, which default to this Grep's defaults for them, so a
+        pattern is valid here exactly when search() with the same flags accepts it.
+
+        Args:
+            pattern: Regular expression pattern to check
+            i, case_smart, unicode, multiline, x, crlf, null_data, engine, regex_size_limit,
+            dfa_size_limit, backtrack_limit, strict_options: As for search()
+
+        Returns:
+            Dict with keys:
+              - valid: True if the pattern compiles
+              - error: Short error message (None if valid)
+              - position: Byte offset where the error starts (None if unknown)
+              - span: (start, end) byte offsets of the offending text (None if unknown)
+              - suggestion: Hint for fixing the pattern (None if there is none)
+        """
+        ...
+
+    def preview(
+        self,
+        path: str,
+        line: int,
+        before: int = 5,
+        after: int = 5,
+        *,
+        encodings: Optional[Dict[str, str]] = None,
+        search_zip: Optional[bool] = None,
+        crlf: Optional[bool] = None,
+        null_data: Optional[bool] = None,
+        ignore_level: Optional[int] = None,
+        lossy: Optional[bool] = None,
+        buffer_size: Optional[int] = None,
+        heap_limit: Optional[int] = None,
+    ) -> List[Tuple[int, str]]:
+        """
+        Return a window of lines around a line of a single file.
+
+        Lines are decoded exactly as in content search, with search()'s decoding options
+        (defaulting to this Grep's defaults for them). The file is read only as far as the
+        window. A file search would treat as binary (invalid UTF-8, or a NUL byte in the
+        lines read) raises IOErrorInfo unless ignore_level=3.
+
+        Args:
+            path: File to read
+            line: 1-based line number at the center of the window
+            before: Number of lines to include before `line`
+            after: Number of lines to include after `line`
+            encodings, search_zip, crlf, null_data, ignore_level, lossy, buffer_size,
+            heap_limit: As for search()
+
+        Returns:
+            List of (line_number, content) tuples, clipped to the file bounds
+        """
+        ...
+
+    def explain(
+        self,
+        path: str,
+        root: Optional[str] = None,
+        *,
+        glob: Optional[Union[str, List[str]]] = None,
+        iglob: Optional[Union[str, List[str]]] = None,
+        type: Optional[Union[str, List[str]]] = None,
+        type_add: Optional[List[str]] = None,
+        type_ignore_case: Optional[bool] = None,
+        custom_ignore_filenames: Optional[List[str]] = None,
+        ignore_file_paths: Optional[List[str]] = None,
+        ignore_level: Optional[int] = None,
+        hidden: Optional[bool] = None,
+        search_git_dir: Optional[bool] = None,
+        one_file_system: Optional[bool] = None,
+        ignore_parents: Optional[bool] = None,
+    ) -> Explanation:
+        """
+        Explain whether a search under `root` would reach `path`, and which rule decided.
+
+        The verdict comes from the same directory walk search() runs with these options.
+        To name the deciding rule, the walker's filters are replayed on each directory
+        level down to `path`: glob overrides first, then ignore files (custom ignore
+        files, .ignore, .gitignore, git excludes, the global gitignore, explicit ignore
+        files), then hidden names, then the type filter.
+        A file inside an ignored or hidden directory is reported with that directory
+        as matched_path. Options not given take this Grep's defaults, as in search().
+        search()'s root= (the ignore root) has no counterpart here, since `root` is the
+        search root.
+
+        Args:
+            path: File or directory to explain
+            root: Search root the path is walked from (default: current directory)
+            glob: Glob pattern or list of patterns, as passed to search()
+            iglob: Case-insensitive glob pattern(s), as passed to search()
+            type: File type(s), as passed to search()
+            type_add: Custom type definitions, as passed to search()
+            type_ignore_case: Match type globs case-insensitively, as in search()
+            custom_ignore_filenames: Extra ignore file names, as passed to search()
+            ignore_file_paths: Extra ignore files, as passed to search()
+            ignore_level: As passed to search(); levels 1 and 2 turn off ignore-file and hidden verdicts
+            hidden: Include hidden files and directories, as in search()
+            search_git_dir: Walk into or skip .git whatever `hidden` says, as in search()
+            one_file_system: Stay on the root's filesystem, as in search()
+            ignore_parents: Read ignore files above the root, as in search()
+
+        Returns:
+            Explanation with the verdict, the deciding rule and the ignore file it came from
+
+        Raises:
+            ValueError: If the path does not exist or is not under root
+        """
+        ...
+
+def merge_results(
+    results: List[Union[List[str], Dict[str, int], List[Dict[str, Any]], bool]],
+    output_mode: Optional[Literal["content", "files_with_matches", "files_without_match", "count", "files", "vimgrep", "matches", "first_match", "quiet", "paths"]] = None,
+    head_limit: Optional[int] = None,
+    truncation_warning: Optional[bool] = None,
+) -> Union[List[str], Dict[str, int], List[Dict[str, Any]], bool]:
+    """
+    Merge the results of several searches run with the same output mode.
+
+    Entries are deduplicated, sorted by path (and line number in content mode),
+    and head_limit is re-applied to the merged output. Truncation markers,
+    file headers and "--" separators in the inputs are dropped; separators are regenerated.
+
+    Args:
+        results: Results returned by Grep.search()
+        output_mode: Output mode the results were produced with (default: "count"
+            for dicts, "quiet" for bools, "files_with_matches" for lists). Quiet results
+            merge to True if any of them is True. Content results must have
+            been produced with n=True and without compact_paths.
+        head_limit: Maximum number of entries (output lines in content mode)
+        truncation_warning: Add "[Content truncated]" if the merge exceeded head_limit
+
+    Returns:
+        Merged results in the same shape as the inputs. Duplicate paths in count
+        mode keep the highest count.
+    """
+    ...
+
+def search(
+    pattern: Optional[Union[str, Pattern]] = None,
+    path: Optional[str] = None,
+    **kwargs: Any,
+) -> Any:
+    """
+    Search with a default Grep instance; takes the same arguments as Grep.search.
+
+    Use a Grep instance directly to reuse configuration across searches.
+    """
+    ...
+
+def files(path: Optional[str] = None, **kwargs: Any) -> List[str]:
+    """
+    List the files a search would look at, like `rg --files`.
+
+    Same as Grep().search(None, path, output_mode="files", **kwargs).
+    Raises TypeError if output_mode is given.
+    """
+    ...
+
+def count(
+    pattern: Union[str, Pattern],
+    path: Optional[str] = None,
+    **kwargs: Any,
+) -> Dict[str, int]:
+    """
+    Count matching lines per file, like `rg --count`.
+
+    Same as Grep().search(pattern, path, output_mode="count", **kwargs).
+    Raises TypeError if output_mode is given.
+    """
+    ...
+
+def features() -> Dict[str, bool]:
+    """
+    Report which optional capabilities this build of pyripgrep includes.
+
+    Returns:
+        Dict mapping capability name to availability:
+          - decompression: search_zip=True (gzip/bzip2/xz/zstd)
+          - archives: archives=True (zip and tar members as archive!/member paths)
+          - fancy_regex: engine="fancy" (look-around and backreferences)
+          - watch: Grep.watch() (filesystem notifications)
+          - pcre2: PCRE2 engine (not currently available)
+          - git: scope="tracked" / "changed" / "staged" and search_rev(); True when the git CLI is on PATH
+          - tree_sitter: syntax-aware search (not currently available)
+          - index: persistent search index (not currently available)
+    """
+    ...
+
+def compile_check(
+    pattern: str,
+    multiline: bool = False,
+    engine: Literal["default", "fancy", "auto"] = "default",
+) -> Dict[str, Any]:
+    """
+    Compile a pattern without running a search and report whether it is valid.
+
+    Args:
+        pattern: Regular expression pattern to check
+        multiline: Enable multiline mode
+        engine: Regex engine to compile with (see Grep.search)
+
+    Returns:
+        Dict with the same keys as Grep.validate_pattern plus:
+          - look_around: True if the pattern uses look-around or backreferences,
+            which the default engine rejects
+    """
+    ...
@@ -1,40 +1,123 @@
-use pyo3::exceptions::{PyValueError, PyTimeoutError};
+#![allow(non_local_definitions)] // emitted by pyo3 0.20 macro expansions
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyDeprecationWarning, PyTypeError, PyUserWarning, PyValueError, PyTimeoutError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::PyTypeInfo;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyTuple};
 use std::path::{Path, PathBuf};
-use std::collections::{HashSet, HashMap};
-use ignore::{WalkBuilder, types::TypesBuilder, overrides::OverrideBuilder};
-use grep_searcher::{Searcher, sinks};
-use grep_regex::{RegexMatcher, RegexMatcherBuilder};
-use grep_matcher::Matcher;
+use std::collections::{BTreeMap, BTreeSet, HashSet, HashMap};
+use ignore::{WalkBuilder, types::TypesBuilder, overrides::{Override, OverrideBuilder}};
+use grep_searcher::{Searcher, SearcherBuilder, Sink, SinkContext, SinkContextKind, SinkError, SinkFinish, SinkMatch, sinks};
+use grep_regex::{RegexCaptures, RegexMatcher, RegexMatcherBuilder};
+use grep_matcher::{Captures, LineTerminator, Matcher};
+use memchr::memmem;
+use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use std::fs::File;
-use std::time::{Duration, Instant};
-use std::io;
+use std::time::{Duration, Instant, SystemTime};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::panic::{self, AssertUnwindSafe};
 
 /// --- Pure-Rust error used while GIL is released ---
 #[derive(Debug)]
 enum RGErr {
     Walk(ignore::Error),
     Io(io::Error),
+    File(String, String),
     Timeout,
+    WalkTimeout(Duration),
+}
+
+create_exception!(pyripgrep, PatternError, PyValueError, "A pattern failed to compile");
+create_exception!(pyripgrep, WalkError, PyValueError, "Directory traversal failed");
+create_exception!(pyripgrep, IOErrorInfo, PyValueError, "A file could not be read or searched");
+create_exception!(pyripgrep, SearchTimeout, PyTimeoutError, "A search ran past timeout or walk_timeout");
+
+/// An exception of class `T` carrying the fields every pyripgrep exception has:
+/// `path` and `offset` (None when they don't apply) and `cause`
+fn structured_error<T: PyTypeInfo>(message: String, path: Option<String>, offset: Option<usize>, cause: String) -> PyErr {
+    let err = PyErr::new::<T, _>(message);
+    Python::with_gil(|py| {
+        let value = err.value(py);
+        // Setting attributes on a fresh exception instance cannot fail
+        let _ = value.setattr("path", path);
+        let _ = value.setattr("offset", offset);
+        let _ = value.setattr("cause", cause);
+    });
+    err
+}
+
+/// PatternError for a pattern that failed to compile, with the byte offset of the offending
+/// part when the regex syntax parser can locate it
+fn pattern_error(pattern: &str, message: String, cause: String) -> PyErr {
+    let offset = match regex_syntax::Parser::new().parse(pattern) {
+        Err(regex_syntax::Error::Parse(err)) => Some(err.span().start.offset),
+        Err(regex_syntax::Error::Translate(err)) => Some(err.span().start.offset),
+        _ => None,
+    };
+    let err = structured_error::<PatternError>(message, None, offset, cause);
+    Python::with_gil(|py| {
+        let _ = err.value(py).setattr("pattern", pattern);
+    });
+    err
+}
+
+/// WalkError for a search root that does not exist
+fn path_not_found(path: &str) -> PyErr {
+    structured_error::<WalkError>(format!("Path not found: {}", path), Some(path.to_string()), None, "path not found".to_string())
 }
 
 fn to_pyerr(e: RGErr) -> PyErr {
     match e {
-        RGErr::Timeout => PyTimeoutError::new_err("search timed out"),
-        RGErr::Walk(err) => PyValueError::new_err(format!("Walk error: {}", err)),
-        RGErr::Io(err) => PyValueError::new_err(format!("IO error: {}", err)),
+        RGErr::Timeout => structured_error::<SearchTimeout>("search timed out".to_string(), None, None, "timeout".to_string()),
+        RGErr::WalkTimeout(budget) => structured_error::<SearchTimeout>(
+            format!("directory traversal too slow: enumeration exceeded walk_timeout of {}s", budget.as_secs_f64()),
+            None,
+            None,
+            "walk_timeout".to_string(),
+        ),
+        RGErr::Walk(err) => {
+            let path = walk_error_path(&err);
+            let path = (!path.as_os_str().is_empty()).then(|| path.to_string_lossy().to_string());
+            structured_error::<WalkError>(format!("Walk error: {}", err), path, None, err.to_string())
+        }
+        RGErr::Io(err) => structured_error::<IOErrorInfo>(format!("IO error: {}", err), None, None, err.to_string()),
+        RGErr::File(path, err) => {
+            structured_error::<IOErrorInfo>(format!("Error searching {}: {}", path, err), Some(path), None, err)
+        }
     }
 }
 
 
+/// Marker appended to results when `truncation_warning` is enabled
+const TRUNCATION_MARKER: &str = "[Content truncated]";
+
 /// Output modes for search results
 #[derive(Debug, Clone, PartialEq)]
 pub enum OutputMode {
     Content,
     FilesWithMatches,
+    /// Searched files with zero matches (rg --files-without-match)
+    FilesWithoutMatch,
     Count,
     Files,
+    Vimgrep,
+    Matches,
+    /// Each matching file's first matching line with its byte offset; each file's search stops there
+    FirstMatch,
+    /// A single bool: whether anything matched; the walk stops at the first match (rg -q)
+    Quiet,
+    /// The pattern is matched against file paths instead of contents (like rg --files | rg)
+    Paths,
 }
 
 impl OutputMode {
@@ -42,780 +125,9109 @@ impl OutputMode {
         match s {
             "content" => Ok(OutputMode::Content),
             "files_with_matches" => Ok(OutputMode::FilesWithMatches),
+            "files_without_match" => Ok(OutputMode::FilesWithoutMatch),
             "count" => Ok(OutputMode::Count),
             "files" => Ok(OutputMode::Files),
+            "vimgrep" => Ok(OutputMode::Vimgrep),
+            "matches" => Ok(OutputMode::Matches),
+            "first_match" => Ok(OutputMode::FirstMatch),
+            "quiet" => Ok(OutputMode::Quiet),
+            "paths" => Ok(OutputMode::Paths),
             _ => Err(PyValueError::new_err(format!("Invalid output mode: {}", s))),
         }
     }
 }
 
+/// How per-file failures (unreadable or undecodable files) are handled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorPolicy {
+    Ignore,
+    Collect,
+    Raise,
+}
+
+impl ErrorPolicy {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "ignore" => Ok(ErrorPolicy::Ignore),
+            "collect" => Ok(ErrorPolicy::Collect),
+            "raise" => Ok(ErrorPolicy::Raise),
+            _ => Err(PyValueError::new_err(format!("Invalid errors policy: {}", s))),
+        }
+    }
+}
+
+/// How unparsable lines in .gitignore and other ignore files are handled (ignore_file_errors=)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum IgnoreFileErrors {
+    /// Emit a UserWarning per error once the search finishes
+    #[default]
+    Warn,
+    Ignore,
+    /// Fail the search with a WalkError
+    Raise,
+}
+
+impl IgnoreFileErrors {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "warn" => Ok(IgnoreFileErrors::Warn),
+            "ignore" => Ok(IgnoreFileErrors::Ignore),
+            "raise" => Ok(IgnoreFileErrors::Raise),
+            _ => Err(PyValueError::new_err(format!("Invalid ignore_file_errors policy: {}", s))),
+        }
+    }
+}
+
+/// What to do with a file that changes while it is being searched (changed_files=)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum ChangedFiles {
+    /// No checks: results reflect whatever was read
+    #[default]
+    Ignore,
+    /// Search it once more; if it changes again, keep the second result and flag it
+    Retry,
+    /// Leave its results out and count it in SkipCounts.changed
+    Skip,
+    /// Keep its results and list it in SearchStats.changed
+    Flag,
+}
+
+impl ChangedFiles {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "ignore" => Ok(ChangedFiles::Ignore),
+            "retry" => Ok(ChangedFiles::Retry),
+            "skip" => Ok(ChangedFiles::Skip),
+            "flag" => Ok(ChangedFiles::Flag),
+            _ => Err(PyValueError::new_err(format!("Invalid changed_files policy: {}", s))),
+        }
+    }
+}
+
 /// Search result for content mode
 #[derive(Debug, Clone)]
 pub struct ContentResult {
     pub path: String,
     pub line_number: u64,
+    /// Last line of the match; differs from line_number only for multiline matches
+    pub end_line_number: u64,
+    /// The matched line, or the matched lines joined with `\n` for a multiline match
     pub content: String,
     pub before_context: Vec<String>,
     pub after_context: Vec<String>,
+    /// Number of matches on the line (only counted for relevance ordering)
+    pub occurrences: usize,
+    /// How many of the context lines the searcher reported with this match; the rest are
+    /// shared with a neighbouring match and shown with it
+    pub shown_before: usize,
+    pub shown_after: usize,
+    /// Whether the searcher broke the context before this match (a `--` in flat output)
+    pub block_start: bool,
+    /// Character ranges of the matches within content (only computed for spans=True)
+    pub spans: Option<Vec<(usize, usize)>>,
 }
 
-/// Search result for count mode
-#[derive(Debug, Clone)]
-pub struct CountResult {
-    pub path: String,
-    pub count: u64,
+impl ContentResult {
+    /// The before and after context lines shown with this match in flat output
+    fn shown_context(&self) -> (&[String], &[String]) {
+        let before = &self.before_context[self.before_context.len().saturating_sub(self.shown_before)..];
+        let after = &self.after_context[..self.shown_after.min(self.after_context.len())];
+        (before, after)
+    }
 }
 
-/// Timeout helper functions
-#[inline]
-fn deadline_from_secs(timeout: Option<f64>) -> Option<Instant> {
-    timeout.map(|t| Instant::now() + Duration::from_secs_f64(t.max(0.0)))
+/// One match as reported by the searcher, with the context lines it reported around it
+#[derive(Debug, Clone, Copy)]
+struct ContextSpan {
+    first_line: u64,
+    last_line: u64,
+    shown_before: usize,
+    shown_after: usize,
+    block_start: bool,
 }
 
-#[inline]
-fn timed_out(deadline: Option<Instant>) -> bool {
-    match deadline {
-        Some(d) => Instant::now() >= d,
-        None => false,
+/// Sink that records where the searcher puts matches, context lines and context breaks,
+/// so overlapping and adjacent context is merged exactly as rg merges it
+struct ContextSink {
+    terminator: u8,
+    /// Without context the searcher never breaks; non-adjacent matches still start a new block
+    has_context: bool,
+    spans: Vec<ContextSpan>,
+    /// Context lines seen since the last match, shown before the next one
+    pending: usize,
+    pending_break: bool,
+}
+
+impl ContextSink {
+    fn new(terminator: u8, has_context: bool) -> Self {
+        ContextSink { terminator, has_context, spans: Vec::new(), pending: 0, pending_break: false }
+    }
+
+    /// The recorded spans; with passthru, lines after the last match belong to it
+    fn finish(mut self) -> Vec<ContextSpan> {
+        if let Some(last) = self.spans.last_mut() {
+            last.shown_after += self.pending;
+        }
+        self.spans
     }
 }
 
-/// Main Grep interface that provides ripgrep-like functionality
-#[pyclass(module = "pyripgrep")]
-pub struct Grep {}
+impl Sink for ContextSink {
+    type Error = io::Error;
 
-#[pymethods]
-impl Grep {
-    #[new]
-    fn new() -> Self {
-        Grep {}
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, io::Error> {
+        let first_line = mat.line_number().unwrap_or(1);
+        let bytes = mat.bytes();
+        let matched = bytes.strip_suffix(&[self.terminator]).unwrap_or(bytes);
+        let extra_lines = matched.iter().filter(|&&b| b == self.terminator).count() as u64;
+        let block_start = match self.spans.last() {
+            None => true,
+            Some(_) if self.has_context => self.pending_break,
+            Some(prev) => prev.last_line + 1 != first_line,
+        };
+        self.spans.push(ContextSpan {
+            first_line,
+            last_line: first_line + extra_lines,
+            shown_before: std::mem::take(&mut self.pending),
+            shown_after: 0,
+            block_start,
+        });
+        self.pending_break = false;
+        Ok(true)
     }
 
-    /// Main search method with ripgrep-like interface
-    /// Supports the exact parameter names as required by the Grep tool specification
-    #[pyo3(signature = (
-        pattern = None,
-        path = None,
-        glob = None,
-        output_mode = None,
-        B = None,      // -B flag: lines before
-        A = None,      // -A flag: lines after
-        C = None,      // -C flag: lines before and after
-        n = None,      // -n flag: show line numbers
-        i = None,      // -i flag: case insensitive
-        r#type = None, // type parameter: file type filter
-        head_limit = None,
-        truncation_warning = None, // add truncation warning
-        compact_paths = None, // show filepath only once per content block
-        multiline = None,
-        timeout = None // timeout in seconds
-    ))]
-    fn search(
-        &self,
-        py: Python,
-        pattern: Option<&str>,
-        path: Option<&str>,
-        glob: Option<&str>,
-        output_mode: Option<&str>,
-        B: Option<u64>,           // -B: lines before match
-        A: Option<u64>,           // -A: lines after match
-        C: Option<u64>,           // -C: lines before and after match
-        n: Option<bool>,          // -n: show line numbers
-        i: Option<bool>,          // -i: case insensitive
-        r#type: Option<&PyAny>,   // type: file type filter (string or list)
-        head_limit: Option<usize>,
-        truncation_warning: Option<bool>, // add truncation warning
-        compact_paths: Option<bool>, // show filepath only once per content block
-        multiline: Option<bool>,
-        timeout: Option<f64>,     // timeout in seconds
-    ) -> PyResult<PyObject> {
-        let output_mode = OutputMode::from_str(output_mode.unwrap_or("files_with_matches"))?;
-        
-        // Validate pattern requirement based on output mode
-        if pattern.is_none() && output_mode != OutputMode::Files {
-            return Err(PyValueError::new_err("Pattern is required for all output modes except 'files'"));
+    fn context(&mut self, _searcher: &Searcher, context: &SinkContext<'_>) -> Result<bool, io::Error> {
+        match (context.kind(), self.spans.last_mut()) {
+            (SinkContextKind::After, Some(last)) => last.shown_after += 1,
+            _ => self.pending += 1,
         }
-        
-        let path = path.unwrap_or(".");
-        let case_insensitive = i.unwrap_or(false);
-        let multiline = multiline.unwrap_or(false);
-        let line_numbers = n.unwrap_or(false);
-        let show_truncation_warning = truncation_warning.unwrap_or(false);
-        let use_compact_paths = compact_paths.unwrap_or(false);
+        Ok(true)
+    }
 
-        // Handle context options - C overrides A and B
-        let (before_ctx, after_ctx) = if let Some(c) = C {
-            (c, c)
-        } else {
-            (B.unwrap_or(0), A.unwrap_or(0))
-        };
+    fn context_break(&mut self, _searcher: &Searcher) -> Result<bool, io::Error> {
+        self.pending_break = true;
+        Ok(true)
+    }
+}
 
-        // Parse types outside allow_threads (can raise Python exceptions here)
-        let parsed_types = Self::parse_types(r#type)?;
+/// Block headers that context="block" climbs past, so a match inside a loop or branch
+/// comes with its whole function rather than just that statement
+const BLOCK_CONTROL_KEYWORDS: &[&str] = &[
+    "if", "elif", "else", "for", "while", "loop", "do", "try", "except", "catch", "finally", "with", "match", "case",
+    "switch", "default",
+];
 
-        // Build matcher (only if pattern is provided)
-        let matcher = if let Some(pattern) = pattern {
-            Some(self.build_matcher(pattern, case_insensitive, multiline)?)
-        } else {
-            None
-        };
+/// Indentation width of a line, with tabs advancing to the next multiple of 8
+fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width = (width / 8 + 1) * 8,
+            _ => break,
+        }
+    }
+    width
+}
 
-        // Compute deadline from timeout
-        let deadline = deadline_from_secs(timeout);
+/// Whether a block header is a control-flow statement rather than a definition or a config key
+fn is_control_header(line: &str) -> bool {
+    let line = line.trim_start().trim_start_matches('}').trim_start();
+    let word: String = line.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+    BLOCK_CONTROL_KEYWORDS.contains(&word.as_str())
+}
 
-        // Build walker outside allow_threads (can raise Python exceptions here)
-        let (walker, type_matcher) = self.build_walker(path, glob, &parsed_types)?;
+/// The lines `[start, end)` of the block enclosing line `at`, found from indentation (context="block")
+///
+/// The block is the innermost enclosing indented block whose header is not a control-flow
+/// statement, from the header (with decorators, attributes and an opening brace on its own
+/// line) to its last indented line plus a closing bracket line. A line that neither opens
+/// nor sits in such a block gets its paragraph: the run of non-blank lines around it.
+fn enclosing_block(lines: &[String], at: usize) -> (usize, usize) {
+    let blank = |i: usize| lines[i].trim().is_empty();
+    let next_code = |from: usize| (from..lines.len()).find(|&i| !blank(i));
+    let opens = |i: usize| next_code(i + 1).is_some_and(|next| indent_width(&lines[next]) > indent_width(&lines[i]));
 
-        // Search based on output mode (heavy part runs without the GIL)
-        match output_mode {
-            OutputMode::Content => {
-                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
-                let results = py.allow_threads(|| {
-                    self.search_content_inner(
-                        matcher,
-                        walker,
-                        type_matcher.as_ref(),
-                        before_ctx,
-                        after_ctx,
-                        deadline,
-                    )
-                }).map_err(to_pyerr)?;
-                Ok(self.format_content_results(py, results, line_numbers, head_limit, show_truncation_warning, use_compact_paths)?)
+    // The match line opens a block itself, or sits in the body of the nearest less indented line
+    let mut header = if opens(at) { Some(at) } else { None };
+    let mut indent = indent_width(&lines[at]);
+    loop {
+        if let Some(h) = header {
+            if !is_control_header(&lines[h]) {
+                break;
             }
-            OutputMode::FilesWithMatches => {
-                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
-                let (mut files, truncated) = py.allow_threads(|| {
-                    self.search_files_inner(matcher, walker, type_matcher.as_ref(), head_limit, deadline)
-                }).map_err(to_pyerr)?;
-                if show_truncation_warning && truncated {
-                    files.push("[Content truncated]".to_string());
+            indent = indent_width(&lines[h]);
+        }
+        match (0..header.unwrap_or(at)).rev().find(|&i| !blank(i) && indent_width(&lines[i]) < indent) {
+            Some(h) => header = Some(h),
+            None => {
+                // Top level: stop at the outermost header found, or fall back to the paragraph
+                if header.is_some_and(|h| is_control_header(&lines[h]) && indent_width(&lines[h]) == 0) {
+                    break;
                 }
-                Ok(files.into_py(py))
-            }
-            OutputMode::Count => {
-                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
-                let counts = py.allow_threads(|| {
-                    self.search_count_inner(matcher, walker, type_matcher.as_ref(), head_limit, deadline)
-                }).map_err(to_pyerr)?;
-                Ok(self.format_count_results(py, counts)?)
-            }
-            OutputMode::Files => {
-                let (mut files, truncated) = py.allow_threads(|| {
-                    self.search_files_no_match_inner(walker, type_matcher.as_ref(), head_limit, deadline)
-                }).map_err(to_pyerr)?;
-                if show_truncation_warning && truncated {
-                    files.push("[Content truncated]".to_string());
+                if header.is_none() {
+                    let start = (0..at).rev().find(|&i| blank(i)).map_or(0, |i| i + 1);
+                    let end = (at..lines.len()).find(|&i| blank(i)).unwrap_or(lines.len());
+                    return (start, end);
                 }
-                Ok(files.into_py(py))
+                break;
             }
         }
     }
-}
-
-impl Grep {
-    /// Create mapping from custom type names to official ripgrep type names
-    /// Only includes custom aliases - official type names are passed through directly
-    fn create_type_mapping() -> HashMap<&'static str, &'static str> {
-        let mut map = HashMap::new();
-
-        // Map custom/alternative names to official ripgrep type names
-        map.insert("python", "py");
-        map.insert("javascript", "js");
-        map.insert("typescript", "ts");
-        map.insert("markdown", "md");
-        map.insert("c++", "cpp");
-        map.insert("ruby", "rb");
-        map.insert("rs", "rust");  // rs -> rust for ripgrep
-        map.insert("yml", "yaml");  // yml -> yaml for ripgrep
-
-        map
-    }
-    
-    /// Parse type parameter from Python (string or list) into official ripgrep type names
-    /// If a type is in the custom mapping, use the mapped value; otherwise pass through directly
-    fn parse_types(type_param: Option<&PyAny>) -> PyResult<Vec<String>> {
-        let type_mapping = Self::create_type_mapping();
-        let mut result_types = Vec::new();
+    let header = header.unwrap_or(at);
+    let header_indent = indent_width(&lines[header]);
 
-        if let Some(param) = type_param {
-            if let Ok(type_str) = param.extract::<&str>() {
-                // Single string type - use mapping if available, otherwise pass through
-                let type_name = type_mapping.get(type_str).unwrap_or(&type_str);
-                result_types.push(type_name.to_string());
-            } else if let Ok(type_list) = param.extract::<Vec<&str>>() {
-                // List of types - use mapping if available, otherwise pass through
-                for type_str in type_list {
-                    let type_name = type_mapping.get(type_str).unwrap_or(&type_str);
-                    result_types.push(type_name.to_string());
+    // Body: every following line indented past the header, then a closing bracket at its level
+    let mut end = header + 1;
+    let mut i = header + 1;
+    while i < lines.len() {
+        if !blank(i) {
+            if indent_width(&lines[i]) <= header_indent {
+                let closing = lines[i].trim_start().starts_with(['}', ')', ']']);
+                if closing && indent_width(&lines[i]) == header_indent {
+                    end = i + 1;
                 }
-            } else {
-                return Err(PyValueError::new_err("Type parameter must be a string or list of strings"));
+                break;
             }
+            end = i + 1;
         }
-
-        Ok(result_types)
+        i += 1;
     }
 
-    /// Build regex matcher based on options
-    fn build_matcher(
-        &self,
-        pattern: &str,
-        case_insensitive: bool,
-        multiline: bool,
-    ) -> PyResult<RegexMatcher> {
-        let mut builder = RegexMatcherBuilder::new();
-
-        if case_insensitive {
-            builder.case_insensitive(true);
+    // Header: a lone opening brace belongs to the line above it; decorators and attributes come along
+    let mut start = header;
+    if lines[start].trim() == "{" {
+        if let Some(prev) = (0..start).rev().find(|&i| !blank(i)) {
+            start = prev;
         }
-
-        if multiline {
-            builder.multi_line(true).dot_matches_new_line(true);
+    }
+    while start > 0 && indent_width(&lines[start - 1]) == header_indent {
+        let above = lines[start - 1].trim_start();
+        if !(above.starts_with('@') || above.starts_with("#[")) {
+            break;
         }
+        start -= 1;
+    }
+    (start, end.max(at + 1))
+}
 
-        builder
-            .build(pattern)
-            .map_err(|e| PyValueError::new_err(format!("Invalid pattern: {}", e)))
+/// Replace the searcher's line context with each match's enclosing block (context="block")
+/// Matches sharing lines show each line once; separate blocks are split by a context break
+fn block_spans(lines: &[String], spans: &mut [ContextSpan]) -> Vec<(usize, usize)> {
+    let blocks: Vec<(usize, usize)> = spans
+        .iter()
+        .map(|span| {
+            let (start, end) = enclosing_block(lines, (span.first_line - 1) as usize);
+            (start, end.max(span.last_line as usize))
+        })
+        .collect();
+    let mut shown_upto = 0;
+    for i in 0..spans.len() {
+        let (match_idx, end_idx) = ((spans[i].first_line - 1) as usize, spans[i].last_line as usize);
+        let (start, end) = blocks[i];
+        let next_match = spans.get(i + 1).map_or(lines.len(), |next| (next.first_line - 1) as usize);
+        let shown_start = start.max(shown_upto).min(match_idx);
+        spans[i].block_start = i == 0 || start >= shown_upto;
+        spans[i].shown_before = match_idx - shown_start;
+        spans[i].shown_after = end.min(next_match).saturating_sub(end_idx);
+        shown_upto = end_idx + spans[i].shown_after;
     }
+    blocks
+}
 
-    /// Search for content with context (GIL-free inner implementation)
-    fn search_content_inner(
-        &self,
-        matcher: &RegexMatcher,
-        walker: ignore::Walk,
-        type_matcher: Option<&ignore::types::Types>,
-        before_context: u64,
-        after_context: u64,
-        deadline: Option<Instant>,
-    ) -> Result<Vec<ContentResult>, RGErr> {
-        let mut results = Vec::new();
+/// Sink that keeps a file's first match as (line number, absolute byte offset, line) and stops
+#[derive(Default)]
+struct FirstMatchSink(Option<(u64, u64, String)>);
 
-        for entry in walker {
-            if timed_out(deadline) {
-                return Err(RGErr::Timeout);
-            }
+impl Sink for FirstMatchSink {
+    type Error = io::Error;
 
-            let entry = entry.map_err(RGErr::Walk)?;
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, io::Error> {
+        let line = utf8_line(mat.bytes())?.trim_end_matches(['\n', '\r']);
+        self.0 = Some((mat.line_number().unwrap_or(1), mat.absolute_byte_offset(), line.to_string()));
+        Ok(false)
+    }
+}
 
-            if !entry.file_type().map_or(false, |ft| ft.is_file()) {
-                continue;
-            }
+/// Limit on content line length in bytes, like rg -M / --max-columns-preview
+#[derive(Debug, Clone, Copy)]
+struct MaxColumns {
+    limit: usize,
+    /// Keep the first `limit` bytes of a long line rather than omitting all of it
+    preview: bool,
+}
 
-            // Apply type filter manually for AND logic with glob
-            if let Some(type_matcher) = type_matcher {
-                if !type_matcher.matched(entry.path(), false).is_whitelist() {
-                    continue;
-                }
+impl MaxColumns {
+    /// Shorten the long lines of a result; context lines are finished here
+    /// Returns None when the matched line was omitted outright (nothing left to rewrite), or
+    /// the suffix to append once the kept preview has been replaced and highlighted
+    fn trim(self, matcher: &SearchMatcher, result: &mut ContentResult) -> Option<String> {
+        for line in result.before_context.iter_mut().chain(result.after_context.iter_mut()) {
+            if line.len() > self.limit {
+                *line = if self.preview {
+                    format!("{} [... omitted end of long line]", &line[..floor_char_boundary(line, self.limit)])
+                } else {
+                    "[Omitted long context line]".to_string()
+                };
             }
-
-            self.search_file_content_inner(
-                matcher,
-                entry.path(),
-                before_context,
-                after_context,
-                &mut results,
-            )?;
         }
-
-        Ok(results)
+        if result.content.len() <= self.limit {
+            return Some(String::new());
+        }
+        if !self.preview {
+            let matches = count_occurrences(matcher, &result.content);
+            result.content = format!("[Omitted long line with {} matches]", matches);
+            return None;
+        }
+        let cut = floor_char_boundary(&result.content, self.limit);
+        let mut remaining = 0;
+        let _ = matcher.find_iter(result.content.as_bytes(), |m| {
+            remaining += usize::from(m.start() >= cut);
+            true
+        });
+        result.content.truncate(cut);
+        Some(format!(" [... {} more match{}]", remaining, if remaining == 1 { "" } else { "es" }))
     }
+}
 
-    /// Search for files containing matches (GIL-free inner implementation)
-    fn search_files_inner(
-        &self,
-        matcher: &RegexMatcher,
-        walker: ignore::Walk,
-        type_matcher: Option<&ignore::types::Types>,
-        head_limit: Option<usize>,
-        deadline: Option<Instant>,
-    ) -> Result<(Vec<String>, bool), RGErr> {
-        let mut files = HashSet::new();
-        let mut searcher = Searcher::new(); // Create once, reuse for all files
-        let mut truncated = false;
-
-        for entry in walker {
-            if timed_out(deadline) {
-                return Err(RGErr::Timeout);
-            }
+/// Largest char boundary of `s` at or below `index`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len())).rev().find(|&i| s.is_char_boundary(i)).unwrap_or(0)
+}
 
-            if let Some(limit) = head_limit {
-                if files.len() >= limit {
-                    truncated = true;
-                    break;
-                }
-            }
+/// Number of non-overlapping matches in `line`
+fn count_occurrences(matcher: &SearchMatcher, line: &str) -> usize {
+    let mut count = 0;
+    let _ = matcher.find_iter(line.as_bytes(), |_| {
+        count += 1;
+        true
+    });
+    count
+}
 
-            let entry = entry.map_err(RGErr::Walk)?;
+/// Relevance of a matching file for rank=True; higher is better
+/// A hit in the file name weighs most, then a hit in its directory names, then the share
+/// of its lines that match and, more weakly, how many do; shallower paths break near-ties
+fn rank_score(matcher: &SearchMatcher, path: &Path, read: &ReadOptions) -> f64 {
+    let hits = |part: Option<&std::ffi::OsStr>| {
+        part.is_some_and(|part| matcher.is_match(part.to_string_lossy().as_bytes()).unwrap_or(false))
+    };
+    let name_hit = hits(path.file_name());
+    let dir_hit = path.parent().is_some_and(|dir| dir.iter().any(|part| hits(Some(part))));
+    let (lines, matches) = match read_file_lines(path, read) {
+        Ok(lines) => {
+            let matches = lines.iter().filter(|line| matcher.is_match(line.as_bytes()).unwrap_or(false)).count();
+            (lines.len(), matches)
+        }
+        Err(_) => (0, 0),
+    };
+    let density = matches as f64 / lines.max(1) as f64;
+    let depth = path.components().filter(|c| matches!(c, std::path::Component::Normal(_))).count();
+    2.0 * f64::from(u8::from(name_hit))
+        + f64::from(u8::from(dir_hit))
+        + density
+        + 0.1 * (1.0 + matches as f64).ln()
+        + 0.25 / depth.max(1) as f64
+}
 
-            if !entry.file_type().map_or(false, |ft| ft.is_file()) {
-                continue;
-            }
+/// Scores for each distinct result path, keyed by the path as shown
+fn rank_paths<'a>(
+    matcher: &SearchMatcher,
+    shown: impl Iterator<Item = &'a str>,
+    paths: &PathStyle,
+    read: &ReadOptions,
+) -> HashMap<String, f64> {
+    let mut scores = HashMap::new();
+    for path in shown {
+        if !scores.contains_key(path) {
+            scores.insert(path.to_string(), rank_score(matcher, &paths.resolve(path), read));
+        }
+    }
+    scores
+}
 
-            // Apply type filter manually for AND logic with glob
-            if let Some(type_matcher) = type_matcher {
-                if !type_matcher.matched(entry.path(), false).is_whitelist() {
-                    continue;
-                }
-            }
+/// Markers wrapped around each match in content mode (color=)
+#[derive(Debug, Clone)]
+struct Highlight {
+    open: String,
+    close: String,
+}
 
-            if self.file_has_match_inner_with_searcher(&mut searcher, matcher, entry.path())? {
-                files.insert(entry.path().to_string_lossy().to_string());
-            }
+impl Highlight {
+    /// "ansi" for terminal colors, an (open, close) pair of strings, or "never"/None for none
+    fn from_py(obj: Option<&PyAny>) -> PyResult<Option<Self>> {
+        let Some(obj) = obj else {
+            return Ok(None);
+        };
+        if let Ok(name) = obj.extract::<&str>() {
+            return match name {
+                // rg's default match style: bold red
+                "ansi" => Ok(Some(Highlight { open: "\x1b[1;31m".to_string(), close: "\x1b[0m".to_string() })),
+                "never" => Ok(None),
+                _ => Err(PyValueError::new_err(format!("Invalid color: {}", name))),
+            };
         }
-
-        Ok((files.into_iter().collect(), truncated))
+        let (open, close): (String, String) = obj
+            .extract()
+            .map_err(|_| PyValueError::new_err("color must be 'ansi', 'never' or an (open, close) tuple of strings"))?;
+        Ok(Some(Highlight { open, close }))
     }
+}
 
-    /// List files that would be searched (no pattern matching) - like rg --files
-    fn search_files_no_match_inner(
-        &self,
-        walker: ignore::Walk,
-        type_matcher: Option<&ignore::types::Types>,
-        head_limit: Option<usize>,
-        deadline: Option<Instant>,
-    ) -> Result<(Vec<String>, bool), RGErr> {
-        let mut files = Vec::new();
-        let mut truncated = false;
-
-        for entry in walker {
-            if timed_out(deadline) {
-                return Err(RGErr::Timeout);
+/// Replace and/or highlight every match in `line`; replacements expand `$1`/`$name`/`${name}`
+/// capture references, and without one the matched text is kept
+fn replace_matches(matcher: &SearchMatcher, line: &str, replacement: Option<&str>, highlight: Option<&Highlight>) -> String {
+    let haystack = line.as_bytes();
+    let mut caps = match matcher.new_captures() {
+        Ok(caps) => caps,
+        Err(_) => return line.to_string(),
+    };
+    let mut dst = Vec::with_capacity(haystack.len());
+    let replaced = matcher.replace_with_captures(haystack, &mut caps, &mut dst, |caps, dst| {
+        let matched = caps.get(0).unwrap_or(grep_matcher::Match::zero(0));
+        // Empty matches have nothing to highlight
+        let highlight = highlight.filter(|_| !matched.is_empty());
+        if let Some(highlight) = highlight {
+            dst.extend_from_slice(highlight.open.as_bytes());
+        }
+        match replacement {
+            Some(replacement) => {
+                caps.interpolate(|name| matcher.capture_index(name), haystack, replacement.as_bytes(), dst)
             }
+            None => dst.extend_from_slice(&haystack[matched]),
+        }
+        if let Some(highlight) = highlight {
+            dst.extend_from_slice(highlight.close.as_bytes());
+        }
+        true
+    });
+    match replaced {
+        Ok(()) => String::from_utf8_lossy(&dst).into_owned(),
+        Err(_) => line.to_string(),
+    }
+}
 
-            if let Some(limit) = head_limit {
-                if files.len() >= limit {
-                    truncated = true;
-                    break;
-                }
-            }
+/// Order of content blocks within each file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineOrder {
+    /// Ascending line numbers
+    Line,
+    /// Blocks with the most matches first, then by line number
+    Relevance,
+    /// Descending line numbers, so the last lines of a file come first
+    Reverse,
+}
 
-            let entry = entry.map_err(RGErr::Walk)?;
+impl LineOrder {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "line" => Ok(LineOrder::Line),
+            "relevance" => Ok(LineOrder::Relevance),
+            "reverse" => Ok(LineOrder::Reverse),
+            _ => Err(PyValueError::new_err(format!("Invalid order_within_file: {}", s))),
+        }
+    }
+}
 
-            if !entry.file_type().map_or(false, |ft| ft.is_file()) {
-                continue;
-            }
+/// How count mode combines per-file counts (`aggregate=`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CountAggregate {
+    /// {path: count}
+    PerFile,
+    /// {directory: count}, summed over the files directly inside each directory
+    PerDir,
+    /// One int across all files
+    Total,
+}
 
-            // Apply type filter manually for AND logic with glob
-            if let Some(type_matcher) = type_matcher {
-                if !type_matcher.matched(entry.path(), false).is_whitelist() {
-                    continue;
+impl CountAggregate {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "per_file" => Ok(CountAggregate::PerFile),
+            "per_dir" => Ok(CountAggregate::PerDir),
+            "total" => Ok(CountAggregate::Total),
+            _ => Err(PyValueError::new_err(format!("Invalid aggregate: {}", s))),
+        }
+    }
+
+    /// Roll per-file counts up to directories, keeping the order directories first appear in
+    fn per_dir(counts: Vec<CountResult>) -> Vec<CountResult> {
+        let mut dirs: Vec<CountResult> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for count in counts {
+            let dir = match Path::new(&count.path).parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+                _ => ".".to_string(),
+            };
+            match index.get(&dir) {
+                Some(&i) => dirs[i].count += count.count,
+                None => {
+                    index.insert(dir.clone(), dirs.len());
+                    dirs.push(CountResult { path: dir, count: count.count });
                 }
             }
+        }
+        dirs
+    }
+}
 
-            files.push(entry.path().to_string_lossy().to_string());
+/// File order selected with `sort=`, applied before searching so every output mode follows it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortBy {
+    /// Walk order (files_with_matches and content group files in the order they are searched)
+    None,
+    Path,
+    /// Last modification time
+    Modified,
+    /// Creation time, where the filesystem records it
+    Created,
+    /// File size in bytes
+    Size,
+}
+
+impl SortBy {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "none" => Ok(SortBy::None),
+            "path" => Ok(SortBy::Path),
+            "modified" => Ok(SortBy::Modified),
+            "created" => Ok(SortBy::Created),
+            "size" => Ok(SortBy::Size),
+            _ => Err(PyValueError::new_err(format!("Invalid sort: {}", s))),
         }
+    }
 
-        Ok((files, truncated))
+    /// Order files by this key, breaking ties by path; files whose key is unavailable come first
+    fn sort(self, files: &mut [PathBuf], descending: bool) {
+        if self == SortBy::None {
+            return;
+        }
+        files.sort();
+        let metadata = |path: &PathBuf| std::fs::metadata(path).ok();
+        match self {
+            SortBy::None | SortBy::Path => {}
+            SortBy::Modified => files.sort_by_cached_key(|p| metadata(p).and_then(|m| m.modified().ok())),
+            SortBy::Created => files.sort_by_cached_key(|p| metadata(p).and_then(|m| m.created().ok())),
+            SortBy::Size => files.sort_by_cached_key(|p| metadata(p).map(|m| m.len())),
+        }
+        if descending {
+            files.reverse();
+        }
     }
+}
 
-    /// Search and count matches per file (GIL-free inner implementation)
-    fn search_count_inner(
-        &self,
-        matcher: &RegexMatcher,
-        walker: ignore::Walk,
-        type_matcher: Option<&ignore::types::Types>,
-        head_limit: Option<usize>,
-        deadline: Option<Instant>,
-    ) -> Result<Vec<CountResult>, RGErr> {
-        let mut counts = Vec::new();
-        let mut searcher = Searcher::new(); // Create once, reuse for all files
+fn parse_sort_order(s: &str) -> PyResult<bool> {
+    match s {
+        "asc" => Ok(false),
+        "desc" => Ok(true),
+        _ => Err(PyValueError::new_err(format!("Invalid sort_order: {}", s))),
+    }
+}
 
-        for entry in walker {
-            if timed_out(deadline) {
-                return Err(RGErr::Timeout);
-            }
+/// How content-mode results are rendered into lines
+#[derive(Debug, Clone, Copy)]
+struct ContentFormat<'a> {
+    line_numbers: bool,
+    head_limit: Option<usize>,
+    /// Results were already cut by max_files / max_matches_* limits
+    truncated: bool,
+    truncation_warning: bool,
+    compact_paths: bool,
+    /// Start each file with an `== path (N matches) ==` header line
+    file_headers: bool,
+    /// Return `{path: [match, ...]}` instead of flat lines with `--` separators
+    group_by_file: bool,
+    /// Line between non-adjacent context blocks and between files (--context-separator)
+    context_separator: &'a str,
+    /// Print each file's path once on its own line, then bare `line:content` lines (--heading)
+    heading: bool,
+    /// Leave paths out of every line (-I / --no-filename)
+    no_filename: bool,
+    order: LineOrder,
+}
 
-            if let Some(limit) = head_limit {
-                if counts.len() >= limit {
-                    break;
-                }
-            }
+/// Header line emitted before each file's content when `file_headers` is enabled
+fn file_header(path: &str, matches: usize) -> String {
+    let noun = if matches == 1 { "match" } else { "matches" };
+    format!("== {path} ({matches} {noun}) ==")
+}
 
-            let entry = entry.map_err(RGErr::Walk)?;
+fn is_file_header(line: &str) -> bool {
+    line.starts_with("== ") && line.ends_with(" ==")
+}
 
-            if !entry.file_type().map_or(false, |ft| ft.is_file()) {
-                continue;
-            }
+/// Contiguous lines of content output: [(line number, content, is_match)]
+type ContextBlock = Vec<(u64, String, bool)>;
 
-            // Apply type filter manually for AND logic with glob
-            if let Some(type_matcher) = type_matcher {
-                if !type_matcher.matched(entry.path(), false).is_whitelist() {
-                    continue;
-                }
-            }
+/// Search result for matches mode: one matched line with its absolute byte offset
+#[derive(Debug, Clone)]
+pub struct MatchRecord {
+    pub path: String,
+    pub line_number: u64,
+    pub byte_offset: u64,
+    pub content: String,
+    /// Character ranges of the matches within content (only computed for spans=True)
+    pub spans: Option<Vec<(usize, usize)>>,
+}
 
-            let count = self.count_matches_in_file_inner_with_searcher(&mut searcher, matcher, entry.path())?;
-            if count > 0 {
-                counts.push(CountResult {
-                    path: entry.path().to_string_lossy().to_string(),
-                    count,
-                });
-            }
-        }
+/// Search result for paths mode: a file whose path matched, with the matched character ranges
+#[derive(Debug, Clone)]
+pub struct PathMatch {
+    pub path: String,
+    pub spans: Vec<(usize, usize)>,
+}
 
-        Ok(counts)
+impl PathMatch {
+    /// Match `path` as shown in results
+    fn find(matcher: &SearchMatcher, path: String) -> Option<Self> {
+        let spans = match_spans(matcher, &path, ColumnUnit::Chars);
+        (!spans.is_empty()).then_some(PathMatch { path, spans })
     }
+}
 
-    /// Build directory walker with filtering options
-    fn build_walker(
-        &self,
-        path: &str,
-        glob: Option<&str>,
-        types: &[String],
-    ) -> PyResult<(ignore::Walk, Option<ignore::types::Types>)> {
-        let path_buf = PathBuf::from(path);
-        if !path_buf.exists() {
-            return Err(PyValueError::new_err(format!("Path not found: {}", path)));
-        }
+/// What reported columns and spans count within a line (column_unit=)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnUnit {
+    /// UTF-8 bytes, as rg --vimgrep reports them
+    Bytes,
+    /// Unicode code points, ready for slicing in Python
+    Chars,
+    /// Screen columns with tabs expanded to stops every n columns
+    Display(usize),
+}
 
-        let mut builder = WalkBuilder::new(&path_buf);
-        builder
-            .hidden(true)
-            .git_ignore(true)
-            .git_global(true)
-            .git_exclude(true)
-            .follow_links(false)
-            .parents(true)
-            .ignore(true)
-            .standard_filters(true);
-
-        // Build type matcher separately for manual checking (AND logic)
-        let type_matcher = if !types.is_empty() {
-            let mut tb = TypesBuilder::new();
-            tb.add_defaults();
-            for t in types {
-                tb.select(t);
-            }
-            Some(tb.build()
-                .map_err(|e| PyValueError::new_err(format!("Invalid file type configuration: {e}")))?)
-        } else {
-            None
+impl ColumnUnit {
+    /// Parse column_unit=, with tab_width= only meaningful for "display" (default 8)
+    fn parse(unit: Option<&str>, tab_width: Option<usize>) -> PyResult<Option<Self>> {
+        let unit = match unit {
+            None if tab_width.is_some() => return Err(PyValueError::new_err("tab_width requires column_unit='display'")),
+            None => return Ok(None),
+            Some("bytes") => ColumnUnit::Bytes,
+            Some("chars") => ColumnUnit::Chars,
+            Some("display") => match tab_width.unwrap_or(8) {
+                0 => return Err(PyValueError::new_err("tab_width must be at least 1")),
+                width => ColumnUnit::Display(width),
+            },
+            Some(other) => return Err(PyValueError::new_err(format!("Invalid column_unit: {}", other))),
         };
-
-        // Use overrides for glob filtering (fast pruning during traversal)
-        if let Some(pat) = glob {
-            let mut ob = OverrideBuilder::new(&path_buf);
-            ob.add(pat).map_err(|e| PyValueError::new_err(format!("Invalid glob: {e}")))?;
-            let overrides = ob.build()
-                .map_err(|e| PyValueError::new_err(format!("Failed to build glob overrides: {e}")))?;
-            builder.overrides(overrides);
+        if tab_width.is_some() && !matches!(unit, ColumnUnit::Display(_)) {
+            return Err(PyValueError::new_err("tab_width requires column_unit='display'"));
         }
+        Ok(Some(unit))
+    }
 
-        Ok((builder.build(), type_matcher))
+    /// The column of byte offset `at` in `line`, counting from 0
+    fn column(self, line: &str, at: usize) -> usize {
+        match self {
+            ColumnUnit::Bytes => at,
+            ColumnUnit::Chars => line[..at].chars().count(),
+            ColumnUnit::Display(tab_width) => line[..at]
+                .chars()
+                .fold(0, |column, c| if c == '\t' { column - column % tab_width + tab_width } else { column + 1 }),
+        }
     }
+}
 
+/// Where `matcher` matches in `text`, as (start, end) columns in `unit`
+fn match_spans(matcher: &SearchMatcher, text: &str, unit: ColumnUnit) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let _ = matcher.find_iter(text.as_bytes(), |m| {
+        spans.push((unit.column(text, m.start()), unit.column(text, m.end())));
+        true
+    });
+    spans
+}
 
+/// Search result for files_with_matches mode with metadata=True
+#[derive(Debug, Clone)]
+pub struct FileSummary {
+    pub path: String,
+    pub first_line: u64,
+    pub match_count: u64,
+    pub size: u64,
+    /// Modification time in seconds since the Unix epoch, when the platform reports one
+    pub mtime: Option<f64>,
+}
 
+/// Search result for count mode
+#[derive(Debug, Clone)]
+pub struct CountResult {
+    pub path: String,
+    pub count: u64,
+}
 
-    /// Search a single file for content with context
-    fn search_file_content_inner(
-        &self,
-        matcher: &RegexMatcher,
-        path: &Path,
-        before_context: u64,
-        after_context: u64,
-        results: &mut Vec<ContentResult>,
-    ) -> Result<(), RGErr> {
-        use std::io::{BufRead, BufReader};
+/// Statistics collected during a search (returned when `stats=True`)
+#[pyclass(module = "pyripgrep")]
+#[derive(Debug, Default, Clone)]
+pub struct SearchStats {
+    /// Files whose contents were searched
+    #[pyo3(get)]
+    pub files_searched: u64,
+    /// Files with at least one match
+    #[pyo3(get)]
+    pub files_matched: u64,
+    /// Matching lines found (files_with_matches stops at the first match per file)
+    #[pyo3(get)]
+    pub matches: u64,
+    /// Total size of the files searched
+    #[pyo3(get)]
+    pub bytes_searched: u64,
+    /// Files skipped because they could not be read or decoded (e.g. binary)
+    #[pyo3(get)]
+    pub files_skipped: u64,
+    /// Entries left out of the search, broken down by the filter or failure responsible
+    #[pyo3(get)]
+    pub skipped: SkipCounts,
+    /// Wall-clock duration of the search in seconds
+    #[pyo3(get)]
+    pub elapsed: f64,
+    /// Options that were downgraded because this build lacks a feature (strict_options=False)
+    #[pyo3(get)]
+    pub warnings: Vec<String>,
+    /// Whether head_limit or a max_* limit left results out
+    #[pyo3(get)]
+    pub truncated: bool,
+    /// Files with matches left out by the limits; a lower bound, as the search stops there
+    #[pyo3(get)]
+    pub suppressed_files: u64,
+    /// Matches left out by the limits; a lower bound, as the search stops there
+    #[pyo3(get)]
+    pub suppressed_matches: u64,
+    /// Files searched, matched, matches and bytes per ripgrep type (e.g. "py"), or per
+    /// extension (e.g. ".cfg") for files no type claims; "" for files with neither
+    #[pyo3(get)]
+    pub by_type: BTreeMap<String, TypeStats>,
+    /// Files left out by dedupe="content", listed under the first file found with the same contents
+    #[pyo3(get)]
+    pub duplicates: BTreeMap<String, Vec<String>>,
+    /// Where the time went, with profile=True
+    #[pyo3(get)]
+    pub profile: Option<Profile>,
+    /// Files whose size or modification time changed while they were searched (changed_files=)
+    #[pyo3(get)]
+    pub changed: Vec<String>,
+}
 
-        let file = File::open(path).map_err(RGErr::Io)?;
+/// Where a search spent its time, in seconds (`SearchStats.profile`, profile=True)
+/// read and matching add up the time of every worker thread, so with threads > 1 they
+/// can exceed total; walk runs alongside the workers on its own thread
+#[pyclass(module = "pyripgrep")]
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+    /// Compiling the pattern and building the walker and filters
+    #[pyo3(get)]
+    pub setup: f64,
+    /// Producing the files to search: listing directories and applying ignore, glob and hidden rules
+    #[pyo3(get)]
+    pub walk: f64,
+    /// Opening, reading and decoding files
+    #[pyo3(get)]
+    pub read: f64,
+    /// Matching file contents, i.e. the time spent on each file other than reading it
+    #[pyo3(get)]
+    pub matching: f64,
+    /// Building the results after the files were searched
+    #[pyo3(get)]
+    pub format: f64,
+    /// Wall-clock duration of the whole search, as SearchStats.elapsed
+    #[pyo3(get)]
+    pub total: f64,
+    /// Wall-clock time spent searching files, which format is measured around
+    searching: f64,
+}
 
-        let path_str = path.to_string_lossy().to_string();
-        let reader = BufReader::new(file);
-        let lines: Result<Vec<String>, _> = reader.lines().collect();
+impl Profile {
+    fn merge(&mut self, other: &Profile) {
+        self.walk += other.walk;
+        self.read += other.read;
+        self.matching += other.matching;
+        self.searching += other.searching;
+    }
+}
 
-        let lines = match lines {
-            Ok(lines) => lines,
-            Err(_) => return Ok(()), // Skip problematic files silently
-        };
+#[pymethods]
+impl Profile {
+    fn __repr__(&self) -> String {
+        format!(
+            "Profile(setup={:.6}, walk={:.6}, read={:.6}, matching={:.6}, format={:.6}, total={:.6})",
+            self.setup, self.walk, self.read, self.matching, self.format, self.total,
+        )
+    }
+}
 
-        // Find all matching line numbers first
-        let mut matching_lines = Vec::new();
-        for (line_idx, line) in lines.iter().enumerate() {
-            let line_num = (line_idx + 1) as u64;
-            if matcher.is_match(line.as_bytes()).unwrap_or(false) {
-                matching_lines.push(line_num);
-            }
-        }
+/// One file type's share of a search (`SearchStats.by_type`)
+#[pyclass(module = "pyripgrep")]
+#[derive(Debug, Default, Clone)]
+pub struct TypeStats {
+    /// Files of this type whose contents were searched
+    #[pyo3(get)]
+    pub files_searched: u64,
+    /// Files of this type with at least one match
+    #[pyo3(get)]
+    pub files_matched: u64,
+    /// Matching lines found in files of this type
+    #[pyo3(get)]
+    pub matches: u64,
+    /// Total size of the files of this type searched
+    #[pyo3(get)]
+    pub bytes_searched: u64,
+}
 
-        // For each match, collect context and create result
-        for &match_line in &matching_lines {
-            let match_idx = (match_line - 1) as usize;
+impl TypeStats {
+    fn merge(&mut self, other: &TypeStats) {
+        self.files_searched += other.files_searched;
+        self.files_matched += other.files_matched;
+        self.matches += other.matches;
+        self.bytes_searched += other.bytes_searched;
+    }
 
-            // Collect before context
-            let before_start = if before_context == 0 {
-                match_idx
-            } else {
-                match_idx.saturating_sub(before_context as usize)
-            };
+    fn record_file(&mut self, bytes: u64, matches: u64) {
+        self.files_searched += 1;
+        self.bytes_searched += bytes;
+        self.matches += matches;
+        if matches > 0 {
+            self.files_matched += 1;
+        }
+    }
+}
 
-            let mut before_ctx = Vec::new();
-            if before_context > 0 {
-                for i in before_start..match_idx {
-                    if i < lines.len() {
-                        before_ctx.push(lines[i].clone());
-                    }
-                }
-            }
+#[pymethods]
+impl TypeStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "TypeStats(files_searched={}, files_matched={}, matches={}, bytes_searched={})",
+            self.files_searched, self.files_matched, self.matches, self.bytes_searched,
+        )
+    }
+}
 
-            // Collect after context
-            let mut after_ctx = Vec::new();
-            if after_context > 0 {
-                let after_end = std::cmp::min(lines.len(), match_idx + 1 + after_context as usize);
-                for i in (match_idx + 1)..after_end {
-                    after_ctx.push(lines[i].clone());
+/// The `SearchStats.by_type` key for a file: the first default ripgrep type, by name, whose
+/// globs match it (so *.h counts as "c"), else its extension
+fn file_type_key(path: &Path) -> String {
+    static DEFAULT_TYPES: OnceLock<(GlobSet, Vec<String>)> = OnceLock::new();
+    let (globs, names) = DEFAULT_TYPES.get_or_init(|| {
+        let mut tb = TypesBuilder::new();
+        tb.add_defaults();
+        let mut builder = GlobSetBuilder::new();
+        let mut names = Vec::new();
+        for def in tb.definitions() {
+            for glob in def.globs() {
+                if let Ok(glob) = Glob::new(glob) {
+                    builder.add(glob);
+                    names.push(def.name().to_string());
                 }
             }
+        }
+        (builder.build().unwrap_or_else(|_| GlobSet::empty()), names)
+    });
+    // Notebook cells count as their notebook; archive members by their own name
+    let path = split_notebook_path(path).map_or(path, |(notebook, _)| notebook);
+    let Some(name) = path.file_name() else {
+        return String::new();
+    };
+    match globs.matches(name).first() {
+        Some(&index) => names[index].clone(),
+        None => path.extension().map_or_else(String::new, |ext| format!(".{}", ext.to_string_lossy())),
+    }
+}
 
-            results.push(ContentResult {
-                path: path_str.clone(),
-                line_number: match_line,
-                content: lines[match_idx].clone(),
-                before_context: before_ctx,
-                after_context: after_ctx,
-            });
+impl SearchStats {
+    /// Add another partial search's counts to these
+    fn merge(&mut self, other: &SearchStats) {
+        self.files_searched += other.files_searched;
+        self.files_matched += other.files_matched;
+        self.matches += other.matches;
+        self.bytes_searched += other.bytes_searched;
+        self.files_skipped += other.files_skipped;
+        self.skipped.merge(&other.skipped);
+        for (key, stats) in &other.by_type {
+            self.by_type.entry(key.clone()).or_default().merge(stats);
         }
+        for (original, duplicates) in &other.duplicates {
+            self.duplicates.entry(original.clone()).or_default().extend(duplicates.iter().cloned());
+        }
+        self.changed.extend(other.changed.iter().cloned());
+        self.suppress(Suppressed { files: other.suppressed_files, matches: other.suppressed_matches });
+        if let (Some(profile), Some(other)) = (&mut self.profile, &other.profile) {
+            profile.merge(other);
+        }
+    }
 
-        Ok(())
+    /// Record results that the limits left out
+    fn suppress(&mut self, suppressed: Suppressed) {
+        if suppressed.files > 0 || suppressed.matches > 0 {
+            self.truncated = true;
+            self.suppressed_files += suppressed.files;
+            self.suppressed_matches += suppressed.matches;
+        }
     }
 
-    /// Check if file has any matches with reused searcher
-    fn file_has_match_inner_with_searcher(&self, searcher: &mut Searcher, matcher: &RegexMatcher, path: &Path) -> Result<bool, RGErr> {
-        let file = File::open(path).map_err(RGErr::Io)?;
+    /// Record a file that was searched to completion
+    fn record_file(&mut self, path: &Path, bytes: u64, matches: u64) {
+        self.files_searched += 1;
+        self.bytes_searched += bytes;
+        self.matches += matches;
+        if matches > 0 {
+            self.files_matched += 1;
+        }
+        self.by_type.entry(file_type_key(path)).or_default().record_file(bytes, matches);
+    }
+}
 
-        let mut has_match = false;
+/// Why an entry was left out of a search, counted in `SkipCounts`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SkipCause {
+    Gitignore,
+    Hidden,
+    TypeFilter,
+    Glob,
+    /// Already searched under another spelling of its path (canonical_paths=)
+    Duplicate,
+    /// Outside max_filesize= / min_filesize=
+    Size,
+    /// Outside modified_after= / modified_before=
+    Modified,
+    /// Missing an all_of= pattern or containing a none_of= pattern
+    ContentFilter,
+    /// Matched by generated_globs= or detected as minified (skip_minified=)
+    Generated,
+    /// Not a regular file, reported with special_files="report" or "follow"
+    Special(SpecialFile),
+    /// Size or modification time changed while it was searched (changed_files="skip")
+    Changed,
+}
 
-        let result = searcher.search_file(matcher, &file, sinks::UTF8(|_lnum, _line| {
-            has_match = true;
-            Ok(false) // Stop after first match
-        }));
+impl SkipCause {
+    /// Reason recorded for each skipped path in forensic mode
+    fn reason(self) -> &'static str {
+        match self {
+            SkipCause::Gitignore => "skipped: ignored by ignore rules",
+            SkipCause::Hidden => "skipped: hidden",
+            SkipCause::TypeFilter => "skipped: not selected by type filter",
+            SkipCause::Glob => "skipped: excluded by glob",
+            SkipCause::Duplicate => "skipped: duplicate of a file already searched",
+            SkipCause::Size => "skipped: outside the file size limits",
+            SkipCause::Modified => "skipped: modified outside the time window",
+            SkipCause::ContentFilter => "skipped: missing an all_of pattern or containing a none_of pattern",
+            SkipCause::Generated => "skipped: generated or minified",
+            SkipCause::Special(kind) => kind.reason(),
+            SkipCause::Changed => "skipped: changed while being searched",
+        }
+    }
 
-        // If the search failed (e.g., binary file), just return false
-        match result {
-            Ok(_) => Ok(has_match),
-            Err(_) => Ok(false), // Skip problematic files
+    /// The `SkipCounts` field this cause is counted under
+    fn name(self) -> &'static str {
+        match self {
+            SkipCause::Gitignore => "gitignore",
+            SkipCause::Hidden => "hidden",
+            SkipCause::TypeFilter => "type_filter",
+            SkipCause::Glob => "glob",
+            SkipCause::Duplicate => "duplicate",
+            SkipCause::Size => "size",
+            SkipCause::Modified => "modified",
+            SkipCause::ContentFilter => "content_filter",
+            SkipCause::Generated => "generated",
+            SkipCause::Special(_) => "special",
+            SkipCause::Changed => "changed",
         }
     }
+}
+
+/// Kinds of walk entries that are neither regular files nor directories
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpecialFile {
+    Symlink,
+    /// A symlink whose target does not exist
+    DanglingSymlink,
+    Fifo,
+    Socket,
+    /// Block or character device
+    Device,
+    Other,
+}
 
-    /// Check if file has any matches
-    fn file_has_match_inner(&self, matcher: &RegexMatcher, path: &Path) -> Result<bool, RGErr> {
-        let mut searcher = Searcher::new();
-        self.file_has_match_inner_with_searcher(&mut searcher, matcher, path)
+impl SpecialFile {
+    /// Classify `path`, whose own (not followed) file type is `file_type`
+    fn of(path: &Path, file_type: std::fs::FileType) -> Self {
+        if file_type.is_symlink() {
+            return if std::fs::metadata(path).is_ok() { SpecialFile::Symlink } else { SpecialFile::DanglingSymlink };
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                return SpecialFile::Fifo;
+            }
+            if file_type.is_socket() {
+                return SpecialFile::Socket;
+            }
+            if file_type.is_block_device() || file_type.is_char_device() {
+                return SpecialFile::Device;
+            }
+        }
+        SpecialFile::Other
     }
 
-    /// Count matches in a file with reused searcher
-    fn count_matches_in_file_inner_with_searcher(&self, searcher: &mut Searcher, matcher: &RegexMatcher, path: &Path) -> Result<u64, RGErr> {
-        let file = File::open(path).map_err(RGErr::Io)?;
+    fn reason(self) -> &'static str {
+        match self {
+            SpecialFile::Symlink => "skipped: symlink",
+            SpecialFile::DanglingSymlink => "skipped: dangling symlink",
+            SpecialFile::Fifo => "skipped: fifo",
+            SpecialFile::Socket => "skipped: socket",
+            SpecialFile::Device => "skipped: device file",
+            SpecialFile::Other => "skipped: not a regular file",
+        }
+    }
+}
 
-        let mut count = 0u64;
+/// What a search does with walk entries that are not regular files (special_files=)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum SpecialFiles {
+    /// Leave them out without a trace, like rg
+    #[default]
+    Skip,
+    /// Count them in `SkipCounts.special` and list them with errors="collect"
+    Report,
+    /// Search symlinks to regular files and report everything else
+    Follow,
+}
 
-        let result = searcher.search_file(matcher, &file, sinks::UTF8(|_lnum, _line| {
-            count += 1;
-            Ok(true)
-        }));
+impl SpecialFiles {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "skip" => Ok(SpecialFiles::Skip),
+            "report" => Ok(SpecialFiles::Report),
+            "follow" => Ok(SpecialFiles::Follow),
+            _ => Err(PyValueError::new_err(format!("Invalid special_files policy: {}", s))),
+        }
+    }
 
-        match result {
-            Ok(_) => Ok(count),
-            Err(_) => Ok(0), // Skip problematic files
+    /// The candidate for an entry that is neither a regular file nor a directory, if any
+    fn candidate(self, path: PathBuf, file_type: std::fs::FileType) -> Option<Candidate> {
+        if self == SpecialFiles::Skip {
+            return None;
         }
+        let kind = SpecialFile::of(&path, file_type);
+        if self == SpecialFiles::Follow && kind == SpecialFile::Symlink && path.is_file() {
+            return Some(Candidate::File(path));
+        }
+        Some(Candidate::Skipped(path, SkipCause::Special(kind)))
     }
+}
 
-    /// Count matches in a file
-    fn count_matches_in_file_inner(&self, matcher: &RegexMatcher, path: &Path) -> Result<u64, RGErr> {
-        let mut searcher = Searcher::new();
-        self.count_matches_in_file_inner_with_searcher(&mut searcher, matcher, path)
+/// Per-cause breakdown of skipped entries (`SearchStats.skipped`)
+/// A directory pruned by gitignore, hidden or glob rules counts once, not per file inside it
+#[pyclass(module = "pyripgrep")]
+#[derive(Debug, Default, Clone)]
+pub struct SkipCounts {
+    /// Excluded by .gitignore, .ignore, custom ignore files, git excludes or the global gitignore
+    #[pyo3(get)]
+    pub gitignore: u64,
+    /// Hidden files and directories (names starting with '.')
+    #[pyo3(get)]
+    pub hidden: u64,
+    /// Files not selected by the `type` filter
+    #[pyo3(get)]
+    pub type_filter: u64,
+    /// Files and directories excluded by the `glob` pattern
+    #[pyo3(get)]
+    pub glob: u64,
+    /// Files whose contents are not valid UTF-8
+    #[pyo3(get)]
+    pub binary: u64,
+    /// Files over max_filesize or under min_filesize
+    #[pyo3(get)]
+    pub size: u64,
+    /// Files that could not be read (permissions, I/O errors, preprocessor failures, ...)
+    #[pyo3(get)]
+    pub errors: u64,
+    /// Files reached again under another path, e.g. through a symlink (canonical_paths=True),
+    /// or repeating the contents of a file already searched (dedupe="content")
+    #[pyo3(get)]
+    pub duplicate: u64,
+    /// Files last modified outside modified_after / modified_before
+    #[pyo3(get)]
+    pub modified: u64,
+    /// Files missing an all_of pattern or containing a none_of pattern
+    #[pyo3(get)]
+    pub content_filter: u64,
+    /// Files matched by generated_globs or detected as minified (skip_minified)
+    #[pyo3(get)]
+    pub generated: u64,
+    /// Symlinks, fifos, sockets and device files (special_files="report" or "follow")
+    #[pyo3(get)]
+    pub special: u64,
+    /// Files that changed while they were searched (changed_files="skip")
+    #[pyo3(get)]
+    pub changed: u64,
+}
+
+impl SkipCounts {
+    fn merge(&mut self, other: &SkipCounts) {
+        self.gitignore += other.gitignore;
+        self.hidden += other.hidden;
+        self.type_filter += other.type_filter;
+        self.glob += other.glob;
+        self.binary += other.binary;
+        self.size += other.size;
+        self.errors += other.errors;
+        self.duplicate += other.duplicate;
+        self.modified += other.modified;
+        self.content_filter += other.content_filter;
+        self.generated += other.generated;
+        self.special += other.special;
+        self.changed += other.changed;
     }
 
-    /// Format content results for Python to match ripgrep CLI output
-    fn format_content_results(
-        &self,
-        py: Python,
-        results: Vec<ContentResult>,
-        show_line_numbers: bool,
-        head_limit: Option<usize>,
-        show_truncation_warning: bool,
-        compact_paths: bool,
-    ) -> PyResult<PyObject> {
-        if results.is_empty() {
-            return Ok(Vec::<String>::new().into_py(py));
+    fn record(&mut self, cause: SkipCause) {
+        match cause {
+            SkipCause::Gitignore => self.gitignore += 1,
+            SkipCause::Hidden => self.hidden += 1,
+            SkipCause::TypeFilter => self.type_filter += 1,
+            SkipCause::Glob => self.glob += 1,
+            SkipCause::Duplicate => self.duplicate += 1,
+            SkipCause::Size => self.size += 1,
+            SkipCause::Modified => self.modified += 1,
+            SkipCause::ContentFilter => self.content_filter += 1,
+            SkipCause::Generated => self.generated += 1,
+            SkipCause::Special(_) => self.special += 1,
+            SkipCause::Changed => self.changed += 1,
         }
+    }
+}
 
-        use std::collections::{BTreeMap, HashMap};
+#[pymethods]
+impl SkipCounts {
+    fn __repr__(&self) -> String {
+        format!(
+            "SkipCounts(gitignore={}, hidden={}, type_filter={}, glob={}, binary={}, size={}, errors={}, duplicate={}, modified={}, content_filter={}, generated={}, special={}, changed={})",
+            self.gitignore,
+            self.hidden,
+            self.type_filter,
+            self.glob,
+            self.binary,
+            self.size,
+            self.errors,
+            self.duplicate,
+            self.modified,
+            self.content_filter,
+            self.generated,
+            self.special,
+            self.changed,
+        )
+    }
+}
 
-        // Group results by file without cloning paths
-        let mut file_groups: HashMap<&str, Vec<&ContentResult>> = HashMap::new();
-        for r in &results {
-            file_groups.entry(&r.path).or_default().push(r);
-        }
+#[pymethods]
+impl SearchStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "SearchStats(files_searched={}, files_matched={}, matches={}, bytes_searched={}, files_skipped={}, truncated={}, elapsed={:.6})",
+            self.files_searched,
+            self.files_matched,
+            self.matches,
+            self.bytes_searched,
+            self.files_skipped,
+            if self.truncated { "True" } else { "False" },
+            self.elapsed,
+        )
+    }
+}
 
-        let mut py_results: Vec<String> = Vec::new();
-        let mut first_file = true;
-        let mut truncated = false;
+/// Files of a git repository a search is limited to (scope=)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GitScope {
+    /// Files in the index
+    Tracked,
+    /// Files whose worktree or index contents differ from HEAD, plus untracked files that are not ignored
+    Changed,
+    /// Files whose index contents differ from HEAD
+    Staged,
+}
 
-        'file_loop: for (file_path, mut file_results) in file_groups {
-            // Add separator between different files (except first file)
-            if !first_file && !py_results.is_empty() {
-                if let Some(limit) = head_limit {
-                    if py_results.len() >= limit {
-                        truncated = true;
-                        break 'file_loop;
-                    }
-                }
-                py_results.push("--".to_string());
+impl GitScope {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "tracked" => Ok(GitScope::Tracked),
+            "changed" => Ok(GitScope::Changed),
+            "staged" => Ok(GitScope::Staged),
+            _ => Err(PyValueError::new_err(format!("Invalid scope: {}", s))),
+        }
+    }
+
+    /// Files in scope under `root`, relative to it, as listed by the git CLI
+    /// Files deleted from the worktree are left out
+    fn files(self, root: &Path) -> PyResult<Vec<String>> {
+        let base;
+        let listings: &[&[&str]] = match self {
+            GitScope::Tracked => &[&["ls-files", "-z"]],
+            GitScope::Changed => {
+                base = Self::diff_base(root)?;
+                &[
+                    &["diff", "--name-only", "--relative", "--diff-filter=d", "-z", &base],
+                    &["ls-files", "-z", "--others", "--exclude-standard"],
+                ]
             }
-            first_file = false;
+            GitScope::Staged => &[&["diff", "--cached", "--name-only", "--relative", "--diff-filter=d", "-z"]],
+        };
+        let mut files = Vec::new();
+        for args in listings {
+            let output = Self::git(root, args)?;
+            if !output.status.success() {
+                return Err(PyValueError::new_err(format!(
+                    "git {} failed in {}: {}",
+                    args[0],
+                    root.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            files.extend(
+                output.stdout
+                    .split(|&b| b == 0)
+                    .map(|name| String::from_utf8_lossy(name).into_owned())
+                    .filter(|name| !name.is_empty() && root.join(name).exists()),
+            );
+        }
+        Ok(files)
+    }
 
-            // Sort results by line number
-            file_results.sort_by_key(|r| r.line_number);
+    /// What changed files are diffed against: HEAD, or the empty tree while the
+    /// branch has no commits yet, so every file in the index counts as changed
+    fn diff_base(root: &Path) -> PyResult<String> {
+        if Self::git(root, &["rev-parse", "--verify", "--quiet", "HEAD"])?.status.success() {
+            return Ok("HEAD".to_string());
+        }
+        // Hashed rather than hardcoded so SHA-256 repositories get their own id
+        let output = Self::git(root, &["hash-object", "-t", "tree", "--stdin"])?;
+        if !output.status.success() {
+            return Err(PyValueError::new_err(format!(
+                "git hash-object failed in {}: {}",
+                root.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
 
-            // Build merged continuous ranges
-            let mut merged_ranges: Vec<(u64, u64, Vec<(u64, String, bool)>)> = Vec::new();
-            let mut current_start: u64 = 0;
-            let mut current_end: u64 = 0;
-            // line_num -> (content, is_match)
-            let mut current_lines: BTreeMap<u64, (String, bool)> = BTreeMap::new();
-
-            // helper to finalize a range
-            let finalize_range = |start: u64,
-                                      end: u64,
-                                      lines: BTreeMap<u64, (String, bool)>,
-                                      out: &mut Vec<(u64, u64, Vec<(u64, String, bool)>)>| {
-                if end > 0 {
-                    let vec_lines = lines
-                        .into_iter()
-                        .map(|(ln, (s, m))| (ln, s, m))
-                        .collect::<Vec<_>>();
-                    out.push((start, end, vec_lines));
-                }
-            };
+    /// Run `git -C root args` with nothing on stdin
+    fn git(root: &Path, args: &[&str]) -> PyResult<std::process::Output> {
+        std::process::Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .map_err(|e| PyValueError::new_err(format!("scope requires git on PATH: {e}")))
+    }
+}
 
-            for result in file_results.iter() {
-                let before_len = result.before_context.len() as u64;
-                let after_len = result.after_context.len() as u64;
+/// Whether the git CLI that scope= and search_rev() run is on PATH, checked once
+fn git_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        std::process::Command::new("git")
+            .arg("--version")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
 
-                // NOTE: keep exact arithmetic semantics (no saturating_sub) to preserve behavior.
-                let range_start = result.line_number - before_len;
-                let range_end = if after_len == 0 {
-                    result.line_number
-                } else {
-                    result.line_number + after_len
-                };
+/// Regular files of a git revision's tree, as (path, blob id) pairs listed by `git ls-tree`
+/// Symlinks, submodules and paths that are not UTF-8 are left out
+fn git_tree_blobs(repo: &Path, rev: &str) -> PyResult<Vec<(String, String)>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["ls-tree", "-r", "-z", "--full-tree", rev])
+        .output()
+        .map_err(|e| PyValueError::new_err(format!("search_rev requires git on PATH: {e}")))?;
+    if !output.status.success() {
+        return Err(PyValueError::new_err(format!(
+            "git ls-tree {} failed in {}: {}",
+            rev,
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output
+        .stdout
+        .split(|&b| b == 0)
+        .filter_map(|entry| {
+            // "<mode> <type> <id>\t<path>"
+            let (meta, path) = std::str::from_utf8(entry).ok()?.split_once('\t')?;
+            let mut fields = meta.split(' ');
+            let (mode, kind, id) = (fields.next()?, fields.next()?, fields.next()?);
+            (kind == "blob" && mode != "120000").then(|| (path.to_string(), id.to_string()))
+        })
+        .collect())
+}
 
-                // start new range if non-overlapping (> current_end + 1)
-                if current_end == 0 || range_start > current_end + 1 {
-                    // finalize previous
-                    finalize_range(current_start, current_end, std::mem::take(&mut current_lines), &mut merged_ranges);
+/// Stream blobs through one `git cat-file --batch` process, calling `visit` with each blob's
+/// index in `ids` and its contents; `visit` returns Ok(false) to stop early
+fn read_git_blobs(
+    repo: &Path,
+    ids: Vec<String>,
+    mut visit: impl FnMut(usize, &[u8]) -> Result<bool, RGErr>,
+) -> Result<(), RGErr> {
+    let mut child = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["cat-file", "--batch"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(RGErr::Io)?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let stdout = child.stdout.take().expect("stdout is piped");
+    // Ids are written from another thread so a full stdout pipe can't block the writer
+    let writer = thread::spawn(move || {
+        for id in ids {
+            if writeln!(stdin, "{id}").is_err() {
+                break;
+            }
+        }
+    });
 
-                    current_start = range_start;
-                    current_end = range_end;
-                } else {
-                    // extend current
-                    if range_end > current_end {
-                        current_end = range_end;
-                    }
-                }
+    let read_all = || -> Result<(), RGErr> {
+        let mut stdout = BufReader::new(stdout);
+        let mut header = String::new();
+        for index in 0.. {
+            header.clear();
+            if stdout.read_line(&mut header).map_err(RGErr::Io)? == 0 {
+                return Ok(());
+            }
+            // "<id> <type> <size>", or "<id> missing"
+            let size: usize = header.trim_end().rsplit(' ').next().and_then(|size| size.parse().ok()).ok_or_else(|| {
+                RGErr::Io(io::Error::new(io::ErrorKind::InvalidData, format!("git cat-file: {}", header.trim_end())))
+            })?;
+            let mut contents = vec![0; size + 1]; // each blob is followed by a newline
+            stdout.read_exact(&mut contents).map_err(RGErr::Io)?;
+            contents.pop();
+            if !visit(index, &contents)? {
+                return Ok(());
+            }
+        }
+        Ok(())
+    };
+    let result = read_all();
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = writer.join();
+    result
+}
+
+/// glob= and iglob= patterns as (glob, case_insensitive), in the order rg applies them:
+/// a later glob takes precedence, and braces expand (`*.{py,pyi}`)
+#[derive(Debug, Clone, Default)]
+struct Globs(Vec<(String, bool)>);
+
+impl Globs {
+    /// Read glob= and iglob=, each a string or a list of strings
+    fn parse(glob: Option<&PyAny>, iglob: Option<&PyAny>) -> PyResult<Self> {
+        let mut globs = Vec::new();
+        for (param, case_insensitive) in [(glob, false), (iglob, true)] {
+            let Some(param) = param else {
+                continue;
+            };
+            if let Ok(glob) = param.extract::<String>() {
+                globs.push((glob, case_insensitive));
+            } else if let Ok(list) = param.extract::<Vec<String>>() {
+                globs.extend(list.into_iter().map(|glob| (glob, case_insensitive)));
+            } else {
+                return Err(PyValueError::new_err("Glob parameter must be a string or list of strings"));
+            }
+        }
+        Ok(Globs(globs))
+    }
+
+    /// Rewrite each glob, e.g. to expand ~ and $VAR
+    fn map(self, f: impl Fn(&str) -> String) -> Self {
+        Globs(self.0.into_iter().map(|(glob, case_insensitive)| (f(&glob), case_insensitive)).collect())
+    }
+
+    /// The globs as written, for explain()'s rule
+    fn describe(&self) -> Option<String> {
+        (!self.0.is_empty()).then(|| self.0.iter().map(|(glob, _)| glob.as_str()).collect::<Vec<_>>().join(", "))
+    }
+
+    /// Split into the exclude globs ("!pattern") and the include globs, each in their original order
+    fn split_excludes(self) -> (Self, Self) {
+        let (excludes, includes) = self.0.into_iter().partition(|(glob, _)| glob.starts_with('!'));
+        (Globs(excludes), Globs(includes))
+    }
+}
+
+/// How glob= and type= combine when both are given (filter_logic=)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum FilterLogic {
+    /// A file must match a glob and be of a selected type
+    #[default]
+    And,
+    /// A file must match an include glob or be of a selected type; exclude globs still apply
+    Or,
+}
+
+impl FilterLogic {
+    fn from_str(s: &str) -> PyResult<Self> {
+        match s {
+            "and" => Ok(FilterLogic::And),
+            "or" => Ok(FilterLogic::Or),
+            _ => Err(PyValueError::new_err(format!("Invalid filter_logic: {}", s))),
+        }
+    }
+}
+
+/// Walker settings beyond the standard filters
+#[derive(Debug, Clone, Default)]
+struct WalkOptions {
+    /// Ignore file names looked up in every directory, with precedence over .ignore and
+    /// .gitignore (custom_ignore_filenames=)
+    ignore_filenames: Vec<String>,
+    /// Ignore files whose rules apply to the whole walk, with the lowest precedence (ignore_file_paths=)
+    ignore_paths: Vec<PathBuf>,
+    /// Don't descend into directories on other filesystems (one_file_system=)
+    same_file_system: bool,
+    /// Skip .gitignore, .ignore, git exclude/global rules and custom ignore files (ignore_level >= 1)
+    no_ignore: bool,
+    /// Search hidden files and directories (ignore_level >= 2)
+    hidden: bool,
+    /// Search `.git` (Some(true)) or skip it (Some(false)) whatever `hidden` says (search_git_dir=)
+    git_dir: Option<bool>,
+    /// Leave out ignore files in directories above the search path (ignore_parents=False)
+    no_parents: bool,
+    /// Directory ignore rules are anchored at, treated as a repository root (root=)
+    ignore_root: Option<PathBuf>,
+    /// What to do with symlinks, fifos, sockets and device files
+    special_files: SpecialFiles,
+}
+
+/// Split rg's -u/-uu/-uuu shorthand into the walk settings and the binary switch it implies
+/// Returns (no_ignore, hidden, binary)
+fn parse_ignore_level(level: Option<u8>) -> PyResult<(bool, bool, bool)> {
+    match level.unwrap_or(0) {
+        level @ 0..=3 => Ok((level >= 1, level >= 2, level >= 3)),
+        level => Err(PyValueError::new_err(format!("ignore_level must be between 0 and 3, got {level}"))),
+    }
+}
+
+/// Ignore files between the root= directory and the search path, which take the place of
+/// the walker's own lookup above the search path
+struct AnchoredIgnores {
+    /// One matcher per ignore file: custom names, then .ignore, then .gitignore, deeper
+    /// directories first within each kind, as they take precedence
+    layers: Vec<ignore::gitignore::Gitignore>,
+    /// Walked paths are matched as absolute, normalized paths
+    cwd: PathBuf,
+}
+
+impl AnchoredIgnores {
+    fn new(root: &Path, path: &Path, ignore_filenames: &[String]) -> PyResult<Self> {
+        let cwd = std::env::current_dir()
+            .map_err(|e| PyValueError::new_err(format!("Cannot determine the working directory: {e}")))?;
+        let root = normalize_path(&cwd.join(root));
+        if !root.is_dir() {
+            return Err(PyValueError::new_err(format!("root must be a directory: {}", root.display())));
+        }
+        let path = normalize_path(&cwd.join(path));
+        if !path.starts_with(&root) {
+            return Err(PyValueError::new_err(format!("path {} is not inside root {}", path.display(), root.display())));
+        }
+        // The walker reads the ignore files of the search path itself and everything below it
+        let dirs: Vec<&Path> = path.ancestors().skip(1).take_while(|dir| dir.starts_with(&root)).collect();
+        let load = |dir: &Path, name: &str| -> Option<ignore::gitignore::Gitignore> {
+            let file = dir.join(name);
+            if !file.is_file() {
+                return None;
+            }
+            let mut builder = ignore::gitignore::GitignoreBuilder::new(dir);
+            builder.add(file);
+            builder.build().ok()
+        };
+        let names = ignore_filenames.iter().rev().map(String::as_str).chain([".ignore", ".gitignore"]);
+        let layers = names.flat_map(|name| dirs.iter().filter_map(move |dir| load(dir, name))).collect();
+        Ok(AnchoredIgnores { layers, cwd })
+    }
+
+    /// Whether the first rule matching `path` ignores it
+    fn ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let path = normalize_path(&self.cwd.join(path));
+        for layer in &self.layers {
+            match layer.matched(&path, is_dir) {
+                ignore::Match::Ignore(_) => return true,
+                ignore::Match::Whitelist(_) => return false,
+                ignore::Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// Display-only rg flags that have no effect on results, with whether they take a value
+const CONFIG_DISPLAY_FLAGS: &[(&str, bool)] = &[
+    ("--color", true),
+    ("--colors", true),
+    ("-p", false),
+    ("--pretty", false),
+    ("--no-messages", false),
+    ("--column", false),
+    ("--no-column", false),
+    ("--trim", false),
+    ("--hyperlink-format", true),
+];
+
+/// Short rg flags that take a value, which may be attached (-j4, -tpy)
+const CONFIG_SHORT_VALUE_FLAGS: &[&str] = &["-g", "-t", "-j", "-m", "-A", "-B", "-C", "-M", "-E"];
+
+/// Parse a byte count with rg's optional K, M or G suffix
+fn parse_config_size(value: &str) -> Option<u64> {
+    let (digits, scale) = match value.chars().last()? {
+        'K' | 'k' => (&value[..value.len() - 1], 1 << 10),
+        'M' | 'm' => (&value[..value.len() - 1], 1 << 20),
+        'G' | 'g' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(scale)
+}
+
+/// Map the flags of a ripgrep config file (`RIPGREP_CONFIG_PATH` format: one argument per
+/// line, `#` comments) to search() keyword arguments
+/// Returns the keyword arguments and the flags that have no pyripgrep equivalent
+fn parse_ripgrep_config<'py>(py: Python<'py>, text: &str, origin: &Path) -> PyResult<(&'py PyDict, Vec<String>)> {
+    let defaults = PyDict::new(py);
+    let mut unsupported = Vec::new();
+    let (mut types, mut type_add, mut globs, mut iglobs, mut ignore_files) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut ignore_level = 0u8;
+    let mut args = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    while let Some(arg) = args.next() {
+        // --flag=value, or a short flag with its value attached (-j4, -tpy)
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ if arg.len() > 2 && arg.get(..2).is_some_and(|flag| CONFIG_SHORT_VALUE_FLAGS.contains(&flag)) => {
+                (&arg[..2], Some(&arg[2..]))
+            }
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline.or_else(|| args.next()).ok_or_else(|| {
+                PyValueError::new_err(format!("{} in {} needs a value", flag, origin.display()))
+            })
+        };
+        let invalid = |value: &str| PyValueError::new_err(format!("Invalid value for {} in {}: {}", flag, origin.display(), value));
+        let number = |value: &str| value.parse::<u64>().map_err(|_| invalid(value));
+        let size = |value: &str| parse_config_size(value).ok_or_else(|| invalid(value));
+
+        match flag {
+            "-i" | "--ignore-case" => {
+                defaults.set_item("i", true)?;
+                defaults.set_item("case_smart", false)?;
+            }
+            "-s" | "--case-sensitive" => {
+                defaults.set_item("i", false)?;
+                defaults.set_item("case_smart", false)?;
+            }
+            "-S" | "--smart-case" => {
+                defaults.set_item("i", false)?;
+                defaults.set_item("case_smart", true)?;
+            }
+            "-." | "--hidden" => defaults.set_item("hidden", true)?,
+            "--no-hidden" => defaults.set_item("hidden", false)?,
+            "-g" | "--glob" => globs.push(value()?.to_string()),
+            "--iglob" => iglobs.push(value()?.to_string()),
+            "-t" | "--type" => types.push(value()?.to_string()),
+            "--type-add" => type_add.push(value()?.to_string()),
+            "--ignore-file" => ignore_files.push(value()?.to_string()),
+            "-j" | "--threads" => defaults.set_item("threads", number(value()?)?)?,
+            "-m" | "--max-count" => defaults.set_item("max_matches_per_file", number(value()?)?)?,
+            "-A" => defaults.set_item("A", number(value()?)?)?,
+            "-B" => defaults.set_item("B", number(value()?)?)?,
+            "-C" | "--context" => defaults.set_item("C", number(value()?)?)?,
+            "-M" | "--max-columns" => defaults.set_item("max_columns", number(value()?)?)?,
+            "--max-columns-preview" => defaults.set_item("max_columns_preview", true)?,
+            "--max-filesize" => defaults.set_item("max_filesize", size(value()?)?)?,
+            "--regex-size-limit" => defaults.set_item("regex_size_limit", size(value()?)?)?,
+            "--dfa-size-limit" => defaults.set_item("dfa_size_limit", size(value()?)?)?,
+            "-n" | "--line-number" => defaults.set_item("n", true)?,
+            "-N" | "--no-line-number" => defaults.set_item("n", false)?,
+            "--heading" => defaults.set_item("heading", true)?,
+            "--no-heading" => defaults.set_item("heading", false)?,
+            "--passthru" => defaults.set_item("passthru", true)?,
+            "--context-separator" => defaults.set_item("context_separator", value()?)?,
+            "-z" | "--search-zip" => defaults.set_item("search_zip", true)?,
+            "-U" | "--multiline" => defaults.set_item("multiline", true)?,
+            "-x" | "--line-regexp" => defaults.set_item("x", true)?,
+            "--crlf" => defaults.set_item("crlf", true)?,
+            "--null-data" => defaults.set_item("null_data", true)?,
+            "--one-file-system" => defaults.set_item("one_file_system", true)?,
+            "--engine" => defaults.set_item("engine", value()?)?,
+            "--pre" => defaults.set_item("preprocessor", value()?)?,
+            "-E" | "--encoding" => match value()? {
+                "auto" => {}
+                label => defaults.set_item("encodings", HashMap::from([("*", label)]))?,
+            },
+            "--sort" | "--sortr" => {
+                defaults.set_item("sort", value()?)?;
+                defaults.set_item("sort_order", if flag == "--sortr" { "desc" } else { "asc" })?;
+            }
+            "--no-ignore" => ignore_level = ignore_level.max(1),
+            "--no-ignore-parent" => defaults.set_item("ignore_parents", false)?,
+            "--ignore-parent" => defaults.set_item("ignore_parents", true)?,
+            "--unrestricted" => ignore_level = (ignore_level + 1).min(3),
+            flag if flag.len() > 1 && flag[1..].chars().all(|c| c == 'u') => {
+                ignore_level = (ignore_level + flag.len() as u8 - 1).min(3);
+            }
+            flag => match CONFIG_DISPLAY_FLAGS.iter().find(|(name, _)| *name == flag) {
+                Some((_, takes_value)) => {
+                    if *takes_value {
+                        value()?;
+                    }
+                }
+                None => unsupported.push(arg.to_string()),
+            },
+        }
+    }
+
+    if !globs.is_empty() {
+        defaults.set_item("glob", globs)?;
+    }
+    if !iglobs.is_empty() {
+        defaults.set_item("iglob", iglobs)?;
+    }
+    if !types.is_empty() {
+        defaults.set_item("type", types)?;
+    }
+    if !type_add.is_empty() {
+        defaults.set_item("type_add", type_add)?;
+    }
+    if !ignore_files.is_empty() {
+        defaults.set_item("ignore_file_paths", ignore_files)?;
+    }
+    if ignore_level > 0 {
+        defaults.set_item("ignore_level", ignore_level)?;
+    }
+    Ok((defaults, unsupported))
+}
+
+/// Why a search would or would not reach a path (`Grep.explain`)
+#[pyclass(module = "pyripgrep")]
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    /// The path as passed to explain()
+    #[pyo3(get)]
+    pub path: String,
+    /// Whether a search under the root would search this path
+    #[pyo3(get)]
+    pub included: bool,
+    /// The `SkipCounts` field the exclusion counts under ("gitignore", "hidden", "glob", "type_filter")
+    #[pyo3(get)]
+    pub cause: Option<String>,
+    /// Human-readable verdict, e.g. "skipped: ignored by ignore rules"
+    #[pyo3(get)]
+    pub reason: String,
+    /// The rule that decided, e.g. "*.log", "!keep.log" or the glob= pattern
+    #[pyo3(get)]
+    pub rule: Option<String>,
+    /// The ignore file the rule came from
+    #[pyo3(get)]
+    pub source: Option<String>,
+    /// The path the rule matched: the path itself or the ancestor directory that pruned it
+    #[pyo3(get)]
+    pub matched_path: Option<String>,
+}
+
+#[pymethods]
+impl Explanation {
+    fn __repr__(&self) -> String {
+        let mut repr = format!("Explanation(path={:?}, included={}", self.path, if self.included { "True" } else { "False" });
+        if let Some(cause) = &self.cause {
+            repr.push_str(&format!(", cause={cause:?}"));
+        }
+        if let Some(rule) = &self.rule {
+            repr.push_str(&format!(", rule={rule:?}"));
+        }
+        if let Some(source) = &self.source {
+            repr.push_str(&format!(", source={source:?}"));
+        }
+        repr.push(')');
+        repr
+    }
+
+    fn __bool__(&self) -> bool {
+        self.included
+    }
+}
+
+/// The decision for one walk entry: whether it passes, and the rule and ignore file that decided
+struct Verdict {
+    cause: Option<SkipCause>,
+    rule: Option<String>,
+    source: Option<PathBuf>,
+}
+
+/// Find the first entry from `root` down to `path` (both absolute and normalized) that the
+/// walk left out of `reached`, and replay the walker's filters on each level to name the rule
+/// behind the walk's verdict. The walk decides; a rule the replay finds on an entry the walk
+/// reached is not reported, and an entry left out by no replayed rule has no cause
+/// Returns the verdict and the entry it applied to
+fn explain_path(
+    root: &Path,
+    path: &Path,
+    reached: &HashSet<PathBuf>,
+    glob: Option<&str>,
+    overrides: Option<&Override>,
+    walk_options: &WalkOptions,
+    type_filter: Option<&TypeFilter>,
+) -> (Option<Verdict>, PathBuf) {
+    let mut whitelisted = None;
+    // The root itself is always walked, like any path given explicitly
+    let below_root: Vec<&Path> = path.ancestors().take_while(|entry| *entry != root).collect();
+    for entry in below_root.into_iter().rev() {
+        let is_dir = entry.is_dir();
+        let verdict = explain_entry(entry, is_dir, glob, overrides, walk_options);
+        if !reached.contains(entry) {
+            return (verdict.cause.is_some().then_some(verdict), entry.to_path_buf());
+        }
+        if verdict.cause.is_none() && verdict.rule.is_some() {
+            whitelisted = Some((verdict, entry.to_path_buf()));
+        }
+    }
+    if path.is_file() && type_filter.is_some_and(|filter| !filter.matches(path)) {
+        let verdict = Verdict { cause: Some(SkipCause::TypeFilter), rule: None, source: None };
+        return (Some(verdict), path.to_path_buf());
+    }
+    let (verdict, matched) =
+        whitelisted.unwrap_or_else(|| (Verdict { cause: None, rule: None, source: None }, path.to_path_buf()));
+    (Some(verdict), matched)
+}
+
+/// One entry's verdict, in the walker's precedence: search_git_dir=False, glob overrides,
+/// ignore files, then hidden (where search_git_dir=True exempts .git)
+fn explain_entry(
+    entry: &Path,
+    is_dir: bool,
+    glob: Option<&str>,
+    overrides: Option<&Override>,
+    walk_options: &WalkOptions,
+) -> Verdict {
+    let name = entry.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    if walk_options.git_dir == Some(false) && name == ".git" {
+        return Verdict { cause: Some(SkipCause::Hidden), rule: None, source: None };
+    }
+    match overrides.map(|o| o.matched(entry, is_dir)) {
+        Some(ignore::Match::Ignore(_)) => {
+            return Verdict { cause: Some(SkipCause::Glob), rule: glob.map(str::to_string), source: None };
+        }
+        Some(ignore::Match::Whitelist(_)) => {
+            return Verdict { cause: None, rule: glob.map(str::to_string), source: None };
+        }
+        _ => {}
+    }
+    if let Some(verdict) = ignore_file_verdict(entry, is_dir, walk_options) {
+        return verdict;
+    }
+    let hidden = !walk_options.hidden && name.starts_with('.') && !(walk_options.git_dir == Some(true) && name == ".git");
+    Verdict { cause: hidden.then_some(SkipCause::Hidden), rule: None, source: None }
+}
+
+/// The first ignore rule matching `entry`: custom ignore files, then .ignore, then (inside a
+/// git repository) .gitignore, .git/info/exclude and the global gitignore, then explicit
+/// ignore files; deeper files win within each kind. With no_ignore only the explicit files apply
+fn ignore_file_verdict(entry: &Path, is_dir: bool, walk_options: &WalkOptions) -> Option<Verdict> {
+    use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+    let load = |root: &Path, file: PathBuf| -> Option<Gitignore> {
+        if !file.is_file() {
+            return None;
+        }
+        let mut builder = GitignoreBuilder::new(root);
+        builder.add(file);
+        builder.build().ok()
+    };
+    let dirs: Vec<&Path> = entry.ancestors().skip(1).collect();
+    let mut layers: Vec<Gitignore> = Vec::new();
+    let names = walk_options.ignore_filenames.iter().map(String::as_str).chain([".ignore"]);
+    for name in names.filter(|_| !walk_options.no_ignore) {
+        layers.extend(dirs.iter().filter_map(|dir| load(dir, dir.join(name))));
+    }
+    let repo = dirs.iter().position(|dir| dir.join(".git").exists()).filter(|_| !walk_options.no_ignore);
+    if let Some(repo) = repo {
+        layers.extend(dirs[..=repo].iter().filter_map(|dir| load(dir, dir.join(".gitignore"))));
+        layers.extend(load(dirs[repo], dirs[repo].join(".git").join("info").join("exclude")));
+        layers.push(Gitignore::global().0);
+    }
+    // Explicit files are rooted nowhere, as in WalkBuilder::add_ignore
+    layers.extend(walk_options.ignore_paths.iter().filter_map(|file| load(Path::new(""), file.clone())));
+
+    layers.iter().find_map(|layer| {
+        let (cause, glob) = match layer.matched(entry, is_dir) {
+            ignore::Match::None => return None,
+            ignore::Match::Ignore(glob) => (Some(SkipCause::Gitignore), glob),
+            ignore::Match::Whitelist(glob) => (None, glob),
+        };
+        Some(Verdict { cause, rule: Some(glob.original().to_string()), source: glob.from().map(Path::to_path_buf) })
+    })
+}
+
+/// File type filter applied manually during the walk (AND logic with glob, unless filter_logic='or')
+#[derive(Debug, Clone)]
+struct TypeFilter {
+    types: ignore::types::Types,
+    ignore_case: bool,
+    /// Include globs that select a file regardless of its type (filter_logic='or')
+    either: Option<Override>,
+}
+
+impl TypeFilter {
+    /// Check whether a file is selected by the type filter
+    /// With `ignore_case`, a lowercased file name is tried as well (e.g. FILE.PY for *.py)
+    fn matches(&self, path: &Path) -> bool {
+        // Notebook cells are selected as their notebook (e.g. type="jupyter")
+        let path = split_notebook_path(path).map_or(path, |(notebook, _)| notebook);
+        if self.either.as_ref().is_some_and(|globs| globs.matched(path, false).is_whitelist()) {
+            return true;
+        }
+        if self.types.matched(path, false).is_whitelist() {
+            return true;
+        }
+        if !self.ignore_case {
+            return false;
+        }
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => {
+                let lowered = path.with_file_name(name.to_lowercase());
+                self.types.matched(&lowered, false).is_whitelist()
+            }
+            None => false,
+        }
+    }
+}
+
+/// Mutable state shared by the inner search loops
+#[derive(Debug)]
+struct SearchReport {
+    stats: SearchStats,
+    errors: Vec<(String, String)>,
+    error_policy: ErrorPolicy,
+    /// List every skipped path with its reason in `errors`, not just the counts (forensic=)
+    forensic: bool,
+    /// How paths are written in results and errors
+    paths: PathStyle,
+    ignore_file_errors: IgnoreFileErrors,
+    /// Ignore file errors to emit as warnings after the search
+    warnings: Vec<String>,
+}
+
+impl SearchReport {
+    fn new(error_policy: ErrorPolicy) -> Self {
+        SearchReport {
+            stats: SearchStats::default(),
+            errors: Vec::new(),
+            error_policy,
+            forensic: false,
+            paths: PathStyle::AsWalked,
+            ignore_file_errors: IgnoreFileErrors::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// An empty report with the same settings, for another thread to fill in
+    fn fork(&self) -> Self {
+        SearchReport {
+            stats: SearchStats { profile: self.stats.profile.as_ref().map(|_| Profile::default()), ..SearchStats::default() },
+            forensic: self.forensic,
+            paths: self.paths.clone(),
+            ignore_file_errors: self.ignore_file_errors,
+            ..SearchReport::new(self.error_policy)
+        }
+    }
+
+    /// `path` as it appears in results
+    fn path_string(&self, path: &Path) -> String {
+        self.paths.format(path)
+    }
+
+    /// Record an entry a filter left out
+    fn skip(&mut self, path: &Path, cause: SkipCause) {
+        self.stats.skipped.record(cause);
+        // Special and changed files were asked to be reported, so errors="collect" lists them too
+        let listed = matches!(cause, SkipCause::Special(_) | SkipCause::Changed) && self.error_policy == ErrorPolicy::Collect;
+        if self.forensic || listed {
+            self.errors.push((self.path_string(path), cause.reason().to_string()));
+        }
+    }
+
+    /// Record a file that could not be searched, applying the error policy
+    fn file_error(&mut self, path: &Path, err: impl std::fmt::Display) -> Result<(), RGErr> {
+        self.stats.skipped.errors += 1;
+        self.apply_error_policy(path, err)
+    }
+
+    /// Record a malformed ignore file line; the walk goes on with the file's other rules
+    fn ignore_file_error(&mut self, err: ignore::Error) -> Result<(), RGErr> {
+        match self.ignore_file_errors {
+            IgnoreFileErrors::Warn => self.warnings.push(err.to_string()),
+            IgnoreFileErrors::Ignore => {}
+            IgnoreFileErrors::Raise => return Err(RGErr::Walk(err)),
+        }
+        Ok(())
+    }
+
+    /// Record a file that failed to read, telling undecodable (binary) contents apart from I/O errors
+    fn read_error(&mut self, path: &Path, err: io::Error) -> Result<(), RGErr> {
+        // A file cut short by the deadline ends the search rather than failing the file
+        if is_deadline_exceeded(&err) {
+            return Err(RGErr::Timeout);
+        }
+        if err.kind() != io::ErrorKind::InvalidData {
+            return self.file_error(path, err);
+        }
+        self.stats.skipped.binary += 1;
+        self.apply_error_policy(path, err)
+    }
+
+    /// Fold in the stats and errors gathered by another thread
+    fn merge(&mut self, other: SearchReport) {
+        self.stats.merge(&other.stats);
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+    }
+
+    fn apply_error_policy(&mut self, path: &Path, err: impl std::fmt::Display) -> Result<(), RGErr> {
+        self.stats.files_skipped += 1;
+        match self.error_policy {
+            ErrorPolicy::Ignore => Ok(()),
+            ErrorPolicy::Collect => {
+                self.errors.push((self.path_string(path), err.to_string()));
+                Ok(())
+            }
+            ErrorPolicy::Raise => Err(RGErr::File(self.path_string(path), err.to_string())),
+        }
+    }
+}
+
+/// ValueError for a type neither ripgrep nor type_add defines, suggesting the closest names
+fn unknown_type_error(name: &str, known: &[String]) -> PyErr {
+    let lower = name.to_lowercase();
+    let aliases = Grep::create_type_mapping();
+    let mut close: Vec<(usize, &str)> = known
+        .iter()
+        .map(String::as_str)
+        .chain(aliases.keys().copied())
+        .map(|candidate| (edit_distance(&lower, candidate), candidate))
+        .filter(|&(distance, _)| distance <= (lower.chars().count() / 3).max(1))
+        .collect();
+    close.sort();
+    close.dedup();
+    let mut message = format!("Unknown file type: {}", name);
+    if close.is_empty() {
+        message.push_str(" (use a ripgrep type name such as \"py\" or \"cmake\", or define it with type_add)");
+    } else {
+        let names: Vec<String> = close.iter().take(3).map(|(_, candidate)| format!("{:?}", candidate)).collect();
+        message.push_str(&format!(" (did you mean {}?)", names.join(", ")));
+    }
+    PyValueError::new_err(message)
+}
+
+/// Levenshtein distance between two strings, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Path an ignore error refers to, if any
+fn walk_error_path(err: &ignore::Error) -> PathBuf {
+    match err {
+        ignore::Error::WithPath { path, .. } => path.clone(),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => walk_error_path(err),
+        _ => PathBuf::new(),
+    }
+}
+
+/// Timeout helper functions
+#[inline]
+fn deadline_from_secs(timeout: Option<f64>) -> Option<Instant> {
+    timeout.map(|t| Instant::now() + Duration::from_secs_f64(t.max(0.0)))
+}
+
+/// Time limits for one search call
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    /// Absolute deadline for the whole search (timeout=)
+    deadline: Option<Instant>,
+    /// Time allowed for directory enumeration alone, excluding matching (walk_timeout=)
+    walk_budget: Option<Duration>,
+}
+
+impl Limits {
+    fn new(timeout: Option<f64>, walk_timeout: Option<f64>) -> Self {
+        Limits {
+            deadline: deadline_from_secs(timeout),
+            walk_budget: walk_timeout.map(|t| Duration::from_secs_f64(t.max(0.0))),
+        }
+    }
+}
+
+/// Result-count limits for one search call; "matches" are matching lines in every mode
+#[derive(Debug, Clone, Copy, Default)]
+struct MatchLimits {
+    /// Stop after this many files with matches (max_files=)
+    max_files: Option<usize>,
+    /// Stop after this many matching lines across all files (max_matches_total=)
+    max_matches_total: Option<usize>,
+    /// Keep at most this many matching lines from each file, like rg -m (max_matches_per_file=)
+    max_matches_per_file: Option<usize>,
+}
+
+/// Results found but left out because of head_limit or the max_* limits
+#[derive(Debug, Default, Clone, Copy)]
+struct Suppressed {
+    files: u64,
+    matches: u64,
+}
+
+/// Tracks how much of the MatchLimits budget the collected results have used
+struct MatchBudget {
+    limits: MatchLimits,
+    files: usize,
+    matches: usize,
+    /// What the limits cut from the files admitted so far, and from the file that stopped the search
+    suppressed: Suppressed,
+}
+
+impl MatchBudget {
+    fn new(limits: MatchLimits) -> Self {
+        MatchBudget { limits, files: 0, matches: 0, suppressed: Suppressed::default() }
+    }
+
+    /// Set once a file's results were cut or dropped because of the limits
+    fn truncated(&self) -> bool {
+        self.suppressed.matches > 0
+    }
+
+    /// Admit a file with `lines` matching lines, returning how many of them to keep
+    /// Returns None once the file or total limit is used up and the search should stop
+    fn admit(&mut self, lines: usize) -> Option<usize> {
+        if lines == 0 {
+            return Some(0);
+        }
+        let files_full = self.limits.max_files.is_some_and(|max| self.files >= max);
+        let total_left = self.limits.max_matches_total.map(|max| max.saturating_sub(self.matches));
+        if files_full || total_left == Some(0) {
+            self.suppressed.files += 1;
+            self.suppressed.matches += lines as u64;
+            return None;
+        }
+        let keep = lines
+            .min(self.limits.max_matches_per_file.unwrap_or(usize::MAX))
+            .min(total_left.unwrap_or(usize::MAX));
+        self.suppressed.matches += (lines - keep) as u64;
+        self.files += 1;
+        self.matches += keep;
+        Some(keep)
+    }
+}
+
+/// Number of distinct lines among per-match entries (entries of one line are adjacent)
+fn distinct_lines<T>(entries: &[T], line: impl Fn(&T) -> u64) -> usize {
+    let mut count = 0;
+    let mut last = None;
+    for entry in entries {
+        if last != Some(line(entry)) {
+            last = Some(line(entry));
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Keep the entries that belong to the first `keep` distinct lines
+fn keep_lines<T>(entries: &mut Vec<T>, keep: usize, line: impl Fn(&T) -> u64) {
+    let mut seen = 0;
+    let mut last = None;
+    entries.retain(|entry| {
+        if last != Some(line(entry)) {
+            last = Some(line(entry));
+            seen += 1;
+        }
+        seen <= keep
+    });
+}
+
+#[inline]
+fn timed_out(deadline: Option<Instant>) -> bool {
+    match deadline {
+        Some(d) => Instant::now() >= d,
+        None => false,
+    }
+}
+
+/// How result paths are written (absolute_paths=, relative_to=)
+#[derive(Debug, Clone)]
+enum PathStyle {
+    /// As walked: under `path` exactly as it was given
+    AsWalked,
+    /// As walked, with `.` and `..` resolved and Windows verbatim prefixes dropped (normalize_paths=)
+    Normalized,
+    /// Absolute; relative paths are joined onto this working directory
+    Absolute(PathBuf),
+    /// Relative to the first directory, with `..` where needed; the second is the working directory
+    RelativeTo(PathBuf, PathBuf),
+}
+
+impl PathStyle {
+    /// Absolute and relative_to paths are always normalized, so normalize_paths only affects the rest
+    fn from_options(absolute_paths: Option<bool>, relative_to: Option<&str>, normalize_paths: bool) -> PyResult<Self> {
+        let cwd = || {
+            std::env::current_dir()
+                .map_err(|e| PyValueError::new_err(format!("Cannot determine the working directory: {e}")))
+        };
+        match (absolute_paths, relative_to) {
+            (Some(true), Some(_)) => Err(PyValueError::new_err("absolute_paths=True cannot be combined with relative_to")),
+            (Some(true), None) => Ok(PathStyle::Absolute(cwd()?)),
+            (_, Some(base)) => {
+                let cwd = cwd()?;
+                Ok(PathStyle::RelativeTo(normalize_path(&cwd.join(base)), cwd))
+            }
+            (Some(false), None) => {
+                let cwd = cwd()?;
+                Ok(PathStyle::RelativeTo(normalize_path(&cwd), cwd))
+            }
+            (None, None) if normalize_paths => Ok(PathStyle::Normalized),
+            (None, None) => Ok(PathStyle::AsWalked),
+        }
+    }
+
+    /// The file behind a path written by `format`
+    fn resolve(&self, shown: &str) -> PathBuf {
+        match self {
+            PathStyle::AsWalked | PathStyle::Normalized | PathStyle::Absolute(_) => PathBuf::from(shown),
+            PathStyle::RelativeTo(base, _) => base.join(shown),
+        }
+    }
+
+    fn format(&self, path: &Path) -> String {
+        match self {
+            PathStyle::AsWalked => path.to_string_lossy().to_string(),
+            PathStyle::Normalized => normalize_path(path).to_string_lossy().to_string(),
+            PathStyle::Absolute(cwd) => normalize_path(&cwd.join(path)).to_string_lossy().to_string(),
+            PathStyle::RelativeTo(base, cwd) => {
+                let path = normalize_path(&cwd.join(path));
+                relative_path(&path, base).unwrap_or(path).to_string_lossy().to_string()
+            }
+        }
+    }
+}
+
+/// Remove `.` and resolve `..` components lexically, without touching the filesystem
+/// A relative path keeps the `..` that climb out of it. Windows verbatim prefixes are
+/// dropped (`\\?\C:\x` becomes `C:\x`, `\\?\UNC\server\share` becomes `\\server\share`),
+/// so long and UNC paths compare and display like any other; the OS still opens them
+/// past MAX_PATH, as Rust's file APIs add the prefix back where needed
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::Prefix(prefix) => match prefix.kind() {
+                std::path::Prefix::VerbatimDisk(disk) => normalized.push(format!("{}:", disk as char)),
+                std::path::Prefix::VerbatimUNC(server, share) => {
+                    normalized.push(format!(r"\\{}\{}", server.to_string_lossy(), share.to_string_lossy()))
+                }
+                _ => normalized.push(prefix.as_os_str()),
+            },
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => match normalized.components().next_back() {
+                Some(std::path::Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                None | Some(std::path::Component::ParentDir) => normalized.push(".."),
+                _ => {} // `..` at the root stays there
+            },
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// `path` relative to `base`, both absolute and normalized; None when they share no root
+/// (e.g. different Windows drives)
+fn relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    let mut path_parts = path.components().peekable();
+    let mut base_parts = base.components().peekable();
+    if path_parts.peek() != base_parts.peek() {
+        return None;
+    }
+    while path_parts.peek().is_some() && path_parts.peek() == base_parts.peek() {
+        path_parts.next();
+        base_parts.next();
+    }
+    let mut relative: PathBuf = base_parts.map(|_| std::path::Component::ParentDir).collect();
+    relative.extend(path_parts);
+    Some(relative)
+}
+
+/// Expand a leading `~` and `$NAME` / `${NAME}` environment variables, like a POSIX shell
+/// Undefined variables are left as written, as os.path.expandvars does
+fn expand_env_vars(input: &str) -> String {
+    let mut input = input.to_string();
+    if input == "~" || input.starts_with("~/") || input.starts_with("~\\") {
+        if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            input.replace_range(..1, &home.to_string_lossy());
+        }
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input.as_str();
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let (name, consumed) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[dollar..dollar + 1 + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where a search gets its candidate files from
+enum FileSource {
+    /// Recursive directory walk honoring ignore files
+    Walk(ignore::Walk, SpecialFiles),
+    /// Directory walk that also reports what its filters excluded (stats=True)
+    TrackedWalk(TrackedWalk),
+    /// Explicit file list; ignore files are bypassed but glob overrides still apply
+    List(Vec<PathBuf>, Option<Override>, SpecialFiles),
+    /// Another source, fully enumerated and then sorted (sort=); the sort key and descending
+    Sorted(Box<FileSource>, SortBy, bool),
+    /// Another source with its files renamed to canonical paths and repeats skipped (canonical_paths=)
+    Canonical(Box<FileSource>),
+    /// Another source with files outside size or modification-time bounds skipped
+    Bounded(Box<FileSource>, FileBounds),
+    /// Another source with files matching generated_globs= skipped
+    Generated(Box<FileSource>, GlobSet),
+    /// Another source with each archive replaced by its members as `archive!/member` paths (archives=)
+    #[cfg(feature = "archives")]
+    Archives(Box<FileSource>),
+    /// Another source with each .ipynb file replaced by its cells as `notebook!/cells/N` paths
+    /// (preprocessor="ipynb")
+    Notebooks(Box<FileSource>),
+    /// Another source with files whose contents repeat an earlier file's skipped (dedupe="content");
+    /// each (duplicate, original) pair is recorded in the shared list
+    Deduped(Box<FileSource>, ReadOptions, Arc<Mutex<Vec<(PathBuf, PathBuf)>>>),
+    /// Another source cut off before the file that would take the data read past a byte
+    /// budget (max_bytes=); the flag records that it cut anything
+    ByteBudget(Box<FileSource>, u64, Arc<AtomicBool>),
+}
+
+/// Size and modification-time limits on searched files
+/// (max_filesize=, min_filesize=, modified_after=, modified_before=)
+#[derive(Debug, Clone, Copy, Default)]
+struct FileBounds {
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+}
+
+impl FileBounds {
+    fn is_unbounded(&self) -> bool {
+        self.min_size.is_none()
+            && self.max_size.is_none()
+            && self.modified_after.is_none()
+            && self.modified_before.is_none()
+    }
+
+    /// Why `path` falls outside the bounds, if it does
+    /// Files whose metadata can't be read pass, so the search reports the error itself
+    fn check(&self, path: &Path) -> Option<SkipCause> {
+        let meta = std::fs::metadata(path).ok()?;
+        let size = meta.len();
+        if self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max) {
+            return Some(SkipCause::Size);
+        }
+        if self.modified_after.is_none() && self.modified_before.is_none() {
+            return None;
+        }
+        let modified = meta.modified().ok()?;
+        let too_old = self.modified_after.is_some_and(|after| modified < after);
+        let too_new = self.modified_before.is_some_and(|before| modified > before);
+        (too_old || too_new).then_some(SkipCause::Modified)
+    }
+}
+
+/// Build the generated_globs= set; as in .gitignore, globs without a '/' match the file name
+/// and relative globs match below any directory
+fn build_generated_globs(globs: Option<Vec<String>>) -> PyResult<Option<GlobSet>> {
+    let Some(globs) = globs.filter(|globs| !globs.is_empty()) else {
+        return Ok(None);
+    };
+    let mut builder = GlobSetBuilder::new();
+    for glob in &globs {
+        let glob = if glob.contains('/') && !glob.starts_with('/') && !glob.starts_with("**") {
+            format!("**/{}", glob)
+        } else {
+            glob.clone()
+        };
+        builder.add(Glob::new(&glob).map_err(|e| PyValueError::new_err(format!("Invalid generated_globs glob: {}", e)))?);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| PyValueError::new_err(format!("Invalid generated_globs glob: {}", e)))
+}
+
+fn is_generated(globs: &GlobSet, path: &Path) -> bool {
+    globs.is_match(path) || path.file_name().is_some_and(|name| globs.is_match(name))
+}
+
+/// Average line length, in bytes, above which skip_minified=True treats a file as minified
+const MINIFIED_LINE_LENGTH: usize = 300;
+
+/// Bytes read from the start of a file to judge whether it is minified
+const MINIFIED_SAMPLE: u64 = 64 * 1024;
+
+/// Parse skip_minified=: True for the default threshold, or an average line length in bytes
+fn parse_skip_minified(value: Option<&PyAny>) -> PyResult<Option<usize>> {
+    match value {
+        None => Ok(None),
+        Some(value) if value.is_instance_of::<PyBool>() => Ok(value.extract::<bool>()?.then_some(MINIFIED_LINE_LENGTH)),
+        Some(value) => match value.extract::<usize>() {
+            Ok(length) if length > 0 => Ok(Some(length)),
+            _ => Err(PyValueError::new_err("skip_minified must be a bool or a positive average line length in bytes")),
+        },
+    }
+}
+
+/// Whether the start of a file averages more than `max_line_length` bytes per line
+/// A bundle or data blob on one huge line always does. Unreadable files are not minified,
+/// so the search reports the error itself
+fn looks_minified(path: &Path, read: &ReadOptions, max_line_length: usize) -> bool {
+    let mut sample = Vec::new();
+    if open_input(path, read).and_then(|input| input.take(MINIFIED_SAMPLE).read_to_end(&mut sample)).is_err() {
+        return false;
+    }
+    let terminator = read.line_terminator.as_byte();
+    let lines = sample.iter().filter(|&&b| b == terminator).count() + usize::from(sample.last().is_some_and(|&b| b != terminator));
+    lines > 0 && sample.len() / lines > max_line_length
+}
+
+/// A file to search, or an entry a filter left out
+enum Candidate {
+    File(PathBuf),
+    Skipped(PathBuf, SkipCause),
+    /// Lines of a directory's ignore files that could not be parsed; its other rules still apply
+    IgnoreFileError(ignore::Error),
+}
+
+impl FileSource {
+    /// Enumerate this source up front and yield its files in `sort` order
+    fn sorted(self, sort: SortBy, descending: bool) -> Self {
+        if sort == SortBy::None {
+            return self;
+        }
+        FileSource::Sorted(Box::new(self), sort, descending)
+    }
+
+    /// Resolve symlinks and `.`/`..` in file paths and skip files already yielded
+    fn canonical(self, canonical: bool) -> Self {
+        if !canonical {
+            return self;
+        }
+        FileSource::Canonical(Box::new(self))
+    }
+
+    /// Skip files outside size or modification-time bounds
+    fn bounded(self, bounds: FileBounds) -> Self {
+        if bounds.is_unbounded() {
+            return self;
+        }
+        FileSource::Bounded(Box::new(self), bounds)
+    }
+
+    /// Skip files with the same contents as one already yielded, recording them in `duplicates`
+    fn deduped(self, dedupe: bool, read: &ReadOptions, duplicates: &Arc<Mutex<Vec<(PathBuf, PathBuf)>>>) -> Self {
+        if !dedupe {
+            return self;
+        }
+        FileSource::Deduped(Box::new(self), read.clone(), duplicates.clone())
+    }
+
+    /// Stop before the file that would take the data read past `max_bytes`, raising `exhausted`
+    fn byte_budget(self, max_bytes: Option<u64>, exhausted: &Arc<AtomicBool>) -> Self {
+        match max_bytes {
+            Some(max_bytes) => FileSource::ByteBudget(Box::new(self), max_bytes, exhausted.clone()),
+            None => self,
+        }
+    }
+
+    /// Skip files matching any of the generated-file globs
+    fn generated(self, globs: Option<GlobSet>) -> Self {
+        match globs {
+            Some(globs) => FileSource::Generated(Box::new(self), globs),
+            None => self,
+        }
+    }
+
+    /// Search the members of .zip/.jar/.whl/.tar/.tar.gz files instead of the archives themselves
+    fn archives(self, archives: bool) -> Self {
+        #[cfg(feature = "archives")]
+        if archives {
+            return FileSource::Archives(Box::new(self));
+        }
+        #[cfg(not(feature = "archives"))]
+        let _ = archives;
+        self
+    }
+
+    /// Search the code and markdown cells of .ipynb files instead of their JSON
+    fn notebooks(self, notebooks: bool) -> Self {
+        if !notebooks {
+            return self;
+        }
+        FileSource::Notebooks(Box::new(self))
+    }
+
+    /// Yield regular files only, with unreadable list entries reported as walk errors
+    fn into_iter(self) -> Box<dyn Iterator<Item = Result<Candidate, ignore::Error>>> {
+        match self {
+            FileSource::Walk(walker, special) => Box::new(walker.filter_map(move |entry| match entry {
+                Ok(entry) => match entry.file_type() {
+                    Some(ft) if ft.is_dir() => entry.error().cloned().map(|err| Ok(Candidate::IgnoreFileError(err))),
+                    Some(ft) if ft.is_file() => Some(Ok(Candidate::File(entry.into_path()))),
+                    Some(ft) if !ft.is_dir() => special.candidate(entry.into_path(), ft).map(Ok),
+                    _ => None,
+                },
+                Err(err) => Some(Err(err)),
+            })),
+            FileSource::TrackedWalk(walk) => Box::new(walk),
+            FileSource::List(paths, overrides, special) => Box::new(paths.into_iter().filter_map(move |path| {
+                match std::fs::metadata(&path) {
+                    Ok(meta) if meta.is_dir() => None,
+                    Ok(meta) if !meta.is_file() => special.candidate(path, meta.file_type()).map(Ok),
+                    Ok(_) if overrides.as_ref().is_some_and(|o| o.matched(&path, false).is_ignore()) => {
+                        Some(Ok(Candidate::Skipped(path, SkipCause::Glob)))
+                    }
+                    Ok(_) => Some(Ok(Candidate::File(path))),
+                    Err(err) => Some(Err(ignore::Error::WithPath {
+                        path,
+                        err: Box::new(ignore::Error::Io(err)),
+                    })),
+                }
+            })),
+            // Enumerated lazily, so the whole walk counts as the first step against walk_timeout
+            FileSource::Sorted(source, sort, descending) => {
+                Box::new(std::iter::once(*source).flat_map(move |source| {
+                    let (files, mut others): (Vec<_>, Vec<_>) = source
+                        .into_iter()
+                        .partition(|entry| matches!(entry, Ok(Candidate::File(_))));
+                    let mut files: Vec<PathBuf> = files
+                        .into_iter()
+                        .filter_map(|entry| match entry {
+                            Ok(Candidate::File(path)) => Some(path),
+                            _ => None,
+                        })
+                        .collect();
+                    sort.sort(&mut files, descending);
+                    others.extend(files.into_iter().map(|path| Ok(Candidate::File(path))));
+                    others
+                }))
+            }
+            FileSource::Canonical(source) => {
+                // Paths inside the working directory are reported relative to it, so the
+                // same file reads the same however the search root was spelled
+                let base = std::env::current_dir().and_then(std::fs::canonicalize).ok();
+                let mut seen = HashSet::new();
+                Box::new(source.into_iter().map(move |entry| match entry {
+                    Ok(Candidate::File(path)) => {
+                        let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                        let canonical = match base.as_deref().and_then(|base| canonical.strip_prefix(base).ok()) {
+                            Some(relative) => relative.to_path_buf(),
+                            None => canonical,
+                        };
+                        if seen.insert(canonical.clone()) {
+                            Ok(Candidate::File(canonical))
+                        } else {
+                            Ok(Candidate::Skipped(path, SkipCause::Duplicate))
+                        }
+                    }
+                    other => other,
+                }))
+            }
+            FileSource::Bounded(source, bounds) => Box::new(source.into_iter().map(move |entry| match entry {
+                Ok(Candidate::File(path)) => match bounds.check(&path) {
+                    Some(cause) => Ok(Candidate::Skipped(path, cause)),
+                    None => Ok(Candidate::File(path)),
+                },
+                other => other,
+            })),
+            FileSource::Deduped(source, read, duplicates) => {
+                // Files are bucketed by size and hash, then compared byte for byte, so a hash
+                // collision never hides a file; unreadable files are left for the search to report
+                let mut seen: HashMap<(u64, u64), Vec<PathBuf>> = HashMap::new();
+                Box::new(source.into_iter().map(move |entry| match entry {
+                    Ok(Candidate::File(path)) => {
+                        let Ok(digest) = content_digest(&path, &read) else {
+                            return Ok(Candidate::File(path));
+                        };
+                        let originals = seen.entry(digest).or_default();
+                        match originals.iter().find(|original| same_content(original, &path, &read).unwrap_or(false)) {
+                            Some(original) => {
+                                if let Ok(mut duplicates) = duplicates.lock() {
+                                    duplicates.push((path.clone(), original.clone()));
+                                }
+                                Ok(Candidate::Skipped(path, SkipCause::Duplicate))
+                            }
+                            None => {
+                                originals.push(path.clone());
+                                Ok(Candidate::File(path))
+                            }
+                        }
+                    }
+                    other => other,
+                }))
+            }
+            FileSource::ByteBudget(source, max_bytes, exhausted) => {
+                // Archive members and notebook cells are read from their container, counted once
+                let mut used = 0u64;
+                let mut last_container: Option<PathBuf> = None;
+                Box::new(source.into_iter().map_while(move |entry| {
+                    if let Ok(Candidate::File(path)) = &entry {
+                        let container = container_file(path);
+                        if last_container.as_deref() != Some(container) {
+                            let size = std::fs::metadata(container).map_or(0, |meta| meta.len());
+                            if used.saturating_add(size) > max_bytes {
+                                exhausted.store(true, Ordering::Relaxed);
+                                return None;
+                            }
+                            used += size;
+                            last_container = Some(container.to_path_buf());
+                        }
+                    }
+                    Some(entry)
+                }))
+            }
+            FileSource::Generated(source, globs) => Box::new(source.into_iter().map(move |entry| match entry {
+                Ok(Candidate::File(path)) if is_generated(&globs, &path) => Ok(Candidate::Skipped(path, SkipCause::Generated)),
+                other => other,
+            })),
+            #[cfg(feature = "archives")]
+            FileSource::Archives(source) => Box::new(source.into_iter().flat_map(|entry| {
+                let (path, kind) = match entry {
+                    Ok(Candidate::File(path)) => match ArchiveKind::of(&path) {
+                        Some(kind) => (path, kind),
+                        None => return vec![Ok(Candidate::File(path))],
+                    },
+                    other => return vec![other],
+                };
+                match expand_archive(&path, kind) {
+                    Ok(members) => members
+                        .into_iter()
+                        .map(|(member, contents)| {
+                            if let Some(contents) = contents {
+                                EXPANDED_MEMBERS.with_borrow_mut(|expanded| expanded.insert(member.clone(), contents.into()));
+                            }
+                            Ok(Candidate::File(member))
+                        })
+                        .collect(),
+                    Err(err) => vec![Err(ignore::Error::WithPath { path, err: Box::new(ignore::Error::Io(err)) })],
+                }
+            })),
+            FileSource::Notebooks(source) => Box::new(source.into_iter().flat_map(|entry| {
+                let path = match entry {
+                    Ok(Candidate::File(path)) if is_notebook(&path) => path,
+                    other => return vec![other],
+                };
+                match notebook_cells(&path) {
+                    Ok(cells) => cells.into_iter().map(|cell| Ok(Candidate::File(cell))).collect(),
+                    Err(err) => vec![Err(ignore::Error::WithPath { path, err: Box::new(ignore::Error::Io(err)) })],
+                }
+            })),
+        }
+    }
+}
+
+/// Directory walk that infers why entries were excluded: each directory the walker
+/// enters is listed, and children it never yields were dropped by a filter.
+/// Costs one extra directory read per directory, so it is only used for stats
+struct TrackedWalk {
+    walker: ignore::Walk,
+    overrides: Option<Override>,
+    special: SpecialFiles,
+    /// Children of entered directories not yet yielded, with whether their name is hidden
+    pending: HashMap<PathBuf, bool>,
+    queued: Vec<(PathBuf, SkipCause)>,
+}
+
+impl TrackedWalk {
+    fn new(walker: ignore::Walk, overrides: Option<Override>, special: SpecialFiles) -> Self {
+        TrackedWalk { walker, overrides, special, pending: HashMap::new(), queued: Vec::new() }
+    }
+
+    fn enter_dir(&mut self, dir: &Path) {
+        let Ok(children) = std::fs::read_dir(dir) else {
+            return; // the walker reports this error itself
+        };
+        for child in children.flatten() {
+            let path = child.path();
+            let is_dir = child.file_type().is_ok_and(|ft| ft.is_dir());
+            // Overrides take precedence over every other rule, as in the walker
+            if self.overrides.as_ref().is_some_and(|o| o.matched(&path, is_dir).is_ignore()) {
+                self.queued.push((path, SkipCause::Glob));
+                continue;
+            }
+            let hidden = child.file_name().to_string_lossy().starts_with('.');
+            self.pending.insert(path, hidden);
+        }
+    }
+}
+
+impl Iterator for TrackedWalk {
+    type Item = Result<Candidate, ignore::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((path, cause)) = self.queued.pop() {
+                return Some(Ok(Candidate::Skipped(path, cause)));
+            }
+            match self.walker.next() {
+                Some(Ok(entry)) => {
+                    self.pending.remove(entry.path());
+                    match entry.file_type() {
+                        Some(ft) if ft.is_dir() => {
+                            self.enter_dir(entry.path());
+                            if let Some(err) = entry.error() {
+                                return Some(Ok(Candidate::IgnoreFileError(err.clone())));
+                            }
+                        }
+                        Some(ft) if ft.is_file() => return Some(Ok(Candidate::File(entry.into_path()))),
+                        Some(ft) => {
+                            if let Some(candidate) = self.special.candidate(entry.into_path(), ft) {
+                                return Some(Ok(candidate));
+                            }
+                        }
+                        None => {}
+                    }
+                }
+                Some(Err(err)) => {
+                    self.pending.remove(&walk_error_path(&err));
+                    return Some(Err(err));
+                }
+                // Whatever the finished walk never yielded was hidden or ignored
+                None if !self.pending.is_empty() => {
+                    self.queued = self
+                        .pending
+                        .drain()
+                        .map(|(path, hidden)| (path, if hidden { SkipCause::Hidden } else { SkipCause::Gitignore }))
+                        .collect();
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+/// Walk files that pass the type filter, calling `visit` for each one
+/// `visit` returns Ok(false) to stop the walk early; walk errors go through the error policy
+fn walk_files(
+    source: FileSource,
+    type_matcher: Option<&TypeFilter>,
+    limits: Limits,
+    report: &mut SearchReport,
+    mut visit: impl FnMut(&Path, &mut SearchReport) -> Result<bool, RGErr>,
+) -> Result<(), RGErr> {
+    let mut entries = source.into_iter();
+    let mut walk_time = Duration::ZERO;
+    loop {
+        // Only time spent producing the next entry counts against the walk budget
+        let walk_started = Instant::now();
+        let Some(entry) = entries.next() else {
+            break;
+        };
+        walk_time += walk_started.elapsed();
+        if let Some(budget) = limits.walk_budget {
+            if walk_time > budget {
+                return Err(RGErr::WalkTimeout(budget));
+            }
+        }
+        if timed_out(limits.deadline) {
+            return Err(RGErr::Timeout);
+        }
+
+        if !visit_candidate(entry, type_matcher, report, &mut visit)? {
+            break;
+        }
+    }
+    // Members of archives the walk expanded but stopped before reaching
+    EXPANDED_MEMBERS.with_borrow_mut(HashMap::clear);
+
+    if let Some(profile) = &mut report.stats.profile {
+        profile.walk += walk_time.as_secs_f64();
+    }
+    Ok(())
+}
+
+/// Handle one walk entry: record skips, apply the error policy and type filter, then visit it
+/// Returns Ok(false) when `visit` asks to stop the walk
+fn visit_candidate(
+    entry: Result<Candidate, ignore::Error>,
+    type_matcher: Option<&TypeFilter>,
+    report: &mut SearchReport,
+    visit: &mut impl FnMut(&Path, &mut SearchReport) -> Result<bool, RGErr>,
+) -> Result<bool, RGErr> {
+    let path = match entry {
+        Ok(Candidate::File(path)) => path,
+        Ok(Candidate::Skipped(path, cause)) => {
+            take_expanded_member(&path);
+            report.skip(&path, cause);
+            return Ok(true);
+        }
+        Ok(Candidate::IgnoreFileError(err)) => {
+            report.ignore_file_error(err)?;
+            return Ok(true);
+        }
+        Err(err) if report.error_policy == ErrorPolicy::Raise => return Err(RGErr::Walk(err)),
+        Err(err) => {
+            report.file_error(&walk_error_path(&err), &err)?;
+            return Ok(true);
+        }
+    };
+
+    // Apply type filter manually for AND logic with glob
+    if let Some(type_matcher) = type_matcher {
+        if !type_matcher.matches(&path) {
+            take_expanded_member(&path);
+            report.skip(&path, SkipCause::TypeFilter);
+            return Ok(true);
+        }
+    }
+
+    // A panic while searching one file becomes a per-file error instead of
+    // unwinding into Python and aborting the whole search
+    let contents = take_expanded_member(&path);
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| with_member(&path, contents, || visit(&path, report))));
+    match outcome {
+        Ok(result) => result,
+        Err(payload) => {
+            report.file_error(&path, format!("panic: {}", panic_message(&payload)))?;
+            Ok(true)
+        }
+    }
+}
+
+/// Worker threads used to search file contents (threads=, queue_size=)
+#[derive(Debug, Clone, Copy)]
+struct WorkerPool {
+    threads: usize,
+    /// Capacity of the bounded queues between walker, workers and collector
+    queue_size: usize,
+}
+
+impl WorkerPool {
+    fn new(threads: Option<usize>, queue_size: Option<usize>) -> Self {
+        let threads = match threads {
+            Some(0) | None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            Some(n) => n,
+        };
+        WorkerPool { threads, queue_size: queue_size.unwrap_or(64).max(1) }
+    }
+}
+
+/// Walk files and run `search` on each one, handing every result to `collect` in walk order
+/// With more than one thread the walk runs on its own thread and files are searched by a
+/// worker pool; bounded queues keep memory flat however large the tree is.
+/// With batch_small_files, workers take files in runs of SMALL_FILE_BATCH to cut queue traffic.
+/// `collect` returns false to stop the search early
+#[allow(clippy::too_many_arguments)]
+fn search_files<T: Send>(
+    source: FileSource,
+    type_matcher: Option<&TypeFilter>,
+    limits: Limits,
+    pool: WorkerPool,
+    read: &ReadOptions,
+    report: &mut SearchReport,
+    search: impl Fn(&mut Searcher, &Path, &mut SearchReport) -> Result<T, RGErr> + Sync,
+    mut collect: impl FnMut(T) -> bool,
+) -> Result<(), RGErr> {
+    let started = Instant::now();
+    if pool.threads <= 1 {
+        let mut searcher = read.searcher(); // Create once, reuse for all files
+        let outcome = walk_files(source, type_matcher, limits, report, |path, report| {
+            let found = profiled(report, |report| {
+                if !read.admits(&mut searcher, path, report) {
+                    return Ok(None);
+                }
+                consistent(report, read.changed_files, path, |report| search(&mut searcher, path, report).map(Some))
+            })?;
+            Ok(found.is_none_or(&mut collect))
+        });
+        record_searching(report, started);
+        return outcome;
+    }
+
+    let template = report.fork();
+    // Each work item is a run of consecutive files, numbered from its first one
+    let batch_size = if read.batch_small_files { SMALL_FILE_BATCH } else { 1 };
+    // Archive members travel with the contents the walk expanded them to
+    let (work_tx, work_rx) = mpsc::sync_channel::<(u64, Vec<(PathBuf, Option<Arc<[u8]>>)>)>(pool.queue_size);
+    let (result_tx, result_rx) = mpsc::sync_channel::<(u64, Result<Option<T>, RGErr>)>(pool.queue_size);
+    // Shared by the workers alone: once the last one exits, the queue closes and a walker
+    // blocked on a full queue gets an error instead of waiting forever
+    let work_rx = Arc::new(Mutex::new(work_rx));
+
+    let outcome = thread::scope(|scope| {
+        let template = &template;
+        let walker = scope.spawn(move || {
+            let mut walk_report = template.fork();
+            let mut seq = 0u64;
+            let mut batch = Vec::with_capacity(batch_size);
+            let walked = walk_files(source, type_matcher, limits, &mut walk_report, |path, _report| {
+                seq += 1;
+                batch.push((path.to_path_buf(), current_member(path)));
+                if batch.len() < batch_size {
+                    return Ok(true);
+                }
+                let first = seq + 1 - batch.len() as u64;
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                Ok(work_tx.send((first, full)).is_ok()) // workers gone: stop walking
+            });
+            if !batch.is_empty() {
+                let _ = work_tx.send((seq + 1 - batch.len() as u64, batch));
+            }
+            (walked, walk_report)
+        });
+
+        let workers: Vec<_> = (0..pool.threads)
+            .map(|_| {
+                let (work_rx, result_tx, search) = (Arc::clone(&work_rx), result_tx.clone(), &search);
+                scope.spawn(move || {
+                    let mut searcher = read.searcher();
+                    let mut worker_report = template.fork();
+                    'work: loop {
+                        let next = work_rx.lock().map_err(|_| ()).and_then(|rx| rx.recv().map_err(|_| ()));
+                        let Ok((first, paths)) = next else {
+                            break;
+                        };
+                        for (seq, (path, contents)) in (first..).zip(paths) {
+                            let result = if timed_out(limits.deadline) {
+                                Err(RGErr::Timeout)
+                            } else {
+                                with_member(&path, contents, || profiled(&mut worker_report, |worker_report| {
+                                    if !read.admits(&mut searcher, &path, worker_report) {
+                                        return Ok(None);
+                                    }
+                                    consistent(worker_report, read.changed_files, &path, |worker_report| {
+                                        // As in walk_files, a panic only fails the file it happened in
+                                        match panic::catch_unwind(AssertUnwindSafe(|| search(&mut searcher, &path, worker_report))) {
+                                            Ok(result) => result.map(Some),
+                                            Err(payload) => worker_report
+                                                .file_error(&path, format!("panic: {}", panic_message(&payload)))
+                                                .map(|()| None),
+                                        }
+                                    })
+                                }))
+                            };
+                            if result_tx.send((seq, result)).is_err() {
+                                break 'work; // collector stopped early
+                            }
+                        }
+                    }
+                    worker_report
+                })
+            })
+            .collect();
+        drop(result_tx);
+        drop(work_rx);
+
+        // Workers finish out of order; buffer results until the next file in walk order arrives
+        let mut pending = BTreeMap::new();
+        let mut next_seq = 1u64;
+        let mut outcome = Ok(());
+        'collect: for (seq, result) in result_rx.iter() {
+            pending.insert(seq, result);
+            while let Some(result) = pending.remove(&next_seq) {
+                next_seq += 1;
+                match result {
+                    Ok(Some(found)) => {
+                        if !collect(found) {
+                            break 'collect;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        outcome = Err(err);
+                        break 'collect;
+                    }
+                }
+            }
+        }
+        drop(result_rx); // unblocks workers and, through them, the walker
+
+        // Per-file panics are caught above; one escaping a thread fails the search
+        // with an IOErrorInfo rather than unwinding into Python
+        for worker in workers {
+            match worker.join() {
+                Ok(worker_report) => report.merge(worker_report),
+                Err(payload) => outcome = outcome.and(Err(thread_panicked("search worker", &payload))),
+            }
+        }
+        let walked = match walker.join() {
+            Ok((walked, walk_report)) => {
+                report.merge(walk_report);
+                walked
+            }
+            Err(payload) => Err(thread_panicked("walker", &payload)),
+        };
+        outcome.and(walked)
+    });
+    record_searching(report, started);
+    outcome
+}
+
+thread_local! {
+    /// Time this thread has spent opening and reading files (profile=True)
+    static READ_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Run `search` on one file, splitting its time between reading and matching (profile=True)
+fn profiled<T>(report: &mut SearchReport, search: impl FnOnce(&mut SearchReport) -> T) -> T {
+    if report.stats.profile.is_none() {
+        return search(report);
+    }
+    let started = Instant::now();
+    let read_before = READ_TIME.get();
+    let result = search(report);
+    let read = READ_TIME.get().saturating_sub(read_before);
+    if let Some(profile) = &mut report.stats.profile {
+        profile.read += read.as_secs_f64();
+        profile.matching += started.elapsed().saturating_sub(read).as_secs_f64();
+    }
+    result
+}
+
+/// Size and modification time of the file behind `path`, to tell whether it changed
+fn file_version(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    std::fs::metadata(container_file(path)).ok().map(|meta| (meta.len(), meta.modified().ok()))
+}
+
+/// Run `search` on one file under the changed_files= policy, comparing the file's size and
+/// modification time before and after; each attempt fills its own report so only the
+/// accepted one is counted
+fn consistent<T>(
+    report: &mut SearchReport,
+    policy: ChangedFiles,
+    path: &Path,
+    mut search: impl FnMut(&mut SearchReport) -> Result<Option<T>, RGErr>,
+) -> Result<Option<T>, RGErr> {
+    if policy == ChangedFiles::Ignore {
+        return search(report);
+    }
+    let mut retries = if policy == ChangedFiles::Retry { 1 } else { 0 };
+    loop {
+        let before = file_version(path);
+        let mut attempt = report.fork();
+        let result = search(&mut attempt);
+        if file_version(path) == before {
+            report.merge(attempt);
+            return result;
+        }
+        if retries > 0 {
+            retries -= 1;
+            continue;
+        }
+        report.stats.changed.push(report.path_string(path));
+        if policy == ChangedFiles::Skip {
+            report.skip(path, SkipCause::Changed);
+            return Ok(None);
+        }
+        report.merge(attempt);
+        return result;
+    }
+}
+
+/// Record the wall-clock time search_files() took since `started` (profile=True)
+fn record_searching(report: &mut SearchReport, started: Instant) {
+    if let Some(profile) = &mut report.stats.profile {
+        profile.searching += started.elapsed().as_secs_f64();
+    }
+}
+
+/// Extract the message from a panic payload
+fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// The error reported when a search thread died from a panic outside any one file
+fn thread_panicked(thread: &str, payload: &Box<dyn Any + Send>) -> RGErr {
+    RGErr::Io(io::Error::other(format!("{} thread panicked: {}", thread, panic_message(payload))))
+}
+
+/// Validate a matched line as UTF-8, reporting failures as `InvalidData` (binary)
+fn utf8_line(line: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Read a file as UTF-8 lines (invalid UTF-8 is reported as `InvalidData`)
+fn read_file_lines(path: &Path, read: &ReadOptions) -> io::Result<Vec<String>> {
+    Ok(read_file_lines_with_offsets(path, read)?.into_iter().map(|(_, line)| line).collect())
+}
+
+/// Read a file's lines along with the byte offset at which each line starts
+/// Line terminators (`\n`, `\r\n` with crlf, or NUL with null_data) are stripped
+fn read_file_lines_with_offsets(path: &Path, read: &ReadOptions) -> io::Result<Vec<(u64, String)>> {
+    read_lines_with_offsets(open_input(path, read)?, read)
+}
+
+/// Read a file's lines unless `matcher`'s prefilter rules the whole file out (None)
+/// Files it cannot rule out, and those that would fail to read as lines, take the full path
+fn read_candidate_lines(path: &Path, read: &ReadOptions, matcher: &SearchMatcher) -> io::Result<Option<Vec<String>>> {
+    let candidate_lines = |bytes: &mut Vec<u8>| {
+        open_input(path, read)?.read_to_end(bytes)?;
+        if read.heap_limit.is_none() && matcher.rejects(bytes) && std::str::from_utf8(bytes).is_ok() {
+            return Ok(None);
+        }
+        Ok(Some(read_lines_with_offsets(bytes.as_slice(), read)?.into_iter().map(|(_, line)| line).collect()))
+    };
+    if !read.batch_small_files {
+        return candidate_lines(&mut Vec::new());
+    }
+    READ_BUFFER.with_borrow_mut(|buffer| {
+        buffer.clear();
+        let lines = candidate_lines(buffer);
+        // Keep the allocation for the next small file, but not one a large file grew
+        if buffer.capacity() > SMALL_FILE_SIZE {
+            *buffer = Vec::with_capacity(SMALL_FILE_SIZE);
+        }
+        lines
+    })
+}
+
+/// Run `searcher` over an opened input of `size` bytes
+/// With batch_small_files, a small input is read whole into the pooled buffer and searched
+/// as a slice instead of through a fresh read buffer
+fn search_input<S: Sink>(
+    searcher: &mut Searcher,
+    matcher: &SearchMatcher,
+    mut input: Box<dyn Read>,
+    size: u64,
+    read: &ReadOptions,
+    sink: S,
+) -> Result<(), S::Error> {
+    if !read.batch_small_files || read.heap_limit.is_some() || size > SMALL_FILE_SIZE as u64 {
+        return searcher.search_reader(matcher, read.buffered(input), sink);
+    }
+    READ_BUFFER.with_borrow_mut(|buffer| {
+        buffer.clear();
+        input.read_to_end(buffer).map_err(S::Error::error_io)?;
+        let result = searcher.search_slice(matcher, buffer, sink);
+        if buffer.capacity() > SMALL_FILE_SIZE {
+            *buffer = Vec::with_capacity(SMALL_FILE_SIZE);
+        }
+        result
+    })
+}
+
+/// Files up to this many bytes are read into the pooled buffer without reallocating it
+const SMALL_FILE_SIZE: usize = 64 * 1024;
+
+/// Files a walker hands to a worker at a time with batch_small_files
+const SMALL_FILE_BATCH: usize = 64;
+
+thread_local! {
+    /// Read buffer reused across the files a thread searches (batch_small_files=)
+    static READ_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(SMALL_FILE_SIZE));
+}
+
+/// Split any reader into lines with their starting byte offsets, as for a file
+fn read_lines_with_offsets(input: impl Read, read: &ReadOptions) -> io::Result<Vec<(u64, String)>> {
+    read_lines_with_offsets_upto(input, read, u64::MAX)
+}
+
+/// Like read_lines_with_offsets, but stop reading after the first `max_lines` lines
+fn read_lines_with_offsets_upto(input: impl Read, read: &ReadOptions, max_lines: u64) -> io::Result<Vec<(u64, String)>> {
+    let mut reader = read.buffered(input);
+    let terminator = read.line_terminator.as_byte();
+    let line_limit = read.heap_limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+    let mut lines = Vec::new();
+    let mut offset = 0u64;
+    let mut buf = Vec::new();
+    while (lines.len() as u64) < max_lines {
+        // Stop reading a line once it outgrows heap_limit instead of buffering all of it
+        let n = (&mut reader).take(line_limit).read_until(terminator, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        if n as u64 == line_limit && buf.last() != Some(&terminator) {
+            return Err(io::Error::other(format!("line exceeds heap_limit of {} bytes", line_limit - 1)));
+        }
+        let start = offset;
+        offset += n as u64;
+        if buf.last() == Some(&terminator) {
+            buf.pop();
+            // As in the searcher, `\r` is part of the line unless crlf= makes it part of the terminator
+            if read.line_terminator.is_crlf() && buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+        let line = if read.raw_bytes {
+            // Without UTF-8 to go by, NUL bytes mark binary files, as in rg (unless ignore_level=3)
+            if !read.binary && terminator != b'\0' && buf.contains(&b'\0') {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "binary file: contains NUL bytes"));
+            }
+            let line = escape_raw_bytes(&buf);
+            buf.clear();
+            line
+        } else {
+            String::from_utf8(std::mem::take(&mut buf)).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        };
+        lines.push((start, line));
+    }
+    Ok(lines)
+}
+
+/// A line as results show it: without the `\r` of a `\r\n` ending, which stays in the
+/// matched text unless crlf= is set, just as the searcher-based modes leave it out
+fn shown_line(line: &str) -> &str {
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// First of the 256 private-use code points that stand in for raw bytes (text_mode="bytes")
+const RAW_BYTE_BASE: u32 = 0x10FF00;
+
+/// Decode `bytes` as UTF-8 for searching, with each byte outside a valid sequence standing in
+/// as RAW_BYTE_BASE + byte; code points already in that range are escaped byte by byte too,
+/// so `unescape_raw_bytes` restores the input exactly
+fn escape_raw_bytes(bytes: &[u8]) -> String {
+    let raw_byte = |byte: u8| char::from_u32(RAW_BYTE_BASE + u32::from(byte)).unwrap_or(char::REPLACEMENT_CHARACTER);
+    let mut text = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if u32::from(c) >= RAW_BYTE_BASE {
+                text.extend(c.encode_utf8(&mut [0; 4]).bytes().map(raw_byte));
+            } else {
+                text.push(c);
+            }
+        }
+        text.extend(chunk.invalid().iter().copied().map(raw_byte));
+    }
+    text
+}
+
+/// The bytes `escape_raw_bytes` read `text` from
+fn unescape_raw_bytes(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match u32::from(c).checked_sub(RAW_BYTE_BASE) {
+            Some(byte) => bytes.push(byte as u8),
+            None => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    bytes
+}
+
+/// Formatted content results with each line turned back into the bytes it was read as
+/// (text_mode="bytes"); grouped results keep their paths and numbers as they are
+fn content_as_bytes(py: Python, results: PyObject) -> PyResult<PyObject> {
+    let to_bytes = |text: &str| -> PyObject { PyBytes::new(py, &unescape_raw_bytes(text)).into_py(py) };
+    let results = results.as_ref(py);
+    if let Ok(lines) = results.extract::<Vec<String>>() {
+        return Ok(lines.iter().map(|line| to_bytes(line)).collect::<Vec<_>>().into_py(py));
+    }
+    let grouped: &PyDict = results.downcast()?;
+    for (_, matches) in grouped {
+        for entry in matches.extract::<Vec<&PyDict>>()? {
+            if let Some(content) = entry.get_item("content")? {
+                entry.set_item("content", to_bytes(content.extract()?))?;
+            }
+            for key in ["before_context", "after_context"] {
+                if let Some(lines) = entry.get_item(key)? {
+                    let lines: Vec<String> = lines.extract()?;
+                    entry.set_item(key, lines.iter().map(|line| to_bytes(line)).collect::<Vec<_>>())?;
+                }
+            }
+        }
+    }
+    Ok(grouped.into_py(py))
+}
+
+/// Matched lines of one file as (line_number, content) pairs
+type NumberedLines = Vec<(u64, String)>;
+
+/// Lines added and removed going from `old` to `new` matches of the same file
+/// Lines are paired by content so that shifted line numbers are not reported as changes
+fn diff_matches(old: &[(u64, String)], new: &[(u64, String)]) -> (NumberedLines, NumberedLines) {
+    let unpaired = |from: &[(u64, String)], to: &[(u64, String)]| {
+        let mut present: HashMap<&str, usize> = HashMap::new();
+        for (_, content) in to {
+            *present.entry(content.as_str()).or_default() += 1;
+        }
+        let mut lines = Vec::new();
+        for (line_number, content) in from {
+            match present.get_mut(content.as_str()) {
+                Some(n) if *n > 0 => *n -= 1,
+                _ => lines.push((*line_number, content.clone())),
+            }
+        }
+        lines
+    };
+    (unpaired(new, old), unpaired(old, new))
+}
+
+/// Transforms a file into searchable text before matching, like `rg --pre`
+#[derive(Debug, Clone)]
+enum Preprocessor {
+    /// Program run as `command <path>` with the file on stdin; its stdout is searched
+    Command(String),
+    /// Python callable receiving the path and returning str or bytes
+    Callable(PyObject),
+}
+
+impl Preprocessor {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        if let Ok(command) = obj.extract::<String>() {
+            return Ok(Preprocessor::Command(command));
+        }
+        if obj.is_callable() {
+            return Ok(Preprocessor::Callable(obj.into()));
+        }
+        Err(PyValueError::new_err("preprocessor must be a command string or a callable"))
+    }
+
+    /// Produce the text to search for `path`
+    fn run(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self {
+            Preprocessor::Command(command) => {
+                let output = std::process::Command::new(command)
+                    .arg(path)
+                    .stdin(File::open(path)?)
+                    .output()?;
+                if !output.status.success() {
+                    return Err(io::Error::other(format!(
+                        "preprocessor {} failed ({}): {}",
+                        command,
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+                Ok(output.stdout)
+            }
+            // Only files that passed the glob/type filters get here, so the GIL is
+            // re-acquired once per candidate file rather than per walked entry
+            Preprocessor::Callable(callback) => Python::with_gil(|py| {
+                let text = callback
+                    .call1(py, (path.to_string_lossy().to_string(),))
+                    .map_err(|e| io::Error::other(format!("preprocessor raised: {e}")))?;
+                let text = text.as_ref(py);
+                if let Ok(bytes) = text.downcast::<PyBytes>() {
+                    return Ok(bytes.as_bytes().to_vec());
+                }
+                text.extract::<String>()
+                    .map(String::into_bytes)
+                    .map_err(|_| io::Error::other("preprocessor must return str or bytes"))
+            }),
+        }
+    }
+}
+
+/// A Python binary file-like object read through `read(n)`, one buffer-sized chunk at a time
+struct PyStream {
+    fileobj: PyObject,
+    /// Bytes returned by `read` beyond what the last caller asked for
+    pending: Vec<u8>,
+    /// The exception raised by `read`, re-raised as-is once the search stops
+    error: Option<PyErr>,
+}
+
+impl PyStream {
+    fn new(fileobj: PyObject) -> Self {
+        PyStream { fileobj, pending: Vec::new(), error: None }
+    }
+}
+
+/// The error a file's reads fail with once the search deadline has passed
+#[derive(Debug)]
+struct DeadlineExceeded;
+
+impl std::fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("search timed out")
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+/// Reader that fails with `DeadlineExceeded` once `deadline` passes, so the searcher gives
+/// up on a file between buffer refills rather than after reading it whole
+struct DeadlineReader {
+    inner: Box<dyn Read>,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if Instant::now() >= self.deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, DeadlineExceeded));
+        }
+        self.inner.read(buf)
+    }
+}
+
+/// Adds the time spent reading from `inner` to READ_TIME (profile=True)
+struct TimedReader {
+    inner: Box<dyn Read>,
+}
+
+impl Read for TimedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let started = Instant::now();
+        let result = self.inner.read(buf);
+        READ_TIME.set(READ_TIME.get() + started.elapsed());
+        result
+    }
+}
+
+fn is_deadline_exceeded(err: &io::Error) -> bool {
+    err.get_ref().is_some_and(|inner| inner.is::<DeadlineExceeded>())
+}
+
+impl Read for PyStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            let chunk = Python::with_gil(|py| -> PyResult<Vec<u8>> {
+                let chunk = self.fileobj.call_method1(py, "read", (buf.len(),))?;
+                match chunk.as_ref(py).downcast::<PyBytes>() {
+                    Ok(bytes) => Ok(bytes.as_bytes().to_vec()),
+                    Err(_) => Err(PyValueError::new_err("fileobj.read() must return bytes; open it in binary mode")),
+                }
+            });
+            match chunk {
+                Ok(chunk) => self.pending = chunk,
+                Err(err) => {
+                    let message = err.to_string();
+                    self.error = Some(err);
+                    return Err(io::Error::other(message));
+                }
+            }
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// How file contents are turned into searchable bytes
+#[derive(Debug, Clone, Default)]
+struct ReadOptions {
+    /// Transparently decompress .gz/.bz2/.xz/.zst files (search_zip=)
+    search_zip: bool,
+    /// Open `archive!/member` paths produced by FileSource::Archives (archives=)
+    archives: bool,
+    /// Open `notebook!/cells/N` paths produced by FileSource::Notebooks (preprocessor="ipynb")
+    notebooks: bool,
+    /// Replace each file's contents with the preprocessor's output (preprocessor=)
+    preprocessor: Option<Preprocessor>,
+    /// `\n` by default, `\r\n` for crlf=, NUL for null_data=
+    line_terminator: LineTerminator,
+    /// Non-UTF-8 encodings to transcode from, by glob (encodings=)
+    encodings: EncodingRules,
+    /// Read buffer capacity in bytes (buffer_size=); the std default of 8 KiB when None
+    buffer_size: Option<usize>,
+    /// Most memory a single line may take before the file fails (heap_limit=)
+    heap_limit: Option<usize>,
+    /// Patterns a file must and must not contain to be searched at all (all_of=, none_of=)
+    gate: Option<ContentGate>,
+    /// Search files that are not valid UTF-8, with invalid bytes replaced by U+FFFD (ignore_level=3)
+    binary: bool,
+    /// Hand files to workers in batches and read them into a reused per-thread buffer (batch_small_files=)
+    batch_small_files: bool,
+    /// Skip files whose lines average more bytes than this (skip_minified=)
+    minified_line_length: Option<usize>,
+    /// The search deadline (timeout=), checked on every read so one huge file can't outlast it
+    deadline: Option<Instant>,
+    /// Keep lines that are not valid UTF-8, with their bytes escaped losslessly (text_mode="bytes")
+    raw_bytes: bool,
+    /// Fail files that are not valid UTF-8 as binary even with `binary` set (lossy=False)
+    strict_utf8: bool,
+    /// Time opening and reading files (profile=True)
+    profile: bool,
+    /// Check each file for changes made while it is searched (changed_files=)
+    changed_files: ChangedFiles,
+}
+
+/// Whole-file conditions checked before a file is searched (all_of=, none_of=)
+#[derive(Debug, Clone, Default)]
+struct ContentGate {
+    all_of: Vec<SearchMatcher>,
+    none_of: Vec<SearchMatcher>,
+}
+
+impl ReadOptions {
+    /// How search decodes a file into lines, from its decoding options; search and preview
+    /// both start from here, so preview shows lines exactly as search reads them
+    #[allow(clippy::too_many_arguments)]
+    fn decoding(
+        search_zip: bool,
+        encodings: Option<&PyDict>,
+        crlf: bool,
+        null_data: bool,
+        binary: bool,
+        lossy: Option<bool>,
+        buffer_size: Option<usize>,
+        heap_limit: Option<usize>,
+    ) -> PyResult<Self> {
+        Ok(ReadOptions {
+            search_zip,
+            line_terminator: if null_data {
+                LineTerminator::byte(b'\0')
+            } else if crlf {
+                LineTerminator::crlf()
+            } else {
+                LineTerminator::default()
+            },
+            encodings: encodings.map(EncodingRules::from_py).transpose()?.unwrap_or_default(),
+            buffer_size,
+            heap_limit,
+            binary,
+            strict_utf8: !lossy.unwrap_or(true),
+            ..ReadOptions::default()
+        })
+    }
+
+    /// Searcher splitting input into lines the same way `read_file_lines` does
+    fn searcher(&self) -> Searcher {
+        SearcherBuilder::new()
+            .line_terminator(self.line_terminator)
+            .heap_limit(self.heap_limit)
+            .build()
+    }
+
+    /// Whether `path` passes the skip_minified and all_of / none_of conditions; rejected files
+    /// are recorded as skipped
+    /// Each pattern stops at its first match, and the checks stop at the first that fails.
+    /// Unreadable files pass, so the search itself reports the error
+    fn admits(&self, searcher: &mut Searcher, path: &Path, report: &mut SearchReport) -> bool {
+        if self.minified_line_length.is_some_and(|length| looks_minified(path, self, length)) {
+            report.skip(path, SkipCause::Generated);
+            return false;
+        }
+        let Some(gate) = &self.gate else {
+            return true;
+        };
+        let mut contents = Vec::new();
+        if open_input(path, self).and_then(|mut input| input.read_to_end(&mut contents)).is_err() {
+            return true;
+        }
+        let mut contains = |matcher: &SearchMatcher| {
+            let mut found = false;
+            let _ = searcher.search_slice(matcher, &contents, sinks::Bytes(|_, _| {
+                found = true;
+                Ok(false)
+            }));
+            found
+        };
+        let admitted = gate.all_of.iter().all(&mut contains) && !gate.none_of.iter().any(&mut contains);
+        if !admitted {
+            report.skip(path, SkipCause::ContentFilter);
+        }
+        admitted
+    }
+
+    /// Buffered reader over `input` with the configured capacity
+    fn buffered<R: Read>(&self, input: R) -> BufReader<R> {
+        match self.buffer_size {
+            Some(capacity) => BufReader::with_capacity(capacity.max(1), input),
+            None => BufReader::new(input),
+        }
+    }
+}
+
+/// Text encodings assigned to files by glob, like a per-glob `rg --encoding`
+#[derive(Debug, Clone, Default)]
+struct EncodingRules {
+    globs: GlobSet,
+    /// Encoding for each glob in `globs`, in the order they were given
+    encodings: Vec<&'static Encoding>,
+}
+
+impl EncodingRules {
+    /// Build rules from a {glob: encoding label} dict; the first matching glob wins
+    fn from_py(dict: &PyDict) -> PyResult<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut encodings = Vec::new();
+        for (glob, label) in dict.iter() {
+            let glob: &str = glob.extract()?;
+            let label: &str = label.extract()?;
+            let glob = Glob::new(glob)
+                .map_err(|e| PyValueError::new_err(format!("Invalid encodings glob: {}", e)))?;
+            // WHATWG labels, plus Python-style spellings such as "latin-1" or "utf_8"
+            let encoding = Encoding::for_label(label.as_bytes())
+                .or_else(|| Encoding::for_label(label.replace(['-', '_'], "").as_bytes()))
+                .ok_or_else(|| PyValueError::new_err(format!("Unknown encoding: {}", label)))?;
+            builder.add(glob);
+            encodings.push(encoding);
+        }
+        let globs = builder
+            .build()
+            .map_err(|e| PyValueError::new_err(format!("Invalid encodings glob: {}", e)))?;
+        Ok(EncodingRules { globs, encodings })
+    }
+
+    fn for_path(&self, path: &Path) -> Option<&'static Encoding> {
+        self.globs.matches(path).into_iter().min().map(|i| self.encodings[i])
+    }
+}
+
+/// Open a file for searching, transcoding it to UTF-8 when an `encodings` glob matches
+fn open_input(path: &Path, read: &ReadOptions) -> io::Result<Box<dyn Read>> {
+    if !read.profile {
+        return open_decoded_input(path, read);
+    }
+    let started = Instant::now();
+    let input = open_decoded_input(path, read);
+    READ_TIME.set(READ_TIME.get() + started.elapsed());
+    Ok(Box::new(TimedReader { inner: input? }))
+}
+
+/// A file's contents as searched: preprocessed or decompressed, then transcoded
+fn open_decoded_input(path: &Path, read: &ReadOptions) -> io::Result<Box<dyn Read>> {
+    let mut input = open_raw_input(path, read)?;
+    if let Some(deadline) = read.deadline {
+        input = Box::new(DeadlineReader { inner: input, deadline });
+    }
+    // Decoding as UTF-8 replaces invalid bytes instead of failing the file as binary
+    let fallback = (read.binary && !read.strict_utf8 && !read.raw_bytes).then_some(encoding_rs::UTF_8);
+    match read.encodings.for_path(path).or(fallback) {
+        // A BOM still takes precedence, as in rg
+        Some(encoding) => Ok(Box::new(DecodeReaderBytesBuilder::new().encoding(Some(encoding)).build(input))),
+        None => Ok(input),
+    }
+}
+
+/// Open a file's bytes, running the preprocessor or decompressing it by
+/// extension when `search_zip` is set
+/// With `archives`, an `archive!/member` path opens that member as stored in the archive
+fn open_raw_input(path: &Path, read: &ReadOptions) -> io::Result<Box<dyn Read>> {
+    if read.notebooks {
+        if let Some((notebook, index)) = split_notebook_path(path) {
+            return Ok(Box::new(io::Cursor::new(read_notebook_cell(notebook, index)?)));
+        }
+    }
+    #[cfg(feature = "archives")]
+    if read.archives {
+        if let Some(contents) = expanded_member(path) {
+            return Ok(Box::new(io::Cursor::new(contents)));
+        }
+        // Only a zip member that failed to expand gets here, to fail with its own error
+        if let Some((archive, kind, member)) = split_archive_path(path) {
+            return Ok(Box::new(io::Cursor::new(read_archive_member(archive, kind, member)?)));
+        }
+    }
+    if let Some(preprocessor) = &read.preprocessor {
+        return Ok(Box::new(io::Cursor::new(preprocessor.run(path)?)));
+    }
+    let file = File::open(path)?;
+    #[cfg(feature = "decompression")]
+    if read.search_zip {
+        let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("gz" | "tgz") => return Ok(Box::new(flate2::read::MultiGzDecoder::new(file))),
+            Some("bz2" | "tbz2") => return Ok(Box::new(bzip2::read::MultiBzDecoder::new(file))),
+            Some("xz" | "txz") => return Ok(Box::new(xz2::read::XzDecoder::new_multi_decoder(file))),
+            Some("zst" | "zstd") => return Ok(Box::new(zstd::stream::read::Decoder::new(file)?)),
+            _ => {}
+        }
+    }
+    #[cfg(not(feature = "decompression"))]
+    let _ = read;
+    Ok(Box::new(file))
+}
+
+/// Separates a notebook's path from a cell's index in result paths (`analysis.ipynb!/cells/3`)
+const NOTEBOOK_CELLS: &str = "!/cells/";
+
+/// Source text of each cell of a notebook; None for cells other than code and markdown
+type NotebookSources = Rc<Vec<Option<String>>>;
+
+thread_local! {
+    /// The notebook whose cells were read last on this thread, keyed by path and modification
+    /// time, so searching its cells one by one parses the JSON once
+    static LAST_NOTEBOOK: RefCell<Option<(PathBuf, Option<SystemTime>, NotebookSources)>> = const { RefCell::new(None) };
+}
+
+fn is_notebook(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Parse a Jupyter notebook's cell sources, reusing the last notebook read on this thread
+fn notebook_sources(path: &Path) -> io::Result<NotebookSources> {
+    let modified = std::fs::metadata(path)?.modified().ok();
+    let cached = LAST_NOTEBOOK.with_borrow(|last| match last {
+        Some((last_path, last_modified, cells)) if last_path == path && *last_modified == modified => Some(cells.clone()),
+        _ => None,
+    });
+    if let Some(cells) = cached {
+        return Ok(cells);
+    }
+
+    let notebook: serde_json::Value = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    let cells = notebook
+        .get("cells")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a Jupyter notebook: no cells array"))?;
+    let sources: Vec<Option<String>> = cells
+        .iter()
+        .map(|cell| {
+            let kind = cell.get("cell_type").and_then(serde_json::Value::as_str);
+            if !matches!(kind, Some("code" | "markdown")) {
+                return None;
+            }
+            // nbformat stores a source as one string or as a list of lines that keep their newlines
+            Some(match cell.get("source") {
+                Some(serde_json::Value::String(text)) => text.clone(),
+                Some(serde_json::Value::Array(lines)) => lines.iter().filter_map(serde_json::Value::as_str).collect(),
+                _ => String::new(),
+            })
+        })
+        .collect();
+    let sources = Rc::new(sources);
+    LAST_NOTEBOOK.set(Some((path.to_path_buf(), modified, sources.clone())));
+    Ok(sources)
+}
+
+/// The code and markdown cells of a notebook, as `notebook!/cells/N` paths (N counts from 0)
+fn notebook_cells(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let sources = notebook_sources(path)?;
+    Ok((0..sources.len())
+        .filter(|&index| sources[index].is_some())
+        .map(|index| {
+            let mut cell = path.as_os_str().to_owned();
+            cell.push(format!("{NOTEBOOK_CELLS}{index}"));
+            PathBuf::from(cell)
+        })
+        .collect())
+}
+
+/// Split a `notebook!/cells/N` path into the notebook and the cell index
+fn split_notebook_path(path: &Path) -> Option<(&Path, usize)> {
+    let (notebook, index) = path.to_str()?.rsplit_once(NOTEBOOK_CELLS)?;
+    let notebook = Path::new(notebook);
+    Some((notebook, index.parse().ok()?)).filter(|_| is_notebook(notebook))
+}
+
+/// The file on disk an `archive!/member` or `notebook!/cells/N` path is read from
+fn container_file(path: &Path) -> &Path {
+    #[cfg(feature = "archives")]
+    if let Some((archive, _, _)) = split_archive_path(path) {
+        return archive;
+    }
+    split_notebook_path(path).map_or(path, |(notebook, _)| notebook)
+}
+
+/// Length and hash of the bytes a search would read from `path` (dedupe="content")
+fn content_digest(path: &Path, read: &ReadOptions) -> io::Result<(u64, u64)> {
+    use std::hash::Hasher;
+    let mut input = open_raw_input(path, read)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buf = vec![0; 64 * 1024];
+    let mut len = 0u64;
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) => return Ok((len, hasher.finish())),
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        hasher.write(&buf[..n]);
+        len += n as u64;
+    }
+}
+
+/// Whether two files read as the same bytes
+fn same_content(a: &Path, b: &Path, read: &ReadOptions) -> io::Result<bool> {
+    let mut a = BufReader::new(open_raw_input(a, read)?);
+    let mut b = BufReader::new(open_raw_input(b, read)?);
+    loop {
+        let (chunk_a, chunk_b) = (a.fill_buf()?, b.fill_buf()?);
+        if chunk_a.is_empty() || chunk_b.is_empty() {
+            return Ok(chunk_a.is_empty() && chunk_b.is_empty());
+        }
+        let n = chunk_a.len().min(chunk_b.len());
+        if chunk_a[..n] != chunk_b[..n] {
+            return Ok(false);
+        }
+        a.consume(n);
+        b.consume(n);
+    }
+}
+
+/// One cell's source, as searched in place of the notebook's JSON
+fn read_notebook_cell(notebook: &Path, index: usize) -> io::Result<Vec<u8>> {
+    match notebook_sources(notebook)?.get(index) {
+        Some(Some(source)) => Ok(source.clone().into_bytes()),
+        _ => Err(io::Error::new(io::ErrorKind::NotFound, format!("no code or markdown cell {index} in notebook"))),
+    }
+}
+
+thread_local! {
+    /// Contents of archive members the walk on this thread has expanded but not yet reached
+    /// (archives=); archive sources are only ever iterated by walk_files, on one thread
+    static EXPANDED_MEMBERS: RefCell<HashMap<PathBuf, Arc<[u8]>>> = RefCell::new(HashMap::new());
+    /// The archive member being visited or searched on this thread, with its contents
+    static CURRENT_MEMBER: RefCell<Option<(PathBuf, Arc<[u8]>)>> = const { RefCell::new(None) };
+}
+
+/// Hand over the expanded contents of `path`, if it is an archive member the walk expanded
+fn take_expanded_member(path: &Path) -> Option<Arc<[u8]>> {
+    EXPANDED_MEMBERS.with_borrow_mut(|expanded| expanded.remove(path))
+}
+
+/// Run `f` with `contents` served for `path` by open_raw_input on this thread
+fn with_member<T>(path: &Path, contents: Option<Arc<[u8]>>, f: impl FnOnce() -> T) -> T {
+    let Some(contents) = contents else {
+        return f();
+    };
+    CURRENT_MEMBER.set(Some((path.to_path_buf(), contents)));
+    let result = f();
+    CURRENT_MEMBER.set(None);
+    result
+}
+
+/// The contents with_member is serving for `path`
+fn current_member(path: &Path) -> Option<Arc<[u8]>> {
+    CURRENT_MEMBER.with_borrow(|current| current.as_ref().filter(|(member, _)| member == path).map(|(_, contents)| Arc::clone(contents)))
+}
+
+/// The contents of an archive member without reopening its archive: the one being searched,
+/// or one the walk has expanded but not yet reached (e.g. read early by dedupe=)
+#[cfg(feature = "archives")]
+fn expanded_member(path: &Path) -> Option<Arc<[u8]>> {
+    current_member(path).or_else(|| EXPANDED_MEMBERS.with_borrow(|expanded| expanded.get(path).cloned()))
+}
+
+/// Separates an archive's path from a member's name in result paths, as in Java's jar: URLs
+#[cfg(feature = "archives")]
+const ARCHIVE_SEPARATOR: &str = "!/";
+
+/// Archive formats searched member by member (archives=)
+#[cfg(feature = "archives")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveKind {
+    /// .zip, .jar and .whl
+    Zip,
+    Tar,
+    /// .tar.gz and .tgz
+    TarGz,
+}
+
+#[cfg(feature = "archives")]
+impl ArchiveKind {
+    /// The archive format of `path`, by file name
+    fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if [".zip", ".jar", ".whl"].iter().any(|ext| name.ends_with(ext)) {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+
+    /// Entries of a tar archive, decompressing .tar.gz on the fly
+    fn tar(self, path: &Path) -> io::Result<tar::Archive<Box<dyn Read>>> {
+        let file = File::open(path)?;
+        let input: Box<dyn Read> = match self {
+            ArchiveKind::TarGz => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            _ => Box::new(file),
+        };
+        Ok(tar::Archive::new(input))
+    }
+}
+
+/// The regular files inside an archive as `archive!/member` paths in archive order, with
+/// their contents; the archive is opened once and read through from start to end
+/// A zip member that fails to read has no contents, and fails again when it is searched
+#[cfg(feature = "archives")]
+fn expand_archive(path: &Path, kind: ArchiveKind) -> io::Result<Vec<(PathBuf, Option<Vec<u8>>)>> {
+    let member_path = |name: &str| {
+        let mut member = path.as_os_str().to_owned();
+        member.push(ARCHIVE_SEPARATOR);
+        member.push(name.trim_start_matches("./"));
+        PathBuf::from(member)
+    };
+    let mut members = Vec::new();
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            for index in 0..archive.len() {
+                let Some(name) = archive.name_for_index(index).map(str::to_string) else {
+                    continue;
+                };
+                if name.ends_with('/') {
+                    continue;
+                }
+                let mut contents = Vec::new();
+                let read = archive.by_index(index).map_err(io::Error::from).and_then(|mut file| file.read_to_end(&mut contents));
+                members.push((member_path(&name), read.ok().map(|_| contents)));
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            for entry in kind.tar(path)?.entries()? {
+                let mut entry = entry?;
+                if entry.header().entry_type().is_file() {
+                    let name = entry.path()?.to_string_lossy().into_owned();
+                    let mut contents = Vec::new();
+                    entry.read_to_end(&mut contents)?;
+                    members.push((member_path(&name), Some(contents)));
+                }
+            }
+        }
+    }
+    Ok(members)
+}
+
+/// Split an `archive!/member` path at the first separator that follows an archive name
+#[cfg(feature = "archives")]
+fn split_archive_path(path: &Path) -> Option<(&Path, ArchiveKind, &str)> {
+    let text = path.to_str()?;
+    text.match_indices(ARCHIVE_SEPARATOR).find_map(|(at, sep)| {
+        let archive = Path::new(&text[..at]);
+        let kind = ArchiveKind::of(archive)?;
+        Some((archive, kind, &text[at + sep.len()..]))
+    })
+}
+
+/// Read one member of an archive into memory on its own
+/// Walks expand each archive whole instead (see expand_archive), so this only re-reads
+/// a zip member that failed there
+#[cfg(feature = "archives")]
+fn read_archive_member(archive: &Path, kind: ArchiveKind, member: &str) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(File::open(archive)?)?;
+            archive.by_name(member)?.read_to_end(&mut contents)?;
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = kind.tar(archive)?;
+            let mut entries = tar.entries()?;
+            let mut entry = loop {
+                let Some(entry) = entries.next().transpose()? else {
+                    return Err(io::Error::new(io::ErrorKind::NotFound, format!("no member {member} in archive")));
+                };
+                if entry.path()?.to_string_lossy().trim_start_matches("./") == member {
+                    break entry;
+                }
+            };
+            entry.read_to_end(&mut contents)?;
+        }
+    }
+    Ok(contents)
+}
+
+/// Regex engine selected with `engine=`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Engine {
+    /// Rust regex engine (linear time, no look-around or backreferences)
+    Default,
+    /// fancy-regex backtracking engine with look-around and backreferences
+    Fancy,
+    /// Default engine, falling back to fancy when the pattern needs it
+    Auto,
+    /// PCRE2, known to features() but never compiled in
+    Pcre2,
+}
+
+impl Engine {
+    /// Parse an engine name, rejecting engines this build lacks
+    fn from_str(s: &str) -> PyResult<Self> {
+        let engine = Self::parse(s)?;
+        match engine.missing_feature() {
+            Some(message) => Err(PyValueError::new_err(message)),
+            None => Ok(engine),
+        }
+    }
+
+    fn parse(s: &str) -> PyResult<Self> {
+        match s {
+            "default" => Ok(Engine::Default),
+            "fancy" => Ok(Engine::Fancy),
+            "auto" => Ok(Engine::Auto),
+            "pcre2" => Ok(Engine::Pcre2),
+            _ => Err(PyValueError::new_err(format!("Invalid engine: {}", s))),
+        }
+    }
+
+    /// Why this engine cannot be used in this build, if it cannot
+    fn missing_feature(self) -> Option<&'static str> {
+        if self == Engine::Fancy && !cfg!(feature = "fancy-regex") {
+            return Some("engine='fancy' requires pyripgrep built with the 'fancy-regex' feature");
+        }
+        if self == Engine::Pcre2 {
+            return Some("engine='pcre2' is not available: pyripgrep is not compiled with PCRE2");
+        }
+        None
+    }
+}
+
+/// Handle an option this build cannot honor: an error under strict_options,
+/// otherwise a warning recorded in stats while the search falls back
+fn unsupported_option(strict: bool, message: &str, fallback: &str, warnings: &mut Vec<String>) -> PyResult<()> {
+    if strict {
+        return Err(PyValueError::new_err(message.to_string()));
+    }
+    warnings.push(format!("{message}; {fallback}"));
+    Ok(())
+}
+
+/// Regex settings shared by search, compile and validate_pattern
+#[derive(Debug, Clone, Copy)]
+struct MatcherOptions {
+    case_insensitive: bool,
+    /// Ignore case unless the pattern has an uppercase literal (-S); case_insensitive wins
+    case_smart: bool,
+    /// Unicode-aware classes such as \w and \b; false restricts them to ASCII
+    unicode: bool,
+    multiline: bool,
+    /// The pattern must match an entire line (-x)
+    line_regexp: bool,
+    /// `$` also matches before `\r\n` (--crlf)
+    crlf: bool,
+    engine: Engine,
+    /// Limit on the compiled regex size in bytes (--regex-size-limit)
+    size_limit: Option<usize>,
+    /// Limit on the lazy DFA cache in bytes (--dfa-size-limit)
+    dfa_size_limit: Option<usize>,
+    /// Backtracking steps the fancy engine may take per match attempt (backtrack_limit=)
+    #[cfg_attr(not(feature = "fancy-regex"), allow(dead_code))]
+    backtrack_limit: Option<usize>,
+}
+
+/// Literal that every match of a pattern contains, located with memchr's memmem
+/// A file without it cannot match, so it is ruled out before being split into lines
+/// The finder is over-aligned for SIMD, so it lives behind an Arc rather than inline in pyclasses
+#[derive(Debug, Clone)]
+struct Prefilter(Arc<memmem::Finder<'static>>);
+
+impl Prefilter {
+    /// The longest literal the pattern requires, if it has one
+    /// Case-insensitive patterns have no single required spelling and get none
+    fn new(pattern: &str, options: &MatcherOptions) -> Option<Self> {
+        if options.case_insensitive || options.case_smart {
+            return None;
+        }
+        let hir = regex_syntax::ParserBuilder::new().unicode(options.unicode).build().parse(pattern).ok()?;
+        let literal = Self::required_literal(&hir)?;
+        Some(Prefilter(Arc::new(memmem::Finder::new(literal).into_owned())))
+    }
+
+    fn required_literal(hir: &regex_syntax::hir::Hir) -> Option<&[u8]> {
+        use regex_syntax::hir::HirKind;
+        match hir.kind() {
+            HirKind::Literal(literal) => Some(&literal.0),
+            HirKind::Capture(group) => Self::required_literal(&group.sub),
+            // Every part of a concatenation has to match, so any literal inside one is required
+            HirKind::Concat(parts) => parts.iter().filter_map(Self::required_literal).max_by_key(|literal| literal.len()),
+            _ => None,
+        }
+    }
+
+    fn rejects(&self, haystack: &[u8]) -> bool {
+        self.0.find(haystack).is_none()
+    }
+}
+
+/// Matcher used by every search, dispatching to the selected regex engine
+#[derive(Debug, Clone)]
+enum SearchMatcher {
+    /// The regex matcher, plus a literal every match must contain when the pattern has one
+    Default(RegexMatcher, Option<Prefilter>),
+    #[cfg(feature = "fancy-regex")]
+    Fancy(FancyMatcher),
+}
+
+/// Capture groups produced by a `SearchMatcher`
+#[derive(Debug)]
+enum SearchCaptures {
+    Default(RegexCaptures),
+    #[cfg(feature = "fancy-regex")]
+    Fancy(FancyCaptures),
+}
+
+impl Captures for SearchCaptures {
+    fn len(&self) -> usize {
+        match self {
+            SearchCaptures::Default(caps) => caps.len(),
+            #[cfg(feature = "fancy-regex")]
+            SearchCaptures::Fancy(caps) => caps.len(),
+        }
+    }
+
+    fn get(&self, i: usize) -> Option<grep_matcher::Match> {
+        match self {
+            SearchCaptures::Default(caps) => caps.get(i),
+            #[cfg(feature = "fancy-regex")]
+            SearchCaptures::Fancy(caps) => caps.get(i),
+        }
+    }
+}
+
+impl SearchMatcher {
+    /// Whether the prefilter proves `haystack` contains no match
+    fn rejects(&self, haystack: &[u8]) -> bool {
+        match self {
+            SearchMatcher::Default(_, Some(prefilter)) => prefilter.rejects(haystack),
+            _ => false,
+        }
+    }
+}
+
+impl Matcher for SearchMatcher {
+    type Captures = SearchCaptures;
+    type Error = String;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<grep_matcher::Match>, String> {
+        match self {
+            SearchMatcher::Default(m, _) => m.find_at(haystack, at).map_err(|e| e.to_string()),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(m) => m.find_at(haystack, at),
+        }
+    }
+
+    fn new_captures(&self) -> Result<SearchCaptures, String> {
+        match self {
+            SearchMatcher::Default(m, _) => m.new_captures().map(SearchCaptures::Default).map_err(|e| e.to_string()),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(m) => Ok(SearchCaptures::Fancy(m.new_captures())),
+        }
+    }
+
+    fn capture_count(&self) -> usize {
+        match self {
+            SearchMatcher::Default(m, _) => m.capture_count(),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(m) => m.regex.captures_len(),
+        }
+    }
+
+    fn capture_index(&self, name: &str) -> Option<usize> {
+        match self {
+            SearchMatcher::Default(m, _) => m.capture_index(name),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(m) => m.names.get(name).copied(),
+        }
+    }
+
+    fn captures_at(&self, haystack: &[u8], at: usize, caps: &mut SearchCaptures) -> Result<bool, String> {
+        match (self, caps) {
+            (SearchMatcher::Default(m, _), SearchCaptures::Default(caps)) => {
+                m.captures_at(haystack, at, caps).map_err(|e| e.to_string())
+            }
+            #[cfg(feature = "fancy-regex")]
+            (SearchMatcher::Fancy(m), SearchCaptures::Fancy(caps)) => m.captures_at(haystack, at, caps),
+            #[cfg(feature = "fancy-regex")]
+            _ => Err("captures belong to a different matcher".to_string()),
+        }
+    }
+
+    // Forward the default engine's line-oriented fast paths to the searcher
+    fn non_matching_bytes(&self) -> Option<&grep_matcher::ByteSet> {
+        match self {
+            SearchMatcher::Default(m, _) => m.non_matching_bytes(),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(_) => None,
+        }
+    }
+
+    fn line_terminator(&self) -> Option<grep_matcher::LineTerminator> {
+        match self {
+            SearchMatcher::Default(m, _) => m.line_terminator(),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(_) => None,
+        }
+    }
+
+    fn find_candidate_line(&self, haystack: &[u8]) -> Result<Option<grep_matcher::LineMatchKind>, String> {
+        match self {
+            SearchMatcher::Default(m, _) => m.find_candidate_line(haystack).map_err(|e| e.to_string()),
+            #[cfg(feature = "fancy-regex")]
+            SearchMatcher::Fancy(m) => Ok(m.find_at(haystack, 0)?.map(|m| grep_matcher::LineMatchKind::Confirmed(m.start()))),
+        }
+    }
+}
+
+/// What a search_with_sink callback asked for after an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SinkControl {
+    Continue,
+    /// Returned False: stop searching the current file
+    StopFile,
+    /// Returned "stop": end the whole search
+    StopSearch,
+}
+
+/// Sink handing every searcher event for one file to a Python callback (search_with_sink)
+/// The GIL is taken per event, so the walk and the matching run without it
+struct PySink<'a> {
+    callback: &'a PyObject,
+    path: &'a str,
+    control: SinkControl,
+    /// Exception raised by the callback; the search stops and re-raises it
+    error: Option<PyErr>,
+}
+
+impl PySink<'_> {
+    /// Send one event dict, returning whether the searcher should keep going
+    fn emit(&mut self, kind: &str, fill: impl FnOnce(&PyDict) -> PyResult<()>) -> Result<bool, io::Error> {
+        let outcome = Python::with_gil(|py| {
+            let event = PyDict::new(py);
+            event.set_item("type", kind)?;
+            event.set_item("path", self.path)?;
+            fill(event)?;
+            let returned = self.callback.call1(py, (event,))?;
+            let returned = returned.as_ref(py);
+            Ok(if returned.is_instance_of::<PyBool>() && !returned.is_true()? {
+                SinkControl::StopFile
+            } else if returned.extract::<&str>().is_ok_and(|s| s == "stop") {
+                SinkControl::StopSearch
+            } else {
+                SinkControl::Continue
+            })
+        });
+        match outcome {
+            Ok(control) => {
+                self.control = control;
+                Ok(control == SinkControl::Continue)
+            }
+            Err(err) => {
+                self.error = Some(err);
+                Err(io::Error::other("search_with_sink callback raised"))
+            }
+        }
+    }
+
+    fn emit_line(&mut self, kind: &str, context: Option<&str>, line_number: Option<u64>, offset: u64, bytes: &[u8]) -> Result<bool, io::Error> {
+        self.emit(kind, |event| {
+            event.set_item("line_number", line_number)?;
+            event.set_item("absolute_offset", offset)?;
+            event.set_item("bytes", PyBytes::new(event.py(), bytes))?;
+            if let Some(context) = context {
+                event.set_item("kind", context)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Sink for PySink<'_> {
+    type Error = io::Error;
+
+    fn begin(&mut self, _searcher: &Searcher) -> Result<bool, io::Error> {
+        self.emit("begin", |_| Ok(()))
+    }
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, io::Error> {
+        self.emit_line("match", None, mat.line_number(), mat.absolute_byte_offset(), mat.bytes())
+    }
+
+    fn context(&mut self, _searcher: &Searcher, context: &SinkContext<'_>) -> Result<bool, io::Error> {
+        let kind = match context.kind() {
+            SinkContextKind::Before => "before",
+            SinkContextKind::After => "after",
+            SinkContextKind::Other => "other",
+        };
+        self.emit_line("context", Some(kind), context.line_number(), context.absolute_byte_offset(), context.bytes())
+    }
+
+    fn context_break(&mut self, _searcher: &Searcher) -> Result<bool, io::Error> {
+        self.emit("context_break", |_| Ok(()))
+    }
+
+    fn finish(&mut self, _searcher: &Searcher, finish: &SinkFinish) -> Result<(), io::Error> {
+        // A file the callback stopped still gets its end event, unless it ended the search
+        if self.control != SinkControl::StopSearch {
+            self.emit("end", |event| event.set_item("bytes_searched", finish.byte_count()))?;
+        }
+        Ok(())
+    }
+}
+
+/// A matcher the searcher must apply to one line at a time, with its terminator stripped
+/// The default engine's fast paths run over many lines at once, where `^` and `$` only hold
+/// at `\n`; hiding them keeps anchors working with NUL and CRLF terminators too
+struct PerLine<'a>(&'a SearchMatcher);
+
+impl Matcher for PerLine<'_> {
+    type Captures = SearchCaptures;
+    type Error = String;
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<grep_matcher::Match>, String> {
+        self.0.find_at(haystack, at)
+    }
+
+    fn new_captures(&self) -> Result<SearchCaptures, String> {
+        self.0.new_captures()
+    }
+}
+
+/// fancy-regex adapter for the grep `Matcher` trait
+/// Haystacks are matched up to their first invalid UTF-8 byte
+#[cfg(feature = "fancy-regex")]
+#[derive(Debug, Clone)]
+struct FancyMatcher {
+    regex: fancy_regex::Regex,
+    names: HashMap<String, usize>,
+}
+
+/// Whether `pattern` contains an uppercase letter outside of escape sequences
+#[cfg(feature = "fancy-regex")]
+fn has_uppercase_literal(pattern: &str) -> bool {
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            // Also skip the class name in \p{Lu} / \P{Greek}
+            if matches!(chars.next(), Some('p' | 'P')) && chars.peek() == Some(&'{') {
+                chars.by_ref().find(|&c| c == '}');
+            }
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(feature = "fancy-regex")]
+impl FancyMatcher {
+    fn new(pattern: &str, options: &MatcherOptions) -> PyResult<Self> {
+        // Inline flags rather than RegexBuilder::case_insensitive, which is not applied
+        // to the backtracking parts of the pattern (e.g. text after a look-behind).
+        // multiline mirrors the default builder: multi_line plus dot_matches_new_line
+        if !options.unicode {
+            return Err(PyValueError::new_err("unicode=False is not supported by the fancy engine"));
+        }
+        // Smart case as in grep-regex: only literal uppercase letters (not \W, \S, ...) count
+        let case_insensitive = options.case_insensitive || (options.case_smart && !has_uppercase_literal(pattern));
+        let flags = match (case_insensitive, options.multiline) {
+            (false, false) => "",
+            (true, false) => "(?i)",
+            (false, true) => "(?ms)",
+            (true, true) => "(?ims)",
+        };
+        let pattern = if options.line_regexp {
+            format!("(?m:^)(?:{pattern})(?m:$)")
+        } else {
+            pattern.to_string()
+        };
+        let mut builder = fancy_regex::RegexBuilder::new(&format!("{flags}{pattern}"));
+        if let Some(limit) = options.size_limit {
+            builder.delegate_size_limit(limit);
+        }
+        if let Some(limit) = options.dfa_size_limit {
+            builder.delegate_dfa_size_limit(limit);
+        }
+        if let Some(limit) = options.backtrack_limit {
+            builder.backtrack_limit(limit);
+        }
+        // The backtracking parser reports no positions, so PatternError.offset stays None
+        let regex = builder.build().map_err(|e| {
+            let err = structured_error::<PatternError>(format!("Invalid pattern: {}", e), None, None, e.to_string());
+            Python::with_gil(|py| {
+                let _ = err.value(py).setattr("pattern", pattern.as_str());
+            });
+            err
+        })?;
+        let names = regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(i, name)| name.map(|name| (name.to_string(), i)))
+            .collect();
+        Ok(FancyMatcher { regex, names })
+    }
+
+    fn text(haystack: &[u8]) -> &str {
+        match std::str::from_utf8(haystack) {
+            Ok(text) => text,
+            Err(err) => std::str::from_utf8(&haystack[..err.valid_up_to()]).unwrap_or_default(),
+        }
+    }
+
+    /// Round `at` up to a char boundary (find_iter steps over empty matches byte by byte)
+    fn start(text: &str, mut at: usize) -> Option<usize> {
+        while at <= text.len() {
+            if text.is_char_boundary(at) {
+                return Some(at);
+            }
+            at += 1;
+        }
+        None
+    }
+
+    fn find_at(&self, haystack: &[u8], at: usize) -> Result<Option<grep_matcher::Match>, String> {
+        let text = Self::text(haystack);
+        let Some(at) = Self::start(text, at) else {
+            return Ok(None);
+        };
+        self.regex
+            .find_from_pos(text, at)
+            .map(|m| m.map(|m| grep_matcher::Match::new(m.start(), m.end())))
+            .map_err(Self::match_error)
+    }
+
+    /// A pathological pattern on one file fails that file, naming the option that stopped it
+    fn match_error(err: fancy_regex::Error) -> String {
+        match err {
+            fancy_regex::Error::RuntimeError(fancy_regex::RuntimeError::BacktrackLimitExceeded) => {
+                "regex backtrack limit exceeded (backtrack_limit=)".to_string()
+            }
+            err => err.to_string(),
+        }
+    }
+
+    fn new_captures(&self) -> FancyCaptures {
+        FancyCaptures(vec![None; self.regex.captures_len()])
+    }
+
+    fn captures_at(&self, haystack: &[u8], at: usize, caps: &mut FancyCaptures) -> Result<bool, String> {
+        let text = Self::text(haystack);
+        caps.0.iter_mut().for_each(|c| *c = None);
+        let Some(at) = Self::start(text, at) else {
+            return Ok(false);
+        };
+        match self.regex.captures_from_pos(text, at).map_err(Self::match_error)? {
+            Some(found) => {
+                for (i, slot) in caps.0.iter_mut().enumerate() {
+                    *slot = found.get(i).map(|m| grep_matcher::Match::new(m.start(), m.end()));
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(feature = "fancy-regex")]
+#[derive(Debug, Clone)]
+struct FancyCaptures(Vec<Option<grep_matcher::Match>>);
+
+#[cfg(feature = "fancy-regex")]
+impl Captures for FancyCaptures {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn get(&self, i: usize) -> Option<grep_matcher::Match> {
+        self.0.get(i).copied().flatten()
+    }
+}
+
+/// Compiled search pattern (matcher plus type filter) reusable across searches
+#[pyclass(module = "pyripgrep")]
+#[derive(Clone)]
+pub struct Pattern {
+    #[pyo3(get)]
+    pattern: String,
+    #[pyo3(get)]
+    case_insensitive: bool,
+    #[pyo3(get)]
+    multiline: bool,
+    #[pyo3(get)]
+    line_regexp: bool,
+    #[pyo3(get)]
+    case_smart: bool,
+    #[pyo3(get)]
+    unicode: bool,
+    #[pyo3(get)]
+    engine: String,
+    matcher: SearchMatcher,
+    options: MatcherOptions,
+    type_filter: Option<TypeFilter>,
+}
+
+#[pymethods]
+impl Pattern {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (pattern, i = None, multiline = None, r#type = None, type_ignore_case = None, engine = None, x = None, case_smart = None, unicode = None))]
+    fn new(
+        pattern: &str,
+        i: Option<bool>,
+        multiline: Option<bool>,
+        r#type: Option<&PyAny>,
+        type_ignore_case: Option<bool>,
+        engine: Option<&str>,
+        x: Option<bool>,
+        case_smart: Option<bool>,
+        unicode: Option<bool>,
+    ) -> PyResult<Self> {
+        let types = Grep::parse_types(r#type)?;
+        let engine = engine.unwrap_or("default");
+        let options = MatcherOptions {
+            case_insensitive: i.unwrap_or(false),
+            case_smart: case_smart.unwrap_or(false),
+            unicode: unicode.unwrap_or(true),
+            multiline: multiline.unwrap_or(false),
+            line_regexp: x.unwrap_or(false),
+            crlf: false,
+            engine: Engine::from_str(engine)?,
+            size_limit: None,
+            dfa_size_limit: None,
+            backtrack_limit: None,
+        };
+
+        Ok(Pattern {
+            pattern: pattern.to_string(),
+            case_insensitive: options.case_insensitive,
+            multiline: options.multiline,
+            line_regexp: options.line_regexp,
+            case_smart: options.case_smart,
+            unicode: options.unicode,
+            matcher: Grep::build_matcher(pattern, &options)?,
+            options,
+            engine: engine.to_string(),
+            type_filter: Grep::build_type_filter(&types, &[], type_ignore_case.unwrap_or(false))?,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Pattern({:?}, i={}, multiline={}, x={}, engine={:?})",
+            self.pattern,
+            if self.case_insensitive { "True" } else { "False" },
+            if self.multiline { "True" } else { "False" },
+            if self.line_regexp { "True" } else { "False" },
+            self.engine,
+        )
+    }
+}
+
+/// Main Grep interface that provides ripgrep-like functionality
+#[pyclass(module = "pyripgrep")]
+#[derive(Default)]
+pub struct Grep {
+    /// search() keyword arguments given to the constructor; each call can override them
+    defaults: Option<Py<PyDict>>,
+}
+
+/// Parameter names of Grep._search in order, read once from its signature
+static SEARCH_PARAMETERS: GILOnceCell<Vec<String>> = GILOnceCell::new();
+
+#[pymethods]
+impl Grep {
+    /// Any search() keyword argument except pattern becomes a default for every search;
+    /// case_insensitive and types are accepted as spellings of i and type
+    #[new]
+    #[pyo3(signature = (**defaults))]
+    fn new(py: Python, defaults: Option<&PyDict>) -> PyResult<Self> {
+        let Some(defaults) = defaults.filter(|defaults| !defaults.is_empty()) else {
+            return Ok(Grep::default());
+        };
+        let parameters = Self::search_parameters(py)?;
+        let normalized = PyDict::new(py);
+        for (key, value) in defaults {
+            let key: &str = key.extract()?;
+            let name = match key {
+                "case_insensitive" => "i",
+                "types" => "type",
+                name => name,
+            };
+            if name == "pattern" || !parameters.iter().any(|parameter| parameter == name) {
+                return Err(PyTypeError::new_err(format!("Grep() got an unexpected keyword argument '{}'", key)));
+            }
+            if normalized.contains(name)? {
+                return Err(PyTypeError::new_err(format!("Grep() got multiple values for '{}'", name)));
+            }
+            normalized.set_item(name, value)?;
+        }
+        Ok(Grep { defaults: Some(normalized.into()) })
+    }
+
+    /// Copy of the defaults given to the constructor, under search()'s parameter names
+    #[getter]
+    fn defaults(&self, py: Python) -> PyResult<PyObject> {
+        match &self.defaults {
+            Some(defaults) => Ok(defaults.as_ref(py).copy()?.into_py(py)),
+            None => Ok(PyDict::new(py).into_py(py)),
+        }
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let Some(defaults) = &self.defaults else {
+            return Ok("Grep()".to_string());
+        };
+        let options = defaults
+            .as_ref(py)
+            .iter()
+            .map(|(key, value)| Ok(format!("{}={}", key, value.repr()?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!("Grep({})", options.join(", ")))
+    }
+
+    /// Build a Grep whose defaults come from a ripgrep config file
+    /// Reads `path`, or `RIPGREP_CONFIG_PATH` when path is None; with neither, the Grep has
+    /// no defaults. Flags without a pyripgrep equivalent are skipped with a UserWarning
+    #[staticmethod]
+    #[pyo3(signature = (path = None))]
+    fn from_config(py: Python, path: Option<&str>) -> PyResult<Self> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match std::env::var_os("RIPGREP_CONFIG_PATH").filter(|path| !path.is_empty()) {
+                Some(path) => PathBuf::from(path),
+                None => return Ok(Grep::default()),
+            },
+        };
+        let text = std::fs::read_to_string(&path)?;
+        let (defaults, unsupported) = parse_ripgrep_config(py, &text, &path)?;
+        if !unsupported.is_empty() {
+            let message = format!("{}: ignoring unsupported ripgrep flags: {}", path.display(), unsupported.join(" "));
+            PyErr::warn(py, py.get_type::<PyUserWarning>(), &message, 1)?;
+        }
+        Self::new(py, Some(defaults))
+    }
+
+    /// Search with this Grep's defaults; arguments given here take precedence
+    /// Takes the same arguments as _search
+    #[pyo3(signature = (*args, **kwargs))]
+    fn search(slf: &PyCell<Self>, args: &PyTuple, kwargs: Option<&PyDict>) -> PyResult<PyObject> {
+        let py = slf.py();
+        let defaults = slf.borrow().defaults.as_ref().map(|defaults| defaults.clone_ref(py).into_ref(py));
+        let kwargs = match defaults {
+            None => kwargs,
+            Some(defaults) => {
+                let merged = defaults.copy()?;
+                // A default gives way to the same parameter passed positionally
+                for name in Self::search_parameters(py)?.iter().take(args.len()) {
+                    if merged.contains(name)? {
+                        merged.del_item(name)?;
+                    }
+                }
+                if let Some(kwargs) = kwargs {
+                    merged.update(kwargs.as_mapping())?;
+                }
+                Some(merged)
+            }
+        };
+        Ok(slf.call_method("_search", args, kwargs)?.into())
+    }
+
+    /// One page of search() results plus a cursor for the next page (None after the last)
+    /// Files are searched in path order a chunk at a time, so a page only searches the files
+    /// it needs; the cursor records where the walk stopped, down to the entry within a file
+    #[pyo3(signature = (pattern = None, cursor = None, page_size = 100, **kwargs))]
+    fn search_page(
+        slf: &PyCell<Self>,
+        pattern: Option<&PyAny>,
+        cursor: Option<&str>,
+        page_size: usize,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<(PyObject, Option<String>)> {
+        let py = slf.py();
+        if page_size == 0 {
+            return Err(PyValueError::new_err("page_size must be at least 1"));
+        }
+        let kwargs = match kwargs {
+            Some(kwargs) => kwargs.copy()?,
+            None => PyDict::new(py),
+        };
+        for key in PAGE_REJECTED_OPTIONS {
+            if kwargs.contains(key)? {
+                return Err(PyTypeError::new_err(format!("search_page() does not accept {key}=")));
+            }
+        }
+        if kwargs.get_item("errors")?.is_some_and(|errors| errors.extract::<&str>().is_ok_and(|errors| errors == "collect")) {
+            return Err(PyTypeError::new_err("search_page() does not accept errors='collect'"));
+        }
+        let output_mode = match kwargs.get_item("output_mode")? {
+            Some(mode) => mode.extract::<Option<String>>()?.unwrap_or_else(|| "files_with_matches".to_string()),
+            None => "files_with_matches".to_string(),
+        };
+        let output_mode = OutputMode::from_str(&output_mode)?;
+        if !matches!(
+            output_mode,
+            OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch | OutputMode::Files | OutputMode::Count | OutputMode::Matches | OutputMode::FirstMatch
+        ) {
+            return Err(PyValueError::new_err(
+                "search_page() supports output_mode 'files_with_matches', 'files_without_match', 'files', 'count', 'matches' and 'first_match'",
+            ));
+        }
+        let (resume_path, resume_skip) = cursor.map(decode_page_cursor).transpose()?.unwrap_or_default();
+
+        // The files the search would look at, in the order pages walk them
+        let listing = kwargs.copy()?;
+        // An explicit None so that a Grep(include_zero=True) default does not apply either
+        listing.set_item("include_zero", py.None())?;
+        listing.set_item("output_mode", "files")?;
+        listing.set_item("sort", "path")?;
+        let files: Vec<String> = slf.call_method("search", (py.None(),), Some(listing))?.extract()?;
+
+        // Each chunk is searched as a file list, which the walk options have already shaped
+        let chunk_kwargs = kwargs.copy()?;
+        for key in ["path", "glob", "iglob", "scope"] {
+            if chunk_kwargs.contains(key)? {
+                chunk_kwargs.del_item(key)?;
+            }
+        }
+        chunk_kwargs.set_item("sort", "path")?;
+
+        let mut page: Vec<(String, PyObject)> = Vec::new();
+        let mut next = None;
+        let mut index = files.partition_point(|file| file.as_str() < resume_path.as_str());
+        'chunks: while index < files.len() {
+            let chunk = &files[index..(index + page_size).min(files.len())];
+            index += chunk.len();
+            chunk_kwargs.set_item("files", chunk)?;
+            let results = slf.call_method("search", (pattern,), Some(chunk_kwargs))?;
+            let entries = page_entries(results, &output_mode)?;
+            let mut current: Option<&str> = None;
+            let mut seen = 0;
+            for (position, (path, entry)) in entries.iter().enumerate() {
+                if current != Some(path.as_str()) {
+                    current = Some(path.as_str());
+                    seen = 0;
+                }
+                seen += 1;
+                if *path == resume_path && seen <= resume_skip {
+                    continue;
+                }
+                page.push((path.clone(), entry.clone_ref(py)));
+                if page.len() == page_size {
+                    // Resume at the next entry of this chunk, or else at the next unsearched file
+                    next = match entries.get(position + 1) {
+                        Some((next_path, _)) if next_path == path => Some((path.clone(), seen)),
+                        Some((next_path, _)) => Some((next_path.clone(), 0)),
+                        None => files.get(index).map(|file| (file.clone(), 0)),
+                    };
+                    break 'chunks;
+                }
+            }
+        }
+
+        let results = if output_mode == OutputMode::Count {
+            let counts = PyDict::new(py);
+            for (path, count) in page {
+                counts.set_item(path, count)?;
+            }
+            counts.into_py(py)
+        } else {
+            page.into_iter().map(|(_, entry)| entry).collect::<Vec<_>>().into_py(py)
+        };
+        Ok((results, next.map(|(path, skip)| encode_page_cursor(&path, skip))))
+    }
+
+    /// The files with matches as a FileSet, whose search() and refine() read only those files
+    /// Takes search()'s keyword arguments except output_mode and the ones that add return values
+    #[pyo3(signature = (pattern = None, **kwargs))]
+    fn find_files(slf: &PyCell<Self>, pattern: Option<&PyAny>, kwargs: Option<&PyDict>) -> PyResult<FileSet> {
+        FileSet::collect(slf.py(), slf.into(), pattern, kwargs, None)
+    }
+
+    /// Main search method with ripgrep-like interface
+    /// Supports the exact parameter names as required by the Grep tool specification
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    #[pyo3(name = "_search", signature = (
+        pattern = None,
+        path = None,
+        glob = None,
+        output_mode = None,
+        B = None,      // -B flag: lines before
+        A = None,      // -A flag: lines after
+        C = None,      // -C flag: lines before and after
+        n = None,      // -n flag: show line numbers
+        i = None,      // -i flag: case insensitive
+        r#type = None, // type parameter: file type filter
+        head_limit = None,
+        truncation_warning = None, // add truncation warning
+        compact_paths = None, // show filepath only once per content block
+        multiline = None,
+        timeout = None, // timeout in seconds
+        stats = None, // return search statistics alongside results
+        errors = None, // per-file error policy: ignore, collect, raise
+        type_ignore_case = None, // match type globs case-insensitively (*.py matches FILE.PY)
+        files = None, // search exactly these files instead of walking path
+        walk_timeout = None, // limit on directory enumeration time alone, in seconds
+        search_zip = None, // decompress .gz/.bz2/.xz/.zst files while searching
+        preprocessor = None, // command or callable producing the text to search per file
+        file_headers = None, // "== path (N matches) ==" line before each file in content mode
+        replace = None, // show matched lines with matches replaced, without touching files
+        order_within_file = None, // content block order per file: line, relevance, reverse
+        engine = None, // regex engine: default, fancy (look-around/backreferences), auto
+        x = None, // -x flag: pattern must match the whole line
+        strict_options = None, // False: fall back when an option needs a missing feature
+        null_data = None, // --null-data: NUL-terminated records instead of lines
+        crlf = None, // --crlf: treat \r\n as the line terminator
+        encodings = None, // {glob: encoding} for non-UTF-8 files
+        sort = None, // file order: none, path, modified, created, size
+        sort_order = None, // asc (default) or desc
+        threads = None, // -j: worker threads for matching (0/None = one per CPU)
+        queue_size = None, // capacity of the work and result queues
+        expand_vars = None, // expand ~ and $VAR in path, glob and files
+        max_files = None, // stop after this many files with matches
+        max_matches_total = None, // stop after this many matching lines overall
+        max_matches_per_file = None, // -m: matching lines kept per file
+        group_by_file = None, // content mode: {path: [match, ...]} instead of a flat list
+        context_separator = None, // --context-separator: line between context blocks (default "--")
+        heading = None, // --heading: path once per file, then bare line:content lines
+        no_filename = None, // -I: leave paths out of content lines
+        passthru = None, // --passthru: every line of matching files, matches flagged
+        color = None, // "ansi" or (open, close) markers around matches in content mode
+        forensic = None, // account for every candidate file: searched, or listed with a skip reason
+        buffer_size = None, // read buffer capacity in bytes
+        heap_limit = None, // max bytes a single line may take (rg --heap-limit semantics)
+        regex_size_limit = None, // --regex-size-limit in bytes
+        dfa_size_limit = None, // --dfa-size-limit in bytes
+        case_smart = None, // -S: ignore case unless the pattern has uppercase
+        unicode = None, // False: ASCII-only \w, \b, \d (faster on ASCII corpora)
+        max_columns = None, // -M: omit content lines longer than this many bytes
+        max_columns_preview = None, // --max-columns-preview: keep the first max_columns bytes instead
+        canonical_paths = None, // resolve symlinks and ./.. in result paths; search each file once
+        absolute_paths = None, // True: absolute result paths; False: relative to the working directory
+        relative_to = None, // result paths relative to this directory
+        custom_ignore_filenames = None, // extra per-directory ignore file names, e.g. [".prettierignore"]
+        ignore_file_paths = None, // --ignore-file: extra ignore files applied to the whole walk
+        one_file_system = None, // --one-file-system: don't cross into other mounts
+        max_filesize = None, // --max-filesize: skip files larger than this many bytes
+        min_filesize = None, // skip files smaller than this many bytes
+        modified_after = None, // skip files last modified before this datetime / timestamp / timedelta ago
+        modified_before = None, // skip files last modified after this datetime / timestamp / timedelta ago
+        all_of = None, // only search files containing every one of these patterns
+        none_of = None, // skip files containing any of these patterns
+        rank = None, // order matching files by relevance instead of walk order
+        metadata = None, // files_with_matches: dicts with first match line, count, size and mtime
+        ignore_level = None, // -u/-uu/-uuu: 1 skips ignore files, 2 adds hidden files, 3 adds binary files
+        archives = None, // search members of .zip/.jar/.whl/.tar/.tar.gz files as archive!/member paths
+        scope = None, // git scope: tracked, changed or staged files only
+        batch_small_files = None, // batch files per worker and reuse read buffers (trees of many tiny files)
+        skip_minified = None, // skip files whose lines average over 300 bytes (or the given length)
+        generated_globs = None, // skip files matching these globs, e.g. lockfiles and bundles
+        hidden = None, // --hidden: search hidden files and directories
+        type_add = None, // --type-add: extra type definitions such as "web:*.{html,css}"
+        aggregate = None, // count mode: per_file (default), per_dir or total
+        include_zero = None, // count mode: also list searched files with no matches
+        special_files = None, // "skip" (default), "report" or "follow"
+        backtrack_limit = None, // fancy engine: backtracking steps per match attempt
+        spans = None, // add each record's match character ranges
+        max_bytes = None, // stop before the file that would take the data read past this many bytes
+        dedupe = None, // "content": search each distinct file content once
+        ignore_file_errors = None, // malformed .gitignore lines: "warn" (default), "ignore" or "raise"
+        iglob = None, // --iglob: globs matched case-insensitively, alongside glob
+        search_git_dir = None, // search .git even without hidden=True, or (False) skip it even with it
+        text_mode = None, // content mode: "str" (default) or "bytes" for lines exactly as read
+        lossy = None, // False: never show U+FFFD for undecodable bytes (ignore_level=3 skips those files)
+        column_unit = None, // what vimgrep columns and spans count: "bytes", "chars" or "display"
+        tab_width = None, // tab stops for column_unit="display" (default 8)
+        profile = None, // time each phase of the search in SearchStats.profile; implies stats
+        normalize_paths = None, // resolve ./.. and drop Windows \\?\ prefixes in result paths
+        changed_files = None, // files changed mid-search: "ignore" (default), "retry", "skip" or "flag"
+        root = None, // anchor ignore rules here: ignore files from root down apply, .gitignore without .git
+        ignore_parents = None, // False: skip ignore files above the search path (rg --no-ignore-parent)
+        spill_over = None, // content mode: past this many lines, return a SpilledResults reader over a JSONL file
+        filter_logic = None, // "and" (default): files must match glob and type; "or": either one selects a file
+        context = None // "block": show each match with its enclosing function or stanza instead of A/B/C lines
+    ))]
+    fn search_impl(
+        &self,
+        py: Python,
+        pattern: Option<&PyAny>,  // regex string or compiled Pattern
+        path: Option<&str>,
+        glob: Option<&PyAny>,
+        output_mode: Option<&str>,
+        B: Option<u64>,           // -B: lines before match
+        A: Option<u64>,           // -A: lines after match
+        C: Option<u64>,           // -C: lines before and after match
+        n: Option<bool>,          // -n: show line numbers
+        i: Option<bool>,          // -i: case insensitive
+        r#type: Option<&PyAny>,   // type: file type filter (string or list)
+        head_limit: Option<usize>,
+        truncation_warning: Option<bool>, // add truncation warning
+        compact_paths: Option<bool>, // show filepath only once per content block
+        multiline: Option<bool>,
+        timeout: Option<f64>,     // timeout in seconds
+        stats: Option<bool>,      // return (results, SearchStats)
+        errors: Option<&str>,     // per-file error policy
+        type_ignore_case: Option<bool>, // case-insensitive type globs
+        files: Option<Vec<String>>, // explicit file list, bypasses the walk
+        walk_timeout: Option<f64>, // traversal-only timeout in seconds
+        search_zip: Option<bool>, // -z: search compressed files
+        preprocessor: Option<&PyAny>, // --pre: command string or callable(path)
+        file_headers: Option<bool>, // per-file header lines in content mode
+        replace: Option<&str>,    // -r: replacement preview for content mode
+        order_within_file: Option<&str>, // content ordering within each file
+        engine: Option<&str>,     // regex engine selection
+        x: Option<bool>,          // -x: line regexp
+        strict_options: Option<bool>, // raise (default) or degrade on unsupported options
+        null_data: Option<bool>,  // --null-data: NUL as the line terminator
+        crlf: Option<bool>,       // --crlf: `$` matches before \r\n
+        encodings: Option<&PyDict>, // per-glob -E: transcode matching files to UTF-8
+        sort: Option<&str>,       // --sort: order files before searching
+        sort_order: Option<&str>, // --sort vs --sortr
+        threads: Option<usize>,   // -j: search worker threads
+        queue_size: Option<usize>, // bounded queue capacity between walker, workers and collector
+        expand_vars: Option<bool>, // expand ~ and environment variables in path inputs
+        max_files: Option<usize>, // limit on files with matches
+        max_matches_total: Option<usize>, // limit on matching lines across all files
+        max_matches_per_file: Option<usize>, // -m: limit on matching lines per file
+        group_by_file: Option<bool>, // per-file dict of match records in content mode
+        context_separator: Option<&str>, // --context-separator
+        heading: Option<bool>,    // --heading
+        no_filename: Option<bool>, // -I / --no-filename
+        passthru: Option<bool>,   // --passthru
+        color: Option<&PyAny>,    // --color: "ansi", "never" or an (open, close) tuple
+        forensic: Option<bool>,   // audit mode: no silent skipping, implies stats and errors="collect"
+        buffer_size: Option<usize>, // read buffer capacity
+        heap_limit: Option<usize>, // per-line memory cap before a file fails
+        regex_size_limit: Option<usize>, // compiled regex size cap
+        dfa_size_limit: Option<usize>, // lazy DFA cache cap
+        case_smart: Option<bool>, // -S: smart case
+        unicode: Option<bool>,    // Unicode-aware character classes (default True)
+        max_columns: Option<usize>, // -M: long line limit in content mode
+        max_columns_preview: Option<bool>, // --max-columns-preview
+        canonical_paths: Option<bool>, // dedupe files reached through several paths
+        absolute_paths: Option<bool>, // result path format
+        relative_to: Option<&str>, // base directory for relative result paths
+        custom_ignore_filenames: Option<Vec<String>>, // gitignore-syntax files honored in every directory
+        ignore_file_paths: Option<Vec<String>>, // explicit gitignore-syntax files
+        one_file_system: Option<bool>, // stay on the filesystem of each root
+        max_filesize: Option<u64>, // upper size bound in bytes
+        min_filesize: Option<u64>, // lower size bound in bytes
+        modified_after: Option<&PyAny>, // lower mtime bound
+        modified_before: Option<&PyAny>, // upper mtime bound
+        all_of: Option<Vec<String>>, // required patterns, checked per file
+        none_of: Option<Vec<String>>, // forbidden patterns, checked per file
+        rank: Option<bool>, // score-ordered results
+        metadata: Option<bool>, // per-file summaries in files_with_matches mode
+        ignore_level: Option<u8>, // rg -u count
+        archives: Option<bool>,   // descend into archives
+        scope: Option<&str>,      // limit the search to files git lists
+        batch_small_files: Option<bool>, // amortize per-file queue and allocation overhead
+        skip_minified: Option<&PyAny>, // True or an average line length threshold in bytes
+        generated_globs: Option<Vec<String>>, // globs for generated files
+        hidden: Option<bool>,     // rg --hidden
+        type_add: Option<Vec<String>>, // rg --type-add definitions
+        aggregate: Option<&str>,  // how count mode combines per-file counts
+        include_zero: Option<bool>,  // keep zero counts in count mode
+        special_files: Option<&str>, // policy for symlinks, fifos, sockets and devices
+        backtrack_limit: Option<usize>, // step limit for the backtracking engine
+        spans: Option<bool>, // match spans in records
+        max_bytes: Option<u64>, // byte budget for the whole search
+        dedupe: Option<&str>, // skip files repeating an earlier file's contents
+        ignore_file_errors: Option<&str>, // policy for unparsable ignore file lines
+        iglob: Option<&PyAny>, // case-insensitive globs
+        search_git_dir: Option<bool>, // .git regardless of the hidden setting
+        text_mode: Option<&str>, // str or lossless bytes content lines
+        lossy: Option<bool>, // allow U+FFFD in decoded text
+        column_unit: Option<&str>, // column counting for vimgrep and spans
+        tab_width: Option<usize>, // display column tab stops
+        profile: Option<bool>, // per-phase timings
+        normalize_paths: Option<bool>, // tidy result paths
+        changed_files: Option<&str>, // consistency policy for live files
+        root: Option<&str>, // ignore rule anchor directory
+        ignore_parents: Option<bool>, // read ignore files above path
+        spill_over: Option<usize>, // line count past which content results go to disk
+        filter_logic: Option<&str>, // how glob and type combine
+        context: Option<&str>, // context by block instead of line counts
+    ) -> PyResult<PyObject> {
+        let started = Instant::now();
+        let output_mode = OutputMode::from_str(output_mode.unwrap_or("files_with_matches"))?;
+        
+        // Validate pattern requirement based on output mode
+        if pattern.is_none() && output_mode != OutputMode::Files {
+            return Err(PyValueError::new_err("Pattern is required for all output modes except 'files'"));
+        }
+        
+        // A compiled Pattern carries its own matcher options and default type filter
+        let compiled: Option<Pattern> = match pattern {
+            Some(p) => p.extract::<PyRef<Pattern>>().ok().map(|p| p.clone()),
+            None => None,
+        };
+        let case_insensitive = compiled.as_ref().map_or(i.unwrap_or(false), |p| p.case_insensitive);
+        let multiline = compiled.as_ref().map_or(multiline.unwrap_or(false), |p| p.multiline);
+        let line_numbers = n.unwrap_or(false);
+        let show_truncation_warning = truncation_warning.unwrap_or(false);
+        let use_compact_paths = compact_paths.unwrap_or(false);
+        let forensic = forensic.unwrap_or(false);
+        let profile = profile.unwrap_or(false);
+        let want_stats = stats.unwrap_or(false) || forensic || profile;
+        let error_policy = ErrorPolicy::from_str(errors.unwrap_or(if forensic { "collect" } else { "ignore" }))?;
+        if forensic {
+            // Every file has to end up searched or in the skip list, so nothing may stop early
+            if error_policy != ErrorPolicy::Collect {
+                return Err(PyValueError::new_err("forensic=True requires errors='collect'"));
+            }
+            if head_limit.is_some() || max_files.is_some() || max_matches_total.is_some() || max_matches_per_file.is_some() {
+                return Err(PyValueError::new_err("forensic=True cannot be combined with result limits"));
+            }
+        }
+        let type_ignore_case = type_ignore_case.unwrap_or(false);
+        let order = LineOrder::from_str(order_within_file.unwrap_or("line"))?;
+        let sort = SortBy::from_str(sort.unwrap_or("none"))?;
+        let sort_descending = parse_sort_order(sort_order.unwrap_or("asc"))?;
+        let (no_ignore, search_hidden, search_binary) = parse_ignore_level(ignore_level)?;
+        let search_hidden = hidden.unwrap_or(search_hidden);
+        let aggregate = CountAggregate::from_str(aggregate.unwrap_or("per_file"))?;
+        if aggregate != CountAggregate::PerFile && output_mode != OutputMode::Count {
+            return Err(PyValueError::new_err("aggregate requires output_mode 'count'"));
+        }
+        let include_zero = include_zero.unwrap_or(false);
+        if include_zero && output_mode != OutputMode::Count {
+            return Err(PyValueError::new_err("include_zero requires output_mode 'count'"));
+        }
+        let spans = spans.unwrap_or(false);
+        if spans {
+            let grouped = output_mode == OutputMode::Content && group_by_file.unwrap_or(false);
+            if !grouped && !matches!(output_mode, OutputMode::Matches | OutputMode::FirstMatch) {
+                return Err(PyValueError::new_err(
+                    "spans=True requires output_mode 'matches' or 'first_match', or 'content' with group_by_file=True",
+                ));
+            }
+            // Spans locate the pattern in the returned text, which replace and color rewrite
+            if replace.is_some() || color.is_some() {
+                return Err(PyValueError::new_err("spans=True cannot be combined with replace or color"));
+            }
+        }
+        let raw_bytes = match text_mode.unwrap_or("str") {
+            "str" => false,
+            "bytes" => true,
+            other => return Err(PyValueError::new_err(format!("Invalid text_mode: {}", other))),
+        };
+        if raw_bytes && output_mode != OutputMode::Content {
+            return Err(PyValueError::new_err("text_mode='bytes' requires output_mode 'content'"));
+        }
+        if raw_bytes && spans {
+            return Err(PyValueError::new_err("text_mode='bytes' cannot be combined with spans=True"));
+        }
+        if spill_over.is_some() && (output_mode != OutputMode::Content || group_by_file.unwrap_or(false) || raw_bytes) {
+            return Err(PyValueError::new_err(
+                "spill_over requires output_mode 'content' without group_by_file or text_mode='bytes'",
+            ));
+        }
+        let column_unit = ColumnUnit::parse(column_unit, tab_width)?;
+        if column_unit.is_some() && !spans && output_mode != OutputMode::Vimgrep {
+            return Err(PyValueError::new_err("column_unit requires spans=True or output_mode 'vimgrep'"));
+        }
+        let span_unit = column_unit.unwrap_or(ColumnUnit::Chars);
+        let metadata = metadata.unwrap_or(false);
+        if metadata && output_mode != OutputMode::FilesWithMatches {
+            return Err(PyValueError::new_err("metadata=True requires output_mode 'files_with_matches'"));
+        }
+        let rank = rank.unwrap_or(false);
+        if rank {
+            if !matches!(output_mode, OutputMode::FilesWithMatches | OutputMode::Count | OutputMode::Content) {
+                return Err(PyValueError::new_err(
+                    "rank=True supports output_mode 'files_with_matches', 'count' and 'content'",
+                ));
+            }
+            if sort != SortBy::None {
+                return Err(PyValueError::new_err("rank=True cannot be combined with sort"));
+            }
+        }
+        let match_limits = MatchLimits { max_files, max_matches_total, max_matches_per_file };
+        if head_limit.is_some() {
+            // head_limit counts output lines in content mode but files elsewhere
+            PyErr::warn(
+                py,
+                py.get_type::<PyDeprecationWarning>(),
+                "head_limit is deprecated; use max_files, max_matches_total or max_matches_per_file",
+                1,
+            )?;
+        }
+        let strict_options = strict_options.unwrap_or(true);
+        let null_data = null_data.unwrap_or(false);
+        let mut warnings = Vec::new();
+        let pattern_options = Self::pattern_options(
+            i, case_smart, unicode, Some(multiline), x, crlf, null_data, engine,
+            regex_size_limit, dfa_size_limit, backtrack_limit, strict_options, &mut warnings,
+        )?;
+        let crlf = pattern_options.crlf;
+        // "ipynb" selects the built-in notebook mode rather than a command of that name;
+        // like any preprocessor it is ignored in files mode
+        let notebooks = output_mode != OutputMode::Files && preprocessor.is_some_and(|p| p.extract::<&str>().is_ok_and(|command| command == "ipynb"));
+        let mut read = ReadOptions {
+            preprocessor: preprocessor.filter(|_| !notebooks).map(Preprocessor::from_py).transpose()?,
+            gate: None,
+            archives: archives.unwrap_or(false),
+            notebooks,
+            batch_small_files: batch_small_files.unwrap_or(false),
+            minified_line_length: parse_skip_minified(skip_minified)?,
+            deadline: None, // set once the limits are known
+            raw_bytes,
+            profile,
+            changed_files: ChangedFiles::from_str(changed_files.unwrap_or("ignore"))?,
+            ..ReadOptions::decoding(
+                search_zip.unwrap_or(false), encodings, crlf, null_data, search_binary, lossy, buffer_size, heap_limit,
+            )?
+        };
+        if read.archives && !cfg!(feature = "archives") {
+            unsupported_option(
+                strict_options,
+                "archives requires pyripgrep built with the 'archives' feature",
+                "archives are searched as regular files",
+                &mut warnings,
+            )?;
+            read.archives = false;
+        }
+        if read.search_zip && !cfg!(feature = "decompression") {
+            unsupported_option(
+                strict_options,
+                "search_zip requires pyripgrep built with the 'decompression' feature",
+                "compressed files are searched as-is",
+                &mut warnings,
+            )?;
+            read.search_zip = false;
+        }
+
+        let passthru = passthru.unwrap_or(false);
+        let highlight = Highlight::from_py(color)?;
+        let max_columns = max_columns.map(|limit| MaxColumns { limit, preview: max_columns_preview.unwrap_or(false) });
+        // context="block" replaces the line counts with each match's enclosing block
+        let block_context = match context {
+            None => false,
+            Some("block") => true,
+            Some(other) => return Err(PyValueError::new_err(format!("Invalid context mode: {}", other))),
+        };
+        if block_context && (output_mode != OutputMode::Content || A.is_some() || B.is_some() || C.is_some() || passthru) {
+            return Err(PyValueError::new_err(
+                "context='block' requires output_mode 'content' and cannot be combined with A, B, C or passthru",
+            ));
+        }
+
+        // Handle context options - C overrides A and B
+        let (before_ctx, after_ctx) = if let Some(c) = C {
+            (c, c)
+        } else {
+            (B.unwrap_or(0), A.unwrap_or(0))
+        };
+
+        // Parse types outside allow_threads (can raise Python exceptions here)
+        let parsed_types = Self::parse_types(r#type)?;
+
+        // Build matcher (only if pattern is provided)
+        let matcher = match (&compiled, pattern) {
+            // CRLF handling and size limits are part of the regex, so a compiled Pattern is rebuilt for them
+            (Some(compiled), _) if crlf || regex_size_limit.is_some() || dfa_size_limit.is_some() || backtrack_limit.is_some() => {
+                let options = MatcherOptions { crlf, size_limit: regex_size_limit, dfa_size_limit, backtrack_limit, ..compiled.options };
+                Some(Self::build_matcher(&compiled.pattern, &options)?)
+            }
+            (Some(compiled), _) => Some(compiled.matcher.clone()),
+            (None, Some(pattern)) => {
+                let pattern: &str = pattern
+                    .extract()
+                    .map_err(|_| PyValueError::new_err("Pattern must be a string or a compiled Pattern"))?;
+                Some(Self::build_matcher(pattern, &pattern_options)?)
+            }
+            (None, None) => None,
+        };
+
+        // File conditions share the main pattern's case and engine settings
+        if all_of.is_some() || none_of.is_some() {
+            let options = MatcherOptions { case_insensitive, multiline: false, line_regexp: false, ..pattern_options };
+            let build = |patterns: Option<Vec<String>>| {
+                patterns.unwrap_or_default().iter().map(|p| Self::build_matcher(p, &options)).collect::<PyResult<Vec<_>>>()
+            };
+            read.gate = Some(ContentGate { all_of: build(all_of)?, none_of: build(none_of)? });
+        }
+
+        // Explicit types override the ones compiled into a Pattern
+        let type_matcher = if parsed_types.is_empty() {
+            compiled.and_then(|p| p.type_filter)
+        } else {
+            Self::build_type_filter(&parsed_types, &type_add.unwrap_or_default(), type_ignore_case)?
+        };
+
+        // Compute time limits from timeout and walk_timeout
+        let limits = Limits::new(timeout, walk_timeout);
+        read.deadline = limits.deadline;
+        let pool = WorkerPool::new(threads, queue_size);
+
+        // Build file source outside allow_threads (can raise Python exceptions here)
+        // Opt-in ~ and $VAR expansion so configs can be shared across machines
+        let expand = |value: &str| if expand_vars.unwrap_or(false) { expand_env_vars(value) } else { value.to_string() };
+        let path = path.map(expand);
+        let globs = Globs::parse(glob, iglob)?.map(expand);
+        // With filter_logic='or', include globs move from the walk into the type filter as an
+        // alternative to the types; exclude globs keep pruning the walk either way
+        let (globs, type_matcher) = match (FilterLogic::from_str(filter_logic.unwrap_or("and"))?, type_matcher) {
+            (FilterLogic::Or, Some(mut filter)) => {
+                let (excludes, includes) = globs.split_excludes();
+                filter.either = Self::build_overrides(Path::new(path.as_deref().unwrap_or(".")), &includes)?;
+                (excludes, Some(filter))
+            }
+            (_, type_matcher) => (globs, type_matcher),
+        };
+        let files = files.map(|files| files.iter().map(|f| expand(f)).collect());
+        // A git scope replaces the walk with the files git lists under the search root
+        let files = match (scope.map(GitScope::from_str).transpose()?, files) {
+            (Some(_), Some(_)) => return Err(PyValueError::new_err("scope cannot be combined with files")),
+            (Some(scope), None) => Some(scope.files(Path::new(path.as_deref().unwrap_or(".")))?),
+            (None, files) => files,
+        };
+        let walk_options = WalkOptions {
+            ignore_filenames: custom_ignore_filenames.unwrap_or_default(),
+            ignore_paths: ignore_file_paths.unwrap_or_default().iter().map(|p| PathBuf::from(expand(p))).collect(),
+            same_file_system: one_file_system.unwrap_or(false),
+            no_ignore,
+            hidden: search_hidden,
+            git_dir: search_git_dir,
+            no_parents: !ignore_parents.unwrap_or(true),
+            ignore_root: root.map(|root| PathBuf::from(expand(root))),
+            // forensic=True accounts for every entry, so it reports them unless told otherwise
+            special_files: SpecialFiles::from_str(special_files.unwrap_or(if forensic { "report" } else { "skip" }))?,
+        };
+        let dedupe = match dedupe {
+            None | Some("none") => false,
+            Some("content") => true,
+            Some(other) => return Err(PyValueError::new_err(format!("Invalid dedupe mode: {}", other))),
+        };
+        let duplicates = Arc::new(Mutex::new(Vec::new()));
+        // Set once max_bytes cuts the search short; reported like any other truncation
+        let bytes_exhausted = Arc::new(AtomicBool::new(false));
+        let bounds = FileBounds {
+            min_size: min_filesize,
+            max_size: max_filesize,
+            modified_after: modified_after.map(|t| Self::parse_time(t, "modified_after")).transpose()?,
+            modified_before: modified_before.map(|t| Self::parse_time(t, "modified_before")).transpose()?,
+        };
+        let source = self
+            .build_source(path.as_deref(), &globs, &walk_options, files, want_stats)?
+            .archives(read.archives)
+            .notebooks(read.notebooks)
+            .generated(build_generated_globs(generated_globs)?)
+            .bounded(bounds)
+            .canonical(canonical_paths.unwrap_or(false))
+            .sorted(sort, sort_descending)
+            .deduped(dedupe, &read, &duplicates)
+            .byte_budget(max_bytes, &bytes_exhausted);
+
+        let mut report = SearchReport {
+            forensic,
+            paths: PathStyle::from_options(absolute_paths, relative_to.map(expand).as_deref(), normalize_paths.unwrap_or(false))?,
+            ignore_file_errors: IgnoreFileErrors::from_str(ignore_file_errors.unwrap_or("warn"))?,
+            ..SearchReport::new(error_policy)
+        };
+        report.stats.warnings = warnings;
+        if profile {
+            report.stats.profile = Some(Profile { setup: started.elapsed().as_secs_f64(), ..Profile::default() });
+        }
+
+        // Search based on output mode (heavy part runs without the GIL)
+        let budget_hit = || bytes_exhausted.load(Ordering::Relaxed);
+        let results = match output_mode {
+            OutputMode::Content => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let (results, truncated) = py.allow_threads(|| {
+                    let (mut results, truncated) = self.search_content_inner(
+                        matcher,
+                        source,
+                        type_matcher.as_ref(),
+                        &read,
+                        before_ctx,
+                        after_ctx,
+                        passthru,
+                        block_context,
+                        multiline,
+                        match_limits,
+                        limits,
+                        pool,
+                        &mut report,
+                    )?;
+                    if order == LineOrder::Relevance {
+                        for result in &mut results {
+                            result.occurrences = count_occurrences(matcher, &result.content);
+                        }
+                    }
+                    if rank {
+                        // Whole files move together; lines keep their order within each file
+                        let scores = rank_paths(matcher, results.iter().map(|r| r.path.as_str()), &report.paths, &read);
+                        let mut first_seen = HashMap::new();
+                        for (index, result) in results.iter().enumerate() {
+                            first_seen.entry(result.path.clone()).or_insert(index);
+                        }
+                        results.sort_by(|a, b| {
+                            scores[&b.path].total_cmp(&scores[&a.path]).then(first_seen[&a.path].cmp(&first_seen[&b.path]))
+                        });
+                    }
+                    // Like rg -r and --color: only matched lines are rewritten, context is shown as-is
+                    if replace.is_some() || highlight.is_some() || max_columns.is_some() {
+                        for result in &mut results {
+                            // Long lines are cut first so that replacement and markers see only the preview
+                            let suffix = match max_columns {
+                                Some(columns) => columns.trim(matcher, result),
+                                None => Some(String::new()),
+                            };
+                            let Some(suffix) = suffix else {
+                                continue; // omitted outright
+                            };
+                            if replace.is_some() || highlight.is_some() {
+                                result.content = replace_matches(matcher, &result.content, replace, highlight.as_ref());
+                            }
+                            result.content.push_str(&suffix);
+                        }
+                    }
+                    if spans {
+                        for result in &mut results {
+                            result.spans = Some(match_spans(matcher, &result.content, span_unit));
+                        }
+                    }
+                    Ok((results, truncated))
+                }).map_err(to_pyerr)?;
+                let format = ContentFormat {
+                    line_numbers,
+                    head_limit,
+                    truncated: truncated || budget_hit(),
+                    truncation_warning: show_truncation_warning,
+                    compact_paths: use_compact_paths,
+                    file_headers: file_headers.unwrap_or(false),
+                    group_by_file: group_by_file.unwrap_or(false),
+                    context_separator: context_separator.unwrap_or("--"),
+                    heading: heading.unwrap_or(false),
+                    no_filename: no_filename.unwrap_or(false),
+                    order,
+                };
+                if let Some(spill_over) = spill_over {
+                    // Past spill_over lines the results go to disk instead of into a Python list
+                    let (lines, suppressed) = py.allow_threads(|| Self::format_content_lines(results, &format));
+                    report.stats.suppress(suppressed);
+                    if lines.len() > spill_over {
+                        Py::new(py, SpilledResults::write(py, lines)?)?.into_py(py)
+                    } else {
+                        lines.into_py(py)
+                    }
+                } else {
+                    let (formatted, suppressed) = self.format_content_results(py, results, &format)?;
+                    report.stats.suppress(suppressed);
+                    if read.raw_bytes {
+                        content_as_bytes(py, formatted)?
+                    } else {
+                        formatted
+                    }
+                }
+            }
+            OutputMode::FilesWithMatches if metadata => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let summaries = py.allow_threads(|| {
+                    let mut summaries = self.search_summaries_inner(
+                        matcher, source, type_matcher.as_ref(), &read, head_limit, match_limits, limits, pool, &mut report,
+                    )?;
+                    if rank {
+                        let scores = rank_paths(matcher, summaries.iter().map(|s| s.path.as_str()), &report.paths, &read);
+                        summaries.sort_by(|a, b| scores[&b.path].total_cmp(&scores[&a.path]));
+                    }
+                    Ok(summaries)
+                }).map_err(to_pyerr)?;
+                self.format_file_summaries(py, summaries)?
+            }
+            OutputMode::FilesWithMatches => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let (mut files, truncated) = py.allow_threads(|| {
+                    let (mut files, truncated) = self.search_files_inner(
+                        matcher, source, type_matcher.as_ref(), &read, head_limit, match_limits, limits, pool, &mut report,
+                    )?;
+                    if rank {
+                        let scores = rank_paths(matcher, files.iter().map(String::as_str), &report.paths, &read);
+                        files.sort_by(|a, b| scores[b].total_cmp(&scores[a]));
+                    }
+                    Ok((files, truncated))
+                }).map_err(to_pyerr)?;
+                if show_truncation_warning && (truncated || budget_hit()) {
+                    files.push(TRUNCATION_MARKER.to_string());
+                }
+                files.into_py(py)
+            }
+            OutputMode::FilesWithoutMatch => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                // Each listed file counts toward max_files
+                let file_limit = head_limit.into_iter().chain(max_files).min();
+                let (mut files, truncated) = py.allow_threads(|| {
+                    self.search_files_without_match_inner(
+                        matcher, source, type_matcher.as_ref(), &read, file_limit, limits, pool, &mut report,
+                    )
+                }).map_err(to_pyerr)?;
+                if show_truncation_warning && (truncated || budget_hit()) {
+                    files.push(TRUNCATION_MARKER.to_string());
+                }
+                files.into_py(py)
+            }
+            OutputMode::Quiet => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let found = py.allow_threads(|| {
+                    self.search_exists_inner(matcher, source, type_matcher.as_ref(), &read, limits, pool, &mut report)
+                }).map_err(to_pyerr)?;
+                found.into_py(py)
+            }
+            OutputMode::Count => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let counts = py.allow_threads(|| {
+                    let mut counts = self.search_count_inner(
+                        matcher, source, type_matcher.as_ref(), &read, head_limit, match_limits, include_zero, limits, pool, &mut report,
+                    )?;
+                    if rank {
+                        let scores = rank_paths(matcher, counts.iter().map(|c| c.path.as_str()), &report.paths, &read);
+                        counts.sort_by(|a, b| scores[&b.path].total_cmp(&scores[&a.path]));
+                    }
+                    Ok(counts)
+                }).map_err(to_pyerr)?;
+                match aggregate {
+                    CountAggregate::PerFile => self.format_count_results(py, counts)?,
+                    CountAggregate::PerDir => self.format_count_results(py, CountAggregate::per_dir(counts))?,
+                    CountAggregate::Total => counts.iter().map(|count| count.count).sum::<u64>().into_py(py),
+                }
+            }
+            OutputMode::Vimgrep => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let (mut lines, truncated) = py.allow_threads(|| {
+                    let columns = column_unit.unwrap_or(ColumnUnit::Bytes);
+                    self.search_vimgrep_inner(matcher, source, type_matcher.as_ref(), &read, columns, head_limit, match_limits, limits, pool, &mut report)
+                }).map_err(to_pyerr)?;
+                if show_truncation_warning && (truncated || budget_hit()) {
+                    lines.push(TRUNCATION_MARKER.to_string());
+                }
+                lines.into_py(py)
+            }
+            OutputMode::Matches => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let records = py.allow_threads(|| {
+                    let mut records = self.search_matches_inner(matcher, source, type_matcher.as_ref(), &read, head_limit, match_limits, limits, pool, &mut report)?;
+                    if spans {
+                        for record in &mut records {
+                            record.spans = Some(match_spans(matcher, &record.content, span_unit));
+                        }
+                    }
+                    Ok(records)
+                }).map_err(to_pyerr)?;
+                self.format_match_records(py, records)?
+            }
+            OutputMode::FirstMatch => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                let records = py.allow_threads(|| {
+                    let mut records = self.search_first_matches_inner(matcher, source, type_matcher.as_ref(), &read, head_limit, match_limits, limits, pool, &mut report)?;
+                    if spans {
+                        for record in &mut records {
+                            record.spans = Some(match_spans(matcher, &record.content, span_unit));
+                        }
+                    }
+                    Ok(records)
+                }).map_err(to_pyerr)?;
+                self.format_match_records(py, records)?
+            }
+            OutputMode::Paths => {
+                let matcher = matcher.as_ref().unwrap(); // Safe because we validated above
+                // Each matching path counts toward max_files
+                let file_limit = head_limit.into_iter().chain(max_files).min();
+                let matches = py.allow_threads(|| {
+                    self.search_paths_inner(matcher, source, type_matcher.as_ref(), &read, file_limit, limits, &mut report)
+                }).map_err(to_pyerr)?;
+                self.format_path_matches(py, matches)?
+            }
+            OutputMode::Files => {
+                // Without a pattern every listed file counts toward max_files
+                let file_limit = head_limit.into_iter().chain(max_files).min();
+                let (mut files, truncated) = py.allow_threads(|| {
+                    self.search_files_no_match_inner(source, type_matcher.as_ref(), &read, file_limit, limits, &mut report)
+                }).map_err(to_pyerr)?;
+                if show_truncation_warning && (truncated || budget_hit()) {
+                    files.push(TRUNCATION_MARKER.to_string());
+                }
+                files.into_py(py)
+            }
+        };
+
+        if budget_hit() {
+            report.stats.truncated = true;
+        }
+        for (duplicate, original) in std::mem::take(&mut *duplicates.lock().unwrap_or_else(|e| e.into_inner())) {
+            let (duplicate, original) = (report.path_string(&duplicate), report.path_string(&original));
+            report.stats.duplicates.entry(original).or_default().push(duplicate);
+        }
+        for warning in &report.warnings {
+            PyErr::warn(py, py.get_type::<PyUserWarning>(), &format!("ignore file error: {}", warning), 1)?;
+        }
+
+        // Extras are appended to the results in a fixed order: stats, then errors
+        let collect_errors = error_policy == ErrorPolicy::Collect;
+        if !want_stats && !collect_errors {
+            return Ok(results);
+        }
+        let mut output = vec![results];
+        if want_stats {
+            report.stats.elapsed = started.elapsed().as_secs_f64();
+            if let Some(profile) = &mut report.stats.profile {
+                profile.total = report.stats.elapsed;
+                profile.format = (profile.total - profile.setup - profile.searching).max(0.0);
+            }
+            output.push(Py::new(py, report.stats)?.into_py(py));
+        }
+        if collect_errors {
+            output.push(report.errors.into_py(py));
+        }
+        Ok(PyTuple::new(py, output).into_py(py))
+    }
+
+    /// Search a binary file-like object (e.g. `sys.stdin.buffer`) instead of files on disk
+    /// The stream is read in `buffer_size` chunks through `read(n)`; results are labelled with `label`
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        pattern,
+        fileobj,
+        output_mode = None,
+        B = None,
+        A = None,
+        C = None,
+        n = None,
+        i = None,
+        multiline = None,
+        x = None,
+        engine = None,
+        replace = None,
+        color = None,
+        passthru = None,
+        null_data = None,
+        crlf = None,
+        buffer_size = None,
+        label = None // name reported in place of a path (default "<stdin>")
+    ))]
+    fn search_stream(
+        &self,
+        py: Python,
+        pattern: &PyAny,          // regex string or compiled Pattern
+        fileobj: &PyAny,          // object with a read(n) method returning bytes
+        output_mode: Option<&str>,
+        B: Option<u64>,
+        A: Option<u64>,
+        C: Option<u64>,
+        n: Option<bool>,
+        i: Option<bool>,
+        multiline: Option<bool>,
+        x: Option<bool>,
+        engine: Option<&str>,
+        replace: Option<&str>,
+        color: Option<&PyAny>,
+        passthru: Option<bool>,
+        null_data: Option<bool>,
+        crlf: Option<bool>,
+        buffer_size: Option<usize>,
+        label: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let output_mode = OutputMode::from_str(output_mode.unwrap_or("content"))?;
+        if output_mode == OutputMode::Files {
+            return Err(PyValueError::new_err("output_mode 'files' lists files on disk and cannot search a stream"));
+        }
+        if output_mode == OutputMode::Paths {
+            return Err(PyValueError::new_err("output_mode 'paths' matches file paths and cannot search a stream"));
+        }
+        if !fileobj.hasattr("read")? {
+            return Err(PyValueError::new_err("fileobj must be a binary file-like object with a read() method"));
+        }
+        let (before_context, after_context) = match C {
+            Some(c) => (c, c),
+            None => (B.unwrap_or(0), A.unwrap_or(0)),
+        };
+        let null_data = null_data.unwrap_or(false);
+        let crlf = crlf.unwrap_or(false) && !null_data;
+        let passthru = passthru.unwrap_or(false);
+        let highlight = Highlight::from_py(color)?;
+        let label = label.unwrap_or("<stdin>");
+
+        let compiled = pattern.extract::<PyRef<Pattern>>();
+        let multiline = compiled.as_ref().map_or(multiline.unwrap_or(false), |p| p.multiline);
+        let matcher = match compiled {
+            Ok(compiled) if crlf => Self::build_matcher(&compiled.pattern, &MatcherOptions { crlf, ..compiled.options })?,
+            Ok(compiled) => compiled.matcher.clone(),
+            Err(_) => {
+                let pattern: &str = pattern
+                    .extract()
+                    .map_err(|_| PyValueError::new_err("Pattern must be a string or a compiled Pattern"))?;
+                Self::build_matcher(pattern, &MatcherOptions {
+                    case_insensitive: i.unwrap_or(false),
+                    case_smart: false,
+                    unicode: true,
+                    multiline,
+                    line_regexp: x.unwrap_or(false),
+                    crlf,
+                    engine: Engine::from_str(engine.unwrap_or("default"))?,
+                    size_limit: None,
+                    dfa_size_limit: None,
+                    backtrack_limit: None,
+                })?
+            }
+        };
+        let read = ReadOptions {
+            line_terminator: if null_data {
+                LineTerminator::byte(b'\0')
+            } else if crlf {
+                LineTerminator::crlf()
+            } else {
+                LineTerminator::default()
+            },
+            buffer_size,
+            ..ReadOptions::default()
+        };
+
+        // Matching runs without the GIL; it is re-acquired only to pull each chunk
+        let mut stream = PyStream::new(fileobj.into());
+        let lines = py.allow_threads(|| read_lines_with_offsets(&mut stream, &read));
+        let lines = match lines {
+            Ok(lines) => lines,
+            Err(err) => {
+                // Exceptions raised by read() propagate unchanged
+                return Err(stream.error.take().unwrap_or_else(|| to_pyerr(RGErr::File(label.to_string(), err.to_string()))));
+            }
+        };
+
+        match output_mode {
+            OutputMode::Content => {
+                let results = py.allow_threads(|| {
+                    let text: Vec<String> = lines.into_iter().map(|(_, line)| line).collect();
+                    let mut results = Vec::new();
+                    Self::content_from_lines(&matcher, label, &text, read.line_terminator, before_context, after_context, passthru, false, multiline, &mut results);
+                    if replace.is_some() || highlight.is_some() {
+                        for result in &mut results {
+                            result.content = replace_matches(&matcher, &result.content, replace, highlight.as_ref());
+                        }
+                    }
+                    results
+                });
+                // Like rg reading stdin, content lines carry no path
+                let format = ContentFormat {
+                    line_numbers: n.unwrap_or(false),
+                    head_limit: None,
+                    truncated: false,
+                    truncation_warning: false,
+                    compact_paths: false,
+                    file_headers: false,
+                    group_by_file: false,
+                    context_separator: "--",
+                    heading: false,
+                    no_filename: true,
+                    order: LineOrder::Line,
+                };
+                Ok(self.format_content_results(py, results, &format)?.0)
+            }
+            OutputMode::Matches | OutputMode::FirstMatch => {
+                let records = py.allow_threads(|| {
+                    let mut records = Vec::new();
+                    Self::matches_from_lines(&matcher, label, lines, &mut records);
+                    if output_mode == OutputMode::FirstMatch {
+                        records.truncate(1);
+                    }
+                    records
+                });
+                self.format_match_records(py, records)
+            }
+            OutputMode::Vimgrep => {
+                let entries = py.allow_threads(|| {
+                    let text: Vec<String> = lines.into_iter().map(|(_, line)| line).collect();
+                    let mut entries = Vec::new();
+                    Self::vimgrep_from_lines(&matcher, label, &text, ColumnUnit::Bytes, &mut entries);
+                    entries
+                });
+                Ok(entries.into_iter().map(|(_, entry)| entry).collect::<Vec<_>>().into_py(py))
+            }
+            OutputMode::Count => {
+                let count = py.allow_threads(|| {
+                    lines.iter().filter(|(_, line)| matcher.is_match(line.as_bytes()).unwrap_or(false)).count() as u64
+                });
+                let counts = (count > 0).then(|| CountResult { path: label.to_string(), count });
+                self.format_count_results(py, counts.into_iter().collect())
+            }
+            OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch | OutputMode::Quiet => {
+                let found = py.allow_threads(|| {
+                    lines.iter().any(|(_, line)| matcher.is_match(line.as_bytes()).unwrap_or(false))
+                });
+                if output_mode == OutputMode::Quiet {
+                    return Ok(found.into_py(py));
+                }
+                let listed = found != (output_mode == OutputMode::FilesWithoutMatch);
+                let files: Vec<&str> = listed.then_some(label).into_iter().collect();
+                Ok(files.into_py(py))
+            }
+            OutputMode::Files | OutputMode::Paths => unreachable!("rejected above"),
+        }
+    }
+
+    /// Search several named patterns in one walk, attributing each matching line to every
+    /// pattern that matches it
+    /// Returns a dict keyed by pattern name, each value shaped like search()'s output_mode result
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        patterns,
+        path = None,
+        glob = None,
+        output_mode = None,
+        n = None,
+        i = None,
+        r#type = None,
+        timeout = None,
+        threads = None
+    ))]
+    fn search_many(
+        &self,
+        py: Python,
+        patterns: &PyDict,
+        path: Option<&str>,
+        glob: Option<&PyAny>,
+        output_mode: Option<&str>,
+        n: Option<bool>,
+        i: Option<bool>,
+        r#type: Option<&PyAny>,
+        timeout: Option<f64>,
+        threads: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let output_mode = OutputMode::from_str(output_mode.unwrap_or("files_with_matches"))?;
+        if !matches!(output_mode, OutputMode::FilesWithMatches | OutputMode::Count | OutputMode::Content) {
+            return Err(PyValueError::new_err(
+                "search_many() supports output_mode 'files_with_matches', 'count' and 'content'",
+            ));
+        }
+        // Kept in dict order, so results list the patterns the way they were given
+        let named = patterns
+            .iter()
+            .map(|(name, pattern)| Ok((name.extract::<String>()?, pattern.extract::<String>()?)))
+            .collect::<PyResult<Vec<_>>>()
+            .map_err(|_| PyValueError::new_err("patterns must map names to regex strings"))?;
+        if named.is_empty() {
+            return Err(PyValueError::new_err("search_many() needs at least one pattern"));
+        }
+
+        let options = MatcherOptions {
+            case_insensitive: i.unwrap_or(false),
+            case_smart: false,
+            unicode: true,
+            multiline: false,
+            line_regexp: false,
+            crlf: false,
+            engine: Engine::Default,
+            size_limit: None,
+            dfa_size_limit: None,
+            backtrack_limit: None,
+        };
+        let matchers = named
+            .iter()
+            .map(|(name, pattern)| {
+                Self::build_matcher(pattern, &options).inspect_err(|e| {
+                    // Keep the PatternError and its fields, naming the pattern in the message
+                    let message = format!("Pattern {name:?}: {}", e.value(py));
+                    let _ = e.value(py).setattr("args", (message,));
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        // One alternation finds candidate lines in a single pass; each pattern then
+        // re-checks only those lines
+        let combined = named.iter().map(|(_, pattern)| format!("(?:{pattern})")).collect::<Vec<_>>().join("|");
+        let combined = Self::build_matcher(&combined, &options)?;
+
+        let parsed_types = Self::parse_types(r#type)?;
+        let type_matcher = Self::build_type_filter(&parsed_types, &[], false)?;
+        let limits = Limits::new(timeout, None);
+        let pool = WorkerPool::new(threads, None);
+        let read = ReadOptions { deadline: limits.deadline, ..ReadOptions::default() };
+        let source = self.build_source(path, &Globs::parse(glob, None)?, &WalkOptions::default(), None, false)?;
+
+        let found = py.allow_threads(|| {
+            let mut report = SearchReport::new(ErrorPolicy::Ignore);
+            let mut found: Vec<(String, Vec<NumberedLines>)> = Vec::new();
+            search_files(
+                source,
+                type_matcher.as_ref(),
+                limits,
+                pool,
+                &read,
+                &mut report,
+                |searcher, path, report| {
+                    let hits = self.attribute_matches_in_file(searcher, &combined, &matchers, path, &read, report)?;
+                    Ok(hits.map(|hits| (report.path_string(path), hits)))
+                },
+                |hits| {
+                    found.extend(hits);
+                    true
+                },
+            )?;
+            Ok(found)
+        }).map_err(to_pyerr)?;
+
+        let results = PyDict::new(py);
+        for (index, (name, _)) in named.iter().enumerate() {
+            let files = found.iter().filter(|(_, hits)| !hits[index].is_empty());
+            match output_mode {
+                OutputMode::Count => {
+                    let counts = PyDict::new(py);
+                    for (path, hits) in files {
+                        counts.set_item(path, hits[index].len())?;
+                    }
+                    results.set_item(name, counts)?;
+                }
+                OutputMode::Content => {
+                    let show_line_numbers = n.unwrap_or(false);
+                    let lines: Vec<String> = files
+                        .flat_map(|(path, hits)| {
+                            hits[index].iter().map(move |(line_number, content)| {
+                                if show_line_numbers {
+                                    format!("{path}:{line_number}:{content}")
+                                } else {
+                                    format!("{path}:{content}")
+                                }
+                            })
+                        })
+                        .collect();
+                    results.set_item(name, lines)?;
+                }
+                _ => {
+                    let paths: Vec<&String> = files.map(|(path, _)| path).collect();
+                    results.set_item(name, paths)?;
+                }
+            }
+        }
+        Ok(results.into_py(py))
+    }
+
+    /// Search files and hand every searcher event to a Python callback
+    /// The callback runs on the calling thread in walk order; it returns False to stop the
+    /// current file or "stop" to end the search, and an exception it raises propagates
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        pattern,
+        callback,
+        path = None,
+        glob = None,
+        B = None,
+        A = None,
+        C = None,
+        i = None,
+        r#type = None,
+        multiline = None,
+        timeout = None
+    ))]
+    fn search_with_sink(
+        &self,
+        py: Python,
+        pattern: &str,
+        callback: PyObject,
+        path: Option<&str>,
+        glob: Option<&PyAny>,
+        B: Option<u64>,
+        A: Option<u64>,
+        C: Option<u64>,
+        i: Option<bool>,
+        r#type: Option<&PyAny>,
+        multiline: Option<bool>,
+        timeout: Option<f64>,
+    ) -> PyResult<()> {
+        if !callback.as_ref(py).is_callable() {
+            return Err(PyValueError::new_err("callback must be callable"));
+        }
+        let (before_context, after_context) = match C {
+            Some(c) => (c, c),
+            None => (B.unwrap_or(0), A.unwrap_or(0)),
+        };
+        let multiline = multiline.unwrap_or(false);
+        let matcher = Self::build_matcher(pattern, &MatcherOptions {
+            case_insensitive: i.unwrap_or(false),
+            case_smart: false,
+            unicode: true,
+            multiline,
+            line_regexp: false,
+            crlf: false,
+            engine: Engine::Default,
+            size_limit: None,
+            dfa_size_limit: None,
+            backtrack_limit: None,
+        })?;
+        let parsed_types = Self::parse_types(r#type)?;
+        let type_matcher = Self::build_type_filter(&parsed_types, &[], false)?;
+        let limits = Limits::new(timeout, None);
+        let read = ReadOptions { deadline: limits.deadline, ..ReadOptions::default() };
+        let source = self.build_source(path, &Globs::parse(glob, None)?, &WalkOptions::default(), None, false)?;
+
+        py.allow_threads(|| {
+            let mut report = SearchReport::new(ErrorPolicy::Ignore);
+            let mut searcher = SearcherBuilder::new()
+                .line_terminator(read.line_terminator)
+                .multi_line(multiline)
+                .before_context(before_context as usize)
+                .after_context(after_context as usize)
+                .build();
+            let mut raised = None;
+            walk_files(source, type_matcher.as_ref(), limits, &mut report, |path, report| {
+                let input = match open_input(path, &read) {
+                    Ok(input) => input,
+                    Err(err) => {
+                        report.read_error(path, err)?;
+                        return Ok(true);
+                    }
+                };
+                let path_str = report.path_string(path);
+                let mut sink = PySink { callback: &callback, path: &path_str, control: SinkControl::Continue, error: None };
+                let result = searcher.search_reader(&matcher, read.buffered(input), &mut sink);
+                if let Some(err) = sink.error {
+                    raised = Some(err);
+                    return Ok(false);
+                }
+                if let Err(err) = result {
+                    report.read_error(path, err)?;
+                }
+                Ok(sink.control != SinkControl::StopSearch)
+            })?;
+            Ok(raised)
+        })
+        .map_err(to_pyerr)?
+        .map_or(Ok(()), Err)
+    }
+
+    /// Search the files of a git revision straight from the object database, without a checkout
+    /// Returns "rev:path:line:content" strings in tree order; blobs that are not UTF-8 are skipped
+    #[pyo3(signature = (pattern, rev = "HEAD", repo = None, **options))]
+    fn search_rev(
+        &self,
+        py: Python,
+        pattern: &str,
+        rev: &str,
+        repo: Option<&str>,
+        options: Option<&PyDict>,
+    ) -> PyResult<Vec<String>> {
+        let options = self.merged_options(py, "search_rev", SEARCH_REV_OPTIONS, options)?;
+        let repo = Path::new(repo.unwrap_or("."));
+        let multiline = flag(options, "multiline")?.unwrap_or(false);
+        let matcher = Self::build_matcher(pattern, &MatcherOptions {
+            case_insensitive: flag(options, "i")?.unwrap_or(false),
+            case_smart: false,
+            unicode: true,
+            multiline,
+            line_regexp: false,
+            crlf: false,
+            engine: Engine::Default,
+            size_limit: None,
+            dfa_size_limit: None,
+            backtrack_limit: None,
+        })?;
+        let parsed_types = Self::parse_types(flag(options, "type")?)?;
+        let type_matcher = Self::build_type_filter(&parsed_types, &[], false)?;
+        let overrides = Self::build_overrides(repo, &Globs::parse(flag(options, "glob")?, None)?)?;
+        let limits = Limits::new(flag(options, "timeout")?, None);
+
+        // glob and type apply to paths within the tree, as they would to a checkout
+        let (paths, ids): (Vec<String>, Vec<String>) = git_tree_blobs(repo, rev)?
+            .into_iter()
+            .filter(|(path, _)| {
+                !overrides.as_ref().is_some_and(|o| o.matched(repo.join(path), false).is_ignore())
+                    && type_matcher.as_ref().is_none_or(|filter| filter.matches(Path::new(path)))
+            })
+            .unzip();
+
+        py.allow_threads(|| {
+            let mut results = Vec::new();
+            let mut searcher = SearcherBuilder::new().multi_line(multiline).build();
+            read_git_blobs(repo, ids, |index, contents| {
+                if timed_out(limits.deadline) {
+                    return Err(RGErr::Timeout);
+                }
+                let mut found = Vec::new();
+                let searched = searcher.search_slice(&matcher, contents, sinks::Bytes(|line_number, line| {
+                    let line = utf8_line(line)?.trim_end_matches(['\n', '\r']);
+                    found.push(format!("{}:{}:{}:{}", rev, paths[index], line_number, line));
+                    Ok(true)
+                }));
+                if searched.is_ok() {
+                    results.extend(found);
+                }
+                Ok(true)
+            })?;
+            Ok(results)
+        })
+        .map_err(to_pyerr)
+    }
+
+    /// Run the same pattern over two roots and report what changed between them
+    /// Paths are reported relative to each root so the two trees line up
+    #[pyo3(signature = (pattern, before, after, **options))]
+    fn compare(
+        &self,
+        py: Python,
+        pattern: &str,
+        before: &str,
+        after: &str,
+        options: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let options = self.merged_options(py, "compare", COMPARE_OPTIONS, options)?;
+        let parsed_types = Self::parse_types(flag(options, "type")?)?;
+        let type_matcher = Self::build_type_filter(&parsed_types, &[], false)?;
+        let multiline = flag(options, "multiline")?.unwrap_or(false);
+        let matcher = Self::build_matcher(pattern, &MatcherOptions {
+            case_insensitive: flag(options, "i")?.unwrap_or(false),
+            case_smart: false,
+            unicode: true,
+            multiline,
+            line_regexp: false,
+            crlf: false,
+            engine: Engine::Default,
+            size_limit: None,
+            dfa_size_limit: None,
+            backtrack_limit: None,
+        })?;
+        let limits = Limits::new(flag(options, "timeout")?, None);
+        let pool = WorkerPool::new(flag(options, "threads")?, None);
+        let read = ReadOptions { deadline: limits.deadline, ..ReadOptions::default() };
+        let globs = Globs::parse(flag(options, "glob")?, None)?;
+        let before_walker = FileSource::Walk(self.build_walker(before, &globs, &WalkOptions::default())?, SpecialFiles::Skip);
+        let after_walker = FileSource::Walk(self.build_walker(after, &globs, &WalkOptions::default())?, SpecialFiles::Skip);
+
+        let (before_results, after_results) = py.allow_threads(|| {
+            let mut report = SearchReport::new(ErrorPolicy::Ignore);
+            let before_results = self.search_content_inner(
+                &matcher, before_walker, type_matcher.as_ref(), &read, 0, 0, false, false, multiline, MatchLimits::default(), limits, pool, &mut report,
+            )?.0;
+            let after_results = self.search_content_inner(
+                &matcher, after_walker, type_matcher.as_ref(), &read, 0, 0, false, false, multiline, MatchLimits::default(), limits, pool, &mut report,
+            )?.0;
+            Ok((before_results, after_results))
+        }).map_err(to_pyerr)?;
+
+        // relative path -> [(line_number, content)]
+        let group = |root: &str, results: Vec<ContentResult>| {
+            let mut files: BTreeMap<String, Vec<(u64, String)>> = BTreeMap::new();
+            for r in results {
+                let rel = Path::new(&r.path)
+                    .strip_prefix(root)
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or(r.path);
+                files.entry(rel).or_default().push((r.line_number, r.content));
+            }
+            files
+        };
+        let before_files = group(before, before_results);
+        let after_files = group(after, after_results);
+
+        let deltas = PyDict::new(py);
+        let mut added: Vec<(String, u64, String)> = Vec::new();
+        let mut removed: Vec<(String, u64, String)> = Vec::new();
+        let empty = Vec::new();
+        let all_paths: BTreeSet<&String> = before_files.keys().chain(after_files.keys()).collect();
+
+        for path in all_paths {
+            let old = before_files.get(path).unwrap_or(&empty);
+            let new = after_files.get(path).unwrap_or(&empty);
+            if old.len() != new.len() {
+                deltas.set_item(path, (old.len(), new.len(), new.len() as i64 - old.len() as i64))?;
+            }
+
+            let (file_added, file_removed) = diff_matches(old, new);
+            added.extend(file_added.into_iter().map(|(line_number, content)| (path.clone(), line_number, content)));
+            removed.extend(file_removed.into_iter().map(|(line_number, content)| (path.clone(), line_number, content)));
+        }
+
+        let dict = PyDict::new(py);
+        dict.set_item("deltas", deltas)?;
+        dict.set_item("added", added)?;
+        dict.set_item("removed", removed)?;
+        Ok(dict.into_py(py))
+    }
+
+    /// Watch a tree and call `callback` with the matches each file change adds or removes
+    /// Returns at once; the tree's current matches are the baseline for later changes
+    #[pyo3(signature = (pattern, path, callback, **options))]
+    fn watch(
+        &self,
+        py: Python,
+        pattern: &str,
+        path: &str,
+        callback: PyObject,
+        options: Option<&PyDict>,
+    ) -> PyResult<Watcher> {
+        if !callback.as_ref(py).is_callable() {
+            return Err(PyValueError::new_err("callback must be callable"));
+        }
+        let options = self.merged_options(py, "watch", WATCH_OPTIONS, options)?;
+        #[cfg(feature = "watch")]
+        {
+            use notify::Watcher as _;
+
+            let parsed_types = Self::parse_types(flag(options, "type")?)?;
+            let multiline = flag(options, "multiline")?.unwrap_or(false);
+            let matcher = Self::build_matcher(pattern, &MatcherOptions {
+                case_insensitive: flag(options, "i")?.unwrap_or(false),
+                case_smart: false,
+                unicode: true,
+                multiline,
+                line_regexp: false,
+                crlf: false,
+                engine: Engine::Default,
+                size_limit: None,
+                dfa_size_limit: None,
+                backtrack_limit: None,
+            })?;
+            let root = std::fs::canonicalize(path).map_err(|_| path_not_found(path))?;
+            let (no_ignore, hidden, binary) = parse_ignore_level(flag(options, "ignore_level")?)?;
+            let cwd = std::env::current_dir()?;
+            let walk_options = WalkOptions {
+                ignore_filenames: flag::<Vec<String>>(options, "custom_ignore_filenames")?.unwrap_or_default(),
+                ignore_paths: flag::<Vec<String>>(options, "ignore_file_paths")?.unwrap_or_default().iter().map(|p| normalize_path(&cwd.join(p))).collect(),
+                no_ignore,
+                hidden: flag(options, "hidden")?.unwrap_or(hidden),
+                git_dir: flag(options, "search_git_dir")?,
+                no_parents: !flag(options, "ignore_parents")?.unwrap_or(true),
+                ..WalkOptions::default()
+            };
+            let read =
+                ReadOptions::decoding(false, flag(options, "encodings")?, false, false, binary, flag(options, "lossy")?, None, None)?;
+            let mut state = WatchState {
+                root: root.clone(),
+                display_root: PathBuf::from(path),
+                globs: Globs::parse(flag(options, "glob")?, None)?,
+                type_filter: Self::build_type_filter(&parsed_types, &[], false)?,
+                walk_options,
+                read,
+                multiline,
+                matcher,
+                known: HashSet::new(),
+                matches: HashMap::new(),
+            };
+            state.rewalk()?;
+            state.matches = py.allow_threads(|| {
+                state
+                    .known
+                    .iter()
+                    .map(|file| (file.clone(), state.search(file)))
+                    .filter(|(_, found)| !found.is_empty())
+                    .collect()
+            });
+
+            let (tx, events) = mpsc::channel();
+            let mut watcher = notify::recommended_watcher(move |event| {
+                let _ = tx.send(event);
+            })
+            .map_err(|e| PyValueError::new_err(format!("Failed to watch {}: {}", path, e)))?;
+            watcher
+                .watch(&root, notify::RecursiveMode::Recursive)
+                .map_err(|e| PyValueError::new_err(format!("Failed to watch {}: {}", path, e)))?;
+
+            let stop = Arc::new(AtomicBool::new(false));
+            let debounce = Duration::from_millis(flag(options, "debounce_ms")?.unwrap_or(100));
+            let thread = thread::spawn({
+                let stop = Arc::clone(&stop);
+                move || {
+                    // Dropping the notify watcher at the end unregisters the watches
+                    let _watcher = watcher;
+                    watch_loop(state, events, callback, debounce, &stop)
+                }
+            });
+            Ok(Watcher { stop, thread: Some(thread) })
+        }
+        #[cfg(not(feature = "watch"))]
+        {
+            let _ = (pattern, path, options);
+            Err(PyValueError::new_err("watch requires pyripgrep built with the 'watch' feature"))
+        }
+    }
+
+    /// Start an incremental search driven by `SearchSession.step()`
+    /// Nothing is searched until the first step, so single-threaded callers (e.g. GUI
+    /// event loops) can interleave bounded chunks of work with their own processing
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        pattern = None,
+        path = None,
+        glob = None,
+        output_mode = None,
+        B = None,
+        A = None,
+        C = None,
+        n = None,
+        i = None,
+        r#type = None,
+        multiline = None,
+        errors = None
+    ))]
+    fn start(
+        &self,
+        pattern: Option<&PyAny>,  // regex string or compiled Pattern
+        path: Option<&str>,
+        glob: Option<&PyAny>,
+        output_mode: Option<&str>,
+        B: Option<u64>,
+        A: Option<u64>,
+        C: Option<u64>,
+        n: Option<bool>,
+        i: Option<bool>,
+        r#type: Option<&PyAny>,
+        multiline: Option<bool>,
+        errors: Option<&str>,
+    ) -> PyResult<SearchSession> {
+        let output_mode = OutputMode::from_str(output_mode.unwrap_or("files_with_matches"))?;
+        if pattern.is_none() && output_mode != OutputMode::Files {
+            return Err(PyValueError::new_err("Pattern is required for all output modes except 'files'"));
+        }
+        let (before_context, after_context) = match C {
+            Some(c) => (c, c),
+            None => (B.unwrap_or(0), A.unwrap_or(0)),
+        };
+        let parsed_types = Self::parse_types(r#type)?;
+
+        let compiled: Option<Pattern> = pattern.and_then(|p| p.extract::<PyRef<Pattern>>().ok().map(|p| p.clone()));
+        let multiline = compiled.as_ref().map_or(multiline.unwrap_or(false), |p| p.multiline);
+        let matcher = match (&compiled, pattern) {
+            (Some(compiled), _) => Some(compiled.matcher.clone()),
+            (None, Some(pattern)) => {
+                let pattern: &str = pattern
+                    .extract()
+                    .map_err(|_| PyValueError::new_err("Pattern must be a string or a compiled Pattern"))?;
+                let options = MatcherOptions {
+                    case_insensitive: i.unwrap_or(false),
+                    case_smart: false,
+                    unicode: true,
+                    multiline,
+                    line_regexp: false,
+                    crlf: false,
+                    engine: Engine::Default,
+                    size_limit: None,
+                    dfa_size_limit: None,
+                    backtrack_limit: None,
+                };
+                Some(Self::build_matcher(pattern, &options)?)
+            }
+            (None, None) => None,
+        };
+        let type_filter = if parsed_types.is_empty() {
+            compiled.and_then(|p| p.type_filter)
+        } else {
+            Self::build_type_filter(&parsed_types, &[], false)?
+        };
+
+        let read = ReadOptions::default();
+        Ok(SearchSession {
+            entries: self.build_source(path, &Globs::parse(glob, None)?, &WalkOptions::default(), None, true)?.into_iter(),
+            matcher,
+            type_filter,
+            output_mode,
+            before_context,
+            after_context,
+            line_numbers: n.unwrap_or(false),
+            multiline,
+            searcher: read.searcher(),
+            read,
+            report: SearchReport::new(ErrorPolicy::from_str(errors.unwrap_or("ignore"))?),
+            elapsed: Duration::ZERO,
+            done: false,
+        })
+    }
+
+    /// Compile a pattern once for reuse across many searches
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (pattern, i = None, multiline = None, r#type = None, type_ignore_case = None, engine = None, x = None, case_smart = None, unicode = None))]
+    fn compile(
+        &self,
+        pattern: &str,
+        i: Option<bool>,
+        multiline: Option<bool>,
+        r#type: Option<&PyAny>,
+        type_ignore_case: Option<bool>,
+        engine: Option<&str>,
+        x: Option<bool>,
+        case_smart: Option<bool>,
+        unicode: Option<bool>,
+    ) -> PyResult<Pattern> {
+        Pattern::new(pattern, i, multiline, r#type, type_ignore_case, engine, x, case_smart, unicode)
+    }
+
+    /// Compile a pattern with the same settings as search and report any error
+    /// Takes search()'s regex flags (PATTERN_FLAGS), on top of this Grep's defaults for them
+    /// Returns a dict with `valid`, `error`, `position`, `span` and `suggestion` keys
+    #[pyo3(signature = (pattern, **flags))]
+    fn validate_pattern(&self, py: Python, pattern: &str, flags: Option<&PyDict>) -> PyResult<PyObject> {
+        let merged = self.merged_options(py, "validate_pattern", PATTERN_FLAGS, flags)?;
+        let options = Self::pattern_options(
+            flag(merged, "i")?,
+            flag(merged, "case_smart")?,
+            flag(merged, "unicode")?,
+            flag(merged, "multiline")?,
+            flag(merged, "x")?,
+            flag(merged, "crlf")?,
+            flag(merged, "null_data")?.unwrap_or(false),
+            flag(merged, "engine")?,
+            flag(merged, "regex_size_limit")?,
+            flag(merged, "dfa_size_limit")?,
+            flag(merged, "backtrack_limit")?,
+            flag(merged, "strict_options")?.unwrap_or(true),
+            &mut Vec::new(),
+        )?;
+        Ok(Self::pattern_diagnostics(py, pattern, &options)?.into_py(py))
+    }
+
+    /// Return a window of lines around `line` (1-based) in a single file
+    /// Takes search()'s decoding options (PREVIEW_OPTIONS), on top of this Grep's defaults for
+    /// them, and reads the file only as far as the window. A file search would treat as
+    /// binary raises an IOErrorInfo
+    #[pyo3(signature = (path, line, before = 5, after = 5, **options))]
+    fn preview(
+        &self,
+        py: Python,
+        path: &str,
+        line: u64,
+        before: u64,
+        after: u64,
+        options: Option<&PyDict>,
+    ) -> PyResult<Vec<(u64, String)>> {
+        if line == 0 {
+            return Err(PyValueError::new_err("Line numbers start at 1"));
+        }
+
+        let path_buf = PathBuf::from(path);
+        if !path_buf.is_file() {
+            return Err(PyValueError::new_err(format!("File not found: {}", path)));
+        }
+
+        let merged = self.merged_options(py, "preview", PREVIEW_OPTIONS, options)?;
+        let search_zip = flag(merged, "search_zip")?.unwrap_or(false);
+        if search_zip && !cfg!(feature = "decompression") {
+            return Err(PyValueError::new_err("search_zip requires pyripgrep built with the 'decompression' feature"));
+        }
+        let null_data = flag(merged, "null_data")?.unwrap_or(false);
+        let (_, _, binary) = parse_ignore_level(flag(merged, "ignore_level")?)?;
+        let read = ReadOptions::decoding(
+            search_zip,
+            flag(merged, "encodings")?,
+            flag(merged, "crlf")?.unwrap_or(false) && !null_data,
+            null_data,
+            binary,
+            flag(merged, "lossy")?,
+            flag(merged, "buffer_size")?,
+            flag(merged, "heap_limit")?,
+        )?;
+
+        let start = line.saturating_sub(before).max(1);
+        let end = line.saturating_add(after);
+        let lines = py.allow_threads(|| -> io::Result<Vec<(u64, String)>> {
+            let lines = read_lines_with_offsets_upto(open_input(&path_buf, &read)?, &read, end)?;
+            // As rg does, a NUL byte in what was read marks the file binary (unless ignore_level=3)
+            if !read.binary && !null_data && lines.iter().any(|(_, line)| line.contains('\0')) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "binary file: contains NUL bytes"));
+            }
+            Ok(lines)
+        });
+        let lines = lines.map_err(|err| to_pyerr(RGErr::File(path.to_string(), err.to_string())))?;
+        Ok((start..=end.min(lines.len() as u64))
+            .map(|ln| (ln, shown_line(&lines[(ln - 1) as usize].1).to_string()))
+            .collect())
+    }
+
+    /// Explain whether a search under `root` would reach `path`, and which rule decided
+    /// The walk search() would run decides; replaying its filters names the deciding rule
+    #[pyo3(signature = (path, root = None, **options))]
+    fn explain(&self, py: Python, path: &str, root: Option<&str>, options: Option<&PyDict>) -> PyResult<Explanation> {
+        let options = self.merged_options(py, "explain", EXPLAIN_OPTIONS, options)?;
+        if !Path::new(path).exists() {
+            return Err(path_not_found(path));
+        }
+        let display_root = root.unwrap_or(".");
+        let cwd = std::env::current_dir()
+            .map_err(|e| PyValueError::new_err(format!("Cannot determine the working directory: {e}")))?;
+        let abs_root = normalize_path(&cwd.join(display_root));
+        let abs_path = normalize_path(&cwd.join(path));
+        if !abs_path.starts_with(&abs_root) {
+            return Err(PyValueError::new_err(format!("{} is not under root {}", path, display_root)));
+        }
+
+        let globs = Globs::parse(flag(options, "glob")?, flag(options, "iglob")?)?;
+        let overrides = Self::build_overrides(&abs_root, &globs)?;
+        let parsed_types = Self::parse_types(flag(options, "type")?)?;
+        let type_filter = Self::build_type_filter(
+            &parsed_types,
+            &flag::<Vec<String>>(options, "type_add")?.unwrap_or_default(),
+            flag(options, "type_ignore_case")?.unwrap_or(false),
+        )?;
+        // Binary files are never excluded by the walk, so the third level changes nothing here
+        let (no_ignore, hidden, _) = parse_ignore_level(flag(options, "ignore_level")?)?;
+        // The same walk settings search() builds from these options
+        let walk_options = WalkOptions {
+            ignore_filenames: flag::<Vec<String>>(options, "custom_ignore_filenames")?.unwrap_or_default(),
+            ignore_paths: flag::<Vec<String>>(options, "ignore_file_paths")?.unwrap_or_default().iter().map(|p| normalize_path(&cwd.join(p))).collect(),
+            same_file_system: flag(options, "one_file_system")?.unwrap_or(false),
+            no_ignore,
+            hidden: flag(options, "hidden")?.unwrap_or(hidden),
+            git_dir: flag(options, "search_git_dir")?,
+            no_parents: !flag(options, "ignore_parents")?.unwrap_or(true),
+            ..WalkOptions::default()
+        };
+        // The walker search() would run, cut down to the entries on the way to the path
+        let reached: HashSet<PathBuf> = self
+            .build_walker_along(&abs_root.to_string_lossy(), &globs, &walk_options, Some(abs_path.clone()))?
+            .filter_map(Result::ok)
+            .map(ignore::DirEntry::into_path)
+            .collect();
+        let (verdict, matched) = explain_path(
+            &abs_root,
+            &abs_path,
+            &reached,
+            globs.describe().as_deref(),
+            overrides.as_ref(),
+            &walk_options,
+            type_filter.as_ref(),
+        );
+
+        // Show the deciding entry the way the walk would have spelled it
+        let relative = matched.strip_prefix(&abs_root).unwrap_or(&matched);
+        let matched_path = Path::new(display_root).join(relative).to_string_lossy().to_string();
+        let Some(verdict) = verdict else {
+            return Ok(Explanation {
+                path: path.to_string(),
+                included: false,
+                cause: None,
+                reason: "skipped: left out by the directory walk".to_string(),
+                rule: None,
+                source: None,
+                matched_path: Some(matched_path),
+            });
+        };
+        let reason = match (verdict.cause, &verdict.rule) {
+            (Some(cause), _) => cause.reason().to_string(),
+            (None, Some(rule)) => format!("searched: included by {rule}"),
+            (None, None) => "searched".to_string(),
+        };
+        Ok(Explanation {
+            path: path.to_string(),
+            included: verdict.cause.is_none(),
+            cause: verdict.cause.map(|cause| cause.name().to_string()),
+            reason,
+            matched_path: (verdict.cause.is_some() || verdict.rule.is_some()).then_some(matched_path),
+            rule: verdict.rule,
+            source: verdict.source.map(|source| source.to_string_lossy().to_string()),
+        })
+    }
+}
+
+impl Grep {
+    /// A point in time from a datetime (anything with .timestamp()), a timedelta
+    /// (that long before now) or a number of seconds since the Unix epoch
+    fn parse_time(value: &PyAny, name: &str) -> PyResult<SystemTime> {
+        let invalid = || PyValueError::new_err(format!("{name} must be a datetime, a timedelta or a Unix timestamp"));
+        if value.hasattr("timestamp")? {
+            let seconds: f64 = value.call_method0("timestamp")?.extract()?;
+            return Self::epoch_offset(seconds).ok_or_else(invalid);
+        }
+        if value.hasattr("total_seconds")? {
+            let ago: f64 = value.call_method0("total_seconds")?.extract()?;
+            let ago = Duration::try_from_secs_f64(ago.abs()).map_err(|_| invalid())?;
+            return SystemTime::now().checked_sub(ago).ok_or_else(invalid);
+        }
+        if value.is_instance_of::<PyBool>() {
+            return Err(invalid());
+        }
+        let seconds: f64 = value.extract().map_err(|_| invalid())?;
+        Self::epoch_offset(seconds).ok_or_else(invalid)
+    }
+
+    fn epoch_offset(seconds: f64) -> Option<SystemTime> {
+        let offset = Duration::try_from_secs_f64(seconds.abs()).ok()?;
+        if seconds >= 0.0 {
+            SystemTime::UNIX_EPOCH.checked_add(offset)
+        } else {
+            SystemTime::UNIX_EPOCH.checked_sub(offset)
+        }
+    }
+
+    /// Create mapping from custom type names to official ripgrep type names
+    /// Only includes custom aliases - official type names are passed through directly
+    fn create_type_mapping() -> HashMap<&'static str, &'static str> {
+        let mut map = HashMap::new();
+
+        // Map custom/alternative names to official ripgrep type names
+        map.insert("python", "py");
+        map.insert("javascript", "js");
+        map.insert("typescript", "ts");
+        map.insert("markdown", "md");
+        map.insert("c++", "cpp");
+        map.insert("ruby", "rb");
+        map.insert("rs", "rust");  // rs -> rust for ripgrep
+        map.insert("yml", "yaml");  // yml -> yaml for ripgrep
+        map.insert("golang", "go");
+        map.insert("c#", "csharp");
+        map.insert("f#", "fsharp");
+        map.insert("objective-c", "objc");
+        map.insert("shell", "sh");
+        map.insert("bash", "sh");
+        map.insert("dockerfile", "docker");
+        map.insert("text", "txt");
+
+        map
+    }
+    
+    /// Parse type parameter from Python (string or list) into official ripgrep type names
+    /// If a type is in the custom mapping (compared case-insensitively), use the mapped value;
+    /// otherwise pass through directly to ripgrep's full type database (checked in build_type_filter)
+    fn parse_types(type_param: Option<&PyAny>) -> PyResult<Vec<String>> {
+        let type_mapping = Self::create_type_mapping();
+        let resolve = |type_str: &str| match type_mapping.get(type_str.to_lowercase().as_str()) {
+            Some(type_name) => type_name.to_string(),
+            None => type_str.to_string(),
+        };
+        let mut result_types = Vec::new();
+
+        if let Some(param) = type_param {
+            if let Ok(type_str) = param.extract::<&str>() {
+                // Single string type - use mapping if available, otherwise pass through
+                result_types.push(resolve(type_str));
+            } else if let Ok(type_list) = param.extract::<Vec<&str>>() {
+                // List of types - use mapping if available, otherwise pass through
+                for type_str in type_list {
+                    result_types.push(resolve(type_str));
+                }
+            } else {
+                return Err(PyValueError::new_err("Type parameter must be a string or list of strings"));
+            }
+        }
+
+        Ok(result_types)
+    }
+
+    /// Build regex matcher based on options
+    fn build_matcher(pattern: &str, options: &MatcherOptions) -> PyResult<SearchMatcher> {
+        let engine = options.engine;
+        if engine != Engine::Fancy {
+            let default = Self::matcher_builder(options).build(pattern);
+            match default {
+                Ok(matcher) => return Ok(SearchMatcher::Default(matcher, Prefilter::new(pattern, options))),
+                Err(e) if engine != Engine::Auto || !cfg!(feature = "fancy-regex") => {
+                    let mut message = format!("Invalid pattern: {}", e);
+                    if engine == Engine::Default && Self::needs_fancy_engine(pattern) {
+                        message.push_str(" (look-around and backreferences need engine=\"fancy\")");
+                    }
+                    return Err(pattern_error(pattern, message, e.to_string()));
+                }
+                Err(_) => {} // Auto: retry with the backtracking engine
+            }
+        }
+        #[cfg(feature = "fancy-regex")]
+        {
+            FancyMatcher::new(pattern, options).map(SearchMatcher::Fancy)
+        }
+        #[cfg(not(feature = "fancy-regex"))]
+        unreachable!("Engine::from_str rejects fancy without the fancy-regex feature")
+    }
+
+    /// Parameter names of _search in order, as Python sees them
+    fn search_parameters(py: Python<'_>) -> PyResult<&Vec<String>> {
+        SEARCH_PARAMETERS.get_or_try_init(py, || {
+            let method = py.get_type::<Grep>().getattr("_search")?;
+            let parameters = py.import("inspect")?.call_method1("signature", (method,))?.getattr("parameters")?;
+            parameters.iter()?.map(|name| name?.extract::<String>()).filter(|name| !matches!(name.as_deref(), Ok("self"))).collect()
+        })
+    }
+
+    /// Compile `pattern` with `options` and describe the outcome as validate_pattern() reports it:
+    /// `valid`, `error`, `position`, `span` and `suggestion`
+    fn pattern_diagnostics<'py>(py: Python<'py>, pattern: &str, options: &MatcherOptions) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        let Err(err) = Self::build_matcher(pattern, options) else {
+            dict.set_item("valid", true)?;
+            for key in ["error", "position", "span", "suggestion"] {
+                dict.set_item(key, py.None())?;
+            }
+            return Ok(dict);
+        };
+        dict.set_item("valid", false)?;
+        // The parser diagnosis only applies when look-around is what the default engine rejected
+        let look_around = Self::needs_fancy_engine(pattern);
+        match Self::diagnose_pattern(pattern).filter(|_| options.engine == Engine::Default || !look_around) {
+            Some((message, span, suggestion)) => {
+                dict.set_item("error", message)?;
+                dict.set_item("position", span.0)?;
+                dict.set_item("span", span)?;
+                dict.set_item("suggestion", suggestion)?;
+            }
+            // Rejected by the matcher builder rather than the parser (e.g. size limits)
+            None => {
+                let position = err.value(py).getattr("offset")?;
+                dict.set_item("error", err.value(py).to_string())?;
+                dict.set_item("position", position)?;
+                dict.set_item("span", py.None())?;
+                dict.set_item("suggestion", py.None())?;
+            }
+        }
+        Ok(dict)
+    }
+
+    /// This Grep's defaults for `allowed` search() options, overridden by `kwargs`
+    /// Any other keyword is a TypeError naming `method`
+    fn merged_options<'py>(
+        &self,
+        py: Python<'py>,
+        method: &str,
+        allowed: &[&str],
+        kwargs: Option<&'py PyDict>,
+    ) -> PyResult<&'py PyDict> {
+        let merged = PyDict::new(py);
+        if let Some(defaults) = &self.defaults {
+            for (key, value) in defaults.as_ref(py) {
+                if allowed.contains(&key.extract::<&str>()?) {
+                    merged.set_item(key, value)?;
+                }
+            }
+        }
+        for (key, value) in kwargs.into_iter().flatten() {
+            let name: &str = key.extract()?;
+            let name = if name == "case_insensitive" { "i" } else { name };
+            if !allowed.contains(&name) {
+                return Err(PyTypeError::new_err(format!("{}() got an unexpected keyword argument '{}'", method, name)));
+            }
+            merged.set_item(name, value)?;
+        }
+        Ok(merged)
+    }
+
+    /// Matcher settings for a pattern string from search()'s regex flags
+    /// search and validate_pattern both resolve their flags here, so they accept the same patterns
+    #[allow(clippy::too_many_arguments)]
+    fn pattern_options(
+        i: Option<bool>,
+        case_smart: Option<bool>,
+        unicode: Option<bool>,
+        multiline: Option<bool>,
+        x: Option<bool>,
+        crlf: Option<bool>,
+        null_data: bool,
+        engine: Option<&str>,
+        size_limit: Option<usize>,
+        dfa_size_limit: Option<usize>,
+        backtrack_limit: Option<usize>,
+        strict_options: bool,
+        warnings: &mut Vec<String>,
+    ) -> PyResult<MatcherOptions> {
+        // Options needing a feature this build lacks raise, or fall back with a warning
+        let mut engine = Engine::parse(engine.unwrap_or("default"))?;
+        if let Some(message) = engine.missing_feature() {
+            unsupported_option(strict_options, message, "using the default engine", warnings)?;
+            engine = Engine::Default;
+        }
+        Ok(MatcherOptions {
+            case_insensitive: i.unwrap_or(false),
+            case_smart: case_smart.unwrap_or(false),
+            unicode: unicode.unwrap_or(true),
+            multiline: multiline.unwrap_or(false),
+            line_regexp: x.unwrap_or(false),
+            // As in rg, NUL-terminated records take precedence over CRLF lines
+            crlf: crlf.unwrap_or(false) && !null_data,
+            engine,
+            size_limit,
+            dfa_size_limit,
+            backtrack_limit,
+        })
+    }
+
+    /// Whether a pattern failed only because it uses look-around or backreferences
+    fn needs_fancy_engine(pattern: &str) -> bool {
+        use regex_syntax::ast::ErrorKind;
+        match regex_syntax::ast::parse::Parser::new().parse(pattern) {
+            Err(err) => matches!(
+                err.kind(),
+                ErrorKind::UnsupportedLookAround | ErrorKind::UnsupportedBackreference
+            ),
+            Ok(_) => false,
+        }
+    }
+
+    /// Regex matcher builder configured exactly as search configures it
+    fn matcher_builder(options: &MatcherOptions) -> RegexMatcherBuilder {
+        let mut builder = RegexMatcherBuilder::new();
+
+        if options.case_insensitive {
+            builder.case_insensitive(true);
+        }
+
+        if options.multiline {
+            builder.multi_line(true).dot_matches_new_line(true);
+        }
+
+        if options.line_regexp {
+            builder.whole_line(true);
+        }
+
+        if options.crlf {
+            builder.crlf(true);
+        }
+
+        if options.case_smart {
+            builder.case_smart(true);
+        }
+
+        if !options.unicode {
+            builder.unicode(false);
+        }
+
+        if let Some(limit) = options.size_limit {
+            builder.size_limit(limit);
+        }
+
+        if let Some(limit) = options.dfa_size_limit {
+            builder.dfa_size_limit(limit);
+        }
+
+        builder
+    }
+
+    /// Locate a pattern error and suggest a fix: (message, (start, end) byte span, suggestion)
+    fn diagnose_pattern(pattern: &str) -> Option<(String, (usize, usize), Option<&'static str>)> {
+        use regex_syntax::ast::ErrorKind;
+
+        let (message, span, kind) = match regex_syntax::Parser::new().parse(pattern) {
+            Ok(_) => return None,
+            Err(regex_syntax::Error::Parse(err)) => (err.kind().to_string(), *err.span(), Some(err.kind().clone())),
+            Err(regex_syntax::Error::Translate(err)) => (err.kind().to_string(), *err.span(), None),
+            Err(err) => (err.to_string(), regex_syntax::ast::Span::splat(regex_syntax::ast::Position::new(0, 1, 1)), None),
+        };
+
+        let suggestion = match kind {
+            Some(ErrorKind::UnsupportedLookAround) => Some("look-around is not supported by the default regex engine"),
+            Some(ErrorKind::UnsupportedBackreference) => Some("backreferences are not supported by the default regex engine"),
+            Some(ErrorKind::GroupUnclosed) => Some("add a closing ')' or escape the '(' as \\("),
+            Some(ErrorKind::GroupUnopened) => Some("remove the ')' or escape it as \\)"),
+            Some(ErrorKind::ClassUnclosed) => Some("add a closing ']' or escape the '[' as \\["),
+            Some(ErrorKind::RepetitionMissing) => Some("escape the repetition operator (e.g. \\* or \\+) to match it literally"),
+            Some(ErrorKind::RepetitionCountUnclosed) => Some("add a closing '}' or escape the '{' as \\{"),
+            Some(ErrorKind::EscapeUnrecognized) => Some("remove the backslash or use a supported escape sequence"),
+            Some(ErrorKind::EscapeUnexpectedEof) => Some("remove the trailing backslash or escape it as \\\\"),
+            _ => None,
+        };
+
+        Some((message, (span.start.offset, span.end.offset), suggestion))
+    }
+
+    /// Search for content with context (GIL-free inner implementation)
+    #[allow(clippy::too_many_arguments)]
+    fn search_content_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        before_context: u64,
+        after_context: u64,
+        passthru: bool,
+        block_context: bool,
+        multiline: bool,
+        match_limits: MatchLimits,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<(Vec<ContentResult>, bool), RGErr> {
+        let mut results = Vec::new();
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |_searcher, path, report| {
+                let mut found = Vec::new();
+                self.search_file_content_inner(matcher, path, read, before_context, after_context, passthru, block_context, multiline, &mut found, report)?;
+                Ok(found)
+            },
+            |mut found: Vec<ContentResult>| {
+                let Some(keep) = budget.admit(found.len()) else {
+                    return false;
+                };
+                if keep < found.len() {
+                    found.truncate(keep);
+                    // Like rg -m, the last kept match still shows all of its after context
+                    if let Some(last) = found.last_mut() {
+                        last.shown_after = last.after_context.len();
+                    }
+                }
+                results.extend(found);
+                true
+            },
+        )?;
+
+        report.stats.suppress(budget.suppressed);
+        Ok((results, budget.truncated()))
+    }
+
+    /// Whether any file matches, stopping the walk at the first match (GIL-free inner implementation)
+    #[allow(clippy::too_many_arguments)]
+    fn search_exists_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<bool, RGErr> {
+        let mut found = false;
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |searcher, path, report| self.file_has_match_inner_with_searcher(searcher, matcher, path, read, report),
+            |has_match| {
+                found = has_match;
+                !has_match
+            },
+        )?;
+
+        Ok(found)
+    }
+
+    /// Search for files containing matches (GIL-free inner implementation)
+    #[allow(clippy::too_many_arguments)]
+    fn search_files_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        match_limits: MatchLimits,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<(Vec<String>, bool), RGErr> {
+        let mut files = Vec::new();
+        let mut seen = HashSet::new();
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |searcher, path, report| {
+                let found = self.file_has_match_inner_with_searcher(searcher, matcher, path, read, report)?;
+                Ok(found.then(|| report.path_string(path)))
+            },
+            |found| {
+                let Some(path) = found else {
+                    return true;
+                };
+                if head_limit.is_some_and(|limit| files.len() >= limit) {
+                    budget.suppressed.files += 1;
+                    budget.suppressed.matches += 1;
+                    return false;
+                }
+                // The search stops at a file's first match, so each file counts as one
+                if budget.admit(1).is_none() {
+                    return false;
+                }
+                if seen.insert(path.clone()) {
+                    files.push(path);
+                }
+                true
+            },
+        )?;
+
+        report.stats.suppress(budget.suppressed);
+        Ok((files, budget.truncated()))
+    }
+
+    /// Search for each matching file's first match (GIL-free inner implementation)
+    #[allow(clippy::too_many_arguments)]
+    fn search_first_matches_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        match_limits: MatchLimits,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<Vec<MatchRecord>, RGErr> {
+        let mut records = Vec::new();
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |searcher, path, report| self.first_match_inner_with_searcher(searcher, matcher, path, read, report),
+            |found: Option<MatchRecord>| {
+                let Some(record) = found else {
+                    return true;
+                };
+                if head_limit.is_some_and(|limit| records.len() >= limit) {
+                    budget.suppressed.files += 1;
+                    budget.suppressed.matches += 1;
+                    return false;
+                }
+                // As in files_with_matches, each file counts as one match
+                if budget.admit(1).is_none() {
+                    return false;
+                }
+                records.push(record);
+                true
+            },
+        )?;
+
+        report.stats.suppress(budget.suppressed);
+        Ok(records)
+    }
+
+    /// Search for files without any match (GIL-free inner implementation) - like rg --files-without-match
+    #[allow(clippy::too_many_arguments)]
+    fn search_files_without_match_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<(Vec<String>, bool), RGErr> {
+        let mut files = Vec::new();
+        let mut seen = HashSet::new();
+        let mut suppressed = Suppressed::default();
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |searcher, path, report| {
+                let found = self.probe_file_inner_with_searcher(searcher, matcher, path, read, report)?;
+                Ok((found == Some(false)).then(|| report.path_string(path)))
+            },
+            |missing| {
+                let Some(path) = missing else {
+                    return true;
+                };
+                if head_limit.is_some_and(|limit| files.len() >= limit) {
+                    suppressed.files += 1;
+                    return false;
+                }
+                if seen.insert(path.clone()) {
+                    files.push(path);
+                }
+                true
+            },
+        )?;
+
+        report.stats.suppress(suppressed);
+        Ok((files, suppressed.files > 0))
+    }
+
+    /// List files that would be searched (no pattern matching) - like rg --files
+    fn search_files_no_match_inner(
+        &self,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        limits: Limits,
+        report: &mut SearchReport,
+    ) -> Result<(Vec<String>, bool), RGErr> {
+        let mut files = Vec::new();
+        let mut truncated = false;
+        let mut searcher = read.searcher();
+
+        walk_files(source, type_matcher, limits, report, |path, report| {
+            if !read.admits(&mut searcher, path, report) {
+                return Ok(true);
+            }
+            if head_limit.is_some_and(|limit| files.len() >= limit) {
+                truncated = true;
+                return Ok(false);
+            }
+
+            files.push(report.path_string(path));
+            Ok(true)
+        })?;
+
+        if truncated {
+            report.stats.suppress(Suppressed { files: 1, matches: 0 });
+        }
+        Ok((files, truncated))
+    }
+
+    /// Match the pattern against the path of every file that would be searched
+    #[allow(clippy::too_many_arguments)]
+    fn search_paths_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        limits: Limits,
+        report: &mut SearchReport,
+    ) -> Result<Vec<PathMatch>, RGErr> {
+        let mut matches = Vec::new();
+        let mut searcher = read.searcher();
+
+        walk_files(source, type_matcher, limits, report, |path, report| {
+            let Some(found) = PathMatch::find(matcher, report.path_string(path)) else {
+                return Ok(true);
+            };
+            if !read.admits(&mut searcher, path, report) {
+                return Ok(true);
+            }
+            if head_limit.is_some_and(|limit| matches.len() >= limit) {
+                report.stats.suppress(Suppressed { files: 1, matches: 0 });
+                return Ok(false);
+            }
+            matches.push(found);
+            Ok(true)
+        })?;
+
+        Ok(matches)
+    }
+
+    /// Search and count matches per file (GIL-free inner implementation)
+    #[allow(clippy::too_many_arguments)]
+    fn search_count_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        match_limits: MatchLimits,
+        include_zero: bool,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<Vec<CountResult>, RGErr> {
+        let mut counts = Vec::new();
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |searcher, path, report| {
+                // None means the file could not be read, which include_zero does not list
+                let count = self.count_matches_in_file_inner_with_searcher(searcher, matcher, path, read, report)?;
+                Ok(count.filter(|&count| count > 0 || include_zero).map(|count| CountResult {
+                    path: report.path_string(path),
+                    count,
+                }))
+            },
+            |found: Option<CountResult>| {
+                let Some(mut found) = found else {
+                    return true;
+                };
+                if head_limit.is_some_and(|limit| counts.len() >= limit) {
+                    budget.suppressed.files += 1;
+                    budget.suppressed.matches += found.count;
+                    return false;
+                }
+                let Some(keep) = budget.admit(found.count as usize) else {
+                    return false;
+                };
+                found.count = keep as u64;
+                counts.push(found);
+                true
+            },
+        )?;
+
+        report.stats.suppress(budget.suppressed);
+        Ok(counts)
+    }
+
+    /// Files with matches plus their first matching line, match count, size and mtime
+    /// Each matching file is read to the end, unlike plain files_with_matches
+    #[allow(clippy::too_many_arguments)]
+    fn search_summaries_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        match_limits: MatchLimits,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<Vec<FileSummary>, RGErr> {
+        let mut summaries = Vec::new();
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |searcher, path, report| self.summarize_file_inner_with_searcher(searcher, matcher, path, read, report),
+            |found: Option<FileSummary>| {
+                let Some(mut found) = found else {
+                    return true;
+                };
+                if head_limit.is_some_and(|limit| summaries.len() >= limit) {
+                    budget.suppressed.files += 1;
+                    budget.suppressed.matches += found.match_count;
+                    return false;
+                }
+                let Some(keep) = budget.admit(found.match_count as usize) else {
+                    return false;
+                };
+                found.match_count = keep as u64;
+                summaries.push(found);
+                true
+            },
+        )?;
+
+        report.stats.suppress(budget.suppressed);
+        Ok(summaries)
+    }
+
+    /// Search producing one `path:line:column:content` entry per match, like rg --vimgrep
+    #[allow(clippy::too_many_arguments)]
+    fn search_vimgrep_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        columns: ColumnUnit,
+        head_limit: Option<usize>,
+        match_limits: MatchLimits,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<(Vec<String>, bool), RGErr> {
+        let mut lines = Vec::new();
+        let limit = head_limit.unwrap_or(usize::MAX);
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |_searcher, path, report| {
+                let mut found = Vec::new();
+                self.search_file_vimgrep_inner(matcher, path, read, columns, &mut found, report)?;
+                Ok(found)
+            },
+            |mut found: Vec<(u64, String)>| {
+                let Some(keep) = budget.admit(distinct_lines(&found, |(line, _)| *line)) else {
+                    return false;
+                };
+                keep_lines(&mut found, keep, |(line, _)| *line);
+                lines.extend(found.into_iter().map(|(_, entry)| entry));
+                lines.len() <= limit
+            },
+        )?;
+
+        let cut = lines.len().saturating_sub(limit);
+        budget.suppressed.matches += cut as u64;
+        report.stats.suppress(budget.suppressed);
+        let truncated = budget.truncated();
+        lines.truncate(limit);
+        Ok((lines, truncated))
+    }
+
+    /// Search producing one record per matched line with its absolute byte range
+    #[allow(clippy::too_many_arguments)]
+    fn search_matches_inner(
+        &self,
+        matcher: &SearchMatcher,
+        source: FileSource,
+        type_matcher: Option<&TypeFilter>,
+        read: &ReadOptions,
+        head_limit: Option<usize>,
+        match_limits: MatchLimits,
+        limits: Limits,
+        pool: WorkerPool,
+        report: &mut SearchReport,
+    ) -> Result<Vec<MatchRecord>, RGErr> {
+        let mut records = Vec::new();
+        let limit = head_limit.unwrap_or(usize::MAX);
+        let mut budget = MatchBudget::new(match_limits);
+
+        search_files(
+            source,
+            type_matcher,
+            limits,
+            pool,
+            read,
+            report,
+            |_searcher, path, report| {
+                let mut found = Vec::new();
+                self.search_file_matches_inner(matcher, path, read, &mut found, report)?;
+                Ok(found)
+            },
+            |mut found: Vec<MatchRecord>| {
+                let Some(keep) = budget.admit(found.len()) else {
+                    return false;
+                };
+                found.truncate(keep);
+                records.extend(found);
+                records.len() < limit
+            },
+        )?;
+
+        budget.suppressed.matches += records.len().saturating_sub(limit) as u64;
+        report.stats.suppress(budget.suppressed);
+        records.truncate(limit);
+        Ok(records)
+    }
+
+    /// Build directory walker with filtering options
+    fn build_walker(
+        &self,
+        path: &str,
+        globs: &Globs,
+        walk_options: &WalkOptions,
+    ) -> PyResult<ignore::Walk> {
+        self.build_walker_along(path, globs, walk_options, None)
+    }
+
+    /// build_walker, additionally pruning every entry that is not `along` or one of its
+    /// ancestors, so explain() can ask the walk itself whether it reaches a path
+    fn build_walker_along(
+        &self,
+        path: &str,
+        globs: &Globs,
+        walk_options: &WalkOptions,
+        along: Option<PathBuf>,
+    ) -> PyResult<ignore::Walk> {
+        let path_buf = PathBuf::from(path);
+        if !path_buf.exists() {
+            return Err(path_not_found(path));
+        }
+
+        // ignore_level lifts the standard filters one group at a time, like rg -u/-uu
+        let respect_ignore = !walk_options.no_ignore;
+        // With root=, the ignore files from there down replace the walker's own lookup above
+        // the search path, and .gitignore applies without a .git directory
+        let anchored = match &walk_options.ignore_root {
+            Some(root) if respect_ignore => Some(AnchoredIgnores::new(root, &path_buf, &walk_options.ignore_filenames)?),
+            _ => None,
+        };
+        let mut builder = WalkBuilder::new(&path_buf);
+        builder
+            .hidden(!walk_options.hidden)
+            .git_ignore(respect_ignore)
+            .git_global(respect_ignore)
+            .git_exclude(respect_ignore)
+            .require_git(walk_options.ignore_root.is_none())
+            .follow_links(false)
+            .parents(respect_ignore && !walk_options.no_parents && walk_options.ignore_root.is_none())
+            .ignore(respect_ignore)
+            .same_file_system(walk_options.same_file_system);
+        for name in walk_options.ignore_filenames.iter().filter(|_| respect_ignore) {
+            builder.add_custom_ignore_filename(name);
+        }
+        for file in &walk_options.ignore_paths {
+            if let Some(err) = builder.add_ignore(file) {
+                return Err(PyValueError::new_err(format!("Invalid ignore file {}: {}", file.display(), err)));
+            }
+        }
+
+        // Use overrides for glob filtering (fast pruning during traversal)
+        let overrides = Self::build_overrides(&path_buf, globs)?;
+        if let Some(overrides) = &overrides {
+            builder.overrides(overrides.clone());
+        }
+
+        // .git is hidden, so including it alone means doing the hidden check here instead,
+        // where the walker would let a glob whitelist a hidden entry
+        let (git_dir, hidden) = (walk_options.git_dir, walk_options.hidden);
+        if git_dir == Some(true) && !hidden {
+            builder.hidden(false);
+        }
+        if git_dir.is_some() || anchored.is_some() || along.is_some() {
+            builder.filter_entry(move |entry| {
+                let name = entry.file_name().to_string_lossy();
+                let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                let kept = match git_dir {
+                    Some(true) if !hidden => {
+                        !name.starts_with('.')
+                            || name == ".git"
+                            || overrides.as_ref().is_some_and(|o| o.matched(entry.path(), is_dir).is_whitelist())
+                    }
+                    Some(false) => name != ".git",
+                    _ => true,
+                };
+                kept && !anchored.as_ref().is_some_and(|anchored| anchored.ignored(entry.path(), is_dir))
+                    && along.as_ref().is_none_or(|along| along.starts_with(entry.path()))
+            });
+        }
+
+        Ok(builder.build())
+    }
+
+    /// Build glob overrides rooted at `root`
+    fn build_overrides(root: &Path, globs: &Globs) -> PyResult<Option<Override>> {
+        if globs.0.is_empty() {
+            return Ok(None);
+        }
+        let mut ob = OverrideBuilder::new(root);
+        for (pat, case_insensitive) in &globs.0 {
+            // iglob globs match case-insensitively; the setting applies to globs added after it
+            ob.case_insensitive(*case_insensitive)
+                .map_err(|e| PyValueError::new_err(format!("Invalid glob: {e}")))?;
+            ob.add(pat).map_err(|e| PyValueError::new_err(format!("Invalid glob: {e}")))?;
+        }
+        let overrides = ob.build()
+            .map_err(|e| PyValueError::new_err(format!("Failed to build glob overrides: {e}")))?;
+        Ok(Some(overrides))
+    }
+
+    /// Walk `path`, or search exactly `files` (relative entries resolve against `path`)
+    /// With `track_skips`, the walk also reports entries its filters excluded
+    fn build_source(
+        &self,
+        path: Option<&str>,
+        globs: &Globs,
+        walk_options: &WalkOptions,
+        files: Option<Vec<String>>,
+        track_skips: bool,
+    ) -> PyResult<FileSource> {
+        let Some(files) = files else {
+            let root = path.unwrap_or(".");
+            let walker = self.build_walker(root, globs, walk_options)?;
+            if track_skips {
+                let overrides = Self::build_overrides(Path::new(root), globs)?;
+                return Ok(FileSource::TrackedWalk(TrackedWalk::new(walker, overrides, walk_options.special_files)));
+            }
+            return Ok(FileSource::Walk(walker, walk_options.special_files));
+        };
+        let root = PathBuf::from(path.unwrap_or("."));
+        let paths = files
+            .into_iter()
+            .map(|f| match path {
+                Some(_) => root.join(f),
+                None => PathBuf::from(f),
+            })
+            .collect();
+        Ok(FileSource::List(paths, Self::build_overrides(&root, globs)?, walk_options.special_files))
+    }
+
+    /// Build type matcher separately for manual checking (AND logic with glob)
+    /// `type_add` holds extra definitions in rg's --type-add form (`name:glob`, `name:include:other`)
+    fn build_type_filter(types: &[String], type_add: &[String], ignore_case: bool) -> PyResult<Option<TypeFilter>> {
+        if types.is_empty() {
+            return Ok(None);
+        }
+
+        let mut tb = TypesBuilder::new();
+        tb.add_defaults();
+        for def in type_add {
+            tb.add_def(def)
+                .map_err(|e| PyValueError::new_err(format!("Invalid type_add definition {:?}: {e}", def)))?;
+        }
+        // Any name in ripgrep's type database (or type_add) is accepted, in any case;
+        // "all" selects every type
+        let known: Vec<String> = tb.definitions().iter().map(|def| def.name().to_string()).collect();
+        for t in types {
+            let name = if t == "all" || known.contains(t) {
+                t
+            } else {
+                known.iter().find(|name| name.eq_ignore_ascii_case(t)).ok_or_else(|| unknown_type_error(t, &known))?
+            };
+            tb.select(name);
+        }
+        let types = tb.build()
+            .map_err(|e| PyValueError::new_err(format!("Invalid file type configuration: {e}")))?;
+        Ok(Some(TypeFilter { types, ignore_case, either: None }))
+    }
+
+    /// Search a single file for content with context
+    #[allow(clippy::too_many_arguments)]
+    fn search_file_content_inner(
+        &self,
+        matcher: &SearchMatcher,
+        path: &Path,
+        read: &ReadOptions,
+        before_context: u64,
+        after_context: u64,
+        passthru: bool,
+        block_context: bool,
+        multiline: bool,
+        results: &mut Vec<ContentResult>,
+        report: &mut SearchReport,
+    ) -> Result<(), RGErr> {
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+        let lines = match read_candidate_lines(path, read, matcher) {
+            Ok(Some(lines)) => lines,
+            Ok(None) => {
+                report.stats.record_file(path, bytes, 0);
+                return Ok(());
+            }
+            Err(err) => return report.read_error(path, err), // Skip problematic files
+        };
+        let path_str = report.path_string(path);
+        let match_count = Self::content_from_lines(
+            matcher, &path_str, &lines, read.line_terminator, before_context, after_context, passthru, block_context, multiline, results,
+        );
+        report.stats.record_file(path, bytes, match_count);
+        Ok(())
+    }
+
+    /// Build the content results for one file's lines, returning the number of matches
+    /// With `multiline`, a match may span several lines and becomes one result for all of them;
+    /// with `block_context`, each match's context is its enclosing block instead of line counts
+    #[allow(clippy::too_many_arguments)]
+    fn content_from_lines(
+        matcher: &SearchMatcher,
+        path_str: &str,
+        lines: &[String],
+        terminator: LineTerminator,
+        before_context: u64,
+        after_context: u64,
+        passthru: bool,
+        block_context: bool,
+        multiline: bool,
+        results: &mut Vec<ContentResult>,
+    ) -> u64 {
+        // The searcher decides which lines are matches, which are context and where blocks break
+        let text = lines.join(std::str::from_utf8(terminator.as_bytes()).unwrap_or("\n"));
+        let mut searcher = SearcherBuilder::new()
+            .line_terminator(terminator)
+            .multi_line(multiline)
+            .before_context(before_context as usize)
+            .after_context(after_context as usize)
+            .passthru(passthru)
+            .build();
+        let has_context = passthru || before_context > 0 || after_context > 0;
+        let mut sink = ContextSink::new(terminator.as_byte(), has_context);
+        let _ = if multiline {
+            searcher.search_slice(matcher, text.as_bytes(), &mut sink)
+        } else {
+            searcher.search_slice(PerLine(matcher), text.as_bytes(), &mut sink)
+        };
+        let mut spans = sink.finish();
+        let blocks = if block_context { block_spans(lines, &mut spans) } else { Vec::new() };
+
+        for (i, span) in spans.iter().enumerate() {
+            let match_idx = (span.first_line - 1) as usize;
+            let end_idx = span.last_line as usize;
+            // With passthru every line of the file is shown once, so a match carries just
+            // the lines the searcher reported around it
+            let (before_len, after_len) = if passthru {
+                (span.shown_before, span.shown_after)
+            } else {
+                (before_context as usize, after_context as usize)
+            };
+            let (before_start, after_end) = match blocks.get(i) {
+                Some(&(start, end)) => (start.min(match_idx), end.max(end_idx)),
+                None => (match_idx.saturating_sub(before_len), std::cmp::min(lines.len(), end_idx + after_len)),
+            };
+
+            results.push(ContentResult {
+                path: path_str.to_string(),
+                line_number: span.first_line,
+                end_line_number: span.last_line,
+                content: lines[match_idx..end_idx].iter().map(|line| shown_line(line)).collect::<Vec<_>>().join("\n"),
+                before_context: lines[before_start..match_idx].iter().map(|line| shown_line(line).to_string()).collect(),
+                after_context: lines[end_idx..after_end].iter().map(|line| shown_line(line).to_string()).collect(),
+                occurrences: 1,
+                shown_before: span.shown_before,
+                shown_after: span.shown_after,
+                block_start: span.block_start,
+                spans: None,
+            });
+        }
+
+        spans.len() as u64
+    }
+
+    /// Append one vimgrep entry per match in a single file (columns are 1-based, counted in `columns`)
+    fn search_file_vimgrep_inner(
+        &self,
+        matcher: &SearchMatcher,
+        path: &Path,
+        read: &ReadOptions,
+        columns: ColumnUnit,
+        out: &mut Vec<(u64, String)>,
+        report: &mut SearchReport,
+    ) -> Result<(), RGErr> {
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+        let lines = match read_candidate_lines(path, read, matcher) {
+            Ok(Some(lines)) => lines,
+            Ok(None) => {
+                report.stats.record_file(path, bytes, 0);
+                return Ok(());
+            }
+            Err(err) => return report.read_error(path, err), // Skip problematic files
+        };
+
+        let match_count = Self::vimgrep_from_lines(matcher, &report.path_string(path), &lines, columns, out);
+        report.stats.record_file(path, bytes, match_count);
+        Ok(())
+    }
+
+    /// Build the vimgrep entries for one file's lines, returning the number of matches
+    fn vimgrep_from_lines(
+        matcher: &SearchMatcher,
+        path_str: &str,
+        lines: &[String],
+        unit: ColumnUnit,
+        out: &mut Vec<(u64, String)>,
+    ) -> u64 {
+        let mut match_count = 0u64;
+        for (line_idx, line) in lines.iter().enumerate() {
+            for (start, _) in match_spans(matcher, line, unit) {
+                let column = start + 1;
+                let line_number = (line_idx + 1) as u64;
+                out.push((line_number, format!("{}:{}:{}:{}", path_str, line_number, column, shown_line(line))));
+                match_count += 1;
+            }
+        }
+        match_count
+    }
+
+    /// Append one record per matched line of a single file
+    fn search_file_matches_inner(
+        &self,
+        matcher: &SearchMatcher,
+        path: &Path,
+        read: &ReadOptions,
+        out: &mut Vec<MatchRecord>,
+        report: &mut SearchReport,
+    ) -> Result<(), RGErr> {
+        let lines = match read_file_lines_with_offsets(path, read) {
+            Ok(lines) => lines,
+            Err(err) => return report.read_error(path, err), // Skip problematic files
+        };
+
+        let match_count = Self::matches_from_lines(matcher, &report.path_string(path), lines, out);
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+        report.stats.record_file(path, bytes, match_count);
+        Ok(())
+    }
+
+    /// Build the match records for one file's lines, returning the number of matching lines
+    fn matches_from_lines(matcher: &SearchMatcher, path_str: &str, lines: Vec<(u64, String)>, out: &mut Vec<MatchRecord>) -> u64 {
+        let mut match_count = 0u64;
+        for (line_idx, (byte_offset, line)) in lines.into_iter().enumerate() {
+            if matcher.is_match(line.as_bytes()).unwrap_or(false) {
+                out.push(MatchRecord {
+                    path: path_str.to_string(),
+                    line_number: (line_idx + 1) as u64,
+                    byte_offset,
+                    content: shown_line(&line).to_string(),
+                    spans: None,
+                });
+                match_count += 1;
+            }
+        }
+        match_count
+    }
+
+    /// Check if file has any matches with reused searcher
+    fn file_has_match_inner_with_searcher(&self, searcher: &mut Searcher, matcher: &SearchMatcher, path: &Path, read: &ReadOptions, report: &mut SearchReport) -> Result<bool, RGErr> {
+        Ok(self.probe_file_inner_with_searcher(searcher, matcher, path, read, report)?.unwrap_or(false))
+    }
+
+    /// Like file_has_match_inner_with_searcher, but None when the file could not be searched
+    fn probe_file_inner_with_searcher(&self, searcher: &mut Searcher, matcher: &SearchMatcher, path: &Path, read: &ReadOptions, report: &mut SearchReport) -> Result<Option<bool>, RGErr> {
+        let input = match open_input(path, read) {
+            Ok(input) => input,
+            Err(err) => {
+                report.read_error(path, err)?;
+                return Ok(None);
+            }
+        };
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+
+        let mut has_match = false;
+
+        let result = search_input(searcher, matcher, input, bytes, read, sinks::Bytes(|_lnum, line| {
+            utf8_line(line)?;
+            has_match = true;
+            Ok(false) // Stop after first match
+        }));
+
+        // If the search failed (e.g., invalid UTF-8), report the file as unsearched
+        match result {
+            Ok(_) => {
+                report.stats.record_file(path, bytes, u64::from(has_match));
+                Ok(Some(has_match))
+            }
+            Err(err) => {
+                report.read_error(path, err)?;
+                Ok(None) // Skip problematic files
+            }
+        }
+    }
+
+    /// A file's first matching line with reused searcher; the search stops at it
+    fn first_match_inner_with_searcher(&self, searcher: &mut Searcher, matcher: &SearchMatcher, path: &Path, read: &ReadOptions, report: &mut SearchReport) -> Result<Option<MatchRecord>, RGErr> {
+        let input = match open_input(path, read) {
+            Ok(input) => input,
+            Err(err) => {
+                report.read_error(path, err)?;
+                return Ok(None);
+            }
+        };
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+
+        let mut sink = FirstMatchSink::default();
+        if let Err(err) = search_input(searcher, matcher, input, bytes, read, &mut sink) {
+            report.read_error(path, err)?;
+            return Ok(None); // Skip problematic files
+        }
+        report.stats.record_file(path, bytes, u64::from(sink.0.is_some()));
+        Ok(sink.0.map(|(line_number, byte_offset, content)| MatchRecord {
+            path: report.path_string(path),
+            line_number,
+            byte_offset,
+            content,
+            spans: None,
+        }))
+    }
+
+    /// Count matches in a file with reused searcher, None if it could not be read
+    fn count_matches_in_file_inner_with_searcher(&self, searcher: &mut Searcher, matcher: &SearchMatcher, path: &Path, read: &ReadOptions, report: &mut SearchReport) -> Result<Option<u64>, RGErr> {
+        let input = match open_input(path, read) {
+            Ok(input) => input,
+            Err(err) => {
+                report.read_error(path, err)?;
+                return Ok(None);
+            }
+        };
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+
+        let mut count = 0u64;
+
+        let result = search_input(searcher, matcher, input, bytes, read, sinks::Bytes(|_lnum, line| {
+            utf8_line(line)?;
+            count += 1;
+            Ok(true)
+        }));
+
+        match result {
+            Ok(_) => {
+                report.stats.record_file(path, bytes, count);
+                Ok(Some(count))
+            }
+            Err(err) => {
+                report.read_error(path, err)?;
+                Ok(None) // Skip problematic files
+            }
+        }
+    }
+
+    /// Count a file's matching lines and note the first one, with the file's size and mtime
+    fn summarize_file_inner_with_searcher(
+        &self,
+        searcher: &mut Searcher,
+        matcher: &SearchMatcher,
+        path: &Path,
+        read: &ReadOptions,
+        report: &mut SearchReport,
+    ) -> Result<Option<FileSummary>, RGErr> {
+        let input = match open_input(path, read) {
+            Ok(input) => input,
+            Err(err) => {
+                report.read_error(path, err)?;
+                return Ok(None);
+            }
+        };
+        let meta = std::fs::metadata(path).ok();
+        let size = meta.as_ref().map_or(0, |m| m.len());
+
+        let mut first_line = None;
+        let mut count = 0u64;
+        let result = searcher.search_reader(matcher, read.buffered(input), sinks::Bytes(|line_number, line| {
+            utf8_line(line)?;
+            first_line.get_or_insert(line_number);
+            count += 1;
+            Ok(true)
+        }));
+        if let Err(err) = result {
+            report.read_error(path, err)?;
+            return Ok(None); // Skip problematic files
+        }
+        report.stats.record_file(path, size, count);
+
+        let mtime = meta
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs_f64());
+        Ok(first_line.map(|first_line| FileSummary {
+            path: report.path_string(path),
+            first_line,
+            match_count: count,
+            size,
+            mtime,
+        }))
+    }
+
+    /// Find lines matching `combined` in one pass, then split them by which of `matchers` hit
+    /// Returns the numbered lines per matcher, or None when nothing matched
+    fn attribute_matches_in_file(
+        &self,
+        searcher: &mut Searcher,
+        combined: &SearchMatcher,
+        matchers: &[SearchMatcher],
+        path: &Path,
+        read: &ReadOptions,
+        report: &mut SearchReport,
+    ) -> Result<Option<Vec<NumberedLines>>, RGErr> {
+        let input = match open_input(path, read) {
+            Ok(input) => input,
+            Err(err) => {
+                report.read_error(path, err)?;
+                return Ok(None);
+            }
+        };
+        let bytes = std::fs::metadata(path).map_or(0, |m| m.len());
+
+        let mut hits: NumberedLines = Vec::new();
+        let result = searcher.search_reader(combined, read.buffered(input), sinks::Bytes(|line_number, line| {
+            let line = utf8_line(line)?.trim_end_matches(['\n', '\r']);
+            hits.push((line_number, line.to_string()));
+            Ok(true)
+        }));
+        if let Err(err) = result {
+            report.read_error(path, err)?;
+            return Ok(None); // Skip problematic files
+        }
+        report.stats.record_file(path, bytes, hits.len() as u64);
+        if hits.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            matchers
+                .iter()
+                .map(|matcher| {
+                    hits.iter()
+                        .filter(|(_, line)| matcher.is_match(line.as_bytes()).unwrap_or(false))
+                        .cloned()
+                        .collect()
+                })
+                .collect(),
+        ))
+    }
+
+    /// Format paths-mode results as a list of `{"path", "spans"}` dicts
+    fn format_path_matches(&self, py: Python, matches: Vec<PathMatch>) -> PyResult<PyObject> {
+        let mut py_matches: Vec<PyObject> = Vec::with_capacity(matches.len());
+        for m in matches {
+            let dict = PyDict::new(py);
+            dict.set_item("path", m.path)?;
+            dict.set_item("spans", m.spans)?;
+            py_matches.push(dict.into_py(py));
+        }
+        Ok(py_matches.into_py(py))
+    }
+
+    /// Format metadata=True results as a list of dicts
+    fn format_file_summaries(&self, py: Python, summaries: Vec<FileSummary>) -> PyResult<PyObject> {
+        let mut py_summaries: Vec<PyObject> = Vec::with_capacity(summaries.len());
+        for s in summaries {
+            let dict = PyDict::new(py);
+            dict.set_item("path", s.path)?;
+            dict.set_item("first_line", s.first_line)?;
+            dict.set_item("match_count", s.match_count)?;
+            dict.set_item("size", s.size)?;
+            dict.set_item("mtime", s.mtime)?;
+            py_summaries.push(dict.into_py(py));
+        }
+        Ok(py_summaries.into_py(py))
+    }
+
+    /// Format matches-mode records as a list of dicts
+    fn format_match_records(&self, py: Python, records: Vec<MatchRecord>) -> PyResult<PyObject> {
+        let mut py_records: Vec<PyObject> = Vec::with_capacity(records.len());
+        for r in records {
+            let dict = PyDict::new(py);
+            dict.set_item("path", r.path)?;
+            dict.set_item("line_number", r.line_number)?;
+            dict.set_item("byte_offset", r.byte_offset)?;
+            dict.set_item("byte_length", r.content.len())?;
+            dict.set_item("content", r.content)?;
+            if let Some(spans) = r.spans {
+                dict.set_item("spans", spans)?;
+            }
+            py_records.push(dict.into_py(py));
+        }
+        Ok(py_records.into_py(py))
+    }
+
+    /// Format content results as `{path: [match, ...]}` for UIs with per-file sections
+    /// Each match carries its own context lines; files keep search order and head_limit counts matches
+    fn format_grouped_content(&self, py: Python, mut results: Vec<ContentResult>, format: &ContentFormat) -> PyResult<(PyObject, Suppressed)> {
+        let mut suppressed = Suppressed::default();
+        if let Some(limit) = format.head_limit.filter(|&limit| limit < results.len()) {
+            let cut = results.split_off(limit);
+            let kept: HashSet<&str> = results.iter().map(|r| r.path.as_str()).collect();
+            let cut_files: HashSet<&str> = cut.iter().map(|r| r.path.as_str()).filter(|p| !kept.contains(p)).collect();
+            suppressed = Suppressed { files: cut_files.len() as u64, matches: cut.len() as u64 };
+        }
+        let mut file_groups: Vec<(String, Vec<ContentResult>)> = Vec::new();
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        for r in results {
+            let index = *group_index.entry(r.path.clone()).or_insert_with(|| {
+                file_groups.push((r.path.clone(), Vec::new()));
+                file_groups.len() - 1
+            });
+            file_groups[index].1.push(r);
+        }
+
+        let grouped = PyDict::new(py);
+        for (path, mut matches) in file_groups {
+            matches.sort_by_key(|r| r.line_number);
+            match format.order {
+                LineOrder::Line => {}
+                LineOrder::Reverse => matches.reverse(),
+                LineOrder::Relevance => matches.sort_by_key(|r| std::cmp::Reverse(r.occurrences)),
+            }
+            let mut py_matches: Vec<PyObject> = Vec::with_capacity(matches.len());
+            for r in matches {
+                let dict = PyDict::new(py);
+                dict.set_item("line_number", r.line_number)?;
+                dict.set_item("end_line_number", r.end_line_number)?;
+                dict.set_item("content", r.content)?;
+                dict.set_item("before_context", r.before_context)?;
+                dict.set_item("after_context", r.after_context)?;
+                if let Some(spans) = r.spans {
+                    dict.set_item("spans", spans)?;
+                }
+                py_matches.push(dict.into_py(py));
+            }
+            grouped.set_item(path, py_matches)?;
+        }
+        Ok((grouped.into_py(py), suppressed))
+    }
+
+    /// Format content results for Python to match ripgrep CLI output
+    /// Also returns what head_limit left out of the output
+    fn format_content_results(
+        &self,
+        py: Python,
+        results: Vec<ContentResult>,
+        format: &ContentFormat,
+    ) -> PyResult<(PyObject, Suppressed)> {
+        if format.group_by_file {
+            return self.format_grouped_content(py, results, format);
+        }
+        let (lines, suppressed) = Self::format_content_lines(results, format);
+        Ok((lines.into_py(py), suppressed))
+    }
+
+    /// The flat content output lines, before they become a Python list
+    fn format_content_lines(results: Vec<ContentResult>, format: &ContentFormat) -> (Vec<String>, Suppressed) {
+        let ContentFormat {
+            line_numbers: show_line_numbers,
+            head_limit,
+            truncated: limits_truncated,
+            truncation_warning: show_truncation_warning,
+            compact_paths,
+            file_headers,
+            group_by_file: _,
+            context_separator,
+            heading,
+            no_filename,
+            order,
+        } = *format;
+
+        if results.is_empty() {
+            let marker = (show_truncation_warning && limits_truncated).then(|| TRUNCATION_MARKER.to_string());
+            return (marker.into_iter().collect(), Suppressed::default());
+        }
+
+        // Group results by file without cloning paths, keeping files in search order
+        let mut file_groups: Vec<(&str, Vec<&ContentResult>)> = Vec::new();
+        let mut group_index: HashMap<&str, usize> = HashMap::new();
+        for r in &results {
+            let index = *group_index.entry(&r.path).or_insert_with(|| {
+                file_groups.push((&r.path, Vec::new()));
+                file_groups.len() - 1
+            });
+            file_groups[index].1.push(r);
+        }
+
+        let mut py_results: Vec<String> = Vec::new();
+        let mut first_file = true;
+        let mut truncated = limits_truncated;
+        // Matched lines and files actually shown, to tell what head_limit cut
+        let total_matches: u64 = results.iter().map(|r| r.end_line_number - r.line_number + 1).sum();
+        let total_files = file_groups.len() as u64;
+        let (mut shown_matches, mut shown_files) = (0u64, 0u64);
+
+        'file_loop: for (file_path, mut file_results) in file_groups {
+            // Start files with a header or heading line, or separate them (except the first file)
+            let separated = !first_file && !py_results.is_empty();
+            let file_start = if file_headers {
+                vec![file_header(file_path, file_results.len())]
+            } else if heading {
+                // Like rg --heading: a blank line between files, then the path on its own
+                let blank = separated.then(String::new);
+                blank.into_iter().chain([file_path.to_string()]).collect()
+            } else if separated {
+                vec![context_separator.to_string()]
+            } else {
+                Vec::new()
+            };
+            if !file_start.is_empty() {
+                if let Some(limit) = head_limit {
+                    if py_results.len() >= limit {
+                        truncated = true;
+                        break 'file_loop;
+                    }
+                }
+                py_results.extend(file_start);
+            }
+            first_file = false;
+            let mut file_shown = false;
+
+            // Sort results by line number
+            file_results.sort_by_key(|r| r.line_number);
+
+            // Blocks of contiguous lines as the searcher reported them, split at its context breaks
+            let mut blocks: Vec<ContextBlock> = Vec::new();
+            for result in file_results.iter() {
+                if result.block_start || blocks.is_empty() {
+                    blocks.push(Vec::new());
+                }
+                let Some(block) = blocks.last_mut() else { continue };
+                let (before, after) = result.shown_context();
+                let before_start = result.line_number - before.len() as u64;
+                block.extend(before.iter().enumerate().map(|(i, line)| (before_start + i as u64, line.clone(), false)));
+                // the match line(s); a multiline match is shown one numbered line at a time
+                let span = (result.end_line_number - result.line_number + 1) as usize;
+                block.extend(
+                    result.content.splitn(span, '\n').enumerate().map(|(i, line)| (result.line_number + i as u64, line.to_string(), true)),
+                );
+                block.extend(after.iter().enumerate().map(|(i, line)| (result.end_line_number + 1 + i as u64, line.clone(), false)));
+            }
+
+            match order {
+                LineOrder::Line => {}
+                LineOrder::Reverse => {
+                    blocks.reverse();
+                    for lines in &mut blocks {
+                        lines.reverse();
+                    }
+                }
+                LineOrder::Relevance => {
+                    let occurrences: HashMap<u64, usize> =
+                        file_results.iter().map(|r| (r.line_number, r.occurrences)).collect();
+                    // Stable sort keeps line order among equally relevant blocks
+                    blocks.sort_by_key(|lines| {
+                        let score: usize = lines
+                            .iter()
+                            .filter(|(_, _, is_match)| *is_match)
+                            .map(|(ln, _, _)| occurrences.get(ln).copied().unwrap_or(1))
+                            .sum();
+                        std::cmp::Reverse(score)
+                    });
+                }
+            }
+
+            // Output blocks, separated like rg's context breaks
+            'range_loop: for (i, lines) in blocks.iter().enumerate() {
+                if i > 0 {
+                    if let Some(limit) = head_limit {
+                        if py_results.len() >= limit {
+                            truncated = true;
+                            break 'file_loop;
+                        }
+                    }
+                    py_results.push(context_separator.to_string());
+                }
+
+                let mut first_line_in_range = true;
+                for (line_num, content, is_match) in lines {
+                    if let Some(limit) = head_limit {
+                        if py_results.len() >= limit {
+                            truncated = true;
+                            break 'range_loop;
+                        }
+                    }
+
+                    let formatted = if heading || no_filename {
+                        // Path already shown as a heading, or left out: rg's bare line format
+                        match (show_line_numbers, *is_match) {
+                            (true, true) => format!("{line_num}:{content}"),
+                            (true, false) => format!("{line_num}-{content}"),
+                            (false, _) => content.to_string(),
+                        }
+                    } else if show_line_numbers && compact_paths {
+                        // Compact paths mode: only show filepath on first line of range
+                        if first_line_in_range {
+                            first_line_in_range = false;
+                            if *is_match {
+                                format!("{file_path}:{line_num}:{content}")
+                            } else {
+                                format!("{file_path}-{line_num}:{content}")
+                            }
+                        } else {
+                            // Subsequent lines: omit filepath
+                            if *is_match {
+                                format!(":{line_num}:{content}")
+                            } else {
+                                format!("-{line_num}:{content}")
+                            }
+                        }
+                    } else if show_line_numbers {
+                        // Regular mode with line numbers
+                        if *is_match {
+                            format!("{file_path}:{line_num}:{content}")
+                        } else {
+                            format!("{file_path}-{line_num}:{content}")
+                        }
+                    } else {
+                        // No line numbers
+                        format!("{file_path}:{content}")
+                    };
+                    py_results.push(formatted);
+                    if *is_match {
+                        shown_matches += 1;
+                        if !file_shown {
+                            file_shown = true;
+                            shown_files += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Add truncation warning if enabled and truncation occurred
+        if show_truncation_warning && truncated {
+            py_results.push(TRUNCATION_MARKER.to_string());
+        }
+
+        let suppressed = Suppressed { files: total_files - shown_files, matches: total_matches - shown_matches };
+        (py_results, suppressed)
+    }
+
+    /// Format count results for Python
+    fn format_count_results(&self, py: Python, counts: Vec<CountResult>) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for count in counts {
+            dict.set_item(&count.path, count.count)?;
+        }
+        Ok(dict.into_py(py))
+    }
+}
+
+/// Incremental search created by `Grep.start()`; call `step()` until `done`
+/// Holds a live directory walk, so it must stay on the thread that created it
+#[pyclass(module = "pyripgrep", unsendable)]
+pub struct SearchSession {
+    entries: Box<dyn Iterator<Item = Result<Candidate, ignore::Error>>>,
+    matcher: Option<SearchMatcher>,
+    type_filter: Option<TypeFilter>,
+    output_mode: OutputMode,
+    before_context: u64,
+    after_context: u64,
+    line_numbers: bool,
+    /// Matches may span lines (multiline=)
+    multiline: bool,
+    searcher: Searcher,
+    read: ReadOptions,
+    report: SearchReport,
+    /// Time spent inside step() calls and iteration
+    elapsed: Duration,
+    /// True once every file has been searched
+    #[pyo3(get)]
+    done: bool,
+}
+
+#[pymethods]
+impl SearchSession {
+    /// Search files until `max_millis` have passed and return the results of the files
+    /// finished in this step, formatted as search() would for the session's output mode
+    /// At least one file is searched per step, so the budget can overrun by one file
+    #[pyo3(signature = (max_millis = 50))]
+    fn step(&mut self, py: Python, max_millis: u64) -> PyResult<PyObject> {
+        let started = Instant::now();
+        let budget = Duration::from_millis(max_millis);
+        let grep = Grep::default();
+        let Self {
+            entries,
+            matcher,
+            type_filter,
+            output_mode,
+            before_context,
+            after_context,
+            multiline,
+            searcher,
+            read,
+            report,
+            done,
+            ..
+        } = self;
+
+        let mut content = Vec::new();
+        let mut files = Vec::new();
+        let mut counts = Vec::new();
+        let mut lines = Vec::new();
+        let mut records = Vec::new();
+        let mut path_matches = Vec::new();
+        let mut found = false;
+        while !*done {
+            let Some(entry) = entries.next() else {
+                *done = true;
+                break;
+            };
+            visit_candidate(entry, type_filter.as_ref(), report, &mut |path, report| {
+                let Some(matcher) = matcher.as_ref() else {
+                    files.push(report.path_string(path)); // files mode: no pattern
+                    return Ok(true);
+                };
+                match output_mode {
+                    OutputMode::Content => grep.search_file_content_inner(
+                        matcher,
+                        path,
+                        read,
+                        *before_context,
+                        *after_context,
+                        false,
+                        false,
+                        *multiline,
+                        &mut content,
+                        report,
+                    )?,
+                    OutputMode::FilesWithMatches => {
+                        if grep.file_has_match_inner_with_searcher(searcher, matcher, path, read, report)? {
+                            files.push(report.path_string(path));
+                        }
+                    }
+                    OutputMode::FilesWithoutMatch => {
+                        if grep.probe_file_inner_with_searcher(searcher, matcher, path, read, report)? == Some(false) {
+                            files.push(report.path_string(path));
+                        }
+                    }
+                    OutputMode::Count => {
+                        let count = grep.count_matches_in_file_inner_with_searcher(searcher, matcher, path, read, report)?;
+                        if let Some(count) = count.filter(|&count| count > 0) {
+                            counts.push(CountResult { path: report.path_string(path), count });
+                        }
+                    }
+                    OutputMode::Vimgrep => {
+                        let mut found = Vec::new();
+                        grep.search_file_vimgrep_inner(matcher, path, read, ColumnUnit::Bytes, &mut found, report)?;
+                        lines.extend(found.into_iter().map(|(_, entry)| entry));
+                    }
+                    OutputMode::Matches => grep.search_file_matches_inner(matcher, path, read, &mut records, report)?,
+                    OutputMode::FirstMatch => records.extend(grep.first_match_inner_with_searcher(searcher, matcher, path, read, report)?),
+                    OutputMode::Files => files.push(report.path_string(path)),
+                    OutputMode::Paths => path_matches.extend(PathMatch::find(matcher, report.path_string(path))),
+                    OutputMode::Quiet => {
+                        found = grep.file_has_match_inner_with_searcher(searcher, matcher, path, read, report)?;
+                        return Ok(!found);
+                    }
+                }
+                Ok(true)
+            })
+            .map_err(to_pyerr)?;
+            // Quiet mode has its answer at the first match
+            if found {
+                *done = true;
+            }
+            if started.elapsed() >= budget {
+                break;
+            }
+        }
+        self.elapsed += started.elapsed();
+
+        match self.output_mode {
+            OutputMode::Content => {
+                let format = ContentFormat {
+                    line_numbers: self.line_numbers,
+                    head_limit: None,
+                    truncated: false,
+                    truncation_warning: false,
+                    compact_paths: false,
+                    file_headers: false,
+                    group_by_file: false,
+                    context_separator: "--",
+                    heading: false,
+                    no_filename: false,
+                    order: LineOrder::Line,
+                };
+                Ok(grep.format_content_results(py, content, &format)?.0)
+            }
+            OutputMode::Count => grep.format_count_results(py, counts),
+            OutputMode::Vimgrep => Ok(lines.into_py(py)),
+            OutputMode::Matches | OutputMode::FirstMatch => grep.format_match_records(py, records),
+            OutputMode::Paths => grep.format_path_matches(py, path_matches),
+            OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch | OutputMode::Files => Ok(files.into_py(py)),
+            OutputMode::Quiet => Ok(found.into_py(py)),
+        }
+    }
+
+    /// Iterating yields paths one at a time, in files_with_matches, files_without_match and files mode only
+    fn __iter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        if !matches!(slf.output_mode, OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch | OutputMode::Files) {
+            return Err(PyValueError::new_err(
+                "only sessions in output_mode 'files_with_matches', 'files_without_match' or 'files' can be iterated; use step()",
+            ));
+        }
+        Ok(slf)
+    }
+
+    /// Search up to the next file to report and return its path, in walk order
+    /// Shares the walk with step(), so the two can be mixed
+    fn __next__(&mut self) -> PyResult<Option<String>> {
+        let started = Instant::now();
+        let grep = Grep::default();
+        let Self { entries, matcher, type_filter, output_mode, searcher, read, report, done, .. } = self;
+        // Files mode lists every file, even when given a pattern
+        let matcher = matcher.as_ref().filter(|_| *output_mode != OutputMode::Files);
+        let listed = *output_mode != OutputMode::FilesWithoutMatch;
+
+        let mut next = None;
+        while next.is_none() && !*done {
+            let Some(entry) = entries.next() else {
+                *done = true;
+                break;
+            };
+            visit_candidate(entry, type_filter.as_ref(), report, &mut |path, report| {
+                let hit = match matcher {
+                    Some(matcher) => grep.probe_file_inner_with_searcher(searcher, matcher, path, read, report)? == Some(listed),
+                    None => true,
+                };
+                if hit {
+                    next = Some(report.path_string(path));
+                }
+                Ok(true)
+            })
+            .map_err(to_pyerr)?;
+        }
+        self.elapsed += started.elapsed();
+        Ok(next)
+    }
+
+    /// Statistics for the files searched so far (elapsed counts time spent searching)
+    #[getter]
+    fn stats(&self) -> SearchStats {
+        SearchStats {
+            elapsed: self.elapsed.as_secs_f64(),
+            ..self.report.stats.clone()
+        }
+    }
+
+    /// (path, message) pairs collected so far with errors="collect"
+    #[getter]
+    fn errors(&self) -> Vec<(String, String)> {
+        self.report.errors.clone()
+    }
+}
+
+/// A directory tree walked once with its ignore rules applied, for repeated searches
+/// `search()` reuses the cached file list until `refresh()` walks the tree again
+#[pyclass(module = "pyripgrep")]
+pub struct Workspace {
+    /// Root directory of the walk
+    #[pyo3(get)]
+    path: String,
+    /// Files that passed the ignore rules, relative to `path`
+    files: Vec<String>,
+}
 
-                // merge lines for this result into current_lines (prefer match over context)
-                // before context
-                for (i, before_line) in result.before_context.iter().enumerate() {
-                    let ln = result.line_number - before_len + i as u64;
-                    current_lines.entry(ln).or_insert_with(|| (before_line.clone(), false));
-                }
-                // the match line
-                current_lines
-                    .entry(result.line_number)
-                    .and_modify(|e| {
-                        if !e.1 {
-                            *e = (result.content.clone(), true);
-                        }
-                    })
-                    .or_insert_with(|| (result.content.clone(), true));
-                // after context
-                for (i, after_line) in result.after_context.iter().enumerate() {
-                    let ln = result.line_number + 1 + i as u64;
-                    current_lines.entry(ln).or_insert_with(|| (after_line.clone(), false));
-                }
+impl Workspace {
+    /// Walk `path` honoring .gitignore/.ignore and hidden-file rules, like search() does
+    fn walk(py: Python, path: &str) -> PyResult<Vec<String>> {
+        let root = PathBuf::from(path);
+        if !root.is_dir() {
+            return Err(PyValueError::new_err(format!("Workspace path must be a directory: {}", path)));
+        }
+        let walker = Grep::default().build_walker(path, &Globs::default(), &WalkOptions::default())?;
+        Ok(py.allow_threads(|| {
+            FileSource::Walk(walker, SpecialFiles::Skip)
+                .into_iter()
+                .filter_map(|candidate| match candidate {
+                    Ok(Candidate::File(file)) => {
+                        Some(file.strip_prefix(&root).unwrap_or(&file).to_string_lossy().to_string())
+                    }
+                    _ => None,
+                })
+                .collect()
+        }))
+    }
+}
+
+#[pymethods]
+impl Workspace {
+    #[new]
+    #[pyo3(signature = (path = None))]
+    fn new(py: Python, path: Option<&str>) -> PyResult<Self> {
+        let path = path.unwrap_or(".").to_string();
+        let files = Self::walk(py, &path)?;
+        Ok(Workspace { path, files })
+    }
+
+    /// Walk the tree again to pick up added, removed and newly ignored files
+    /// Returns the number of files now cached
+    fn refresh(&mut self, py: Python) -> PyResult<usize> {
+        self.files = Self::walk(py, &self.path)?;
+        Ok(self.files.len())
+    }
+
+    /// Cached files, relative to `path`
+    #[getter]
+    fn files(&self) -> Vec<String> {
+        self.files.clone()
+    }
+
+    /// Same as `Grep.search()` over the cached files; every search() keyword except
+    /// `path` and `files` is accepted, and glob/type filters still apply per call
+    #[pyo3(signature = (pattern = None, **kwargs))]
+    fn search(&self, py: Python, pattern: Option<&PyAny>, kwargs: Option<&PyDict>) -> PyResult<PyObject> {
+        let kwargs = match kwargs {
+            Some(kwargs) => kwargs.copy()?,
+            None => PyDict::new(py),
+        };
+        for key in ["path", "files"] {
+            if kwargs.contains(key)? {
+                return Err(PyValueError::new_err(format!(
+                    "Workspace.search() does not accept {key}=; create a Workspace for another root instead"
+                )));
             }
+        }
+        kwargs.set_item("path", &self.path)?;
+        kwargs.set_item("files", &self.files)?;
+        Py::new(py, Grep::default())?.call_method(py, "search", (pattern,), Some(kwargs))
+    }
 
-            // finalize last range
-            finalize_range(current_start, current_end, current_lines, &mut merged_ranges);
+    fn __len__(&self) -> usize {
+        self.files.len()
+    }
 
-            // Output merged ranges
-            'range_loop: for (i, (_start, _end, lines)) in merged_ranges.iter().enumerate() {
-                if i > 0 {
-                    if let Some(limit) = head_limit {
-                        if py_results.len() >= limit {
-                            truncated = true;
-                            break 'file_loop;
-                        }
-                    }
-                    py_results.push("--".to_string());
+    fn __repr__(&self) -> String {
+        format!("Workspace(path='{}', files={})", self.path, self.files.len())
+    }
+}
+
+/// Files with matches found by `Grep.find_files()` or `FileSet.refine()`
+/// Later searches read only these files, without walking the tree again
+#[pyclass(module = "pyripgrep")]
+pub struct FileSet {
+    /// The Grep that found the files; its defaults apply to every search over them
+    grep: Py<Grep>,
+    /// Matching files as search() reported them
+    files: Vec<String>,
+}
+
+impl FileSet {
+    /// Search for files with matches, only among `within` when refining a set
+    fn collect(py: Python, grep: Py<Grep>, pattern: Option<&PyAny>, kwargs: Option<&PyDict>, within: Option<&FileSet>) -> PyResult<Self> {
+        let caller = if within.is_some() { "refine" } else { "find_files" };
+        let kwargs = match kwargs {
+            Some(kwargs) => kwargs.copy()?,
+            None => PyDict::new(py),
+        };
+        for key in FILE_SET_REJECTED_OPTIONS {
+            if kwargs.contains(key)? {
+                return Err(PyTypeError::new_err(format!("{caller}() does not accept {key}=")));
+            }
+        }
+        if kwargs.get_item("errors")?.is_some_and(|errors| errors.extract::<&str>().is_ok_and(|errors| errors == "collect")) {
+            return Err(PyTypeError::new_err(format!("{caller}() does not accept errors='collect'")));
+        }
+        if let Some(within) = within {
+            within.restrict(kwargs, caller)?;
+        }
+        kwargs.set_item("output_mode", "files_with_matches")?;
+        let files = grep.call_method(py, "search", (pattern,), Some(kwargs))?.extract(py)?;
+        Ok(FileSet { grep, files })
+    }
+
+    /// Point `kwargs` at the set's files in place of a walk
+    fn restrict(&self, kwargs: &PyDict, caller: &str) -> PyResult<()> {
+        for key in ["path", "files", "scope"] {
+            if kwargs.contains(key)? {
+                return Err(PyTypeError::new_err(format!("{caller}() does not accept {key}=; the file set replaces the walk")));
+            }
+        }
+        // The reported paths already include any path= of the search that found them
+        kwargs.set_item("path", kwargs.py().None())?;
+        kwargs.set_item("files", &self.files)
+    }
+}
+
+#[pymethods]
+impl FileSet {
+    /// The files in the set, as search() reported them
+    #[getter]
+    fn files(&self) -> Vec<String> {
+        self.files.clone()
+    }
+
+    /// Same as `Grep.search()` over the files in the set; every search() keyword except
+    /// `path`, `files` and `scope` is accepted, and glob/type filters still apply per call
+    #[pyo3(signature = (pattern = None, **kwargs))]
+    fn search(&self, py: Python, pattern: Option<&PyAny>, kwargs: Option<&PyDict>) -> PyResult<PyObject> {
+        let kwargs = match kwargs {
+            Some(kwargs) => kwargs.copy()?,
+            None => PyDict::new(py),
+        };
+        self.restrict(kwargs, "search")?;
+        self.grep.call_method(py, "search", (pattern,), Some(kwargs))
+    }
+
+    /// The files in the set that also match `pattern`, as a new FileSet
+    #[pyo3(signature = (pattern = None, **kwargs))]
+    fn refine(&self, py: Python, pattern: Option<&PyAny>, kwargs: Option<&PyDict>) -> PyResult<FileSet> {
+        FileSet::collect(py, self.grep.clone_ref(py), pattern, kwargs, Some(self))
+    }
+
+    fn __len__(&self) -> usize {
+        self.files.len()
+    }
+
+    fn __contains__(&self, path: &str) -> bool {
+        self.files.iter().any(|file| file == path)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FileSet(files={})", self.files.len())
+    }
+}
+
+/// Content results written to a temporary JSONL file (spill_over=), one JSON string per line
+/// Iterating reads the lines back one at a time; the file is deleted by close() or once
+/// the object and its iterators are gone
+#[pyclass(module = "pyripgrep")]
+pub struct SpilledResults {
+    /// The JSONL file, or None once closed
+    path: Option<PathBuf>,
+    /// Number of result lines in the file
+    len: usize,
+}
+
+impl SpilledResults {
+    /// Write `lines` to a new file in the system temporary directory, without the GIL
+    fn write(py: Python, lines: Vec<String>) -> PyResult<Self> {
+        static SPILLS: AtomicUsize = AtomicUsize::new(0);
+        let len = lines.len();
+        let path = py.allow_threads(|| -> io::Result<PathBuf> {
+            let (path, file) = loop {
+                let name = format!("pyripgrep-{}-{}.jsonl", std::process::id(), SPILLS.fetch_add(1, Ordering::Relaxed));
+                let path = std::env::temp_dir().join(name);
+                match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                    Ok(file) => break (path, file),
+                    Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                    Err(err) => return Err(err),
                 }
+            };
+            let mut out = io::BufWriter::new(file);
+            for line in &lines {
+                serde_json::to_writer(&mut out, line)?;
+                out.write_all(b"\n")?;
+            }
+            out.flush()?;
+            Ok(path)
+        })?;
+        Ok(SpilledResults { path: Some(path), len })
+    }
 
-                let mut first_line_in_range = true;
-                for (line_num, content, is_match) in lines {
-                    if let Some(limit) = head_limit {
-                        if py_results.len() >= limit {
-                            truncated = true;
-                            break 'range_loop;
-                        }
-                    }
+    fn remove(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
 
-                    let formatted = if show_line_numbers && compact_paths {
-                        // Compact paths mode: only show filepath on first line of range
-                        if first_line_in_range {
-                            first_line_in_range = false;
-                            if *is_match {
-                                format!("{file_path}:{line_num}:{content}")
-                            } else {
-                                format!("{file_path}-{line_num}:{content}")
-                            }
-                        } else {
-                            // Subsequent lines: omit filepath
-                            if *is_match {
-                                format!(":{line_num}:{content}")
-                            } else {
-                                format!("-{line_num}:{content}")
-                            }
-                        }
-                    } else if show_line_numbers {
-                        // Regular mode with line numbers
-                        if *is_match {
-                            format!("{file_path}:{line_num}:{content}")
-                        } else {
-                            format!("{file_path}-{line_num}:{content}")
-                        }
-                    } else {
-                        // No line numbers
-                        format!("{file_path}:{content}")
+impl Drop for SpilledResults {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[pymethods]
+impl SpilledResults {
+    /// Path of the JSONL file; copy it elsewhere to keep it past close()
+    #[getter]
+    fn path(&self) -> PyResult<String> {
+        match &self.path {
+            Some(path) => Ok(path.to_string_lossy().to_string()),
+            None => Err(PyValueError::new_err("SpilledResults is closed")),
+        }
+    }
+
+    /// Delete the file; iterating afterwards raises ValueError
+    fn close(&mut self) {
+        self.remove();
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyResult<SpilledLines> {
+        let Some(path) = &slf.path else {
+            return Err(PyValueError::new_err("SpilledResults is closed"));
+        };
+        let lines = BufReader::new(File::open(path)?).lines();
+        Ok(SpilledLines { lines, _results: slf.into() })
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.remove();
+        false
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.path {
+            Some(path) => format!("SpilledResults(len={}, path='{}')", self.len, path.display()),
+            None => format!("SpilledResults(len={}, closed)", self.len),
+        }
+    }
+}
+
+/// Iterator over the lines of a SpilledResults file
+#[pyclass(module = "pyripgrep", unsendable)]
+pub struct SpilledLines {
+    lines: io::Lines<BufReader<File>>,
+    /// Keeps the file from being deleted while it is read
+    _results: Py<SpilledResults>,
+}
+
+#[pymethods]
+impl SpilledLines {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<String>> {
+        match self.lines.next() {
+            Some(line) => {
+                let line = serde_json::from_str(&line?).map_err(|e| PyValueError::new_err(format!("Corrupt spill file: {e}")))?;
+                Ok(Some(line))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Live search started by `Grep.watch()`; call `stop()` (or leave a `with` block) to end it
+/// The callback runs on a background thread, which holds the GIL only while calling it
+#[pyclass(module = "pyripgrep")]
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+    /// Returns the exception raised by the callback, if that is what ended the watch
+    thread: Option<thread::JoinHandle<Option<PyErr>>>,
+}
+
+#[pymethods]
+impl Watcher {
+    /// Stop watching and wait for the background thread to finish
+    /// Re-raises the exception that stopped the watch early, if the callback raised one
+    fn stop(&mut self, py: Python) -> PyResult<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        // The thread may be waiting for the GIL to run the callback
+        match py.allow_threads(|| thread.join()) {
+            Ok(None) => Ok(()),
+            Ok(Some(err)) => Err(err),
+            Err(payload) => Err(PyValueError::new_err(format!("watch thread panicked: {}", panic_message(&payload)))),
+        }
+    }
+
+    /// False once stopped, or once the callback raised
+    #[getter]
+    fn running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __exit__(&mut self, py: Python, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> PyResult<bool> {
+        self.stop(py)?;
+        Ok(false)
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Files under a watched root and their current matches
+#[cfg(feature = "watch")]
+struct WatchState {
+    /// Canonical root, as reported by the notification backend
+    root: PathBuf,
+    /// The `path` given to watch(), used to report paths the way search() does
+    display_root: PathBuf,
+    globs: Globs,
+    type_filter: Option<TypeFilter>,
+    walk_options: WalkOptions,
+    read: ReadOptions,
+    /// Let matches span lines (multiline=)
+    multiline: bool,
+    matcher: SearchMatcher,
+    /// Files the ignore rules, glob and type filter let through
+    known: HashSet<PathBuf>,
+    /// Matched (line_number, content) pairs per file with matches
+    matches: HashMap<PathBuf, Vec<(u64, String)>>,
+}
+
+#[cfg(feature = "watch")]
+impl WatchState {
+    /// Walk the tree again, picking up new files and changed ignore rules
+    fn rewalk(&mut self) -> PyResult<()> {
+        let root = self.root.to_string_lossy();
+        let walker = Grep::default().build_walker(&root, &self.globs, &self.walk_options)?;
+        self.known = walker
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .map(|entry| entry.into_path())
+            .filter(|path| self.type_filter.as_ref().is_none_or(|types| types.matches(path)))
+            .collect();
+        Ok(())
+    }
+
+    /// Matching lines of one file; unreadable or binary files have none
+    /// The decoded file is searched whole, so a multiline match reports each line it spans
+    fn search(&self, path: &Path) -> Vec<(u64, String)> {
+        let text = read_file_lines(path, &self.read).unwrap_or_default().join("\n");
+        let mut searcher = SearcherBuilder::new().multi_line(self.multiline).build();
+        let mut found = Vec::new();
+        let _ = searcher.search_slice(&self.matcher, text.as_bytes(), sinks::Bytes(|line_number, lines| {
+            let lines = utf8_line(lines)?.trim_end_matches('\n');
+            found.extend(lines.split('\n').zip(line_number..).map(|(line, n)| (n, shown_line(line).to_string())));
+            Ok(true)
+        }));
+        found
+    }
+
+    fn display(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.display_root.join(relative).to_string_lossy().to_string()
+    }
+
+    /// Re-search changed files and call `callback` for each one whose matches changed
+    fn update(&mut self, py: Python, changed: BTreeSet<PathBuf>, callback: &PyObject) -> PyResult<()> {
+        if changed.iter().any(|path| !self.known.contains(path) && path.is_file()) {
+            self.rewalk()?;
+        }
+        for path in changed {
+            if !path.exists() {
+                self.known.remove(&path);
+            }
+            let old = self.matches.remove(&path).unwrap_or_default();
+            let new = if self.known.contains(&path) { py.allow_threads(|| self.search(&path)) } else { Vec::new() };
+            let (added, removed) = diff_matches(&old, &new);
+            if !new.is_empty() {
+                self.matches.insert(path.clone(), new);
+            }
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+            let event = PyDict::new(py);
+            event.set_item("path", self.display(&path))?;
+            event.set_item("added", added)?;
+            event.set_item("removed", removed)?;
+            callback.call1(py, (event,))?;
+        }
+        Ok(())
+    }
+}
+
+/// Watch loop run on the Watcher's thread: batch notifications until the tree has been
+/// quiet for `debounce`, then report the match changes of the files they touched
+#[cfg(feature = "watch")]
+fn watch_loop(
+    mut state: WatchState,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    callback: PyObject,
+    debounce: Duration,
+    stop: &AtomicBool,
+) -> Option<PyErr> {
+    let poll = Duration::from_millis(50);
+    let touched = |changed: &mut BTreeSet<PathBuf>, event: notify::Result<notify::Event>| {
+        // Our own reads show up as access events
+        if let Ok(event) = event {
+            if !matches!(event.kind, notify::EventKind::Access(_)) {
+                changed.extend(event.paths);
+            }
+        }
+    };
+    while !stop.load(Ordering::Relaxed) {
+        let mut changed = BTreeSet::new();
+        match events.recv_timeout(poll) {
+            Ok(event) => touched(&mut changed, event),
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return None,
+        }
+        while let Ok(event) = events.recv_timeout(debounce) {
+            touched(&mut changed, event);
+        }
+        if changed.is_empty() || stop.load(Ordering::Relaxed) {
+            continue;
+        }
+        if let Err(err) = Python::with_gil(|py| state.update(py, changed, &callback)) {
+            return Some(err);
+        }
+    }
+    None
+}
+
+/// Merge results of several searches run with the same output mode
+/// Entries are deduplicated, sorted by path (and line), and head_limit is re-applied
+#[pyfunction]
+#[pyo3(signature = (results, output_mode = None, head_limit = None, truncation_warning = None))]
+fn merge_results(
+    py: Python,
+    results: Vec<&PyAny>,
+    output_mode: Option<&str>,
+    head_limit: Option<usize>,
+    truncation_warning: Option<bool>,
+) -> PyResult<PyObject> {
+    let output_mode = match output_mode {
+        Some(mode) => OutputMode::from_str(mode)?,
+        None if results.first().is_some_and(|r| r.is_instance_of::<PyDict>()) => OutputMode::Count,
+        None if results.first().is_some_and(|r| r.is_instance_of::<PyBool>()) => OutputMode::Quiet,
+        None => OutputMode::FilesWithMatches,
+    };
+    let show_truncation_warning = truncation_warning.unwrap_or(false);
+    let limit = head_limit.unwrap_or(usize::MAX);
+
+    match output_mode {
+        OutputMode::Quiet => {
+            let mut any = false;
+            for result in results {
+                any |= result.extract::<bool>()?;
+            }
+            Ok(any.into_py(py))
+        }
+        OutputMode::Count => {
+            // Duplicate paths keep the highest count
+            let mut merged: BTreeMap<String, u64> = BTreeMap::new();
+            for result in results {
+                let counts: HashMap<String, u64> = result.extract()?;
+                for (path, count) in counts {
+                    let entry = merged.entry(path).or_insert(0);
+                    *entry = (*entry).max(count);
+                }
+            }
+            let dict = PyDict::new(py);
+            for (path, count) in merged.into_iter().take(limit) {
+                dict.set_item(path, count)?;
+            }
+            Ok(dict.into_py(py))
+        }
+        OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch | OutputMode::Files => {
+            let mut merged: BTreeSet<String> = BTreeSet::new();
+            for result in results {
+                let files: Vec<String> = result.extract()?;
+                merged.extend(files.into_iter().filter(|f| f != TRUNCATION_MARKER));
+            }
+            let truncated = merged.len() > limit;
+            let mut files: Vec<String> = merged.into_iter().take(limit).collect();
+            if show_truncation_warning && truncated {
+                files.push(TRUNCATION_MARKER.to_string());
+            }
+            Ok(files.into_py(py))
+        }
+        OutputMode::Vimgrep => {
+            let line_re = regex::Regex::new(r"(?s)^(.+?):(\d+):(\d+):(.*)$").unwrap();
+            // (path, line_num, column) -> entry
+            let mut merged: BTreeMap<(String, u64, u64), String> = BTreeMap::new();
+            for result in results {
+                let lines: Vec<String> = result.extract()?;
+                for line in lines {
+                    if line == TRUNCATION_MARKER {
+                        continue;
+                    }
+                    let caps = line_re.captures(&line).ok_or_else(|| {
+                        PyValueError::new_err(format!("Invalid vimgrep line: {}", line))
+                    })?;
+                    let key = (
+                        caps[1].to_string(),
+                        caps[2].parse().unwrap_or(0),
+                        caps[3].parse().unwrap_or(0),
+                    );
+                    merged.insert(key, line);
+                }
+            }
+            let truncated = merged.len() > limit;
+            let mut lines: Vec<String> = merged.into_values().take(limit).collect();
+            if show_truncation_warning && truncated {
+                lines.push(TRUNCATION_MARKER.to_string());
+            }
+            Ok(lines.into_py(py))
+        }
+        OutputMode::Paths => {
+            // path -> entry; entries for the same path are identical
+            let mut merged: BTreeMap<String, PyObject> = BTreeMap::new();
+            for result in results {
+                let entries: Vec<&PyDict> = result.extract()?;
+                for entry in entries {
+                    let path = entry
+                        .get_item("path")?
+                        .ok_or_else(|| PyValueError::new_err("Path match is missing 'path'"))?;
+                    merged.insert(path.extract()?, entry.into_py(py));
+                }
+            }
+            Ok(merged.into_values().take(limit).collect::<Vec<_>>().into_py(py))
+        }
+        OutputMode::Matches | OutputMode::FirstMatch => {
+            // (path, line_number) -> record; records for the same line are identical
+            let mut merged: BTreeMap<(String, u64), PyObject> = BTreeMap::new();
+            for result in results {
+                let records: Vec<&PyDict> = result.extract()?;
+                for record in records {
+                    let field = |key: &str| {
+                        record.get_item(key)?.ok_or_else(|| {
+                            PyValueError::new_err(format!("Match record is missing '{}'", key))
+                        })
                     };
-                    py_results.push(formatted);
+                    let key = (field("path")?.extract()?, field("line_number")?.extract()?);
+                    merged.insert(key, record.into_py(py));
                 }
             }
+            if output_mode == OutputMode::FirstMatch {
+                // Keep each file's earliest line
+                let mut seen = HashSet::new();
+                merged.retain(|(path, _), _| seen.insert(path.clone()));
+            }
+            Ok(merged.into_values().take(limit).collect::<Vec<_>>().into_py(py))
         }
+        OutputMode::Content => {
+            // Content lines must carry line numbers (n=True) to be merged
+            let line_re = regex::Regex::new(r"(?s)^(.+?)([:-])(\d+):(.*)$").unwrap();
+            // (path, line_num) -> (content, is_match); matches win over context
+            let mut merged: BTreeMap<(String, u64), (String, bool)> = BTreeMap::new();
+            for result in results {
+                let lines: Vec<String> = result.extract()?;
+                for line in lines {
+                    if line == "--" || line == TRUNCATION_MARKER || is_file_header(&line) {
+                        continue;
+                    }
+                    let caps = line_re.captures(&line).ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Cannot merge content line without line numbers (use n=True): {}",
+                            line
+                        ))
+                    })?;
+                    let is_match = &caps[2] == ":";
+                    let line_num: u64 = caps[3].parse().map_err(|_| {
+                        PyValueError::new_err(format!("Invalid line number in: {}", line))
+                    })?;
+                    merged
+                        .entry((caps[1].to_string(), line_num))
+                        .and_modify(|e| {
+                            if is_match && !e.1 {
+                                *e = (caps[4].to_string(), true);
+                            }
+                        })
+                        .or_insert_with(|| (caps[4].to_string(), is_match));
+                }
+            }
 
-        // Add truncation warning if enabled and truncation occurred
-        if show_truncation_warning && truncated {
-            py_results.push("[Content truncated]".to_string());
+            let mut py_results: Vec<String> = Vec::new();
+            let mut truncated = false;
+            let mut prev: Option<(&str, u64)> = None;
+            for ((path, line_num), (content, is_match)) in &merged {
+                // Separate files and non-contiguous ranges within a file
+                let contiguous = prev.is_some_and(|(p, ln)| p == path && ln + 1 == *line_num);
+                let needs_separator = prev.is_some() && !contiguous;
+                if py_results.len() + usize::from(needs_separator) >= limit {
+                    truncated = true;
+                    break;
+                }
+                if needs_separator {
+                    py_results.push("--".to_string());
+                }
+                let sep = if *is_match { ':' } else { '-' };
+                py_results.push(format!("{path}{sep}{line_num}:{content}"));
+                prev = Some((path, *line_num));
+            }
+            if show_truncation_warning && truncated {
+                py_results.push(TRUNCATION_MARKER.to_string());
+            }
+            Ok(py_results.into_py(py))
         }
+    }
+}
+
+/// Optional capabilities and whether this build includes them
+/// Capabilities that pyripgrep does not implement yet are listed as unavailable
+/// so callers can probe them without catching errors
+const FEATURES: &[(&str, bool)] = &[
+    ("decompression", cfg!(feature = "decompression")),
+    ("archives", cfg!(feature = "archives")),
+    ("fancy_regex", cfg!(feature = "fancy-regex")),
+    ("watch", cfg!(feature = "watch")),
+    ("pcre2", false),
+    ("tree_sitter", false),
+    ("index", false),
+];
 
-        Ok(py_results.into_py(py))
+/// Report which optional capabilities this build was compiled with
+#[pyfunction]
+fn features(py: Python) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    for (name, enabled) in FEATURES {
+        dict.set_item(name, enabled)?;
     }
+    // Not compiled in: scope= and search_rev() run the git CLI found on PATH
+    dict.set_item("git", git_available())?;
+    Ok(dict.into_py(py))
+}
 
-    /// Format count results for Python
-    fn format_count_results(&self, py: Python, counts: Vec<CountResult>) -> PyResult<PyObject> {
-        let dict = PyDict::new(py);
-        for count in counts {
-            dict.set_item(&count.path, count.count)?;
+/// Run Grep().search with `args` and `kwargs`, forcing `output_mode` when given
+fn search_with_default_grep(
+    py: Python,
+    function: &str,
+    args: &PyTuple,
+    kwargs: Option<&PyDict>,
+    output_mode: Option<&str>,
+) -> PyResult<PyObject> {
+    let kwargs = match kwargs {
+        Some(kwargs) => kwargs.copy()?,
+        None => PyDict::new(py),
+    };
+    if let Some(output_mode) = output_mode {
+        if kwargs.contains("output_mode")? {
+            return Err(PyTypeError::new_err(format!("{}() does not accept output_mode", function)));
         }
-        Ok(dict.into_py(py))
+        kwargs.set_item("output_mode", output_mode)?;
+    }
+    Py::new(py, Grep::default())?.call_method(py, "search", args, Some(kwargs))
+}
+
+/// An option from a merged_options() dict; None counts as not given
+fn flag<'a, T: FromPyObject<'a>>(options: &'a PyDict, name: &str) -> PyResult<Option<T>> {
+    options.get_item(name)?.filter(|value| !value.is_none()).map(|value| value.extract()).transpose()
+}
+
+/// search() options that decide how a file is decoded into lines, accepted by preview()
+const PREVIEW_OPTIONS: &[&str] =
+    &["encodings", "search_zip", "crlf", "null_data", "ignore_level", "lossy", "buffer_size", "heap_limit"];
+
+/// search_rev() keyword options
+const SEARCH_REV_OPTIONS: &[&str] = &["glob", "i", "type", "multiline", "timeout"];
+
+/// compare() keyword options
+const COMPARE_OPTIONS: &[&str] = &["glob", "i", "type", "multiline", "timeout", "threads"];
+
+/// watch() keyword options; debounce_ms is the only one search() lacks
+const WATCH_OPTIONS: &[&str] = &[
+    "glob",
+    "i",
+    "type",
+    "multiline",
+    "debounce_ms",
+    "encodings",
+    "lossy",
+    "ignore_level",
+    "hidden",
+    "search_git_dir",
+    "custom_ignore_filenames",
+    "ignore_file_paths",
+    "ignore_parents",
+];
+
+/// explain() keyword options: the search() options that decide which files the walk reaches
+const EXPLAIN_OPTIONS: &[&str] = &[
+    "glob",
+    "iglob",
+    "type",
+    "type_add",
+    "type_ignore_case",
+    "custom_ignore_filenames",
+    "ignore_file_paths",
+    "ignore_level",
+    "hidden",
+    "search_git_dir",
+    "one_file_system",
+    "ignore_parents",
+];
+
+/// search() options that decide how a pattern compiles, accepted by validate_pattern()
+const PATTERN_FLAGS: &[&str] = &[
+    "i",
+    "case_smart",
+    "unicode",
+    "multiline",
+    "x",
+    "crlf",
+    "null_data",
+    "engine",
+    "regex_size_limit",
+    "dfa_size_limit",
+    "backtrack_limit",
+    "strict_options",
+];
+
+/// search() options that would change the shape of the results a FileSet is built from
+const FILE_SET_REJECTED_OPTIONS: &[&str] = &["output_mode", "stats", "forensic", "profile", "metadata", "aggregate", "spill_over"];
+
+/// search() options that would change the shape or order of search_page() results
+const PAGE_REJECTED_OPTIONS: &[&str] = &[
+    "files",
+    "sort",
+    "sort_order",
+    "rank",
+    "head_limit",
+    "max_files",
+    "max_matches_total",
+    "max_bytes",
+    "truncation_warning",
+    "stats",
+    "forensic",
+    "profile",
+    "aggregate",
+    "metadata",
+    "relative_to",
+    "spill_over",
+];
+
+/// Split one chunk's search() results into (path, entry) pairs; count entries are the counts
+fn page_entries(results: &PyAny, output_mode: &OutputMode) -> PyResult<Vec<(String, PyObject)>> {
+    let py = results.py();
+    match output_mode {
+        OutputMode::Count => {
+            let counts: &PyDict = results.downcast()?;
+            counts.iter().map(|(path, count)| Ok((path.extract()?, count.into_py(py)))).collect()
+        }
+        OutputMode::Matches | OutputMode::FirstMatch => {
+            let records: Vec<&PyDict> = results.extract()?;
+            records
+                .into_iter()
+                .map(|record| {
+                    let path = record.get_item("path")?.ok_or_else(|| PyValueError::new_err("Match record is missing 'path'"))?;
+                    Ok((path.extract()?, record.into_py(py)))
+                })
+                .collect()
+        }
+        _ => {
+            let files: Vec<String> = results.extract()?;
+            Ok(files.into_iter().map(|file| (file.clone(), file.into_py(py))).collect())
+        }
+    }
+}
+
+/// Cursors are the hex-encoded JSON `[path, entries of that file already returned]`
+fn encode_page_cursor(path: &str, skip: usize) -> String {
+    let json = serde_json::json!([path, skip]).to_string();
+    json.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_page_cursor(cursor: &str) -> PyResult<(String, usize)> {
+    let invalid = || PyValueError::new_err(format!("Invalid cursor: {}", cursor));
+    if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+        return Err(invalid());
     }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| invalid())?;
+    serde_json::from_slice(&bytes).map_err(|_| invalid())
+}
+
+/// One-off search with a default Grep; takes the same arguments as Grep.search
+#[pyfunction]
+#[pyo3(name = "search", signature = (*args, **kwargs))]
+fn search_default(py: Python, args: &PyTuple, kwargs: Option<&PyDict>) -> PyResult<PyObject> {
+    search_with_default_grep(py, "search", args, kwargs, None)
+}
+
+/// List the files a search would look at, like `rg --files`
+#[pyfunction]
+#[pyo3(name = "files", signature = (path = None, **kwargs))]
+fn files_default(py: Python, path: Option<&str>, kwargs: Option<&PyDict>) -> PyResult<PyObject> {
+    search_with_default_grep(py, "files", PyTuple::new(py, [None, path]), kwargs, Some("files"))
+}
+
+/// Count matching lines per file, like `rg --count`
+#[pyfunction]
+#[pyo3(name = "count", signature = (pattern, path = None, **kwargs))]
+fn count_default(py: Python, pattern: &PyAny, path: Option<&str>, kwargs: Option<&PyDict>) -> PyResult<PyObject> {
+    search_with_default_grep(py, "count", PyTuple::new(py, [pattern.into_py(py), path.into_py(py)]), kwargs, Some("count"))
+}
+
+/// Compile a pattern without searching and report whether it is valid
+/// Returns the same keys as Grep.validate_pattern plus `look_around`
+#[pyfunction]
+#[pyo3(signature = (pattern, multiline = false, engine = "default"))]
+fn compile_check(py: Python, pattern: &str, multiline: bool, engine: &str) -> PyResult<PyObject> {
+    let options = Grep::pattern_options(
+        None, None, None, Some(multiline), None, None, false, Some(engine), None, None, None, true, &mut Vec::new(),
+    )?;
+    let dict = Grep::pattern_diagnostics(py, pattern, &options)?;
+    dict.set_item("look_around", Grep::needs_fancy_engine(pattern))?;
+    Ok(dict.into_py(py))
 }
 
 /// Python module definition
 #[pymodule]
-fn pyripgrep(_py: Python, m: &PyModule) -> PyResult<()> {
+fn pyripgrep(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Grep>()?;
+    m.add_class::<SearchStats>()?;
+    m.add_class::<SkipCounts>()?;
+    m.add_class::<TypeStats>()?;
+    m.add_class::<Profile>()?;
+    m.add_class::<SearchSession>()?;
+    m.add_class::<Pattern>()?;
+    m.add_class::<Workspace>()?;
+    m.add_class::<FileSet>()?;
+    m.add_class::<SpilledResults>()?;
+    m.add_class::<Watcher>()?;
+    m.add_class::<Explanation>()?;
+    m.add_function(wrap_pyfunction!(merge_results, m)?)?;
+    m.add_function(wrap_pyfunction!(features, m)?)?;
+    m.add_function(wrap_pyfunction!(compile_check, m)?)?;
+    m.add_function(wrap_pyfunction!(search_default, m)?)?;
+    m.add_function(wrap_pyfunction!(files_default, m)?)?;
+    m.add_function(wrap_pyfunction!(count_default, m)?)?;
+    m.add("PatternError", py.get_type::<PatternError>())?;
+    m.add("WalkError", py.get_type::<WalkError>())?;
+    m.add("IOErrorInfo", py.get_type::<IOErrorInfo>())?;
+    m.add("SearchTimeout", py.get_type::<SearchTimeout>())?;
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     Ok(())
 }
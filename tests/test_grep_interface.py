@@ -10,12 +10,14 @@ This test validates the exact interface as defined in the schema:
 
 import pytest
 import pyripgrep
+import io
 import os
 import tempfile
 import json
 import subprocess
 import shutil
 import time
+import warnings
 from typing import List, Dict, Union
 
 
@@ -1594,6 +1596,3053 @@ def test_function_{i}():  # MATCH
             content_lines = len([line for line in results if line != "--"])
             assert len(results) == content_lines + separator_count, "Total should include separators"
 
+    def test_preview_window(self):
+        """Test preview returns numbered lines around a target line"""
+        grep = pyripgrep.Grep()
+        main_py = os.path.join(self.tmpdir, "main.py")
+
+        window = grep.preview(main_py, 6, before=1, after=1)
+        assert window == [(5, ""), (6, "def main():"), (7, '    print("Hello World")')]
+
+        # Windows are clipped at the start and end of the file
+        head = grep.preview(main_py, 1, before=5, after=0)
+        assert head == [(1, "#!/usr/bin/env python3")]
+        tail = grep.preview(main_py, 15, before=0, after=10)
+        assert tail == [(15, '        print(f"ERROR: {msg}")')]
+
+        # Files content search would skip as binary raise instead of producing lines
+        binary_file = os.path.join(self.tmpdir, "invalid.txt")
+        with open(binary_file, 'wb') as f:
+            f.write(b"ERROR \xff\xfe\n")
+        with pytest.raises(pyripgrep.IOErrorInfo):
+            grep.preview(binary_file, 1)
+
+        with pytest.raises(ValueError):
+            grep.preview(main_py, 0)
+        with pytest.raises(ValueError):
+            grep.preview(os.path.join(self.tmpdir, "missing.py"), 1)
+
+    def test_merge_results(self):
+        """Test merging results from several searches"""
+        grep = pyripgrep.Grep()
+        main_py = os.path.join(self.tmpdir, "main.py")
+        utils_py = os.path.join(self.subdir, "utils.py")
+
+        # Overlapping roots produce duplicate paths that are collapsed and sorted
+        all_files = grep.search("error", path=self.tmpdir)
+        sub_files = grep.search("error", path=self.subdir)
+        merged = pyripgrep.merge_results([sub_files, all_files])
+        assert merged == sorted(set(all_files))
+
+        limited = pyripgrep.merge_results([sub_files, all_files], head_limit=1, truncation_warning=True)
+        assert limited == [sorted(all_files)[0], "[Content truncated]"]
+
+        # Count dicts are merged by path
+        counts = pyripgrep.merge_results([
+            grep.search("error", path=self.subdir, output_mode="count"),
+            grep.search("error", path=self.tmpdir, output_mode="count"),
+        ])
+        assert isinstance(counts, dict)
+        assert list(counts) == sorted(counts)
+        assert counts[utils_py] == 2
+
+        # Content lines from different patterns are interleaved by line number
+        defs = grep.search("def main", path=main_py, output_mode="content", n=True)
+        prints = grep.search("print", path=main_py, output_mode="content", n=True)
+        merged_content = pyripgrep.merge_results([prints, defs], output_mode="content")
+        assert merged_content == [
+            f"{main_py}:6:def main():",
+            f"{main_py}:7:    print(\"Hello World\")",
+            "--",
+            f"{main_py}:15:        print(f\"ERROR: {{msg}}\")",
+        ]
+
+        # Content without line numbers cannot be merged
+        plain = grep.search("def main", path=main_py, output_mode="content")
+        with pytest.raises(ValueError):
+            pyripgrep.merge_results([plain], output_mode="content")
+
+    def test_stats_parameter(self):
+        """Test stats=True returns a SearchStats object alongside results"""
+        grep = pyripgrep.Grep()
+
+        with open(os.path.join(self.tmpdir, "invalid.txt"), 'wb') as f:
+            f.write(b"ERROR \xff\xfe\n")
+
+        counts, stats = grep.search("ERROR", path=self.tmpdir, output_mode="count", stats=True)
+        assert counts == grep.search("ERROR", path=self.tmpdir, output_mode="count")
+        assert isinstance(stats, pyripgrep.SearchStats)
+        assert stats.files_matched == len(counts)
+        assert stats.matches == sum(counts.values())
+        assert stats.files_searched == 5
+        assert stats.files_skipped == 1
+        assert stats.bytes_searched == sum(
+            os.path.getsize(os.path.join(root, name))
+            for root, _, names in os.walk(self.tmpdir)
+            for name in names if name != "invalid.txt"
+        )
+        assert stats.elapsed >= 0
+        assert "files_searched=5" in repr(stats)
+
+        content, content_stats = grep.search("ERROR", path=self.tmpdir, output_mode="content", stats=True)
+        assert content_stats.matches == len([line for line in content if line != "--"])
+        assert content_stats.files_skipped == 1
+
+        # Results are returned bare without stats
+        assert isinstance(grep.search("ERROR", path=self.tmpdir, stats=False), list)
+
+    def test_errors_policy(self):
+        """Test errors parameter controls how unreadable files are reported"""
+        grep = pyripgrep.Grep()
+        invalid_file = os.path.join(self.tmpdir, "invalid.txt")
+        with open(invalid_file, 'wb') as f:
+            f.write(b"ERROR \xff\xfe\n")
+
+        # Default policy skips the file silently
+        ignored = grep.search("ERROR", path=self.tmpdir, output_mode="content")
+        assert sorted(ignored) == sorted(grep.search("ERROR", path=self.tmpdir, output_mode="content", errors="ignore"))
+        assert not any(invalid_file in line for line in ignored)
+
+        # Collect returns the failures alongside the results in every mode
+        for mode in ["content", "files_with_matches", "count"]:
+            results, errors = grep.search("ERROR", path=self.tmpdir, output_mode=mode, errors="collect")
+            assert len(results) > 0
+            assert len(errors) == 1
+            assert errors[0][0] == invalid_file
+            assert isinstance(errors[0][1], str) and errors[0][1]
+
+        # Stats come before errors when both are requested
+        files, stats, errors = grep.search("ERROR", path=self.tmpdir, stats=True, errors="collect")
+        assert isinstance(stats, pyripgrep.SearchStats)
+        assert stats.files_skipped == len(errors) == 1
+
+        with pytest.raises(ValueError, match="invalid.txt"):
+            grep.search("ERROR", path=self.tmpdir, errors="raise")
+
+        with pytest.raises(ValueError, match="Invalid errors policy"):
+            grep.search("ERROR", path=self.tmpdir, errors="explode")
+
+    def test_type_ignore_case(self):
+        """Test type_ignore_case lets type filters match uppercase extensions"""
+        grep = pyripgrep.Grep()
+        upper_file = os.path.join(self.tmpdir, "LEGACY.PY")
+        with open(upper_file, 'w') as f:
+            f.write("import legacy\n")
+
+        # Type globs are case-sensitive by default
+        default_results = grep.search("import", path=self.tmpdir, type="python")
+        assert upper_file not in default_results
+
+        results = grep.search("import", path=self.tmpdir, type="python", type_ignore_case=True)
+        assert upper_file in results
+        assert os.path.join(self.tmpdir, "main.py") in results
+
+        files = grep.search(path=self.tmpdir, output_mode="files", type="python", type_ignore_case=True)
+        assert upper_file in files
+        assert not any(f.endswith(".rs") for f in files)
+
+    def test_vimgrep_output_mode(self):
+        """Test vimgrep output mode produces path:line:column:content per match"""
+        grep = pyripgrep.Grep()
+        test_file = os.path.join(self.tmpdir, "vim.txt")
+        with open(test_file, 'w') as f:
+            f.write("no match here\nfoo and foo\n  foo\n")
+
+        results = grep.search("foo", path=test_file, output_mode="vimgrep")
+        assert results == [
+            f"{test_file}:2:1:foo and foo",
+            f"{test_file}:2:9:foo and foo",
+            f"{test_file}:3:3:  foo",
+        ]
+
+        limited = grep.search("foo", path=test_file, output_mode="vimgrep", head_limit=2, truncation_warning=True)
+        assert limited == results[:2] + ["[Content truncated]"]
+
+        # Entries from several files are never separated by "--"
+        everything = grep.search("ERROR", path=self.tmpdir, output_mode="vimgrep")
+        assert "--" not in everything
+        assert all(line.count(":") >= 3 for line in everything)
+
+        merged = pyripgrep.merge_results([results[::-1]], output_mode="vimgrep")
+        assert merged == results
+
+    def test_validate_pattern(self):
+        """Test validate_pattern reports structured diagnostics without searching"""
+        grep = pyripgrep.Grep()
+
+        ok = grep.validate_pattern(r"fn\s+\w+")
+        assert ok == {"valid": True, "error": None, "position": None, "span": None, "suggestion": None}
+
+        unclosed = grep.validate_pattern("foo(bar")
+        assert unclosed["valid"] is False
+        assert unclosed["position"] == 3
+        assert unclosed["span"] == (3, 4)
+        assert "')'" in unclosed["suggestion"]
+
+        lookbehind = grep.validate_pattern(r"(?<!self\.)foo")
+        assert lookbehind["valid"] is False
+        assert lookbehind["position"] == 0
+        assert "look-around" in lookbehind["suggestion"]
+
+        repetition = grep.validate_pattern("*foo")
+        assert repetition["valid"] is False
+        assert repetition["position"] == 0
+        assert "escape" in repetition["suggestion"]
+
+        # Validation agrees with search
+        with pytest.raises(ValueError, match="Invalid pattern"):
+            grep.search("foo(bar", path=self.tmpdir)
+        assert grep.validate_pattern("ERROR", i=True, multiline=True)["valid"] is True
+
+    def test_compiled_pattern_reuse(self):
+        """Test a compiled Pattern can be reused across searches"""
+        grep = pyripgrep.Grep()
+
+        compiled = grep.compile("error", i=True)
+        assert isinstance(compiled, pyripgrep.Pattern)
+        assert compiled.pattern == "error"
+        assert compiled.case_insensitive is True
+
+        # Same results as the equivalent string search, in any root and mode
+        for root in [self.tmpdir, self.subdir]:
+            assert sorted(grep.search(compiled, path=root)) == sorted(grep.search("error", path=root, i=True))
+        assert grep.search(compiled, path=self.tmpdir, output_mode="count") == \
+            grep.search("error", path=self.tmpdir, output_mode="count", i=True)
+
+        # Compiled type filters apply unless search() passes its own type
+        py_only = pyripgrep.Pattern("error", i=True, type="python")
+        assert all(f.endswith(".py") for f in grep.search(py_only, path=self.tmpdir))
+        rust_override = grep.search(py_only, path=self.tmpdir, type="rust")
+        assert rust_override == [os.path.join(self.tmpdir, "lib.rs")]
+
+        with pytest.raises(ValueError):
+            pyripgrep.Pattern("foo(")
+        with pytest.raises(ValueError):
+            grep.search(42, path=self.tmpdir)
+
+    def test_compare_roots(self):
+        """Test compare reports per-file deltas and added/removed matches"""
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as before, tempfile.TemporaryDirectory() as after:
+            with open(os.path.join(before, "a.py"), "w") as f:
+                f.write("old_api(1)\nold_api(2)\nkeep\n")
+            with open(os.path.join(before, "gone.py"), "w") as f:
+                f.write("old_api(3)\n")
+            # Same match, shifted down a line, plus one removed and one new file
+            with open(os.path.join(after, "a.py"), "w") as f:
+                f.write("header\nold_api(1)\nkeep\n")
+            with open(os.path.join(after, "new.py"), "w") as f:
+                f.write("old_api(4)\n")
+
+            diff = grep.compare(r"old_api\(", before, after)
+
+            assert diff["deltas"] == {
+                "a.py": (2, 1, -1),
+                "gone.py": (1, 0, -1),
+                "new.py": (0, 1, 1),
+            }
+            assert diff["added"] == [("new.py", 1, "old_api(4)")]
+            assert sorted(diff["removed"]) == [("a.py", 2, "old_api(2)"), ("gone.py", 1, "old_api(3)")]
+
+            unchanged = grep.compare("keep", before, before)
+            assert unchanged == {"deltas": {}, "added": [], "removed": []}
+
+    def test_explicit_files_list(self):
+        """Test files= searches exactly the given paths without walking"""
+        grep = pyripgrep.Grep()
+
+        main_py = os.path.join(self.tmpdir, "main.py")
+        lib_rs = os.path.join(self.tmpdir, "lib.rs")
+        results = grep.search("error", files=[main_py, lib_rs], i=True)
+        assert sorted(results) == sorted(r for r in grep.search("error", path=self.tmpdir, i=True)
+                                         if r in (main_py, lib_rs))
+
+        # Relative entries resolve against path; glob/type filters still apply
+        assert grep.search("error", path=self.tmpdir, files=["main.py", "lib.rs"], i=True, type="python") == [main_py]
+        assert grep.search("error", path=self.tmpdir, files=["main.py", "lib.rs"], i=True, glob="*.rs") == [lib_rs]
+        assert grep.search(path=self.tmpdir, files=["main.py"], output_mode="files") == [main_py]
+
+        # Missing files follow the errors policy instead of failing the search
+        missing = os.path.join(self.tmpdir, "deleted.py")
+        assert grep.search("error", files=[missing, main_py], i=True) == [main_py]
+        _, errors = grep.search("error", files=[missing], errors="collect")
+        assert [p for p, _ in errors] == [missing]
+        with pytest.raises(ValueError):
+            grep.search("error", files=[missing], errors="raise")
+
+    def test_walk_timeout(self):
+        """Test walk_timeout limits directory traversal separately from timeout"""
+        grep = pyripgrep.Grep()
+
+        # A generous traversal budget does not change results
+        assert sorted(grep.search("error", path=self.tmpdir, i=True, walk_timeout=60)) == \
+            sorted(grep.search("error", path=self.tmpdir, i=True))
+
+        # An exhausted budget fails with a traversal-specific error
+        with pytest.raises(TimeoutError, match="traversal too slow"):
+            grep.search("error", path=self.tmpdir, walk_timeout=0)
+        with pytest.raises(TimeoutError, match="traversal too slow"):
+            grep.search(path=self.tmpdir, output_mode="files", walk_timeout=0)
+
+    def test_search_zip(self):
+        """Test search_zip searches inside compressed files"""
+        import gzip, bz2, lzma
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as logs:
+            payload = b"INFO start\nERROR disk full\nINFO done\n"
+            for name, opener in [("app.log.gz", gzip.open), ("app.log.bz2", bz2.open), ("app.log.xz", lzma.open)]:
+                with opener(os.path.join(logs, name), "wb") as f:
+                    f.write(payload)
+
+            # Without search_zip the deflated bytes are not searched as text
+            assert grep.search("disk full", path=logs, glob="*.gz") == []
+
+            files = grep.search("disk full", path=logs, search_zip=True)
+            assert sorted(os.path.basename(f) for f in files) == ["app.log.bz2", "app.log.gz", "app.log.xz"]
+
+            counts = grep.search("INFO", path=logs, search_zip=True, output_mode="count")
+            assert sorted(counts.values()) == [2, 2, 2]
+
+            content = grep.search("ERROR", path=logs, search_zip=True, output_mode="content", n=True, glob="*.gz")
+            assert content == [f"{os.path.join(logs, 'app.log.gz')}:2:ERROR disk full"]
+
+    def test_matches_output_mode(self):
+        """Test matches mode reports each matched line's absolute byte range"""
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as tmp:
+            target = os.path.join(tmp, "crlf.txt")
+            data = "first\r\nneedle one\r\nmiddle\nneedle two\nlast".encode()
+            with open(target, "wb") as f:
+                f.write(data)
+
+            records = grep.search("needle", path=tmp, output_mode="matches")
+            assert [r["line_number"] for r in records] == [2, 4]
+            for r in records:
+                assert r["path"] == target
+                start, length = r["byte_offset"], r["byte_length"]
+                assert data[start:start + length].decode() == r["content"]
+            assert records[0]["byte_offset"] == len(b"first\r\n")
+
+            assert len(grep.search("needle", path=tmp, output_mode="matches", head_limit=1)) == 1
+
+            merged = pyripgrep.merge_results([records[1:], records], output_mode="matches")
+            assert merged == records
+
+    def test_preprocessor(self):
+        """Test preprocessor callables and commands transform files before matching"""
+        grep = pyripgrep.Grep()
+
+        seen = []
+        def upper(path):
+            seen.append(path)
+            with open(path) as f:
+                return f.read().upper()
+
+        # Only files that pass the filters are handed to the callable
+        files = grep.search("ERROR", path=self.tmpdir, type="python", preprocessor=upper)
+        assert files and all(f.endswith(".py") for f in files)
+        assert seen and all(p.endswith(".py") for p in seen)
+
+        lines = grep.search("HELLO", path=self.tmpdir, glob="main.py", output_mode="content", preprocessor=lambda p: b"HELLO bytes\n")
+        assert lines == [f"{os.path.join(self.tmpdir, 'main.py')}:HELLO bytes"]
+
+        # Commands receive the path as argument; their stdout is searched
+        assert grep.search("error", path=self.tmpdir, glob="*.rs", i=True, preprocessor="cat") == \
+            grep.search("error", path=self.tmpdir, glob="*.rs", i=True)
+
+        def broken(path):
+            raise RuntimeError("cannot extract")
+        _, errors = grep.search("x", path=self.tmpdir, glob="main.py", preprocessor=broken, errors="collect")
+        assert len(errors) == 1 and "cannot extract" in errors[0][1]
+
+        with pytest.raises(ValueError):
+            grep.search("x", path=self.tmpdir, preprocessor=42)
+
+    def test_file_headers(self):
+        """Test file_headers adds a per-file header with the match count"""
+        grep = pyripgrep.Grep()
+
+        lines = grep.search("error", path=self.tmpdir, i=True, output_mode="content", n=True, file_headers=True)
+        counts = grep.search("error", path=self.tmpdir, i=True, output_mode="count")
+
+        headers = [l for l in lines if l.startswith("== ")]
+        assert "--" not in lines
+        assert len(headers) == len(counts)
+        for path, count in counts.items():
+            noun = "match" if count == 1 else "matches"
+            assert f"== {path} ({count} {noun}) ==" in headers
+        assert lines[0].startswith("== ")
+
+        # Headers are dropped when merging, like separators
+        merged = pyripgrep.merge_results([lines], output_mode="content")
+        assert not any(l.startswith("== ") for l in merged)
+
+    def test_replace_preview(self):
+        """Test replace previews substitutions in content mode without editing files"""
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as tmp:
+            target = os.path.join(tmp, "calls.py")
+            original = "old_call(1)\nkeep old_call\nother\nold_call(2) + old_call(3)\n"
+            with open(target, "w") as f:
+                f.write(original)
+
+            lines = grep.search(r"old_call\((?P<arg>\d)\)", path=tmp, output_mode="content", n=True,
+                                replace="new_call(${arg}0)")
+            assert lines == [
+                f"{target}:1:new_call(10)",
+                "--",
+                f"{target}:4:new_call(20) + new_call(30)",
+            ]
+
+            # Numbered groups, and context lines are left untouched
+            lines = grep.search(r"(old)_call", path=tmp, output_mode="content", n=True, A=1, replace="${1}_fn")
+            assert f"{target}:2:keep old_fn" in lines
+            assert f"{target}-3:other" in lines
+
+            with open(target) as f:
+                assert f.read() == original
+
+    def test_order_within_file(self):
+        """Test order_within_file controls line order in content mode"""
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as tmp:
+            log = os.path.join(tmp, "app.log")
+            with open(log, "w") as f:
+                f.write("ERROR a\ninfo\nERROR b ERROR c\ninfo\nERROR d\n")
+
+            def line_numbers(lines):
+                return [int(l.split(":")[1]) for l in lines if l != "--"]
+
+            search = lambda **kw: grep.search("ERROR", path=tmp, output_mode="content", n=True, **kw)
+            assert line_numbers(search()) == [1, 3, 5]
+            assert line_numbers(search(order_within_file="line")) == [1, 3, 5]
+            assert line_numbers(search(order_within_file="reverse")) == [5, 3, 1]
+            assert line_numbers(search(order_within_file="relevance")) == [3, 1, 5]
+
+            # head_limit applies after ordering: newest entry only
+            assert search(order_within_file="reverse", head_limit=1) == [f"{log}:5:ERROR d"]
+
+            with pytest.raises(ValueError):
+                search(order_within_file="random")
+
+    def test_fancy_engine(self):
+        """Test engine="fancy" supports look-around and backreferences"""
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as tmp:
+            target = os.path.join(tmp, "calls.py")
+            with open(target, "w") as f:
+                f.write("self.foo()\nfoo()\nabab\n")
+
+            pattern = r"(?<!self\.)\bfoo\b"
+            with pytest.raises(ValueError, match="engine"):
+                grep.search(pattern, path=tmp)
+
+            assert grep.search(pattern, path=tmp, engine="fancy", output_mode="content", n=True) == [f"{target}:2:foo()"]
+            assert grep.search(pattern, path=tmp, engine="auto", output_mode="count") == {target: 1}
+            assert grep.search(r"(ab)\1", path=tmp, engine="fancy", output_mode="vimgrep") == [f"{target}:3:1:abab"]
+            assert grep.search(r"(?<!self\.)(?P<name>foo)", path=tmp, engine="fancy", output_mode="content",
+                               replace="bar_${name}") == [f"{target}:bar_foo()"]
+
+            compiled = grep.compile(r"(?<=self\.)FOO", i=True, engine="fancy")
+            assert compiled.engine == "fancy"
+            assert grep.search(compiled, path=tmp) == [target]
+
+            with pytest.raises(ValueError):
+                grep.search("foo", path=tmp, engine="pcre3")
+
+    def test_features(self):
+        """Test features() reports the optional capabilities of this build"""
+        features = pyripgrep.features()
+        assert set(features) >= {"decompression", "archives", "fancy_regex", "watch", "pcre2", "git", "tree_sitter", "index"}
+        assert all(isinstance(v, bool) for v in features.values())
+
+        # Reported capabilities match what the options actually accept
+        grep = pyripgrep.Grep()
+        if features["fancy_regex"]:
+            grep.search(r"(?<=a)b", path=self.tmpdir, engine="fancy")
+        else:
+            with pytest.raises(ValueError):
+                grep.search(r"(?<=a)b", path=self.tmpdir, engine="fancy")
+        if not features["decompression"]:
+            with pytest.raises(ValueError):
+                grep.search("x", path=self.tmpdir, search_zip=True)
+        if not features["archives"]:
+            with pytest.raises(ValueError):
+                grep.search("x", path=self.tmpdir, archives=True)
+
+    def test_line_regexp(self):
+        """Test x=True only matches patterns spanning the entire line"""
+        grep = pyripgrep.Grep()
+
+        with tempfile.TemporaryDirectory() as tmp:
+            target = os.path.join(tmp, "words.txt")
+            with open(target, "w") as f:
+                f.write("foo\nfoobar\nbar\nfoo bar\n")
+
+            lines = grep.search("foo|bar", path=tmp, output_mode="content", n=True, x=True)
+            assert lines == [f"{target}:1:foo", "--", f"{target}:3:bar"]
+            assert grep.search("foo|bar", path=tmp, output_mode="count", x=True) == {target: 2}
+            assert grep.search("FOO", path=tmp, output_mode="count", x=True, i=True) == {target: 1}
+            assert grep.search("foo|bar", path=tmp, output_mode="count", x=True, engine="fancy") == {target: 2}
+
+            compiled = grep.compile("foo", x=True)
+            assert compiled.line_regexp is True
+            assert grep.search(compiled, path=tmp, output_mode="count") == {target: 1}
+
+    def test_strict_options(self):
+        """Test strict_options=False degrades unsupported options with a warning"""
+        grep = pyripgrep.Grep()
+        features = pyripgrep.features()
+
+        # Supported options never produce warnings
+        _, stats = grep.search("error", path=self.tmpdir, i=True, strict_options=False, stats=True)
+        assert stats.warnings == []
+
+        # pcre2 is never compiled in, so every build exercises a fallback
+        assert features["pcre2"] is False
+        with pytest.raises(ValueError, match="pcre2"):
+            grep.search("error", path=self.tmpdir, engine="pcre2")
+        results, stats = grep.search("error", path=self.tmpdir, i=True, engine="pcre2",
+                                     strict_options=False, stats=True)
+        assert sorted(results) == sorted(grep.search("error", path=self.tmpdir, i=True))
+        assert len(stats.warnings) == 1 and "pcre2" in stats.warnings[0]
+        assert "default engine" in stats.warnings[0]
+
+        if not features["fancy_regex"]:
+            with pytest.raises(ValueError):
+                grep.search("error", path=self.tmpdir, engine="fancy")
+            results, stats = grep.search("error", path=self.tmpdir, i=True, engine="fancy",
+                                         strict_options=False, stats=True)
+            assert sorted(results) == sorted(grep.search("error", path=self.tmpdir, i=True))
+            assert any("fancy" in w for w in stats.warnings)
+        if not features["decompression"]:
+            _, stats = grep.search("error", path=self.tmpdir, search_zip=True, strict_options=False, stats=True)
+            assert any("search_zip" in w for w in stats.warnings)
+
+    def test_null_data(self):
+        """Test null_data=True splits records on NUL bytes"""
+        grep = pyripgrep.Grep()
+        dump = os.path.join(self.tmpdir, "paths.bin")
+        with open(dump, "wb") as f:
+            f.write(b"src/main.py\0docs/error handling.md\0lib/errors.rs\0")
+
+        results = grep.search("error", path=dump, output_mode="content", n=True, null_data=True)
+        assert results == [f"{dump}:2:docs/error handling.md", f"{dump}:3:lib/errors.rs"]
+
+        counts = grep.search("error", path=dump, output_mode="count", null_data=True)
+        assert counts == {dump: 2}
+
+        # Anchors apply per record
+        assert grep.search("^lib/", path=dump, output_mode="content", null_data=True) == [f"{dump}:lib/errors.rs"]
+
+        # Without null_data the whole dump is a single line
+        assert grep.search("error", path=dump, output_mode="count") == {dump: 1}
+
+    def test_crlf(self):
+        """Test crlf=True makes $ match before \\r\\n line endings"""
+        grep = pyripgrep.Grep()
+        windows_file = os.path.join(self.tmpdir, "windows.c")
+        with open(windows_file, "wb") as f:
+            f.write(b"int x = 1;\r\nif (x) {\r\n}\r\n")
+
+        assert grep.search(";$", path=windows_file) == []
+        assert grep.search(";$", path=windows_file, crlf=True) == [windows_file]
+        assert grep.search("[;{]$", path=windows_file, output_mode="count", crlf=True) == {windows_file: 2}
+
+        # Compiled patterns pick up CRLF handling too
+        pattern = pyripgrep.Pattern(";$")
+        assert grep.search(pattern, path=windows_file, crlf=True) == [windows_file]
+
+        # Neither matched nor context lines carry a trailing \r
+        results = grep.search(r"\{$", path=windows_file, output_mode="content", C=1, crlf=True)
+        assert results == [f"{windows_file}:int x = 1;", f"{windows_file}:if (x) {{", f"{windows_file}:}}"]
+
+    def test_encodings(self):
+        """Test per-glob encodings transcode non-UTF-8 files before matching"""
+        grep = pyripgrep.Grep()
+        resource = os.path.join(self.tmpdir, "app.rc")
+        with open(resource, "wb") as f:
+            f.write("IDS_ERROR \"Fehler beim Öffnen\"\n".encode("utf-16le"))
+        export = os.path.join(self.tmpdir, "export.csv")
+        with open(export, "wb") as f:
+            f.write("id,name\n1,café error\n".encode("latin-1"))
+
+        encodings = {"*.rc": "utf-16le", "*.csv": "latin-1"}
+        results = grep.search("Öffnen|café", path=self.tmpdir, output_mode="content", encodings=encodings)
+        matches = sorted(line for line in results if line != "--")
+        assert matches == sorted([f'{resource}:IDS_ERROR "Fehler beim Öffnen"', f"{export}:1,café error"])
+
+        # Without encodings neither file decodes as UTF-8
+        assert grep.search("Öffnen|café", path=self.tmpdir) == []
+
+        with pytest.raises(ValueError, match="Unknown encoding"):
+            grep.search("error", path=self.tmpdir, encodings={"*.rc": "no-such-encoding"})
+
+    def test_stats_skipped_breakdown(self):
+        """Test stats.skipped breaks skipped entries down by cause"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "skips")
+        os.makedirs(os.path.join(root, "build"))
+        files = {
+            ".ignore": "build/\n*.log\n",
+            "keep.py": "error\n",
+            "other.rs": "error\n",
+            "debug.log": "error\n",
+            ".env": "error\n",
+            os.path.join("build", "gen.py"): "error\n",
+        }
+        for name, content in files.items():
+            with open(os.path.join(root, name), "w") as f:
+                f.write(content)
+        with open(os.path.join(root, "blob.py"), "wb") as f:
+            f.write(b"error \xff\xfe\n")
+
+        _, stats = grep.search("error", path=root, stats=True)
+        skipped = stats.skipped
+        assert (skipped.gitignore, skipped.hidden, skipped.binary) == (2, 2, 1)
+        assert (skipped.type_filter, skipped.glob, skipped.errors) == (0, 0, 0)
+        assert stats.files_skipped == 1
+
+        _, stats = grep.search("error", path=root, type="python", stats=True)
+        assert stats.skipped.type_filter == 1
+
+        _, stats = grep.search("error", path=root, glob="*.py", output_mode="content", stats=True)
+        # Glob overrides take precedence, so hidden non-.py files count as glob skips
+        assert (stats.skipped.glob, stats.skipped.hidden) == (4, 0)
+
+    def test_sort(self):
+        """Test sort= orders files consistently across output modes"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "sorted")
+        os.makedirs(root)
+        sizes = {"b.txt": 3, "a.txt": 1, "c.txt": 2}
+        for age, (name, lines) in enumerate(sizes.items()):
+            path = os.path.join(root, name)
+            with open(path, "w") as f:
+                f.write("match\n" * lines)
+            os.utime(path, (1_000_000 + age, 1_000_000 + age))
+        by_path = [os.path.join(root, n) for n in ("a.txt", "b.txt", "c.txt")]
+
+        assert grep.search("match", path=root, sort="path") == by_path
+        assert grep.search("match", path=root, sort="path", sort_order="desc") == by_path[::-1]
+        assert grep.search("match", path=root, output_mode="files", sort="path") == by_path
+        assert list(grep.search("match", path=root, output_mode="count", sort="path")) == by_path
+
+        by_mtime = [os.path.join(root, n) for n in ("b.txt", "a.txt", "c.txt")]
+        assert grep.search("match", path=root, sort="modified") == by_mtime
+        by_size = [os.path.join(root, n) for n in ("a.txt", "c.txt", "b.txt")]
+        assert grep.search("match", path=root, sort="size") == by_size
+
+        # Content blocks follow the same file order, and head_limit truncates deterministically
+        content = grep.search("match", path=root, output_mode="content", sort="size", sort_order="desc")
+        assert [line.split(":")[0] for line in content if line != "--"] == [by_size[2]] * 3 + [by_size[1]] * 2 + [by_size[0]]
+        assert grep.search("match", path=root, sort="path", head_limit=1) == by_path[:1]
+
+        with pytest.raises(ValueError, match="Invalid sort"):
+            grep.search("match", path=root, sort="name")
+        with pytest.raises(ValueError, match="Invalid sort_order"):
+            grep.search("match", path=root, sort="path", sort_order="up")
+
+    def test_incremental_session(self):
+        """Test Grep.start() sessions return the same results as search() across steps"""
+        grep = pyripgrep.Grep()
+
+        session = grep.start("error", path=self.tmpdir, output_mode="content", n=True)
+        assert not session.done
+        stepped = []
+        steps = 0
+        while not session.done:
+            stepped.extend(session.step(max_millis=0))  # one file per step
+            steps += 1
+        expected = grep.search("error", path=self.tmpdir, output_mode="content", n=True)
+        assert sorted(l for l in stepped if l != "--") == sorted(l for l in expected if l != "--")
+        assert steps > 1
+        assert session.step() == []
+        assert session.stats.files_matched == len(grep.search("error", path=self.tmpdir))
+
+        session = grep.start("error", path=self.tmpdir, output_mode="count", i=True)
+        counts = {}
+        while not session.done:
+            counts.update(session.step())
+        assert counts == grep.search("error", path=self.tmpdir, output_mode="count", i=True)
+        assert session.stats.matches == sum(counts.values())
+
+        session = grep.start(path=self.tmpdir, output_mode="files")
+        listed = []
+        while not session.done:
+            listed.extend(session.step(max_millis=1000))
+        assert sorted(listed) == sorted(grep.search(path=self.tmpdir, output_mode="files"))
+
+    def test_threads(self):
+        """Test the worker pool returns the same results as a sequential search"""
+        grep = pyripgrep.Grep()
+        for mode in ["files_with_matches", "content", "count", "vimgrep", "matches"]:
+            sequential = grep.search("error", path=self.tmpdir, output_mode=mode, i=True, sort="path", threads=1)
+            for threads, queue_size in [(4, None), (3, 1)]:
+                parallel = grep.search("error", path=self.tmpdir, output_mode=mode, i=True, sort="path",
+                                       threads=threads, queue_size=queue_size)
+                assert parallel == sequential, mode
+
+        # Per-file stats from every worker are merged
+        _, seq_stats = grep.search("error", path=self.tmpdir, i=True, output_mode="count", threads=1, stats=True)
+        _, par_stats = grep.search("error", path=self.tmpdir, i=True, output_mode="count", threads=4, stats=True)
+        assert (par_stats.files_searched, par_stats.matches) == (seq_stats.files_searched, seq_stats.matches)
+
+        assert len(grep.search("error", path=self.tmpdir, i=True, threads=4, head_limit=2)) == 2
+
+    def test_threads_file_list_and_compare(self):
+        """Test threads= fans out files= lists in list order and applies to compare"""
+        grep = pyripgrep.Grep()
+
+        listed = list(reversed(grep.search(path=self.tmpdir, output_mode="files", sort="path")))
+        sequential = grep.search("error", files=listed, output_mode="content", i=True, threads=1)
+        assert sequential
+        assert grep.search("error", files=listed, output_mode="content", i=True, threads=4) == sequential
+
+        before = grep.compare("error", self.tmpdir, self.tmpdir, i=True, threads=1)
+        assert grep.compare("error", self.tmpdir, self.tmpdir, i=True, threads=4) == before
+
+    def test_expand_vars(self):
+        """Test expand_vars=True expands ~ and environment variables in path inputs"""
+        grep = pyripgrep.Grep()
+        os.environ["PYRIPGREP_TEST_ROOT"] = self.tmpdir
+        os.environ["PYRIPGREP_TEST_EXT"] = "py"
+        try:
+            expected = grep.search("error", path=self.tmpdir, glob="*.py")
+            assert expected
+            assert grep.search("error", path="$PYRIPGREP_TEST_ROOT", glob="*.${PYRIPGREP_TEST_EXT}",
+                               expand_vars=True) == expected
+            assert grep.search("error", files=["$PYRIPGREP_TEST_ROOT/main.py"],
+                               expand_vars=True) == [os.path.join(self.tmpdir, "main.py")]
+
+            # Without the flag the value is taken literally
+            with pytest.raises(ValueError, match="Path not found"):
+                grep.search("error", path="$PYRIPGREP_TEST_ROOT")
+        finally:
+            del os.environ["PYRIPGREP_TEST_ROOT"]
+            del os.environ["PYRIPGREP_TEST_EXT"]
+
+        home = os.path.expanduser("~")
+        rel = os.path.relpath(self.tmpdir, home)
+        if not rel.startswith(".."):
+            assert grep.search("error", path=os.path.join("~", rel), expand_vars=True) == \
+                grep.search("error", path=self.tmpdir)
+
+    def test_match_limits(self):
+        """Test max_files / max_matches_total / max_matches_per_file mean the same in every mode"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "limits")
+        os.makedirs(root)
+        for name, lines in (("a.txt", 3), ("b.txt", 2), ("c.txt", 1)):
+            with open(os.path.join(root, name), "w") as f:
+                f.write("hit hit\n" * lines)
+        paths = [os.path.join(root, n) for n in ("a.txt", "b.txt", "c.txt")]
+
+        assert grep.search("hit", path=root, sort="path", max_files=2) == paths[:2]
+        assert grep.search("hit", path=root, output_mode="files", sort="path", max_files=1) == paths[:1]
+        counts = grep.search("hit", path=root, output_mode="count", sort="path", max_matches_per_file=2)
+        assert counts == {paths[0]: 2, paths[1]: 2, paths[2]: 1}
+        counts = grep.search("hit", path=root, output_mode="count", sort="path", max_matches_total=4)
+        assert counts == {paths[0]: 3, paths[1]: 1}
+
+        # Matches are lines: context lines and several hits on one line count once
+        content = grep.search("hit", path=root, output_mode="content", sort="path", n=True, C=1,
+                              max_matches_total=4, truncation_warning=True)
+        assert [line for line in content if line != "--"] == [f"{paths[0]}:{n}:hit hit" for n in (1, 2, 3)] + [
+            f"{paths[1]}:1:hit hit", f"{paths[1]}-2:hit hit", "[Content truncated]"]
+        vimgrep = grep.search("hit", path=root, output_mode="vimgrep", sort="path", max_matches_per_file=1)
+        assert vimgrep == [f"{p}:1:{col}:hit hit" for p in paths for col in (1, 5)]
+        records = grep.search("hit", path=root, output_mode="matches", sort="path", max_files=1, max_matches_per_file=2)
+        assert [r["line_number"] for r in records] == [1, 2]
+
+        with pytest.warns(DeprecationWarning, match="head_limit"):
+            assert grep.search("hit", path=root, sort="path", head_limit=1) == paths[:1]
+
+    def test_group_by_file(self):
+        """Test group_by_file returns per-file match records in content mode"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "grouped")
+        os.makedirs(root)
+        with open(os.path.join(root, "a.txt"), "w") as f:
+            f.write("one\nhit\ntwo\nthree\nhit hit\n")
+        with open(os.path.join(root, "b.txt"), "w") as f:
+            f.write("hit\n")
+        a, b = os.path.join(root, "a.txt"), os.path.join(root, "b.txt")
+
+        grouped = grep.search("hit", path=root, output_mode="content", C=1, sort="path", group_by_file=True)
+        assert list(grouped) == [a, b]
+        assert grouped[a] == [
+            {"line_number": 2, "end_line_number": 2, "content": "hit", "before_context": ["one"], "after_context": ["two"]},
+            {"line_number": 5, "end_line_number": 5, "content": "hit hit", "before_context": ["three"], "after_context": []},
+        ]
+        assert grouped[b] == [
+            {"line_number": 1, "end_line_number": 1, "content": "hit", "before_context": [], "after_context": []}
+        ]
+
+        reverse = grep.search("hit", path=a, output_mode="content", order_within_file="reverse", group_by_file=True)
+        assert [m["line_number"] for m in reverse[a]] == [5, 2]
+        assert grep.search("missing", path=root, output_mode="content", group_by_file=True) == {}
+
+    def test_forensic(self):
+        """Test forensic=True accounts for every candidate file: searched or listed with a reason"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "forensic")
+        os.makedirs(os.path.join(root, "build"))
+        for name in (".ignore", "keep.py", "miss.py", "other.rs", ".env", os.path.join("build", "gen.py")):
+            with open(os.path.join(root, name), "w") as f:
+                f.write("build/\n" if name == ".ignore" else "error\n" if name != "miss.py" else "fine\n")
+        with open(os.path.join(root, "blob.py"), "wb") as f:
+            f.write(b"error \xff\xfe\n")
+
+        results, stats, skips = grep.search("error", path=root, type="python", sort="path", forensic=True)
+        assert results == [os.path.join(root, "keep.py")]
+        reasons = {os.path.relpath(path, root): reason for path, reason in skips}
+        assert reasons["build"] == "skipped: ignored by ignore rules"
+        assert reasons[".env"] == reasons[".ignore"] == "skipped: hidden"
+        assert reasons["other.rs"] == "skipped: not selected by type filter"
+        assert "blob.py" in reasons
+        # Searched files plus the skip list cover the whole directory
+        assert stats.files_searched + len(skips) == len(os.listdir(root))
+
+        with pytest.raises(ValueError, match="errors='collect'"):
+            grep.search("error", path=root, forensic=True, errors="ignore")
+        with pytest.raises(ValueError, match="result limits"):
+            grep.search("error", path=root, forensic=True, max_files=1)
+
+    def test_memory_limits(self):
+        """Test buffer_size, heap_limit and regex/DFA size limits"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "memory")
+        os.makedirs(root)
+        short, long = os.path.join(root, "short.txt"), os.path.join(root, "long.txt")
+        with open(short, "w") as f:
+            f.write("needle\n" * 100)
+        with open(long, "w") as f:
+            f.write("x" * 10000 + " needle\n")
+
+        assert grep.search("needle", path=root, sort="path", buffer_size=16) == [long, short]
+        assert len(grep.search("needle", path=short, output_mode="content", buffer_size=1)) == 100
+
+        # A line longer than heap_limit fails that file instead of being buffered whole
+        for mode in ("files_with_matches", "content"):
+            results, errors = grep.search("needle", path=root, output_mode=mode, heap_limit=1024, errors="collect")
+            assert [path for path, _ in errors] == [long]
+            assert all(long not in line for line in results)
+
+        with pytest.raises(ValueError, match="Invalid pattern"):
+            grep.search(r"\w{100}", path=root, regex_size_limit=100)
+        assert grep.search("needle", path=root, regex_size_limit=1 << 20, dfa_size_limit=1 << 20)
+
+    def test_unicode_and_case_smart(self):
+        """Test unicode=False ASCII classes and case_smart/i interplay"""
+        grep = pyripgrep.Grep()
+        path = os.path.join(self.tmpdir, "unicode.txt")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write("café\nError here\nerror there\n")
+
+        assert grep.search(r"caf\w\b", path=path, output_mode="content") == [path + ":café"]
+        assert grep.search(r"caf\w\b", path=path, output_mode="content", unicode=False) == []
+
+        # Lowercase pattern: ignore case; any uppercase literal: match case exactly
+        assert len(grep.search("error", path=path, output_mode="content", case_smart=True)) == 2
+        assert grep.search("Error", path=path, output_mode="content", case_smart=True) == [path + ":Error here"]
+        # Escapes such as \S are not uppercase literals, and i=True always ignores case
+        assert len(grep.search(r"error\S*", path=path, output_mode="content", case_smart=True)) == 2
+        assert len(grep.search("Error", path=path, output_mode="content", case_smart=True, i=True)) == 2
+
+        pattern = pyripgrep.Pattern("error", case_smart=True, unicode=False)
+        assert (pattern.case_smart, pattern.unicode) == (True, False)
+        assert len(grep.search(pattern, path=path, output_mode="content")) == 2
+
+        if pyripgrep.features()["fancy_regex"]:
+            assert grep.search("(?<=r )here", path=path, output_mode="content", engine="fancy", case_smart=True) == [path + ":Error here"]
+            assert len(grep.search("(?<=r )t?HERE", path=path, output_mode="content", engine="fancy", i=True)) == 2
+            with pytest.raises(ValueError, match="unicode=False"):
+                grep.search("(?<=r )here", path=path, engine="fancy", unicode=False)
+
+    def test_quiet_mode(self):
+        """Test output_mode="quiet" returns a bool and stops at the first match"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "quiet")
+        os.makedirs(root)
+        for i in range(20):
+            with open(os.path.join(root, f"f{i:02}.txt"), "w") as f:
+                f.write("old_api()\n")
+
+        assert grep.search("old_api", path=root, output_mode="quiet") is True
+        assert grep.search("new_api", path=root, output_mode="quiet") is False
+
+        found, stats = grep.search("old_api", path=root, output_mode="quiet", sort="path", threads=1, stats=True)
+        assert found is True
+        assert stats.files_searched == 1
+
+        session = grep.start("old_api", path=root, output_mode="quiet")
+        assert session.step() is True
+        assert session.done
+        assert pyripgrep.merge_results([False, True]) is True
+
+    def test_heading_and_separators(self):
+        """Test context_separator, heading and no_filename content formatting"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "heading")
+        os.makedirs(root)
+        a, b = os.path.join(root, "a.txt"), os.path.join(root, "b.txt")
+        with open(a, "w") as f:
+            f.write("hit\nx\nx\nx\nhit\n")
+        with open(b, "w") as f:
+            f.write("ctx\nhit\n")
+
+        lines = grep.search("hit", path=root, output_mode="content", n=True, sort="path", context_separator="...")
+        assert lines == [f"{a}:1:hit", "...", f"{a}:5:hit", "...", f"{b}:2:hit"]
+
+        lines = grep.search("hit", path=root, output_mode="content", n=True, B=1, sort="path", heading=True)
+        assert lines == [a, "1:hit", "--", "4-x", "5:hit", "", b, "1-ctx", "2:hit"]
+
+        lines = grep.search("hit", path=b, output_mode="content", n=True, B=1, no_filename=True)
+        assert lines == ["1-ctx", "2:hit"]
+        assert grep.search("hit", path=b, output_mode="content", no_filename=True) == ["hit"]
+
+    def test_passthru(self):
+        """Test passthru=True emits every line of matching files with matches flagged"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "passthru")
+        os.makedirs(root)
+        a = os.path.join(root, "a.txt")
+        with open(a, "w") as f:
+            f.write("one\nhit\ntwo\nthree\nhit\nfour\n")
+        with open(os.path.join(root, "b.txt"), "w") as f:
+            f.write("nothing here\n")
+
+        lines = grep.search("hit", path=root, output_mode="content", n=True, passthru=True)
+        assert lines == [f"{a}-1:one", f"{a}:2:hit", f"{a}-3:two", f"{a}-4:three", f"{a}:5:hit", f"{a}-6:four"]
+
+        lines = grep.search("hit", path=a, output_mode="content", n=True, passthru=True, heading=True)
+        assert lines == [a, "1-one", "2:hit", "3-two", "4-three", "5:hit", "6-four"]
+
+    def test_color(self):
+        """Test color= wraps matches in ANSI codes or custom markers in content mode"""
+        grep = pyripgrep.Grep()
+        path = os.path.join(self.tmpdir, "color.txt")
+        with open(path, "w") as f:
+            f.write("foo bar foo\nctx\n")
+
+        lines = grep.search("foo", path=path, output_mode="content", color="ansi")
+        assert lines == [path + ":\x1b[1;31mfoo\x1b[0m bar \x1b[1;31mfoo\x1b[0m"]
+        lines = grep.search("foo", path=path, output_mode="content", A=1, color=("<<", ">>"), no_filename=True)
+        assert lines == ["<<foo>> bar <<foo>>", "ctx"]
+        # Highlighting wraps the replacement when combined with replace=
+        lines = grep.search(r"f(o+)", path=path, output_mode="content", replace="$1", color=("[", "]"), no_filename=True)
+        assert lines == ["[oo] bar [oo]"]
+        assert grep.search("foo", path=path, output_mode="content", color="never", no_filename=True) == ["foo bar foo"]
+
+        with pytest.raises(ValueError, match="Invalid color"):
+            grep.search("foo", path=path, output_mode="content", color="red")
+        with pytest.raises(ValueError, match="color must be"):
+            grep.search("foo", path=path, output_mode="content", color=42)
+
+    def test_search_stream(self):
+        """Test search_stream reads a binary file-like object in chunks"""
+        grep = pyripgrep.Grep()
+        data = b"alpha\nbeta\r\ngamma beta\n"
+
+        assert grep.search_stream("beta", io.BytesIO(data)) == ["beta", "gamma beta"]
+        assert grep.search_stream("beta", io.BytesIO(data), n=True, B=1) == ["1-alpha", "2:beta", "3:gamma beta"]
+        assert grep.search_stream("beta", io.BytesIO(data), output_mode="count") == {"<stdin>": 2}
+        assert grep.search_stream("beta", io.BytesIO(data), output_mode="vimgrep", label="log") == [
+            "log:2:1:beta",
+            "log:3:7:gamma beta",
+        ]
+        records = grep.search_stream("gamma", io.BytesIO(data), output_mode="matches")
+        assert [(r["line_number"], r["byte_offset"]) for r in records] == [(3, 12)]
+        assert grep.search_stream("zzz", io.BytesIO(data), output_mode="files_with_matches") == []
+        assert grep.search_stream("beta", io.BytesIO(data), output_mode="quiet") is True
+
+        # Chunks smaller than a line are stitched back together
+        big = b"x" * 100000 + b"needle\n"
+        assert grep.search_stream("needle", io.BytesIO(big), output_mode="count", buffer_size=7) == {"<stdin>": 1}
+
+        class Broken:
+            def read(self, n):
+                raise OSError("pipe closed")
+
+        with pytest.raises(OSError, match="pipe closed"):
+            grep.search_stream("a", Broken())
+        with pytest.raises(ValueError, match="binary mode"):
+            grep.search_stream("a", io.StringIO("a"))
+        with pytest.raises(ValueError, match="cannot search a stream"):
+            grep.search_stream("a", io.BytesIO(data), output_mode="files")
+
+    def test_workspace(self):
+        """Test Workspace caches the walked file list until refresh()"""
+        root = os.path.join(self.tmpdir, "ws")
+        os.makedirs(os.path.join(root, "src"))
+        with open(os.path.join(root, ".ignore"), "w") as f:
+            f.write("skipped.txt\n")
+        for name, text in [("src/a.py", "foo\n"), ("b.txt", "foo bar\n"), ("skipped.txt", "foo\n")]:
+            with open(os.path.join(root, name), "w") as f:
+                f.write(text)
+
+        ws = pyripgrep.Workspace(root)
+        assert sorted(ws.files) == ["b.txt", os.path.join("src", "a.py")]
+        assert len(ws) == 2
+        grep = pyripgrep.Grep()
+        assert sorted(ws.search("foo")) == sorted(grep.search("foo", path=root))
+        assert ws.search("foo", output_mode="count", type="python") == {os.path.join(root, "src", "a.py"): 1}
+
+        # New files are only seen after refresh()
+        with open(os.path.join(root, "c.txt"), "w") as f:
+            f.write("foo\n")
+        assert os.path.join(root, "c.txt") not in ws.search("foo")
+        assert ws.refresh() == 3
+        assert os.path.join(root, "c.txt") in ws.search("foo")
+
+        with pytest.raises(ValueError, match="does not accept path"):
+            ws.search("foo", path=root)
+        with pytest.raises(ValueError, match="must be a directory"):
+            pyripgrep.Workspace(os.path.join(root, "b.txt"))
+
+    def test_watch(self):
+        """Test watch() reports matches added and removed by file changes"""
+        if not pyripgrep.features()["watch"]:
+            with pytest.raises(ValueError, match="'watch' feature"):
+                pyripgrep.Grep().watch("foo", self.tmpdir, print)
+            return
+        root = os.path.join(self.tmpdir, "watched")
+        os.makedirs(root)
+        existing = os.path.join(root, "a.txt")
+        with open(existing, "w") as f:
+            f.write("foo 1\n")
+        events = []
+
+        def wait_for(count):
+            deadline = time.time() + 5
+            while len(events) < count and time.time() < deadline:
+                time.sleep(0.05)
+
+        with pyripgrep.Grep().watch("foo", root, events.append, debounce_ms=20) as watcher:
+            assert watcher.running
+            with open(existing, "w") as f:
+                f.write("bar\nfoo 1\nfoo 2\n")
+            wait_for(1)
+            # The unchanged "foo 1" only moved, so just the new line is reported
+            assert events[0] == {"path": existing, "added": [(3, "foo 2")], "removed": []}
+
+            created = os.path.join(root, "b.txt")
+            with open(created, "w") as f:
+                f.write("foo new\n")
+            wait_for(2)
+            assert events[1] == {"path": created, "added": [(1, "foo new")], "removed": []}
+
+            os.remove(existing)
+            wait_for(3)
+            assert events[2]["removed"] == [(2, "foo 1"), (3, "foo 2")]
+        assert not watcher.running
+
+        def broken(event):
+            raise RuntimeError("callback failed")
+
+        watcher = pyripgrep.Grep().watch("foo", root, broken, debounce_ms=20)
+        with open(created, "a") as f:
+            f.write("foo again\n")
+        deadline = time.time() + 5
+        while watcher.running and time.time() < deadline:
+            time.sleep(0.05)
+        with pytest.raises(RuntimeError, match="callback failed"):
+            watcher.stop()
+
+    def test_multiline_spans(self):
+        """Test multiline=True content results cover every line a match spans"""
+        grep = pyripgrep.Grep()
+        path = os.path.join(self.tmpdir, "spans.rs")
+        with open(path, "w") as f:
+            f.write("a\nstruct Foo {\n  x: u8,\n}\nb\n")
+        pattern = r"struct \w+ \{[^}]*\}"
+
+        lines = grep.search(pattern, path=path, output_mode="content", multiline=True, n=True, B=1, A=1)
+        assert lines == [
+            path + "-1:a",
+            path + ":2:struct Foo {",
+            path + ":3:  x: u8,",
+            path + ":4:}",
+            path + "-5:b",
+        ]
+        grouped = grep.search(pattern, path=path, output_mode="content", multiline=True, group_by_file=True)
+        assert grouped[path] == [{
+            "line_number": 2,
+            "end_line_number": 4,
+            "content": "struct Foo {\n  x: u8,\n}",
+            "before_context": [],
+            "after_context": [],
+        }]
+        # Without multiline the same pattern cannot cross a line break
+        assert grep.search(pattern, path=path, output_mode="content") == []
+
+    def test_max_columns(self):
+        """Test max_columns omits or previews long content lines like rg -M"""
+        grep = pyripgrep.Grep()
+        path = os.path.join(self.tmpdir, "minified.js")
+        long_line = "foo" + "x" * 50 + "foo" + "y" * 50 + "foo"
+        with open(path, "w") as f:
+            f.write("z" * 80 + "\nfoo short\n" + long_line + "\n")
+
+        lines = grep.search("foo", path=path, output_mode="content", max_columns=20, no_filename=True, B=1)
+        assert lines == ["[Omitted long context line]", "foo short", "[Omitted long line with 3 matches]"]
+        lines = grep.search(
+            "foo", path=path, output_mode="content", max_columns=10, max_columns_preview=True,
+            no_filename=True, B=1, color=("<", ">"),
+        )
+        assert lines == [
+            "z" * 10 + " [... omitted end of long line]",
+            "<foo> short",
+            "<foo>" + "x" * 7 + " [... 2 more matches]",
+        ]
+        # Short lines and other output modes are unaffected
+        assert grep.search("foo", path=path, output_mode="count", max_columns=5) == {path: 2}
+
+    def test_canonical_paths(self):
+        """Test canonical_paths=True dedupes files reached through several paths"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "canon")
+        os.makedirs(os.path.join(root, "src"))
+        with open(os.path.join(root, "src", "a.py"), "w") as f:
+            f.write("foo\n")
+        os.symlink(os.path.join("src", "a.py"), os.path.join(root, "link.py"))
+        spellings = ["src/a.py", "./src/a.py", os.path.join(root, "src", "a.py"), "link.py"]
+
+        cwd = os.getcwd()
+        os.chdir(root)
+        try:
+            assert len(grep.search("foo", files=spellings)) == 4
+            files, stats = grep.search("foo", files=spellings, canonical_paths=True, stats=True)
+            assert files == [os.path.join("src", "a.py")]
+            assert stats.skipped.duplicate == 3
+            # The reported path does not depend on how the root was written
+            for root_spelling in [".", "./src/../src", root]:
+                assert grep.search("foo", path=root_spelling, canonical_paths=True) == [os.path.join("src", "a.py")]
+            # Outside the working directory paths stay absolute
+            os.makedirs(os.path.join(self.tmpdir, "elsewhere"))
+            os.chdir(os.path.join(self.tmpdir, "elsewhere"))
+            expected = [os.path.realpath(os.path.join(root, "src", "a.py"))]
+            assert grep.search("foo", path=root, canonical_paths=True) == expected
+        finally:
+            os.chdir(cwd)
+
+    def test_absolute_paths(self):
+        """Test absolute_paths and relative_to give one path format however path= was spelled"""
+        grep = pyripgrep.Grep()
+        root = os.path.realpath(os.path.join(self.tmpdir, "pathstyle"))
+        os.makedirs(os.path.join(root, "src"))
+        os.makedirs(os.path.join(root, "docs"))
+        with open(os.path.join(root, "src", "a.py"), "w") as f:
+            f.write("x = 1  # TODO\n")
+        absolute = os.path.join(root, "src", "a.py")
+
+        cwd = os.getcwd()
+        os.chdir(root)
+        try:
+            for spelling in ["./src", "src/../src", os.path.join(root, "src")]:
+                assert grep.search("TODO", path=spelling, absolute_paths=True) == [absolute]
+                assert grep.search("TODO", path=spelling, absolute_paths=False) == ["src/a.py"]
+                assert grep.search("TODO", path=spelling, relative_to="docs") == ["../src/a.py"]
+
+            lines = grep.search("TODO", path="./src", output_mode="content", n=True, relative_to="src")
+            assert lines == ["a.py:1:x = 1  # TODO"]
+            counts = grep.search("TODO", path="./src", output_mode="count", absolute_paths=True)
+            assert counts == {absolute: 1}
+
+            with pytest.raises(ValueError, match="relative_to"):
+                grep.search("TODO", path="src", absolute_paths=True, relative_to="docs")
+        finally:
+            os.chdir(cwd)
+
+    def test_explain(self):
+        """Test explain() reports the rule that includes or excludes a path"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "explain")
+        for d in ["build", "src", ".cache", ".git"]:
+            os.makedirs(os.path.join(root, d))
+        with open(os.path.join(root, ".gitignore"), "w") as f:
+            f.write("build/\n*.log\n!keep.log\n")
+        for name in ["build/out.py", "src/a.py", "src/b.rs", "a.log", "keep.log", ".cache/c.py"]:
+            with open(os.path.join(root, name), "w") as f:
+                f.write("x\n")
+
+        ignored = grep.explain(os.path.join(root, "build", "out.py"), root=root)
+        assert not ignored and ignored.cause == "gitignore"
+        assert ignored.rule == "build/"
+        assert ignored.source == os.path.join(root, ".gitignore")
+        assert ignored.matched_path == os.path.join(root, "build")
+
+        assert grep.explain(os.path.join(root, "a.log"), root=root).rule == "*.log"
+        kept = grep.explain(os.path.join(root, "keep.log"), root=root)
+        assert kept.included and kept.rule == "!keep.log"
+
+        assert grep.explain(os.path.join(root, ".cache", "c.py"), root=root).cause == "hidden"
+        assert grep.explain(os.path.join(root, "src", "b.rs"), root=root, type="python").cause == "type_filter"
+        assert grep.explain(os.path.join(root, "src", "a.py"), root=root, glob="*.rs").cause == "glob"
+
+        plain = grep.explain(os.path.join(root, "src", "a.py"), root=root)
+        assert plain.included and plain.cause is None and plain.reason == "searched"
+        # The verdicts agree with what search actually walks
+        searched = set(grep.search(path=root, output_mode="files"))
+        assert os.path.join(root, "src", "a.py") in searched
+        assert os.path.join(root, "build", "out.py") not in searched
+
+        with pytest.raises(ValueError, match="not under root"):
+            grep.explain(os.path.join(root, "a.log"), root=os.path.join(root, "src"))
+
+    def test_custom_ignore_files(self):
+        """Test custom_ignore_filenames and ignore_file_paths add ignore rules to the walk"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "custom_ignore")
+        os.makedirs(os.path.join(root, "sub"))
+        with open(os.path.join(root, ".prettierignore"), "w") as f:
+            f.write("*.gen.js\n")
+        for name in ["a.js", "b.gen.js", "sub/c.gen.js", "d.tmp"]:
+            with open(os.path.join(root, name), "w") as f:
+                f.write("needle\n")
+        extra = os.path.join(self.tmpdir, "extra.ignore")
+        with open(extra, "w") as f:
+            f.write("*.tmp\n")
+
+        def names(**kwargs):
+            return sorted(os.path.relpath(p, root) for p in grep.search("needle", path=root, **kwargs))
+
+        assert names() == ["a.js", "b.gen.js", "d.tmp", "sub/c.gen.js"]
+        assert names(custom_ignore_filenames=[".prettierignore"]) == ["a.js", "d.tmp"]
+        assert names(ignore_file_paths=[extra]) == ["a.js", "b.gen.js", "sub/c.gen.js"]
+
+        _, stats = grep.search("needle", path=root, custom_ignore_filenames=[".prettierignore"], stats=True)
+        assert stats.skipped.gitignore == 2
+
+        why = grep.explain(os.path.join(root, "sub", "c.gen.js"), root=root, custom_ignore_filenames=[".prettierignore"])
+        assert why.cause == "gitignore" and why.source == os.path.join(root, ".prettierignore")
+
+        with pytest.raises(ValueError, match="Invalid ignore file"):
+            grep.search("needle", path=root, ignore_file_paths=[os.path.join(root, "missing.ignore")])
+
+    def test_one_file_system(self):
+        """Test one_file_system=True keeps results on a tree without other mounts"""
+        grep = pyripgrep.Grep()
+        everything = grep.search("def", path=self.tmpdir)
+        assert grep.search("def", path=self.tmpdir, one_file_system=True) == everything
+        assert grep.search("def", path=self.tmpdir, one_file_system=False) == everything
+
+    def test_size_and_mtime_filters(self):
+        """Test max_filesize, min_filesize, modified_after and modified_before"""
+        import datetime
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "bounds")
+        os.makedirs(root)
+        sizes = {"small.txt": 10, "medium.txt": 100, "large.txt": 1000}
+        for name, size in sizes.items():
+            with open(os.path.join(root, name), "w") as f:
+                f.write("x" * (size - 1) + "\n")
+        now = time.time()
+        os.utime(os.path.join(root, "small.txt"), (now - 3 * 86400, now - 3 * 86400))
+        os.utime(os.path.join(root, "large.txt"), (now - 10 * 86400, now - 10 * 86400))
+
+        def names(**kwargs):
+            return sorted(os.path.basename(p) for p in grep.search("x", path=root, **kwargs))
+
+        assert names(max_filesize=100) == ["medium.txt", "small.txt"]
+        assert names(min_filesize=100) == ["large.txt", "medium.txt"]
+        assert names(min_filesize=50, max_filesize=500) == ["medium.txt"]
+
+        assert names(modified_after=datetime.timedelta(days=1)) == ["medium.txt"]
+        assert names(modified_after=now - 5 * 86400) == ["medium.txt", "small.txt"]
+        assert names(modified_before=datetime.datetime.fromtimestamp(now - 86400)) == ["large.txt", "small.txt"]
+        assert names(modified_after=datetime.timedelta(days=5), modified_before=datetime.timedelta(days=1)) == ["small.txt"]
+
+        _, stats = grep.search("x", path=root, max_filesize=100, modified_after=datetime.timedelta(days=5), stats=True)
+        assert stats.skipped.size == 1
+        assert stats.skipped.modified == 0  # large.txt was already skipped by size
+
+        with pytest.raises(ValueError, match="modified_after"):
+            grep.search("x", path=root, modified_after="yesterday")
+
+    def test_search_many(self):
+        """Test search_many attributes matches to named patterns in one walk"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "many")
+        os.makedirs(root)
+        with open(os.path.join(root, "a.py"), "w") as f:
+            f.write("# TODO: fix\nprint('x')  # TODO\n")
+        with open(os.path.join(root, "b.py"), "w") as f:
+            f.write("print('y')\n")
+        patterns = {"todo": r"TODO", "print": r"print\(", "none": r"zzz"}
+
+        files = grep.search_many(patterns, path=root)
+        assert list(files) == ["todo", "print", "none"]
+        assert files["todo"] == [os.path.join(root, "a.py")]
+        assert sorted(files["print"]) == sorted(grep.search(r"print\(", path=root))
+        assert files["none"] == []
+
+        counts = grep.search_many(patterns, path=root, output_mode="count")
+        assert counts["todo"] == {os.path.join(root, "a.py"): 2}
+        assert sum(counts["print"].values()) == 2
+
+        lines = grep.search_many(patterns, path=root, output_mode="content", n=True)
+        assert lines["todo"] == [
+            f"{os.path.join(root, 'a.py')}:1:# TODO: fix",
+            f"{os.path.join(root, 'a.py')}:2:print('x')  # TODO",
+        ]
+        assert f"{os.path.join(root, 'a.py')}:2:print('x')  # TODO" in lines["print"]
+
+        assert grep.search_many({"todo": "todo"}, path=root, i=True)["todo"] == [os.path.join(root, "a.py")]
+        with pytest.raises(ValueError, match="bad"):
+            grep.search_many({"bad": "("}, path=root)
+        with pytest.raises(ValueError):
+            grep.search_many(patterns, path=root, output_mode="files")
+
+    def test_all_of_none_of(self):
+        """Test all_of and none_of select files by which patterns they contain"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "boolean")
+        os.makedirs(root)
+        contents = {
+            "both.py": "import os\nimport sys\n",
+            "both_and_c.py": "import os\nimport sys\nimport re\n",
+            "only_a.py": "import os\n",
+        }
+        for name, text in contents.items():
+            with open(os.path.join(root, name), "w") as f:
+                f.write(text)
+
+        def names(pattern="import", **kwargs):
+            return sorted(os.path.basename(p) for p in grep.search(pattern, path=root, **kwargs))
+
+        assert names(all_of=["os", "sys"]) == ["both.py", "both_and_c.py"]
+        assert names(all_of=["os", "sys"], none_of=["re$"]) == ["both.py"]
+        assert names(none_of=["sys"]) == ["only_a.py"]
+        assert names(all_of=["OS"], i=True) == ["both.py", "both_and_c.py", "only_a.py"]
+        assert names(None, output_mode="files", all_of=["sys"]) == ["both.py", "both_and_c.py"]
+
+        counts = grep.search("import", path=root, output_mode="count", all_of=["sys"], none_of=["re$"])
+        assert counts == {os.path.join(root, "both.py"): 2}
+
+        _, stats = grep.search("import", path=root, all_of=["os", "sys"], none_of=["re$"], stats=True)
+        assert stats.skipped.content_filter == 2
+
+    def test_rank(self):
+        """Test rank=True orders files by name hits, then match density"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "rank")
+        os.makedirs(os.path.join(root, "a"))
+        files = {
+            "a/sparse.py": "session\n" + "x = 1\n" * 19,
+            "a/dense.py": "session\nsession\nx = 1\n",
+            "session.py": "x = 1\n" * 9 + "session\n",
+        }
+        for name, text in files.items():
+            with open(os.path.join(root, name), "w") as f:
+                f.write(text)
+        expected = [os.path.join(root, name) for name in ["session.py", "a/dense.py", "a/sparse.py"]]
+
+        assert grep.search("session", path=root, rank=True) == expected
+        assert list(grep.search("session", path=root, output_mode="count", rank=True)) == expected
+
+        lines = grep.search("session", path=root, output_mode="content", rank=True)
+        paths = [line.split(":")[0] for line in lines if line != "--"]
+        assert paths == [expected[0], expected[1], expected[1], expected[2]]
+
+        with pytest.raises(ValueError, match="sort"):
+            grep.search("session", path=root, rank=True, sort="path")
+        with pytest.raises(ValueError, match="rank"):
+            grep.search("session", path=root, rank=True, output_mode="vimgrep")
+
+    def test_paths_mode(self):
+        """Test output_mode="paths" matches file paths with character spans"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "paths_mode")
+        os.makedirs(os.path.join(root, "tests"))
+        os.makedirs(os.path.join(root, "ignored"))
+        os.makedirs(os.path.join(root, ".git"))
+        with open(os.path.join(root, ".gitignore"), "w") as f:
+            f.write("ignored/\n")
+        for name in ["tests/test_a.py", "tests/helper.py", "ignored/test_b.py", "tést_c.py"]:
+            with open(os.path.join(root, name), "w") as f:
+                f.write("no match inside\n")
+
+        hits = grep.search(r"t[eé]st_\w+", path=root, output_mode="paths")
+        by_path = {hit["path"]: hit["spans"] for hit in hits}
+        assert sorted(by_path) == sorted([os.path.join(root, "tests", "test_a.py"), os.path.join(root, "tést_c.py")])
+        for path, spans in by_path.items():
+            start, end = spans[0]
+            assert path[start:end] in ("test_a", "tést_c")
+
+        assert grep.search("helper", path=root, output_mode="paths", type="rust") == []
+        assert len(grep.search("test", path=root, output_mode="paths", max_files=1)) == 1
+
+        session = grep.start("helper", path=root, output_mode="paths")
+        stepped = []
+        while not session.done:
+            stepped.extend(session.step())
+        assert [hit["path"] for hit in stepped] == [os.path.join(root, "tests", "helper.py")]
+
+        merged = pyripgrep.merge_results([hits, hits], output_mode="paths")
+        assert sorted(hit["path"] for hit in merged) == sorted(by_path)
+
+    def test_files_metadata(self):
+        """Test metadata=True returns per-file summaries in files_with_matches mode"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "metadata")
+        os.makedirs(root)
+        path = os.path.join(root, "a.txt")
+        with open(path, "w") as f:
+            f.write("one\nTODO two\nthree\nTODO four\n")
+        os.utime(path, (1_700_000_000, 1_700_000_000))
+        with open(os.path.join(root, "b.txt"), "w") as f:
+            f.write("nothing\n")
+
+        summaries = grep.search("TODO", path=root, metadata=True)
+        assert summaries == [{
+            "path": path,
+            "first_line": 2,
+            "match_count": 2,
+            "size": os.path.getsize(path),
+            "mtime": 1_700_000_000.0,
+        }]
+        capped = grep.search("TODO", path=root, metadata=True, max_matches_per_file=1)
+        assert capped[0]["match_count"] == 1
+
+        with pytest.raises(ValueError, match="metadata"):
+            grep.search("TODO", path=root, metadata=True, output_mode="count")
+
+    def test_context_blocks(self):
+        """Test overlapping and adjacent context merges into blocks with rg's -- placement"""
+        grep = pyripgrep.Grep()
+        target = os.path.join(self.tmpdir, "blocks.txt")
+        with open(target, "w") as f:
+            f.write("a1\nx\nx\na2\nx\nx\nx\nx\na3\na4\nx\n")
+
+        def lines(**kwargs):
+            return [line.replace(target, "") for line in grep.search("a", path=target, output_mode="content", n=True, **kwargs)]
+
+        # Overlapping context is shown once; a gap breaks the block
+        assert lines(C=1) == [":1:a1", "-2:x", "-3:x", ":4:a2", "-5:x", "--", "-8:x", ":9:a3", ":10:a4", "-11:x"]
+        # After context of one match running into the next match's line
+        assert lines(A=2) == [":1:a1", "-2:x", "-3:x", ":4:a2", "-5:x", "-6:x", "--", ":9:a3", ":10:a4", "-11:x"]
+        # Blocks that only touch are merged without a separator
+        assert lines(A=2, B=2) == [":1:a1", "-2:x", "-3:x", ":4:a2", "-5:x", "-6:x", "-7:x", "-8:x", ":9:a3", ":10:a4", "-11:x"]
+        assert lines(B=3) == [":1:a1", "-2:x", "-3:x", ":4:a2", "--", "-6:x", "-7:x", "-8:x", ":9:a3", ":10:a4"]
+
+        # Per-match context is still complete when grouped by file
+        grouped = grep.search("a", path=target, output_mode="content", n=True, C=1, group_by_file=True)[target]
+        assert [(m["before_context"], m["after_context"]) for m in grouped[2:]] == [(["x"], ["a4"]), (["a3"], ["x"])]
+
+        # Anchors hold on every line, whatever terminates it
+        with open(target, "wb") as f:
+            f.write(b"key = 1\r\nother\r\nkey = 2\r\n")
+        assert grep.search("^key", path=target, output_mode="content", n=True, A=1, crlf=True) == [
+            f"{target}:1:key = 1", f"{target}-2:other", f"{target}:3:key = 2",
+        ]
+
+    def test_search_with_sink(self):
+        """Test search_with_sink streams searcher events and honors the callback's return value"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "sink")
+        os.makedirs(root)
+        first, second = os.path.join(root, "a.txt"), os.path.join(root, "b.txt")
+        with open(first, "w") as f:
+            f.write("hit\nx\nx\nx\nhit\nhit\n")
+        with open(second, "w") as f:
+            f.write("hit\n")
+
+        events = []
+        grep.search_with_sink("hit", events.append, path=first, A=1)
+        assert [(e["type"], e.get("line_number"), e.get("kind")) for e in events] == [
+            ("begin", None, None),
+            ("match", 1, None),
+            ("context", 2, "after"),
+            ("context_break", None, None),
+            ("match", 5, None),
+            ("match", 6, None),
+            ("end", None, None),
+        ]
+        assert events[4]["bytes"] == b"hit\n" and events[4]["absolute_offset"] == 10
+        assert events[-1]["bytes_searched"] == 18
+
+        # False stops the current file only; "stop" ends the whole search
+        def first_match_only(event):
+            seen.append((event["type"], event["path"]))
+            return event["type"] != "match"
+
+        seen = []
+        grep.search_with_sink("hit", first_match_only, path=root)
+        assert [t for t, _ in seen] == ["begin", "match", "end"] * 2
+
+        seen = []
+        grep.search_with_sink("hit", lambda e: seen.append(e["type"]) or ("stop" if e["type"] == "match" else None), path=root)
+        assert seen == ["begin", "match"]
+
+        # Exceptions from the callback propagate
+        def fail(event):
+            raise KeyError("boom")
+
+        with pytest.raises(KeyError):
+            grep.search_with_sink("hit", fail, path=root)
+        with pytest.raises(ValueError):
+            grep.search_with_sink("hit", None, path=root)
+
+    def test_truncation_stats(self):
+        """Test stats report whether the limits cut results, and how much, in every output mode"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "truncation")
+        os.makedirs(root)
+        for name in ("a.txt", "b.txt", "c.txt"):
+            with open(os.path.join(root, name), "w") as f:
+                f.write("hit\nhit\nmiss\n")
+
+        _, stats = grep.search("hit", path=root, output_mode="count", stats=True)
+        assert not stats.truncated
+        assert (stats.suppressed_files, stats.suppressed_matches) == (0, 0)
+
+        # The search stops at the first file the limit leaves out
+        _, stats = grep.search("hit", path=root, output_mode="count", max_files=2, stats=True)
+        assert stats.truncated
+        assert (stats.suppressed_files, stats.suppressed_matches) == (1, 2)
+
+        # Lines cut from files that are kept
+        _, stats = grep.search("hit", path=root, output_mode="content", max_matches_per_file=1, stats=True)
+        assert stats.truncated
+        assert (stats.suppressed_files, stats.suppressed_matches) == (0, 3)
+
+        for mode in ("files_with_matches", "vimgrep", "matches", "files", "paths"):
+            pattern = "txt" if mode == "paths" else "hit"
+            _, stats = grep.search(pattern, path=root, output_mode=mode, max_files=1, max_matches_total=1, stats=True)
+            assert stats.truncated, mode
+            _, stats = grep.search(pattern, path=root, output_mode=mode, max_files=3, stats=True)
+            assert not stats.truncated, mode
+
+        # Reaching a limit exactly cuts nothing
+        _, stats = grep.search("hit", path=root, output_mode="matches", max_matches_total=6, stats=True)
+        assert not stats.truncated
+
+    def test_session_iteration(self):
+        """Test iterating a session yields matching paths one at a time in walk order"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "streamed")
+        os.makedirs(root)
+        for name in ("a.txt", "b.txt", "c.txt", "d.txt"):
+            with open(os.path.join(root, name), "w") as f:
+                f.write("hit\n" if name != "c.txt" else "miss\n")
+
+        expected = grep.search("hit", path=root, sort="path")
+        session = grep.start("hit", path=root)
+        first = next(iter(session))
+        assert first in expected and not session.done
+        rest = list(session)
+        assert sorted([first] + rest) == expected
+        assert session.done
+        assert session.stats.files_matched == 3
+
+        # Order is the walk order, the same as search() returns
+        assert list(grep.start("hit", path=root)) == grep.search("hit", path=root, threads=1)
+
+        # files mode lists every file; other modes use step()
+        assert len(list(grep.start(path=root, output_mode="files"))) == 4
+        with pytest.raises(ValueError):
+            iter(grep.start("hit", path=root, output_mode="count"))
+
+    def test_files_without_match(self):
+        """Test output_mode="files_without_match" lists searched files with zero matches"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "license")
+        os.makedirs(root)
+        for name, text in [("a.rs", "// SPDX-License-Identifier: MIT\nfn a() {}\n"),
+                           ("b.rs", "fn b() {}\n"),
+                           ("c.rs", "fn c() {}\n")]:
+            with open(os.path.join(root, name), "w") as f:
+                f.write(text)
+
+        missing = grep.search("SPDX-License-Identifier", root, output_mode="files_without_match")
+        assert sorted(os.path.basename(p) for p in missing) == ["b.rs", "c.rs"]
+
+        limited, stats = grep.search("SPDX", root, output_mode="files_without_match", max_files=1, stats=True)
+        assert len(limited) == 1
+        assert stats.truncated and stats.suppressed_files == 1
+
+        session = grep.start("SPDX", root, output_mode="files_without_match")
+        assert sorted(os.path.basename(p) for p in session) == ["b.rs", "c.rs"]
+
+        stream = grep.search_stream("SPDX", io.BytesIO(b"fn main() {}\n"), output_mode="files_without_match")
+        assert stream == ["<stdin>"]
+
+    def test_ignore_level(self):
+        """Test ignore_level lifts ignore files, then hidden files, then binary skipping"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "levels")
+        os.makedirs(os.path.join(root, ".hidden"))
+        with open(os.path.join(root, ".ignore"), "w") as f:
+            f.write("ignored.txt\n")
+        for name in ["plain.txt", "ignored.txt", os.path.join(".hidden", "secret.txt")]:
+            with open(os.path.join(root, name), "w") as f:
+                f.write("needle\n")
+        with open(os.path.join(root, "blob.dat"), "wb") as f:
+            f.write(b"needle\xff\xfe\n")
+
+        def found(level):
+            return sorted(os.path.relpath(p, root) for p in grep.search("needle", root, ignore_level=level))
+
+        assert found(0) == ["plain.txt"]
+        assert found(1) == ["ignored.txt", "plain.txt"]
+        assert found(2) == [os.path.join(".hidden", "secret.txt"), "ignored.txt", "plain.txt"]
+        assert found(3) == [os.path.join(".hidden", "secret.txt"), "blob.dat", "ignored.txt", "plain.txt"]
+
+        assert not grep.explain(os.path.join(root, "ignored.txt"), root=root)
+        assert grep.explain(os.path.join(root, "ignored.txt"), root=root, ignore_level=1)
+
+        with pytest.raises(ValueError):
+            grep.search("needle", root, ignore_level=4)
+
+    def test_archives(self):
+        """Test archives=True searches zip and tar members as archive!/member paths"""
+        import tarfile
+        import zipfile
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "archives")
+        os.makedirs(root)
+        with zipfile.ZipFile(os.path.join(root, "lib.jar"), "w", zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr("com/app/Main.java", "class Main {}\n// needle\n")
+            archive.writestr("META-INF/", "")
+        data = b"first\nneedle here\n"
+        with tarfile.open(os.path.join(root, "src.tar.gz"), "w:gz") as archive:
+            info = tarfile.TarInfo("pkg/mod.py")
+            info.size = len(data)
+            archive.addfile(info, io.BytesIO(data))
+
+        assert grep.search("needle", root) == []
+
+        lines = grep.search("needle", root, archives=True, output_mode="content", n=True)
+        jar = os.path.join(root, "lib.jar") + "!/com/app/Main.java"
+        tar = os.path.join(root, "src.tar.gz") + "!/pkg/mod.py"
+        assert sorted(line for line in lines if line != "--") == sorted([f"{jar}:2:// needle", f"{tar}:2:needle here"])
+
+        assert grep.search("needle", root, archives=True, type="py") == [tar]
+
+    def test_notebook_preprocessor(self):
+        """Test preprocessor="ipynb" searches notebook cells with in-cell line numbers"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "notebooks")
+        os.makedirs(root)
+        notebook = {
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Load\n", "read the data\n"]},
+                {"cell_type": "code", "source": "import pandas as pd\ndf = pd.read_csv('x.csv')",
+                 "outputs": [{"output_type": "stream", "text": ["read_csv done\n"]}]},
+                {"cell_type": "raw", "source": "read_csv in a raw cell"},
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5,
+        }
+        path = os.path.join(root, "eda.ipynb")
+        with open(path, "w") as f:
+            json.dump(notebook, f, indent=1)
+
+        lines = grep.search("read", root, preprocessor="ipynb", output_mode="content", n=True)
+        assert [line for line in lines if line != "--"] == [
+            f"{path}!/cells/0:2:read the data",
+            f"{path}!/cells/1:2:df = pd.read_csv('x.csv')",
+        ]
+
+        counts = grep.search("read_csv", root, preprocessor="ipynb", type="jupyter", output_mode="count")
+        assert counts == {f"{path}!/cells/1": 1}
+
+    def test_git_scope(self):
+        """Test scope= limits the search to tracked, changed or staged files"""
+        if shutil.which("git") is None:
+            pytest.skip("git not available")
+        grep = pyripgrep.Grep()
+        repo = os.path.join(self.tmpdir, "repo")
+        os.makedirs(repo)
+
+        def git(*args):
+            subprocess.run(["git", "-C", repo, *args], check=True, capture_output=True)
+
+        def write(name, text):
+            with open(os.path.join(repo, name), "w") as f:
+                f.write(text)
+
+        git("init", "-q")
+        git("config", "user.email", "dev@example.com")
+        git("config", "user.name", "dev")
+        write("committed.txt", "needle\n")
+        write("edited.txt", "hay\n")
+        git("add", ".")
+        git("commit", "-q", "-m", "initial")
+        write("edited.txt", "hay\nneedle\n")
+        write("staged.txt", "needle\n")
+        git("add", "staged.txt")
+        write("untracked.txt", "needle\n")
+
+        def found(scope):
+            return sorted(os.path.basename(p) for p in grep.search("needle", repo, scope=scope))
+
+        assert found("tracked") == ["committed.txt", "edited.txt", "staged.txt"]
+        assert found("changed") == ["edited.txt", "staged.txt", "untracked.txt"]
+        assert found("staged") == ["staged.txt"]
+
+        with pytest.raises(ValueError):
+            grep.search("needle", repo, scope="changed", files=["edited.txt"])
+        with pytest.raises(ValueError):
+            grep.search("needle", repo, scope="everything")
+
+    def test_search_rev(self):
+        """Test search_rev reads files of a git revision without a checkout"""
+        if shutil.which("git") is None:
+            pytest.skip("git not available")
+        grep = pyripgrep.Grep()
+        repo = os.path.join(self.tmpdir, "history")
+        os.makedirs(repo)
+
+        def git(*args):
+            subprocess.run(["git", "-C", repo, *args], check=True, capture_output=True)
+
+        git("init", "-q")
+        git("config", "user.email", "dev@example.com")
+        git("config", "user.name", "dev")
+        with open(os.path.join(repo, "api.py"), "w") as f:
+            f.write("def old_name():\n    pass\n")
+        git("add", ".")
+        git("commit", "-q", "-m", "first")
+        with open(os.path.join(repo, "api.py"), "w") as f:
+            f.write("def new_name():\n    pass\n")
+        git("commit", "-q", "-am", "rename")
+
+        assert grep.search_rev("old_name", repo=repo) == []
+        assert grep.search_rev("old_name", rev="HEAD~1", repo=repo) == ["HEAD~1:api.py:1:def old_name():"]
+        assert grep.search_rev("name", rev="HEAD~1", repo=repo, glob="*.txt") == []
+
+        with pytest.raises(ValueError):
+            grep.search_rev("old_name", rev="no-such-rev", repo=repo)
+
+    def test_exception_hierarchy(self):
+        """Test errors raise pyripgrep exception classes with structured fields"""
+        grep = pyripgrep.Grep()
+
+        with pytest.raises(pyripgrep.PatternError) as info:
+            grep.search("foo(bar", self.tmpdir)
+        assert isinstance(info.value, ValueError)
+        assert info.value.pattern == "foo(bar"
+        assert info.value.offset == 3
+        assert "unclosed group" in info.value.cause
+
+        with pytest.raises(pyripgrep.PatternError) as info:
+            grep.search_many({"broken": "x["}, self.tmpdir)
+        assert "broken" in str(info.value)
+        assert info.value.pattern == "x["
+
+        missing = os.path.join(self.tmpdir, "missing")
+        with pytest.raises(pyripgrep.WalkError) as info:
+            grep.search("foo", missing)
+        assert info.value.path == missing
+
+        undecodable = os.path.join(self.tmpdir, "latin1.txt")
+        with open(undecodable, "wb") as f:
+            f.write(b"caf\xe9 foo\n")
+        with pytest.raises(pyripgrep.IOErrorInfo) as info:
+            grep.search("foo", files=[undecodable], errors="raise")
+        assert info.value.path == undecodable
+        assert info.value.offset is None
+
+        with pytest.raises(pyripgrep.SearchTimeout) as info:
+            grep.search("foo", self.tmpdir, timeout=1e-9)
+        assert isinstance(info.value, TimeoutError)
+        assert info.value.cause == "timeout"
+
+    def test_compile_check(self):
+        """Test module-level compile_check reports pattern problems without searching"""
+        ok = pyripgrep.compile_check(r"fn\s+\w+")
+        assert ok["valid"] is True
+        assert ok["error"] is None and ok["look_around"] is False
+
+        unclosed = pyripgrep.compile_check("foo(bar")
+        assert unclosed["valid"] is False
+        assert unclosed["position"] == 3
+        assert "\\(" in unclosed["suggestion"]
+
+        lookbehind = pyripgrep.compile_check(r"(?<!self\.)foo")
+        assert lookbehind["valid"] is False
+        assert lookbehind["look_around"] is True
+        if pyripgrep.features()["fancy_regex"]:
+            fancy = pyripgrep.compile_check(r"(?<!self\.)foo", engine="fancy")
+            assert fancy["valid"] is True and fancy["look_around"] is True
+
+        assert pyripgrep.compile_check("a.b", multiline=True)["valid"] is True
+        with pytest.raises(ValueError):
+            pyripgrep.compile_check("foo", engine="bogus")
+
+    def test_literal_prefilter(self):
+        """Test files ruled out by a required literal still search and count the same"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "prefilter")
+        os.makedirs(root)
+        with open(os.path.join(root, "hit.txt"), "w") as f:
+            f.write("alpha\nconnect timeout here\nfoo bar\n")
+        with open(os.path.join(root, "miss.txt"), "w") as f:
+            f.write("nothing to see\nfoo\n")
+
+        # Plain literal, required literal inside a regex, and an anchored pattern
+        lines, stats = grep.search("timeout", path=root, output_mode="content", stats=True)
+        assert lines == [os.path.join(root, "hit.txt") + ":connect timeout here"]
+        assert stats.files_searched == 2
+        assert grep.search(r"conn\w+ (timeout)", path=root, output_mode="content", sort="path") == lines
+        assert len([line for line in grep.search("^foo", path=root, output_mode="content") if line != "--"]) == 2
+        assert grep.search("^foo", path=root, output_mode="vimgrep", sort="path") == [
+            os.path.join(root, "hit.txt") + ":3:1:foo bar",
+            os.path.join(root, "miss.txt") + ":2:1:foo",
+        ]
+
+        # Case-insensitive patterns have no required spelling and must still match
+        assert len(grep.search("TIMEOUT", path=root, output_mode="content", i=True)) == 1
+        assert grep.search("TIMEOUT", path=root, output_mode="content") == []
+
+    def test_batch_small_files(self):
+        """Test batch_small_files=True returns the same results in the same order"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "tiny")
+        os.makedirs(root)
+        for i in range(150):
+            with open(os.path.join(root, f"f{i:03d}.txt"), "w") as f:
+                f.write("TODO here\n" if i % 7 == 0 else "nothing\n")
+
+        for mode in ["files_with_matches", "content", "count", "vimgrep"]:
+            for threads in [1, 4]:
+                plain = grep.search("TODO", path=root, output_mode=mode, sort="path", threads=threads)
+                batched = grep.search("TODO", path=root, output_mode=mode, sort="path", threads=threads,
+                                      batch_small_files=True)
+                assert batched == plain, (mode, threads)
+
+        # Files still count individually, and early stops still stop
+        _, stats = grep.search("TODO", path=root, output_mode="count", batch_small_files=True, stats=True)
+        assert (stats.files_searched, stats.files_matched) == (150, 22)
+        assert len(grep.search("TODO", path=root, batch_small_files=True, max_files=3)) == 3
+
+    def test_skip_generated(self):
+        """Test skip_minified and generated_globs keep bundles and lockfiles out of results"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "generated")
+        os.makedirs(os.path.join(root, "dist"))
+        with open(os.path.join(root, "app.js"), "w") as f:
+            f.write("const apiKey = load();\nexport default apiKey;\n")
+        with open(os.path.join(root, "dist", "app.min.js"), "w") as f:
+            f.write("var a=1;" * 200 + "var apiKey=2;")
+        with open(os.path.join(root, "wide.js"), "w") as f:
+            f.write(("x" * 150 + " apiKey\n") * 10)
+        with open(os.path.join(root, "yarn.lock"), "w") as f:
+            f.write("apiKey@1.0.0:\n  version 1.0.0\n")
+
+        def names(**kwargs):
+            return sorted(os.path.basename(p) for p in grep.search("apiKey", path=root, **kwargs))
+
+        assert names() == ["app.js", "app.min.js", "wide.js", "yarn.lock"]
+        assert names(skip_minified=True) == ["app.js", "wide.js", "yarn.lock"]
+        assert names(skip_minified=100) == ["app.js", "yarn.lock"]
+        assert names(generated_globs=["*.lock", "dist/**"]) == ["app.js", "wide.js"]
+        assert names(generated_globs=["yarn.lock"], skip_minified=True, output_mode="count") == ["app.js", "wide.js"]
+
+        _, stats = grep.search("apiKey", path=root, skip_minified=True, generated_globs=["*.lock"], stats=True)
+        assert stats.skipped.generated == 2
+
+        with pytest.raises(ValueError):
+            grep.search("apiKey", path=root, skip_minified=0)
+        with pytest.raises(ValueError):
+            grep.search("apiKey", path=root, generated_globs=["a[b"])
+
+    def test_module_functions(self):
+        """Test module-level search, files and count match a default Grep"""
+        grep = pyripgrep.Grep()
+        assert pyripgrep.search("error", path=self.tmpdir, i=True) == grep.search("error", path=self.tmpdir, i=True)
+        assert pyripgrep.search("error", self.tmpdir, output_mode="content") == \
+            grep.search("error", self.tmpdir, output_mode="content")
+        assert sorted(pyripgrep.files(self.tmpdir)) == sorted(grep.search(path=self.tmpdir, output_mode="files"))
+        assert pyripgrep.files(self.tmpdir, type="python") == grep.search(path=self.tmpdir, output_mode="files", type="python")
+        assert pyripgrep.count("error", self.tmpdir, i=True) == grep.search("error", self.tmpdir, output_mode="count", i=True)
+
+        with pytest.raises(TypeError):
+            pyripgrep.count("error", self.tmpdir, output_mode="content")
+        with pytest.raises(ValueError):
+            pyripgrep.search(path=self.tmpdir)
+
+    def test_grep_defaults(self):
+        """Test Grep(**defaults) applies defaults that search() arguments override"""
+        plain = pyripgrep.Grep()
+        assert plain.defaults == {} and repr(plain) == "Grep()"
+
+        grep = pyripgrep.Grep(case_insensitive=True, types=["python"], path=self.tmpdir, threads=2)
+        assert grep.defaults == {"i": True, "type": ["python"], "path": self.tmpdir, "threads": 2}
+        assert "i=True" in repr(grep)
+
+        expected = plain.search("error", path=self.tmpdir, i=True, type=["python"])
+        assert grep.search("error") == expected
+        # Keyword and positional arguments both take precedence over defaults
+        assert grep.search("error", i=False) == plain.search("error", path=self.tmpdir, type=["python"])
+        assert grep.search("error", self.subdir, type=None) == plain.search("error", self.subdir, i=True)
+
+        hidden_root = os.path.join(self.tmpdir, "hidden_defaults")
+        os.makedirs(os.path.join(hidden_root, ".config"))
+        with open(os.path.join(hidden_root, ".config", "settings.py"), "w") as f:
+            f.write("ERROR = 1\n")
+        assert pyripgrep.Grep().search("ERROR", path=hidden_root) == []
+        assert len(pyripgrep.Grep(hidden=True).search("ERROR", path=hidden_root)) == 1
+
+        with pytest.raises(TypeError):
+            pyripgrep.Grep(casefold=True)
+        with pytest.raises(TypeError):
+            pyripgrep.Grep(pattern="x")
+        with pytest.raises(TypeError):
+            pyripgrep.Grep(i=True, case_insensitive=True)
+
+    def test_from_config(self):
+        """Test Grep.from_config maps a ripgrep config file to search defaults"""
+        root = os.path.join(self.tmpdir, "configured")
+        os.makedirs(os.path.join(root, ".hidden"))
+        for name, text in {"page.html": "<p>TODO</p>\n", "style.css": "/* todo */\n",
+                           "main.py": "# TODO\n", ".hidden/site.html": "TODO\n"}.items():
+            with open(os.path.join(root, name), "w") as f:
+                f.write(text)
+        config = os.path.join(self.tmpdir, "ripgreprc")
+        with open(config, "w") as f:
+            f.write("# comments and blank lines are skipped\n\n--smart-case\n--hidden\n"
+                    "--type-add\nweb:*.{html,css}\n-tweb\n--max-columns=150\n--colors=match:fg:red\n-j2\n")
+
+        grep = pyripgrep.Grep.from_config(config)
+        assert grep.defaults["case_smart"] is True and grep.defaults["threads"] == 2
+        assert grep.defaults["type_add"] == ["web:*.{html,css}"]
+        names = lambda results: sorted(os.path.relpath(p, root) for p in results)
+        assert names(grep.search("todo", path=root)) == [".hidden/site.html", "page.html", "style.css"]
+        assert names(grep.search("TODO", path=root)) == [".hidden/site.html", "page.html"]
+
+        old = os.environ.get("RIPGREP_CONFIG_PATH")
+        try:
+            os.environ["RIPGREP_CONFIG_PATH"] = config
+            assert pyripgrep.Grep.from_config().defaults == grep.defaults
+            del os.environ["RIPGREP_CONFIG_PATH"]
+            assert pyripgrep.Grep.from_config().defaults == {}
+        finally:
+            if old is not None:
+                os.environ["RIPGREP_CONFIG_PATH"] = old
+
+        with open(config, "w") as f:
+            f.write("--follow\n-i\n")
+        with pytest.warns(UserWarning, match="--follow"):
+            assert pyripgrep.Grep.from_config(config).defaults == {"i": True, "case_smart": False}
+        with open(config, "w") as f:
+            f.write("--threads\n")
+        with pytest.raises(ValueError):
+            pyripgrep.Grep.from_config(config)
+        with pytest.raises(FileNotFoundError):
+            pyripgrep.Grep.from_config(os.path.join(self.tmpdir, "missing"))
+        with pytest.raises(ValueError):
+            pyripgrep.Grep().search("x", path=root, type="web", type_add=["web"])
+
+    def test_count_aggregate(self):
+        """Test aggregate= rolls count mode up to a total or per directory"""
+        grep = pyripgrep.Grep()
+        counts = grep.search("error", path=self.tmpdir, output_mode="count", i=True)
+        assert grep.search("error", path=self.tmpdir, output_mode="count", i=True, aggregate="per_file") == counts
+
+        total = grep.search("error", path=self.tmpdir, output_mode="count", i=True, aggregate="total")
+        assert isinstance(total, int) and total == sum(counts.values())
+
+        by_dir = grep.search("error", path=self.tmpdir, output_mode="count", i=True, aggregate="per_dir")
+        expected = {}
+        for path, count in counts.items():
+            expected[os.path.dirname(path)] = expected.get(os.path.dirname(path), 0) + count
+        assert by_dir == expected
+        assert by_dir[self.subdir] == counts[os.path.join(self.subdir, "utils.py")]
+
+        assert grep.search("nomatchanywhere", path=self.tmpdir, output_mode="count", aggregate="total") == 0
+        with pytest.raises(ValueError):
+            grep.search("error", path=self.tmpdir, output_mode="count", aggregate="per_line")
+        with pytest.raises(ValueError):
+            grep.search("error", path=self.tmpdir, aggregate="total")
+
+    def test_count_include_zero(self):
+        """include_zero lists searched files without matches in count mode"""
+        grep = pyripgrep.Grep()
+
+        counts = grep.search("def", path=self.tmpdir, output_mode="count", include_zero=True)
+        plain = grep.search("def", path=self.tmpdir, output_mode="count")
+        assert all(counts[path] == count for path, count in plain.items())
+        assert set(plain) < set(counts)
+        assert all(count == 0 for path, count in counts.items() if path not in plain)
+
+        missing = grep.search("no_such_token_anywhere", path=self.tmpdir, output_mode="count", include_zero=True)
+        assert missing and set(missing.values()) == {0}
+        assert grep.search("no_such_token_anywhere", path=self.tmpdir, output_mode="count", aggregate="total", include_zero=True) == 0
+
+        with pytest.raises(ValueError):
+            grep.search("def", path=self.tmpdir, include_zero=True)
+
+    def test_count_include_zero_default(self):
+        """include_zero can be a Grep default that search() and search_page() resolve like the other flags"""
+        plain = pyripgrep.Grep()
+        grep = pyripgrep.Grep(include_zero=True, output_mode="count")
+        assert grep.defaults == {"include_zero": True, "output_mode": "count"}
+
+        expected = plain.search("def", path=self.tmpdir, output_mode="count", include_zero=True)
+        assert 0 in expected.values()
+        assert grep.search("def", path=self.tmpdir) == expected
+        assert grep.search("def", path=self.tmpdir, include_zero=False) == \
+            plain.search("def", path=self.tmpdir, output_mode="count")
+        assert grep.search("def", path=self.tmpdir, include_zero=None) == \
+            plain.search("def", path=self.tmpdir, output_mode="count")
+
+        counts, _ = grep.search_page("def", page_size=1000, path=self.tmpdir, output_mode="count")
+        assert counts == expected
+
+    def test_first_match_mode(self):
+        """first_match returns one record per matching file: its first matching line"""
+        grep = pyripgrep.Grep()
+
+        records = grep.search("def", path=self.tmpdir, output_mode="first_match")
+        files = grep.search("def", path=self.tmpdir)
+        assert sorted(r["path"] for r in records) == sorted(files)
+
+        all_matches = grep.search("def", path=self.tmpdir, output_mode="matches")
+        for record in records:
+            first = min((m for m in all_matches if m["path"] == record["path"]), key=lambda m: m["line_number"])
+            assert record == first
+            with open(record["path"], "rb") as f:
+                data = f.read()
+            start = record["byte_offset"]
+            assert data[start:start + record["byte_length"]].decode() == record["content"]
+
+        assert len(grep.search("def", path=self.tmpdir, output_mode="first_match", max_files=1)) == 1
+
+        merged = pyripgrep.merge_results([all_matches], output_mode="first_match")
+        assert sorted(merged, key=lambda r: r["path"]) == sorted(records, key=lambda r: r["path"])
+
+    def test_special_files(self):
+        """special_files= skips, reports or follows symlinks, fifos and dangling links"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "special")
+        os.makedirs(root)
+        target = os.path.join(root, "target.txt")
+        with open(target, "w") as f:
+            f.write("needle\n")
+        outside = os.path.join(self.tmpdir, "outside.txt")
+        with open(outside, "w") as f:
+            f.write("needle\n")
+        os.symlink(outside, os.path.join(root, "link.txt"))
+        os.symlink(os.path.join(root, "gone.txt"), os.path.join(root, "dangling.txt"))
+        os.mkfifo(os.path.join(root, "pipe"))
+
+        files, stats = grep.search("needle", path=root, stats=True)
+        assert files == [target]
+        assert stats.skipped.special == 0
+
+        files, stats, errors = grep.search("needle", path=root, special_files="report", stats=True, errors="collect")
+        assert files == [target]
+        assert stats.skipped.special == 3
+        reasons = {os.path.basename(path): reason for path, reason in errors}
+        assert reasons == {
+            "link.txt": "skipped: symlink",
+            "dangling.txt": "skipped: dangling symlink",
+            "pipe": "skipped: fifo",
+        }
+
+        files, stats = grep.search("needle", path=root, special_files="follow", sort="path", stats=True)
+        assert files == [os.path.join(root, "link.txt"), target]
+        assert stats.skipped.special == 2
+
+        # forensic=True reports them by default, so the coverage check still adds up
+        _, stats, skips = grep.search("needle", path=root, forensic=True)
+        assert stats.files_searched + len(skips) == len(os.listdir(root))
+
+        with pytest.raises(ValueError):
+            grep.search("needle", path=root, special_files="bogus")
+
+    def test_backtrack_limit(self):
+        """backtrack_limit= fails only the file a pathological pattern blows up on"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "backtrack")
+        os.makedirs(root)
+        with open(os.path.join(root, "ok.txt"), "w") as f:
+            f.write("aab\n")
+        with open(os.path.join(root, "bad.txt"), "w") as f:
+            f.write("a" * 30 + "!\n")
+        pattern = r"(?:a|aa)+(?=b)"
+
+        files, stats, errors = grep.search(pattern, path=root, engine="fancy", backtrack_limit=1000, stats=True, errors="collect")
+        assert files == [os.path.join(root, "ok.txt")]
+        assert errors == [(os.path.join(root, "bad.txt"), "regex backtrack limit exceeded (backtrack_limit=)")]
+        assert stats.skipped.errors == 1
+
+        with pytest.raises(ValueError, match="backtrack limit"):
+            grep.search(pattern, path=root, engine="fancy", backtrack_limit=1000, errors="raise")
+        # The default engine never backtracks, so the limit changes nothing there
+        assert grep.search("a+!", path=root, backtrack_limit=1) == [os.path.join(root, "bad.txt")]
+
+    def test_timeout_within_file(self):
+        """timeout= stops a search partway through a single large file"""
+        grep = pyripgrep.Grep()
+        big = os.path.join(self.tmpdir, "big.log")
+        with open(big, "wb") as f:
+            f.write((b"x" * 99 + b"\n") * 300_000)
+
+        started = time.time()
+        assert grep.search("needle", files=[big], output_mode="count") == {}
+        full = time.time() - started
+
+        for mode in ["files_with_matches", "content", "count", "matches"]:
+            started = time.time()
+            with pytest.raises(pyripgrep.SearchTimeout):
+                grep.search("needle", files=[big], output_mode=mode, timeout=0.001)
+            assert time.time() - started < full / 2
+
+    def test_search_page(self):
+        """search_page() pages through results with an opaque cursor, resuming mid-file"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "paged")
+        os.makedirs(root)
+        for i in range(5):
+            with open(os.path.join(root, f"f{i}.txt"), "w") as f:
+                f.write("hit\n" * (i % 3 + 1) + "miss\n")
+
+        for mode in ["matches", "count", "files_with_matches"]:
+            expected = grep.search("hit", path=root, output_mode=mode, sort="path")
+            collected = {} if mode == "count" else []
+            cursor = None
+            while True:
+                page, cursor = grep.search_page("hit", path=root, output_mode=mode, page_size=2, cursor=cursor)
+                assert len(page) <= 2
+                if mode == "count":
+                    collected.update(page)
+                else:
+                    collected += page
+                if cursor is None:
+                    break
+            assert collected == expected
+
+        # A page can end inside a file; the next one picks up at its following line
+        first, cursor = grep.search_page("hit", path=root, output_mode="matches", page_size=2)
+        second, _ = grep.search_page("hit", path=root, output_mode="matches", page_size=2, cursor=cursor)
+        assert [r["path"] for r in first] == [os.path.join(root, "f0.txt"), os.path.join(root, "f1.txt")]
+        assert (second[0]["path"], second[0]["line_number"]) == (os.path.join(root, "f1.txt"), 2)
+
+        with pytest.raises(TypeError):
+            grep.search_page("hit", path=root, max_files=1)
+        with pytest.raises(ValueError):
+            grep.search_page("hit", path=root, output_mode="content")
+        with pytest.raises(ValueError):
+            grep.search_page("hit", path=root, cursor="not a cursor")
+
+    def test_match_spans(self):
+        """spans=True adds each match's character range within the returned line"""
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "spans")
+        os.makedirs(root)
+        target = os.path.join(root, "notes.txt")
+        with open(target, "w", encoding="utf-8") as f:
+            f.write("café foo and foo\nnothing here\n")
+
+        records = grep.search("foo", path=root, output_mode="matches", spans=True)
+        assert records[0]["spans"] == [(5, 8), (13, 16)]
+        line = records[0]["content"]
+        assert [line[start:end] for start, end in records[0]["spans"]] == ["foo", "foo"]
+
+        first = grep.search("foo", path=root, output_mode="first_match", spans=True)
+        assert first[0]["spans"] == [(5, 8), (13, 16)]
+        grouped = grep.search("foo", path=root, output_mode="content", group_by_file=True, spans=True)
+        assert grouped[target][0]["spans"] == [(5, 8), (13, 16)]
+        assert "spans" not in grep.search("foo", path=root, output_mode="matches")[0]
+
+        with pytest.raises(ValueError):
+            grep.search("foo", path=root, output_mode="content", spans=True)
+        with pytest.raises(ValueError):
+            grep.search("foo", path=root, output_mode="matches", spans=True, replace="bar")
+
+    def test_max_bytes(self):
+        """Test max_bytes stops the search before the file that would exceed the budget"""
+        grep = pyripgrep.Grep()
+
+        budget_dir = os.path.join(self.tmpdir, "budget")
+        os.makedirs(budget_dir)
+        for i in range(5):
+            with open(os.path.join(budget_dir, f"f{i}.txt"), "w") as f:
+                f.write("needle\n" + "x" * 93 + "\n")  # 101 bytes each
+
+        files, stats = grep.search("needle", path=budget_dir, sort="path", max_bytes=250, stats=True)
+        assert [os.path.basename(f) for f in files] == ["f0.txt", "f1.txt"]
+        assert stats.files_searched == 2
+        assert stats.truncated
+
+        files = grep.search("needle", path=budget_dir, sort="path", max_bytes=250, truncation_warning=True)
+        assert files[-1] == "[Content truncated]"
+
+        # A budget the tree fits within changes nothing
+        files, stats = grep.search("needle", path=budget_dir, max_bytes=10_000, stats=True)
+        assert len(files) == 5
+        assert not stats.truncated
+
+    def test_stats_by_type(self):
+        """Test stats=True breaks the search down by file type"""
+        grep = pyripgrep.Grep()
+
+        typed_dir = os.path.join(self.tmpdir, "typed")
+        os.makedirs(typed_dir)
+        for name, content in [
+            ("a.py", "TODO one\nTODO two\n"),
+            ("b.py", "nothing here\n"),
+            ("c.h", "// TODO\n"),
+            ("settings.zzz", "TODO\n"),
+        ]:
+            with open(os.path.join(typed_dir, name), "w") as f:
+                f.write(content)
+
+        _, stats = grep.search("TODO", path=typed_dir, output_mode="count", stats=True)
+        assert set(stats.by_type) == {"py", "c", ".zzz"}
+
+        py = stats.by_type["py"]
+        assert py.files_searched == 2
+        assert py.files_matched == 1
+        assert py.matches == 2
+        assert py.bytes_searched == len("TODO one\nTODO two\n") + len("nothing here\n")
+        assert stats.by_type[".zzz"].matches == 1
+
+        # The breakdown adds up to the totals
+        assert sum(t.files_searched for t in stats.by_type.values()) == stats.files_searched
+        assert sum(t.bytes_searched for t in stats.by_type.values()) == stats.bytes_searched
+
+    def test_dedupe_content(self):
+        """Test dedupe="content" searches each distinct file content once"""
+        grep = pyripgrep.Grep()
+
+        mirror_dir = os.path.join(self.tmpdir, "mirror")
+        for sub in ["a", "b", "c"]:
+            os.makedirs(os.path.join(mirror_dir, sub))
+        for sub in ["a", "b", "c"]:
+            with open(os.path.join(mirror_dir, sub, "lib.py"), "w") as f:
+                f.write("TODO: shared\n")
+        # Same length, different bytes
+        with open(os.path.join(mirror_dir, "c", "own.py"), "w") as f:
+            f.write("TODO: others\n")
+
+        files, stats = grep.search("TODO", path=mirror_dir, sort="path", dedupe="content", stats=True)
+        original = os.path.join(mirror_dir, "a", "lib.py")
+        assert sorted(files) == sorted([original, os.path.join(mirror_dir, "c", "own.py")])
+        assert stats.skipped.duplicate == 2
+        assert stats.duplicates == {
+            original: [os.path.join(mirror_dir, "b", "lib.py"), os.path.join(mirror_dir, "c", "lib.py")]
+        }
+
+        # Without dedupe every copy is searched
+        assert len(grep.search("TODO", path=mirror_dir)) == 4
+
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=mirror_dir, dedupe="name")
+
+    def test_ignore_file_errors(self):
+        """Test malformed .gitignore lines surface as warnings instead of being dropped silently"""
+        grep = pyripgrep.Grep()
+
+        repo_dir = os.path.join(self.tmpdir, "badignore")
+        os.makedirs(os.path.join(repo_dir, ".git"))
+        with open(os.path.join(repo_dir, ".gitignore"), "w") as f:
+            f.write("foo[\n*.log\n")
+        for name in ["keep.txt", "drop.log"]:
+            with open(os.path.join(repo_dir, name), "w") as f:
+                f.write("TODO\n")
+
+        # The bad line is reported; the good one still applies
+        with pytest.warns(UserWarning, match="foo\\["):
+            files = grep.search("TODO", path=repo_dir)
+        assert files == [os.path.join(repo_dir, "keep.txt")]
+
+        with warnings.catch_warnings():
+            warnings.simplefilter("error")
+            assert grep.search("TODO", path=repo_dir, ignore_file_errors="ignore") == files
+
+        with pytest.raises(pyripgrep.WalkError):
+            grep.search("TODO", path=repo_dir, ignore_file_errors="raise")
+
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=repo_dir, ignore_file_errors="loud")
+
+    def test_full_type_database(self):
+        """Test any ripgrep type name is accepted, not only the aliased ones"""
+        grep = pyripgrep.Grep()
+
+        types_dir = os.path.join(self.tmpdir, "types")
+        os.makedirs(types_dir)
+        for name in ["CMakeLists.txt", "schema.sql", "Dockerfile", "notes.txt"]:
+            with open(os.path.join(types_dir, name), "w") as f:
+                f.write("MARKER\n")
+
+        found = grep.search("MARKER", path=types_dir, type=["cmake", "sql", "docker"])
+        assert sorted(os.path.basename(f) for f in found) == ["CMakeLists.txt", "Dockerfile", "schema.sql"]
+
+        # Aliases still map onto ripgrep names
+        assert grep.search("def", path=self.subdir, type="python") == grep.search("def", path=self.subdir, type="py")
+
+        with pytest.raises(ValueError, match="Unknown file type: nosuchtype"):
+            grep.search("MARKER", path=types_dir, type="nosuchtype")
+
+    def test_type_aliases_and_suggestions(self):
+        """Test type names are case-insensitive, common aliases resolve and typos get suggestions"""
+        grep = pyripgrep.Grep()
+
+        alias_dir = os.path.join(self.tmpdir, "aliases")
+        os.makedirs(alias_dir)
+        for name in ["main.go", "Program.cs", "build.sh"]:
+            with open(os.path.join(alias_dir, name), "w") as f:
+                f.write("MARKER\n")
+
+        def names(type_filter):
+            return sorted(os.path.basename(f) for f in grep.search("MARKER", path=alias_dir, type=type_filter))
+
+        assert names("golang") == ["main.go"]
+        assert names("C#") == ["Program.cs"]
+        assert names("shell") == ["build.sh"]
+        assert names("GO") == names("Go") == ["main.go"]
+        assert grep.search("def", path=self.subdir, type="Python") == grep.search("def", path=self.subdir, type="py")
+
+        with pytest.raises(ValueError, match='did you mean "python"'):
+            grep.search("MARKER", path=alias_dir, type="pyhton")
+
+    def test_glob_list_and_iglob(self):
+        """Test glob= takes a list with brace expansion, and iglob= matches case-insensitively"""
+        grep = pyripgrep.Grep()
+
+        glob_dir = os.path.join(self.tmpdir, "globs")
+        os.makedirs(os.path.join(glob_dir, "src", "nested"))
+        for name in ["a.py", "b.pyi", "c.rs", "src/nested/d.rs", "E.PY", "notes.md"]:
+            with open(os.path.join(glob_dir, name), "w") as f:
+                f.write("TODO\n")
+        names = lambda **kwargs: sorted(
+            os.path.relpath(p, glob_dir) for p in grep.search("TODO", path=glob_dir, **kwargs)
+        )
+
+        assert names(glob="*.{py,pyi}") == ["a.py", "b.pyi"]
+        assert names(glob=["*.py", "*.pyi", "src/**/*.rs"]) == ["a.py", "b.pyi", "src/nested/d.rs"]
+        assert names(iglob="*.py") == ["E.PY", "a.py"]
+        assert names(glob="*.md", iglob=["*.py"]) == ["E.PY", "a.py", "notes.md"]
+        assert names(glob=["*", "!*.rs"]) == ["E.PY", "a.py", "b.pyi", "notes.md"]
+
+        # Every --glob in a config file applies, not just the last
+        config = os.path.join(self.tmpdir, "globrc")
+        with open(config, "w") as f:
+            f.write("-g\n*.py\n--glob=*.md\n--iglob\n*.PYI\n")
+        configured = pyripgrep.Grep.from_config(config)
+        assert configured.defaults["glob"] == ["*.py", "*.md"]
+        assert sorted(os.path.relpath(p, glob_dir) for p in configured.search("TODO", path=glob_dir)) == [
+            "a.py", "b.pyi", "notes.md"
+        ]
+
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=glob_dir, glob=42)
+
+    def test_search_git_dir(self):
+        """Test search_git_dir includes or excludes .git independently of hidden"""
+        grep = pyripgrep.Grep()
+
+        repo_dir = os.path.join(self.tmpdir, "gitdir")
+        os.makedirs(os.path.join(repo_dir, ".git", "hooks"))
+        os.makedirs(os.path.join(repo_dir, ".cache"))
+        for name in [".git/config", ".git/hooks/pre-commit", ".cache/blob", "main.py"]:
+            with open(os.path.join(repo_dir, name), "w") as f:
+                f.write("SECRET\n")
+        names = lambda **kwargs: sorted(
+            os.path.relpath(p, repo_dir) for p in grep.search("SECRET", path=repo_dir, **kwargs)
+        )
+
+        # Default: .git is skipped like any hidden directory
+        assert names() == ["main.py"]
+        # .git alone, without the other hidden entries
+        assert names(search_git_dir=True) == [".git/config", ".git/hooks/pre-commit", "main.py"]
+        assert names(hidden=True) == [".cache/blob", ".git/config", ".git/hooks/pre-commit", "main.py"]
+        assert names(hidden=True, search_git_dir=False) == [".cache/blob", "main.py"]
+
+    def test_text_mode_bytes(self):
+        """text_mode="bytes" returns content lines losslessly, undecodable files included"""
+        grep = pyripgrep.Grep()
+        latin = os.path.join(self.tmpdir, "latin.txt")
+        with open(latin, "wb") as f:
+            f.write(b"caf\xe9 TODO\nplain TODO\n")
+
+        assert grep.search("TODO", path=latin, output_mode="content") == []
+        lines = grep.search("TODO", path=latin, output_mode="content", text_mode="bytes")
+        assert lines == [latin.encode() + b":caf\xe9 TODO", latin.encode() + b":plain TODO"]
+
+        grouped = grep.search("caf", path=latin, output_mode="content", text_mode="bytes", group_by_file=True)
+        assert grouped[latin][0]["content"] == b"caf\xe9 TODO"
+
+        utf8 = grep.search("def", path=self.subdir, output_mode="content", text_mode="bytes")
+        assert utf8 and all(isinstance(line, bytes) for line in utf8)
+
+        lossy = grep.search("caf", path=latin, output_mode="content", ignore_level=3)
+        assert lossy == [latin + ":caf� TODO"]
+        assert grep.search("caf", path=latin, output_mode="content", ignore_level=3, lossy=False) == []
+
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=latin, text_mode="bytes")
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=latin, output_mode="content", text_mode="latin-1")
+
+    def test_column_unit(self):
+        """column_unit= counts vimgrep columns and spans in bytes, chars or tab-expanded columns"""
+        grep = pyripgrep.Grep()
+        path = os.path.join(self.tmpdir, "cols.txt")
+        with open(path, "w", encoding="utf-8") as f:
+            f.write("\té TODO\n")
+
+        def column(**kwargs):
+            entry = grep.search("TODO", path=path, output_mode="vimgrep", **kwargs)[0]
+            return int(entry[len(path) + 1:].split(":")[1])
+
+        assert column() == 5
+        assert column(column_unit="bytes") == 5
+        assert column(column_unit="chars") == 4
+        assert column(column_unit="display") == 11
+        assert column(column_unit="display", tab_width=4) == 7
+
+        def spans(**kwargs):
+            return grep.search("TODO", path=path, output_mode="matches", spans=True, **kwargs)[0]["spans"]
+
+        assert spans() == [(3, 7)]
+        assert spans(column_unit="bytes") == [(4, 8)]
+        assert spans(column_unit="display", tab_width=2) == [(4, 8)]
+
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=path, output_mode="content", column_unit="chars")
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=path, output_mode="vimgrep", column_unit="chars", tab_width=4)
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=path, output_mode="vimgrep", column_unit="display", tab_width=0)
+        with pytest.raises(ValueError):
+            grep.search("TODO", path=path, output_mode="vimgrep", column_unit="graphemes")
+
+    def test_find_files_refine(self):
+        """find_files() returns a FileSet whose refine() and search() read only its files"""
+        grep = pyripgrep.Grep()
+        for name, text in [("a.py", "needle\nTODO\n"), ("b.py", "needle\n"), ("c.py", "TODO\n")]:
+            with open(os.path.join(self.tmpdir, name), "w") as f:
+                f.write(text)
+
+        found = grep.find_files("needle", path=self.tmpdir)
+        assert isinstance(found, pyripgrep.FileSet)
+        assert sorted(found.files) == sorted(grep.search("needle", path=self.tmpdir))
+        assert len(found) == 2
+
+        refined = found.refine("TODO")
+        assert refined.files == [os.path.join(self.tmpdir, "a.py")]
+        assert os.path.join(self.tmpdir, "a.py") in refined
+        assert len(refined.refine("nothing here")) == 0
+        assert refined.search("TODO", output_mode="count") == {os.path.join(self.tmpdir, "a.py"): 1}
+
+        with pytest.raises(TypeError):
+            grep.find_files("needle", path=self.tmpdir, output_mode="content")
+        with pytest.raises(TypeError):
+            found.refine("TODO", path=self.tmpdir)
+        with pytest.raises(TypeError):
+            found.search("TODO", files=["a.py"])
+
+    def test_profile(self):
+        """profile=True breaks the search time down by phase in SearchStats.profile"""
+        grep = pyripgrep.Grep()
+
+        results, stats = grep.search("def", path=self.tmpdir, output_mode="content", profile=True)
+        assert results
+        profile = stats.profile
+        assert isinstance(profile, pyripgrep.Profile)
+        assert profile.total == stats.elapsed
+        for phase in [profile.setup, profile.walk, profile.read, profile.matching, profile.format]:
+            assert phase >= 0
+        assert profile.setup <= profile.total
+
+        for threads in [1, 4]:
+            _, stats = grep.search("def", path=self.tmpdir, threads=threads, profile=True)
+            assert stats.profile.walk > 0
+            assert stats.profile.read > 0
+            assert stats.profile.matching > 0
+
+        _, stats = grep.search("def", path=self.tmpdir, stats=True)
+        assert stats.profile is None
+
+    def test_normalize_paths_and_long_paths(self):
+        """Deep trees past MAX_PATH are searched; normalize_paths=True tidies result paths"""
+        grep = pyripgrep.Grep()
+        deep = self.tmpdir
+        for i in range(30):
+            deep = os.path.join(deep, f"nested_directory_{i:02d}")
+        os.makedirs(deep)
+        target = os.path.join(deep, "deep.txt")
+        with open(target, "w") as f:
+            f.write("needle\n")
+        assert len(target) > 300
+
+        assert grep.search("needle", path=self.tmpdir) == [target]
+
+        untidy = os.path.join(self.tmpdir, ".", "src", "..", "nested_directory_00")
+        [shown] = grep.search("needle", path=untidy)
+        assert shown.startswith(untidy)
+        assert grep.search("needle", path=untidy, normalize_paths=True) == [target]
+
+    @pytest.mark.skipif(os.name != "nt", reason="verbatim paths are a Windows feature")
+    def test_verbatim_root_windows(self):
+        """A \\\\?\\ root is searched and shown without its prefix with normalize_paths=True"""
+        grep = pyripgrep.Grep()
+        root = "\\\\?\\" + os.path.abspath(self.subdir)
+        assert grep.search("def", path=root)
+        assert grep.search("def", path=root, normalize_paths=True) == [os.path.join(os.path.abspath(self.subdir), "utils.py")]
+
+    def test_changed_files(self):
+        """changed_files= detects files that change while they are searched"""
+        grep = pyripgrep.Grep()
+        log = os.path.join(self.tmpdir, "live.log")
+        with open(log, "w") as f:
+            f.write("ERROR one\n")
+        calls = []
+
+        def growing(path, grow_calls=1):
+            # Stands in for a writer appending to the log while it is read
+            calls.append(path)
+            with open(path, "rb") as f:
+                data = f.read()
+            if len(calls) <= grow_calls:
+                with open(path, "a") as f:
+                    f.write("ERROR appended\n")
+            return data
+
+        assert grep.search("ERROR", path=log, preprocessor=growing) == [log]
+        assert grep.search("ERROR", path=log, preprocessor=growing, changed_files="ignore") == [log]
+
+        calls.clear()
+        results, stats = grep.search("ERROR", path=log, preprocessor=growing, changed_files="flag", stats=True)
+        assert results == [log]
+        assert stats.changed == [log]
+        assert stats.files_searched == 1
+
+        calls.clear()
+        results, stats = grep.search("ERROR", path=log, preprocessor=growing, changed_files="skip", stats=True)
+        assert results == []
+        assert stats.changed == [log]
+        assert stats.skipped.changed == 1
+        assert stats.files_searched == 0
+
+        calls.clear()
+        results, stats = grep.search("ERROR", path=log, output_mode="count", preprocessor=growing, changed_files="retry", stats=True)
+        assert len(calls) == 2
+        with open(log) as f:
+            assert results == {log: len(f.readlines())}  # the retry saw the appended line
+        assert stats.changed == []
+        assert stats.files_searched == 1
+
+        calls.clear()
+        _, stats = grep.search("ERROR", path=log, preprocessor=lambda p: growing(p, 2), changed_files="retry", stats=True)
+        assert len(calls) == 2
+        assert stats.changed == [log]
+
+        with pytest.raises(ValueError):
+            grep.search("ERROR", path=log, changed_files="wait")
+
+    def test_ignore_root_and_parents(self):
+        """root= anchors ignore rules above the search path; ignore_parents=False drops them"""
+        grep = pyripgrep.Grep()
+        repo = os.path.join(self.tmpdir, "export")
+        services = os.path.join(repo, "services")
+        os.makedirs(os.path.join(services, "build"))
+        os.makedirs(os.path.join(services, "api"))
+        with open(os.path.join(repo, ".gitignore"), "w") as f:
+            f.write("/services/build/\n*.log\n!keep.log\n")
+        for name in ["build/out.txt", "api/main.py", "api/debug.log", "api/keep.log"]:
+            with open(os.path.join(services, name), "w") as f:
+                f.write("needle\n")
+
+        def found(**kwargs):
+            return sorted(os.path.relpath(p, services) for p in grep.search("needle", path=services, **kwargs))
+
+        everything = sorted(os.path.join(*name.split("/")) for name in ["build/out.txt", "api/main.py", "api/debug.log", "api/keep.log"])
+        # No .git directory: .gitignore is not honored unless root= anchors it
+        assert found() == everything
+        expected = sorted([os.path.join("api", "main.py"), os.path.join("api", "keep.log")])
+        assert found(root=repo) == expected
+        assert found(root=services) == everything
+        assert found(root=repo, ignore_level=1) == everything
+
+        # Inside a repository the walker reads parent ignore files unless told not to
+        os.makedirs(os.path.join(repo, ".git"))
+        assert found() == expected
+        assert found(ignore_parents=False) == everything
+
+        with pytest.raises(ValueError):
+            grep.search("needle", path=services, root=self.subdir)
+
+    def test_spill_over(self):
+        """Test spill_over writes large content results to a JSONL file"""
+        grep = pyripgrep.Grep()
+
+        expected = grep.search("def", path=self.tmpdir, output_mode="content", n=True, sort="path")
+        assert len(expected) > 1
+
+        spilled = grep.search("def", path=self.tmpdir, output_mode="content", n=True, sort="path", spill_over=1)
+        assert isinstance(spilled, pyripgrep.SpilledResults)
+        assert len(spilled) == len(expected)
+        assert list(spilled) == expected
+        assert list(spilled) == expected  # each iteration reads the file again
+
+        path = spilled.path
+        with open(path) as f:
+            assert [json.loads(line) for line in f] == expected
+        spilled.close()
+        assert not os.path.exists(path)
+        with pytest.raises(ValueError):
+            iter(spilled)
+
+        with grep.search("def", path=self.tmpdir, output_mode="content", spill_over=1) as spilled:
+            path = spilled.path
+            assert os.path.exists(path)
+        assert not os.path.exists(path)
+
+        # At or below the threshold the usual list comes back
+        results = grep.search("def", path=self.tmpdir, output_mode="content", n=True, sort="path", spill_over=len(expected))
+        assert results == expected
+
+        with pytest.raises(ValueError, match="spill_over"):
+            grep.search("def", path=self.tmpdir, output_mode="count", spill_over=1)
+        with pytest.raises(ValueError, match="spill_over"):
+            grep.search("def", path=self.tmpdir, output_mode="content", group_by_file=True, spill_over=1)
+
+    def test_filter_logic(self):
+        """Test filter_logic combines glob and type with AND or OR"""
+        grep = pyripgrep.Grep()
+
+        with open(os.path.join(self.tmpdir, "notes.txt"), "w") as f:
+            f.write("def in a text file\n")
+        with open(os.path.join(self.tmpdir, "skip.txt"), "w") as f:
+            f.write("def in an excluded text file\n")
+
+        def found(**kwargs):
+            results = grep.search("def", path=self.tmpdir, **kwargs)
+            return sorted(os.path.basename(path) for path in results)
+
+        python_files = found(type="python")
+        assert "notes.txt" not in python_files
+
+        # Default AND: no Python file matches *.txt
+        assert found(type="python", glob="*.txt") == []
+        assert found(type="python", glob="*.txt", filter_logic="and") == []
+
+        # OR: Python files plus the text files the glob selects
+        assert found(type="python", glob="*.txt", filter_logic="or") == sorted(python_files + ["notes.txt", "skip.txt"])
+
+        # Exclude globs still apply to every file
+        assert found(type="python", glob=["*.txt", "!skip.txt"], filter_logic="or") == sorted(python_files + ["notes.txt"])
+
+        # With only one of glob and type, OR changes nothing
+        assert found(glob="*.txt", filter_logic="or") == found(glob="*.txt")
+
+        with pytest.raises(ValueError, match="filter_logic"):
+            grep.search("def", path=self.tmpdir, filter_logic="xor")
+
+    def test_context_block(self):
+        """Test context='block' returns each match's enclosing block"""
+        grep = pyripgrep.Grep()
+
+        source = os.path.join(self.tmpdir, "blocks.py")
+        with open(source, "w") as f:
+            f.write(
+                "import os\n"
+                "\n"
+                "\n"
+                "@cached\n"
+                "def load(path):\n"
+                "    for line in open(path):\n"
+                "        if 'needle' in line:\n"
+                "            return line\n"
+                "    return 'needle'\n"
+                "\n"
+                "\n"
+                "def other():\n"
+                "    pass\n"
+            )
+        results = grep.search("needle", path=source, output_mode="content", n=True, no_filename=True, context="block")
+        # Both matches share the function, shown once and without a separator
+        assert results == [
+            "4-@cached",
+            "5-def load(path):",
+            "6-    for line in open(path):",
+            "7:        if 'needle' in line:",
+            "8-            return line",
+            "9:    return 'needle'",
+        ]
+
+        rust = os.path.join(self.tmpdir, "blocks.rs")
+        with open(rust, "w") as f:
+            f.write("fn main() {\n    if ready {\n        println!(\"needle\");\n    }\n}\n\nfn other() {}\n")
+        results = grep.search("needle", path=rust, output_mode="content", no_filename=True, context="block")
+        assert results == ["fn main() {", "    if ready {", "        println!(\"needle\");", "    }", "}"]
+
+        config = os.path.join(self.tmpdir, "blocks.ini")
+        with open(config, "w") as f:
+            f.write("[cache]\nsize = 10\n\n[server]\nhost = needle\nport = 80\n\n[log]\nlevel = needle\n")
+        results = grep.search("needle", path=config, output_mode="content", n=True, no_filename=True, context="block")
+        assert results == ["4-[server]", "5:host = needle", "6-port = 80", "--", "8-[log]", "9:level = needle"]
+
+        grouped = grep.search("needle", path=config, output_mode="content", context="block", group_by_file=True)
+        assert grouped[config][0]["before_context"] == ["[server]"]
+        assert grouped[config][0]["after_context"] == ["port = 80"]
+
+        with pytest.raises(ValueError, match="block"):
+            grep.search("needle", path=source, output_mode="content", context="block", C=2)
+        with pytest.raises(ValueError, match="block"):
+            grep.search("needle", path=source, output_mode="count", context="block")
+        with pytest.raises(ValueError, match="context"):
+            grep.search("needle", path=source, output_mode="content", context="lines")
+
+    def test_parallel_search_stops_early(self):
+        """Test stopping early with several threads doesn't leave the walker blocked on a full queue"""
+        import threading
+        grep = pyripgrep.Grep()
+
+        tree = os.path.join(self.tmpdir, "many")
+        os.makedirs(tree)
+        for i in range(200):
+            with open(os.path.join(tree, f"file{i:03d}.txt"), "w") as f:
+                f.write("needle\n")
+
+        runs = [
+            ("quiet", lambda: grep.search("needle", path=tree, output_mode="quiet", threads=4, queue_size=2)),
+            ("max_files", lambda: grep.search("needle", path=tree, max_files=1, threads=4, queue_size=2)),
+            ("max_matches_total", lambda: grep.search("needle", path=tree, output_mode="content",
+                                                      max_matches_total=1, threads=4, queue_size=2)),
+        ]
+        for name, run in runs:
+            results = []
+            thread = threading.Thread(target=lambda: results.append(run()), daemon=True)
+            thread.start()
+            thread.join(timeout=30)
+            assert not thread.is_alive(), f"{name} search did not return"
+            assert results[0] is True or len(results[0]) == 1
+
+    def test_crlf_consistent_across_modes(self):
+        """Test every output mode applies the same line terminator rule to CRLF files"""
+        grep = pyripgrep.Grep()
+        path = os.path.join(self.tmpdir, "crlf.txt")
+        with open(path, "wb") as f:
+            f.write(b"foo\r\nbar foo\r\n")
+
+        def modes(**kwargs):
+            content = grep.search("foo$", path=path, output_mode="content", no_filename=True, **kwargs)
+            count = grep.search("foo$", path=path, output_mode="count", **kwargs)
+            files = grep.search("foo$", path=path, output_mode="files_with_matches", **kwargs)
+            matches = grep.search("foo$", path=path, output_mode="matches", **kwargs)
+            return content, count, files, [m["line_number"] for m in matches]
+
+        # Without crlf the \r is part of the line, so `$` never matches before it
+        assert modes() == ([], {}, [], [])
+        assert modes(crlf=False) == ([], {}, [], [])
+
+        # With crlf every mode matches both lines, shown without the \r
+        assert modes(crlf=True) == (["foo", "bar foo"], {path: 2}, [path], [1, 2])
+
+        # Lines are shown without the \r either way
+        assert grep.search("bar", path=path, output_mode="content", no_filename=True) == ["bar foo"]
+
+    def test_validate_pattern_flags(self):
+        """Test validate_pattern compiles with search's regex flags and the Grep's defaults"""
+        grep = pyripgrep.Grep()
+
+        # Each flag changes the verdict exactly as it changes whether search accepts the pattern
+        cases = [
+            (r"\w{1000}", {"regex_size_limit": 1000}),
+            (r"\p{Greek}", {"unicode": False}),
+            (r"(?<=a)b", {"engine": "auto"}),
+            ("ERROR$", {"crlf": True, "x": True, "case_smart": True}),
+        ]
+        if pyripgrep.features()["fancy_regex"]:
+            cases.append((r"(?<=a)b", {"engine": "fancy"}))
+        for pattern, flags in cases:
+            try:
+                grep.search(pattern, path=self.tmpdir, **flags)
+                accepted = True
+            except ValueError:
+                accepted = False
+            assert grep.validate_pattern(pattern, **flags)["valid"] is accepted, (pattern, flags)
+
+        assert grep.validate_pattern(r"\w{1000}")["valid"] is True
+        limited = grep.validate_pattern(r"\w{1000}", regex_size_limit=1000)
+        assert limited["valid"] is False
+        assert "size limit" in limited["error"]
+        assert grep.validate_pattern(r"(?<=a)b", engine="auto")["valid"] is True
+
+        # Defaults given to the Grep apply, and flags passed here override them
+        limited_grep = pyripgrep.Grep(regex_size_limit=1000, path=self.tmpdir)
+        assert limited_grep.validate_pattern(r"\w{1000}")["valid"] is False
+        assert limited_grep.validate_pattern(r"\w{1000}", regex_size_limit=None)["valid"] is True
+
+        with pytest.raises(TypeError, match="path"):
+            grep.validate_pattern("foo", path=self.tmpdir)
+        with pytest.raises(ValueError, match="Invalid engine"):
+            grep.validate_pattern("foo", engine="pcre")
+
+    def test_preview_read_options(self):
+        """Test preview decodes files with search's read options and binary policy"""
+        grep = pyripgrep.Grep()
+
+        latin = os.path.join(self.tmpdir, "latin.txt")
+        with open(latin, "wb") as f:
+            f.write(b"caf\xe9\nline two\n")
+        with pytest.raises(pyripgrep.IOErrorInfo):
+            grep.preview(latin, 1)
+        assert grep.preview(latin, 1, encodings={"*.txt": "latin-1"}) == [(1, "café"), (2, "line two")]
+        assert grep.preview(latin, 1, ignore_level=3) == [(1, "caf�"), (2, "line two")]
+        # Defaults given to the Grep apply, as they do to search
+        latin_grep = pyripgrep.Grep(encodings={"*.txt": "latin-1"}, path=self.tmpdir)
+        assert latin_grep.preview(latin, 2, before=0) == [(2, "line two")]
+
+        # A NUL byte marks the file binary unless ignore_level=3
+        nul = os.path.join(self.tmpdir, "nul.txt")
+        with open(nul, "wb") as f:
+            f.write(b"head\x00tail\nnext\n")
+        with pytest.raises(pyripgrep.IOErrorInfo, match="binary"):
+            grep.preview(nul, 1)
+        assert grep.preview(nul, 1, ignore_level=3) == [(1, "head\x00tail"), (2, "next")]
+
+        # Only the lines up to the window are read, so later undecodable bytes don't matter
+        tail = os.path.join(self.tmpdir, "tail.txt")
+        with open(tail, "wb") as f:
+            f.write(b"one\r\ntwo\r\nthree\r\n" + b"\xff" * 10)
+        assert grep.preview(tail, 1, after=1) == [(1, "one"), (2, "two")]
+        assert grep.preview(tail, 1, after=1, crlf=True) == [(1, "one"), (2, "two")]
+
+        with pytest.raises(TypeError, match="output_mode"):
+            grep.preview(tail, 1, output_mode="content")
+
+    def test_panic_is_file_error(self):
+        """Test a panic while searching one file is reported as that file's error"""
+        grep = pyripgrep.Grep()
+
+        # PyO3 resumes a Rust panic when a callback raises its PanicException;
+        # the class exists once any Python error has passed through PyO3
+        with pytest.raises(ValueError):
+            grep.search("x", path=self.tmpdir, preprocessor=42)
+        panic_exception = next(c for c in BaseException.__subclasses__() if c.__name__ == "PanicException")
+
+        main = os.path.join(self.tmpdir, "main.py")
+        def panicking(path):
+            if path == main:
+                raise panic_exception("preprocessor blew up")
+            with open(path) as f:
+                return f.read()
+
+        expected = grep.search("ERROR", path=self.tmpdir)
+        assert main in expected
+        for threads in (1, 4):
+            files, errors = grep.search("ERROR", path=self.tmpdir, preprocessor=panicking, threads=threads, errors="collect")
+            assert sorted(files) == sorted(f for f in expected if f != main)
+            assert len(errors) == 1
+            assert errors[0][0] == main and "panic" in errors[0][1] and "preprocessor blew up" in errors[0][1]
+
+    def test_compare_grep_defaults(self):
+        """Test compare() applies the Grep's defaults, which its arguments override"""
+        grep = pyripgrep.Grep(case_insensitive=True)
+
+        with tempfile.TemporaryDirectory() as before, tempfile.TemporaryDirectory() as after:
+            with open(os.path.join(before, "a.txt"), "w") as f:
+                f.write("FOO 1\n")
+            with open(os.path.join(after, "a.txt"), "w") as f:
+                f.write("FOO 1\nfoo2\n")
+
+            diff = grep.compare("FOO", before, after)
+            assert diff["deltas"] == {"a.txt": (1, 2, 1)}
+            assert diff["added"] == [("a.txt", 2, "foo2")]
+            assert grep.compare("FOO", before, after, i=False)["deltas"] == {}
+
+            with pytest.raises(TypeError):
+                grep.compare("FOO", before, after, casefold=True)
+
+    def test_search_rev_grep_defaults(self):
+        """Test search_rev() applies the Grep's defaults, which its arguments override"""
+        if shutil.which("git") is None:
+            pytest.skip("git not available")
+        repo = os.path.join(self.tmpdir, "rev_defaults")
+        os.makedirs(repo)
+
+        def git(*args):
+            subprocess.run(["git", "-C", repo, *args], check=True, capture_output=True)
+
+        git("init", "-q")
+        git("config", "user.email", "dev@example.com")
+        git("config", "user.name", "dev")
+        with open(os.path.join(repo, "api.py"), "w") as f:
+            f.write("def Export():\n")
+        with open(os.path.join(repo, "notes.txt"), "w") as f:
+            f.write("export notes\n")
+        git("add", ".")
+        git("commit", "-q", "-m", "first")
+
+        grep = pyripgrep.Grep(i=True, type="python")
+        assert grep.search_rev("export", repo=repo) == ["HEAD:api.py:1:def Export():"]
+        assert grep.search_rev("export", repo=repo, i=False) == []
+        assert sorted(grep.search_rev("export", repo=repo, type=None)) == \
+            ["HEAD:api.py:1:def Export():", "HEAD:notes.txt:1:export notes"]
+
+    def test_watch_grep_defaults(self):
+        """Test watch() applies the Grep's defaults"""
+        if not pyripgrep.features()["watch"]:
+            return
+        root = os.path.join(self.tmpdir, "watched_defaults")
+        os.makedirs(root)
+        watched = os.path.join(root, "a.txt")
+        with open(watched, "w") as f:
+            f.write("start\n")
+        events = []
+
+        with pyripgrep.Grep(i=True).watch("foo", root, events.append, debounce_ms=20):
+            with open(watched, "w") as f:
+                f.write("start\nFOO 1\n")
+            deadline = time.time() + 5
+            while not events and time.time() < deadline:
+                time.sleep(0.05)
+        assert events == [{"path": watched, "added": [(2, "FOO 1")], "removed": []}]
+
+    def test_explain_grep_defaults(self):
+        """Test explain() applies the Grep's defaults, which its arguments override"""
+        root = os.path.join(self.tmpdir, "explain_defaults")
+        os.makedirs(os.path.join(root, "src"))
+        with open(os.path.join(root, "src", "b.rs"), "w") as f:
+            f.write("fn main() {}\n")
+        target = os.path.join(root, "src", "b.rs")
+
+        grep = pyripgrep.Grep(type="python")
+        assert grep.explain(target, root=root).cause == "type_filter"
+        assert grep.explain(target, root=root, type=None).included
+        assert pyripgrep.Grep().explain(target, root=root).included
+
+        with pytest.raises(TypeError):
+            grep.explain(target, root=root, casefold=True)
+
+    def test_archives_read_once(self):
+        """Test archive members are searched from one pass over each archive, in any search shape"""
+        import tarfile
+        import zipfile
+        grep = pyripgrep.Grep()
+        root = os.path.join(self.tmpdir, "many_members")
+        os.makedirs(root)
+        wheel = os.path.join(root, "vendored.whl")
+        with zipfile.ZipFile(wheel, "w", zipfile.ZIP_DEFLATED) as archive:
+            for index in range(300):
+                archive.writestr(f"pkg/mod{index:03}.py", f"value = {index}\n" + ("needle\n" if index % 3 == 0 else ""))
+            archive.writestr("pkg/copy.py", "value = 0\nneedle\n")
+        sdist = os.path.join(root, "sdist.tar.gz")
+        with tarfile.open(sdist, "w:gz") as archive:
+            for index in range(300):
+                data = f"line {index}\n".encode() + (b"needle\n" if index % 5 == 0 else b"")
+                info = tarfile.TarInfo(f"./src/file{index:03}.txt")
+                info.size = len(data)
+                archive.addfile(info, io.BytesIO(data))
+
+        expected = sorted(
+            [f"{wheel}!/pkg/mod{index:03}.py" for index in range(0, 300, 3)]
+            + [f"{wheel}!/pkg/copy.py"]
+            + [f"{sdist}!/src/file{index:03}.txt" for index in range(0, 300, 5)]
+        )
+        for threads in (1, 4):
+            assert sorted(grep.search("needle", root, archives=True, threads=threads)) == expected
+            counts = grep.search("needle", root, archives=True, threads=threads, batch_small_files=True, output_mode="count")
+            assert sorted(counts) == expected and set(counts.values()) == {1}
+        assert grep.search("needle", root, archives=True, sort="path") == expected
+        assert sorted(grep.search("needle", root, archives=True, type="py")) == [path for path in expected if path.endswith(".py")]
+
+        # copy.py repeats mod000.py, so dedupe reads both members before either is searched
+        deduped = grep.search("needle", root, archives=True, dedupe="content", threads=1)
+        assert f"{wheel}!/pkg/copy.py" not in deduped and len(deduped) == len(expected) - 1
+
+    def test_git_scope_unborn_head(self):
+        """Test scope= works in a repository that has no commits yet"""
+        if not pyripgrep.features()["git"]:
+            pytest.skip("git not available")
+        grep = pyripgrep.Grep()
+        repo = os.path.join(self.tmpdir, "fresh_repo")
+        os.makedirs(repo)
+        subprocess.run(["git", "-C", repo, "init", "-q"], check=True, capture_output=True)
+        for name in ("added.txt", "untracked.txt"):
+            with open(os.path.join(repo, name), "w") as f:
+                f.write("hello\n")
+        subprocess.run(["git", "-C", repo, "add", "added.txt"], check=True, capture_output=True)
+
+        def found(scope):
+            return sorted(os.path.basename(p) for p in grep.search("hello", path=repo, scope=scope))
+
+        assert found("changed") == ["added.txt", "untracked.txt"]
+        assert found("staged") == ["added.txt"]
+        assert found("tracked") == ["added.txt"]
+
+    def test_features_git(self):
+        """Test features() reports git by whether the git CLI scope= runs is on PATH"""
+        assert pyripgrep.features()["git"] == (shutil.which("git") is not None)
+
+    def test_explain_hidden_matches_search(self):
+        """explain() takes search()'s walk options and agrees with the walk search() runs"""
+        os.makedirs(os.path.join(self.tmpdir, ".config"))
+        os.makedirs(os.path.join(self.tmpdir, ".git"))
+        with open(os.path.join(self.tmpdir, ".config", "app.txt"), "w") as f:
+            f.write("needle\n")
+        with open(os.path.join(self.tmpdir, ".git", "HEAD"), "w") as f:
+            f.write("needle\n")
+        with open(os.path.join(self.tmpdir, "Readme.TXT"), "w") as f:
+            f.write("needle\n")
+        grep = pyripgrep.Grep()
+
+        hidden_file = os.path.join(self.tmpdir, ".config", "app.txt")
+        searched = grep.search("needle", path=self.tmpdir, hidden=True)
+        assert hidden_file in searched
+        assert grep.explain(hidden_file, root=self.tmpdir, hidden=True).included
+        assert pyripgrep.Grep(hidden=True).explain(hidden_file, root=self.tmpdir).included
+        why = grep.explain(hidden_file, root=self.tmpdir)
+        assert not why.included
+        assert why.cause == "hidden"
+        assert hidden_file not in grep.search("needle", path=self.tmpdir)
+
+        git_file = os.path.join(self.tmpdir, ".git", "HEAD")
+        assert git_file in searched
+        assert grep.explain(git_file, root=self.tmpdir, hidden=True).included
+        assert git_file not in grep.search("needle", path=self.tmpdir, hidden=True, search_git_dir=False)
+        assert not grep.explain(git_file, root=self.tmpdir, hidden=True, search_git_dir=False).included
+        assert git_file in grep.search("needle", path=self.tmpdir, search_git_dir=True)
+        assert grep.explain(git_file, root=self.tmpdir, search_git_dir=True).included
+
+        readme = os.path.join(self.tmpdir, "Readme.TXT")
+        assert grep.search("needle", path=self.tmpdir, iglob="*.txt") == [readme]
+        assert grep.explain(readme, root=self.tmpdir, iglob="*.txt").included
+        assert not grep.explain(hidden_file, root=self.tmpdir, iglob="*.txt").included
+
+    def test_watch_multiline(self):
+        """Test watch() searches whole files, so multiline patterns and walk options apply"""
+        if not pyripgrep.features()["watch"]:
+            return
+        root = os.path.join(self.tmpdir, "watched_multiline")
+        os.makedirs(os.path.join(root, ".hidden"))
+        watched = os.path.join(root, "a.txt")
+        hidden = os.path.join(root, ".hidden", "b.txt")
+        for path in (watched, hidden):
+            with open(path, "w") as f:
+                f.write("start\n")
+        events = []
+
+        with pyripgrep.Grep().watch("foo\nbar", root, events.append, multiline=True, hidden=True, debounce_ms=20):
+            for path in (watched, hidden):
+                with open(path, "w") as f:
+                    f.write("start\nfoo\nbar\n")
+            deadline = time.time() + 5
+            while len(events) < 2 and time.time() < deadline:
+                time.sleep(0.05)
+        assert sorted(events, key=lambda event: event["path"]) == [
+            {"path": hidden, "added": [(2, "foo"), (3, "bar")], "removed": []},
+            {"path": watched, "added": [(2, "foo"), (3, "bar")], "removed": []},
+        ]
+
 
 def run_comprehensive_test():
     """Run a comprehensive test of the Grep interface"""